@@ -0,0 +1,4 @@
+// Loads the wasm of the compute worker, whose main function then listens for
+// the requests of the app.
+importScripts("./compute_worker.js");
+wasm_bindgen("./compute_worker_bg.wasm");
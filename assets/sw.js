@@ -4,6 +4,9 @@ var filesToCache = [
   './index.html',
   './fe_levels_gui.js',
   './fe_levels_gui.bg.wasm',
+  './compute_worker.js',
+  './compute_worker_bg.wasm',
+  './compute_worker_loader.js',
 ];
 
 /* Start the service worker and cache all of the app's content */
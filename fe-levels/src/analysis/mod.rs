@@ -44,13 +44,23 @@ pub(crate) fn binomial_analysis<SIT>(
 ) -> Option<Vec<BTreeMap<SIT, BTreeMap<StatType, f64>>>>
 where
     SIT : StatIndexType
+{
+    Some(binomial_analysis_iter(levels, character)?.collect())
+}
+
+/// The distributions of [`binomial_analysis`] computed one stat change at a
+/// time as the iterator advances.
+pub(crate) fn binomial_analysis_iter<'a, SIT>(
+    levels : &'a [StatChange<SIT>],
+    character : &Character<SIT>
+) -> Option<impl Iterator<Item = BTreeMap<SIT, BTreeMap<StatType, f64>>> + 'a>
+where
+    SIT : StatIndexType + 'a
 {
     if !levels.iter().all(binomial_stat_change_acceptable) {
         return None;
     }
 
-    let mut collection : Vec<BTreeMap<SIT, DistributedStat>> = Vec::new();
-
     let current : BTreeMap<SIT, DistributedStat> = character
         .stats
         .iter()
@@ -68,15 +78,10 @@ where
             )
         })
         .collect();
-    collection.push(current.clone());
-
-    collection.append(&mut levels.iter().scan(current, process_statchange).collect());
-
     Some(
-        collection
-            .into_iter()
+        std::iter::once(current.clone())
+            .chain(levels.iter().scan(current, process_statchange))
             .map(|m| m.into_iter().map(|(i, sm)| (i, sm.stats)).collect())
-            .collect()
     )
 }
 
@@ -502,14 +507,17 @@ fn internal_process_promotion<SIT : StatIndexType>(
     )
 }
 
-fn binomial_stat_change_acceptable<SIT : StatIndexType>(stat_change : &StatChange<SIT>) -> bool {
+pub(crate) fn binomial_stat_change_acceptable<SIT : StatIndexType>(
+    stat_change : &StatChange<SIT>
+) -> bool {
     match stat_change {
         StatChange::LevelUp {
             blank_avoidance: BlankAvoidance::GuaranteedStats(num_stats, _),
             ..
         } => {
-            (num_stats.contains(&0) && num_stats.end_bound() == Unbounded)
-                || num_stats.start_bound() == num_stats.end_bound()
+            // fixed numbers of stats (FE10 BEXP) would be exact as well, but
+            // process_levelup has no handler for them yet
+            num_stats.contains(&0) && num_stats.end_bound() == Unbounded
         },
         StatChange::LevelUp {
             blank_avoidance: BlankAvoidance::VariableGuaranteedStats,
@@ -0,0 +1,315 @@
+//! Analyses a unit and their stat changes described in json, for other tools
+//! to drive the library without Rust:
+//!
+//! ```sh
+//! fe-levels-json [character.json] [--samples <n>] [--output <data.json>]
+//! ```
+//!
+//! The input describes the unit by their stats and the stat changes by their
+//! `type`, fields left out taking their defaults:
+//!
+//! ```json
+//! {
+//!     "character": {
+//!         "name": "Seth",
+//!         "level": 1,
+//!         "stats": { "hp": { "base": 30, "growth": 90, "cap": 60 } }
+//!     },
+//!     "stat_changes": [
+//!         { "type": "LevelUp", "temp_growth_bonus": { "hp": 30 }, "retries_to_avoid_blank": 2 },
+//!         { "type": "Promotion", "promo_bonuses": { "hp": 2 }, "new_caps": { "hp": 60 } }
+//!     ]
+//! }
+//! ```
+//!
+//! The output lists the distribution of every stat, before the first stat
+//! change and after each one, as the probabilities of the stat's values:
+//! `[{ "hp": { "30": 1.0 } }, { "hp": { "30": 0.1, "31": 0.9 } }]`.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    path::PathBuf,
+    sync::Arc
+};
+
+use fe_levels::{
+    generate_histograms, BlankAvoidance, Character, GrowthType, Stat, StatChange, StatType
+};
+use serde::{Deserialize, Serialize};
+
+const USAGE : &str =
+    "usage: fe-levels-json [character.json] [--samples <n>] [--output <data.json>]";
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type")]
+enum JsonStatChange {
+    LevelUp {
+        #[serde(default)]
+        temp_growth_bonus : HashMap<String, GrowthType>,
+        #[serde(default)]
+        retries_to_avoid_blank : u32,
+        /// only there for the reader, the analysis counts the stat changes
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        new_level : Option<usize>
+    },
+    Promotion {
+        #[serde(default)]
+        promo_bonuses : HashMap<String, StatType>,
+        /// the caps the promotion raises, the others stay as they are
+        #[serde(default)]
+        new_caps : HashMap<String, StatType>
+    }
+}
+
+impl From<JsonStatChange> for StatChange<String> {
+    fn from(change : JsonStatChange) -> Self {
+        match change {
+            JsonStatChange::LevelUp {
+                temp_growth_bonus,
+                retries_to_avoid_blank,
+                new_level: _
+            } => StatChange::LevelUp {
+                temporary_growth_override : if temp_growth_bonus.is_empty() {
+                    None
+                }
+                else {
+                    Some(Arc::new(move |name : &String, growth| {
+                        growth.saturating_add(temp_growth_bonus.get(name).copied().unwrap_or(0))
+                    }))
+                },
+                blank_avoidance : BlankAvoidance::RetriesForNoBlank(retries_to_avoid_blank)
+            },
+            JsonStatChange::Promotion {
+                promo_bonuses,
+                new_caps
+            } => StatChange::Promotion {
+                promo_changes : Arc::new(move |name : &String, mut stat : Stat| {
+                    if let Some(cap) = new_caps.get(name) {
+                        stat.cap = *cap;
+                    }
+                    if let Some(bonus) = promo_bonuses.get(name) {
+                        stat.base += bonus;
+                        stat.increase_value(*bonus);
+                    }
+                    stat
+                })
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+struct JsonStat {
+    base : StatType,
+    growth : GrowthType,
+    cap : StatType,
+    /// the value the unit has now, their base if left out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    value : Option<StatType>
+}
+
+fn first_level() -> usize { 1 }
+
+#[derive(Serialize, Deserialize)]
+struct JsonCharacter {
+    #[serde(default)]
+    name : String,
+    #[serde(default = "first_level")]
+    level : usize,
+    stats : BTreeMap<String, JsonStat>
+}
+
+impl From<JsonCharacter> for Character<String> {
+    fn from(character : JsonCharacter) -> Self {
+        Character {
+            stats : character
+                .stats
+                .into_iter()
+                .map(|(name, stat)| {
+                    (
+                        name,
+                        Stat {
+                            base : stat.base,
+                            cap : stat.cap,
+                            growth : stat.growth,
+                            value : stat.value.unwrap_or(stat.base)
+                        }
+                    )
+                })
+                .collect(),
+            name : character.name,
+            level : character.level
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonTask {
+    character : JsonCharacter,
+    stat_changes : Vec<JsonStatChange>
+}
+
+#[derive(Debug, PartialEq)]
+struct Options {
+    input : PathBuf,
+    output : PathBuf,
+    /// how many samples to simulate if the stat changes can't be analysed
+    /// exactly
+    samples : Option<u64>
+}
+
+#[derive(Debug)]
+enum Error {
+    /// the arguments can't be made sense of, for the reason given
+    Usage(String),
+    IoError(PathBuf, std::io::Error),
+    JsonError(serde_json::Error),
+    /// the stat changes can't be analysed exactly, which is all the library
+    /// does so far
+    NotExact
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(error : serde_json::Error) -> Self { Error::JsonError(error) }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f : &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Error::Usage(reason) => write!(f, "{reason}\n{USAGE}"),
+            Error::IoError(path, error) => write!(f, "{}: {error}", path.display()),
+            Error::JsonError(error) => write!(f, "{error}"),
+            Error::NotExact => write!(
+                f,
+                "The stat changes can't be analysed exactly and simulating them isn't supported \
+                 yet."
+            )
+        }
+    }
+}
+
+fn parse_options(args : impl IntoIterator<Item = String>) -> Result<Options, Error> {
+    let mut options = Options {
+        input : PathBuf::from("./character.json"),
+        output : PathBuf::from("./data.json"),
+        samples : None
+    };
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |flag : &str| {
+            args.next()
+                .ok_or_else(|| Error::Usage(format!("{flag} needs a value")))
+        };
+        match arg.as_str() {
+            "--output" => options.output = PathBuf::from(value("--output")?),
+            "--samples" => {
+                let samples = value("--samples")?;
+                options.samples = Some(samples.parse().map_err(|_error| {
+                    Error::Usage(format!("--samples takes a number, not {samples}"))
+                })?);
+            },
+            flag if flag.starts_with("--") => {
+                return Err(Error::Usage(format!("unknown flag {flag}")));
+            },
+            input => options.input = PathBuf::from(input)
+        }
+    }
+    Ok(options)
+}
+
+/// The distributions of the unit's stats before the first stat change and after
+/// each one.
+fn analyse(
+    task : JsonTask,
+    samples : Option<u64>
+) -> Result<Vec<BTreeMap<String, BTreeMap<StatType, f64>>>, Error> {
+    let character : Character<String> = task.character.into();
+    let stat_changes = task
+        .stat_changes
+        .into_iter()
+        .map(StatChange::from)
+        .collect::<Vec<StatChange<String>>>();
+    let histograms = generate_histograms(&stat_changes, &character, samples);
+    if histograms.is_empty() {
+        Err(Error::NotExact)
+    }
+    else {
+        Ok(histograms)
+    }
+}
+
+fn run(options : &Options) -> Result<(), Error> {
+    let text = std::fs::read_to_string(&options.input)
+        .map_err(|error| Error::IoError(options.input.clone(), error))?;
+    let histograms = analyse(serde_json::from_str(&text)?, options.samples)?;
+    std::fs::write(&options.output, serde_json::to_string_pretty(&histograms)?)
+        .map_err(|error| Error::IoError(options.output.clone(), error))
+}
+
+fn main() {
+    if let Err(error) = parse_options(std::env::args().skip(1)).and_then(|options| run(&options)) {
+        eprintln!("{error}");
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(args : &[&str]) -> Vec<String> { args.iter().map(|arg| arg.to_string()).collect() }
+
+    #[test]
+    fn the_sample_character_is_analysed() {
+        let task : JsonTask = serde_json::from_str(include_str!("../../character.json")).unwrap();
+        let levels = task.stat_changes.len();
+        let histograms = analyse(task, None).unwrap();
+        assert_eq!(histograms.len(), levels + 1);
+        assert_eq!(histograms[0]["hp"], BTreeMap::from([(30, 1.0)]));
+        for histogram in &histograms {
+            for distribution in histogram.values() {
+                assert!((distribution.values().sum::<f64>() - 1.0).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn promotions_raise_stats_and_caps() {
+        let task : JsonTask = serde_json::from_str(
+            r#"{
+                "character": { "stats": { "hp": { "base": 20, "growth": 0, "cap": 20 } } },
+                "stat_changes": [
+                    { "type": "Promotion", "promo_bonuses": { "hp": 3 }, "new_caps": { "hp": 60 } },
+                    { "type": "LevelUp", "temp_growth_bonus": { "hp": 100 } }
+                ]
+            }"#
+        )
+        .unwrap();
+        let histograms = analyse(task, None).unwrap();
+        assert_eq!(histograms[1]["hp"], BTreeMap::from([(23, 1.0)]));
+        assert_eq!(histograms[2]["hp"], BTreeMap::from([(24, 1.0)]));
+    }
+
+    #[test]
+    fn options_are_parsed() {
+        let options = parse_options(strings(&[
+            "in.json",
+            "--samples",
+            "1000",
+            "--output",
+            "out.json"
+        ]));
+        assert_eq!(
+            options.unwrap(),
+            Options {
+                input : PathBuf::from("in.json"),
+                output : PathBuf::from("out.json"),
+                samples : Some(1000)
+            }
+        );
+        assert!(parse_options(strings(&["--samples", "lots"])).is_err());
+        assert!(parse_options(strings(&["--output"])).is_err());
+        assert!(parse_options(strings(&["--verbose"])).is_err());
+    }
+}
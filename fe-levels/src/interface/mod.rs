@@ -5,8 +5,12 @@ use std::{
     sync::Arc
 };
 
-use crate::analysis::binomial_analysis;
+use crate::{
+    analysis::{binomial_analysis, binomial_analysis_iter, binomial_stat_change_acceptable},
+    simulation::{event_frequencies, joint_benchmarks, simulate_histograms}
+};
 
+use rand::{rngs::StdRng, SeedableRng};
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 pub type GrowthType = u16;
@@ -100,6 +104,12 @@ pub enum StatChange<SIT : StatIndexType> {
     }
 }
 
+/// Returns true iff [`generate_histograms`] computes the given levels exactly
+/// rather than falling back to simulation.
+pub fn supports_exact_analysis<SIT : StatIndexType>(levels : &[StatChange<SIT>]) -> bool {
+    levels.iter().all(binomial_stat_change_acceptable)
+}
+
 pub fn generate_histograms<SIT : StatIndexType>(
     levels : &[StatChange<SIT>],
     character : &Character<SIT>,
@@ -109,14 +119,243 @@ pub fn generate_histograms<SIT : StatIndexType>(
         return analysis_result;
     }
 
-    // TODO: First call into the analysis on the levels
-    // then if the analysis rejects the level pattern
-    // call into the simulation
+    num_samples
+        .and_then(|num_samples| {
+            simulate_histograms(levels, character, num_samples, &mut simulation_rng(None))
+        })
+        .unwrap_or_default()
+}
+
+/// The generator of the simulation, reproducible when seeded and drawing from
+/// the platform's entropy otherwise.
+fn simulation_rng(seed : Option<u64>) -> StdRng {
+    seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64)
+}
+
+/// The histograms of [`generate_histograms`] computed one level at a time, e.g.
+/// to show progress on long progressions. `None` if they can't be computed
+/// exactly.
+pub fn stream_histograms<'a, SIT : StatIndexType + 'a>(
+    levels : &'a [StatChange<SIT>],
+    character : &Character<SIT>
+) -> Option<impl Iterator<Item = BTreeMap<SIT, BTreeMap<StatType, f64>>> + 'a> {
+    binomial_analysis_iter(levels, character)
+}
+
+/// How the distribution of one stat compares to that of another, the two
+/// taken to be independent.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StatComparison {
+    /// the expected value of the first stat minus that of the second
+    pub mean_difference : f64,
+    /// the probability of the first stat being at least as high as the second
+    pub probability_at_least : f64
+}
+
+/// Simulates how often each event holds after every stat change, indexed by
+/// the stat change and then by the event. The events see the values of all
+/// stats of a sampled run at once, e.g. to check benchmarks on several stats
+/// together. `None` if the levels can't be simulated.
+pub fn simulate_event_chances<SIT, F>(
+    levels : &[StatChange<SIT>],
+    character : &Character<SIT>,
+    events : &[F],
+    num_samples : u64,
+    seed : Option<u64>
+) -> Option<Vec<Vec<f64>>>
+where
+    SIT : StatIndexType,
+    F : Fn(&BTreeMap<SIT, StatType>) -> bool
+{
+    let mut rng = simulation_rng(seed);
+    event_frequencies(levels, character, events, num_samples, &mut rng)
+}
+
+/// The chance of several stats reaching their benchmarks at once, next to what
+/// it would be were the stats independent. Blank avoidance such as GBA FE's
+/// re-rolls ties the stats of a level-up together, so the product of the
+/// stats' own chances is off by [`JointBenchmark::correlation_delta`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct JointBenchmark {
+    /// the simulated chance of every stat being at least its benchmark
+    pub joint : f64,
+    /// the product of the stats' own simulated chances to reach their
+    /// benchmarks
+    pub independent : f64
+}
+
+impl JointBenchmark {
+    /// How much more likely reaching every benchmark is than the stats'
+    /// independence suggests, e.g. -0.012 for 1.2 percentage points less.
+    pub fn correlation_delta(&self) -> f64 { self.joint - self.independent }
+}
+
+/// Simulates how likely the stats are to reach their benchmarks together
+/// after every stat change, the first entry being before the first change as
+/// in [`generate_histograms`]. `None` if the levels can't be simulated.
+pub fn joint_benchmark_chances<SIT : StatIndexType>(
+    levels : &[StatChange<SIT>],
+    character : &Character<SIT>,
+    benchmarks : &BTreeMap<SIT, StatType>,
+    num_samples : u64,
+    seed : Option<u64>
+) -> Option<Vec<JointBenchmark>> {
+    let mut rng = simulation_rng(seed);
+    joint_benchmarks(levels, character, benchmarks, num_samples, &mut rng)
+}
+
+/// The expected value of the stat the histogram is the distribution of.
+pub fn mean(histogram : &BTreeMap<StatType, f64>) -> f64 {
+    histogram
+        .iter()
+        .map(|(value, probability)| f64::from(*value) * probability)
+        .sum()
+}
+
+/// Compares the histograms of a stat, e.g. those of two units at the same
+/// level as [`generate_histograms`] computes them.
+pub fn compare_histograms(
+    first : &BTreeMap<StatType, f64>,
+    second : &BTreeMap<StatType, f64>
+) -> StatComparison {
+    let probability_at_least = first
+        .iter()
+        .map(|(value, probability)| {
+            probability
+                * second
+                    .range(..=*value)
+                    .map(|(_value, below)| below)
+                    .sum::<f64>()
+        })
+        .sum();
+    StatComparison {
+        mean_difference : mean(first) - mean(second),
+        probability_at_least
+    }
+}
+
+/// slack for the rounding errors of summed up chances
+const ERROR_BOUND : f64 = 1e-9;
+
+/// When a stat first reaches a value over a progression, as
+/// [`arrival_distribution`] computes it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArrivalDistribution {
+    /// the chance of the stat first reaching the value at every snapshot, the
+    /// first snapshot being the character before any stat change
+    pub first_at : Vec<f64>,
+    /// the chance of the stat not reaching the value over the whole progression
+    pub never : f64
+}
+
+impl ArrivalDistribution {
+    /// The first snapshot by which the stat reached the value with at least
+    /// the chance, e.g. 0.5 for the median arrival. None if the stat is too
+    /// likely to never get there.
+    pub fn quantile(&self, chance : f64) -> Option<usize> {
+        let mut reached = 0.0;
+        self.first_at.iter().position(|arrived| {
+            reached += arrived;
+            reached >= chance - ERROR_BOUND
+        })
+    }
+}
 
-    vec![]
+/// The distribution of the first snapshot at which the stat is at least the
+/// threshold, from its distributions at every snapshot as
+/// [`generate_histograms`] computes them.
+///
+/// The distributions of single snapshots don't tell when a stat first gets
+/// somewhere in general, as it could reach a value and drop below it again.
+/// Stats only ever grow over a progression though, so having reached the
+/// threshold by a snapshot is the same as being at least the threshold at it:
+/// P(first ≤ k) = P(value at k ≥ threshold), and the chance of first arriving
+/// at k is the difference to the snapshot before. Should a promotion lower the
+/// stat, the chance of having arrived by a snapshot is the largest one so far,
+/// which then only bounds the true one from below.
+pub fn arrival_distribution<'a>(
+    histograms : impl IntoIterator<Item = &'a BTreeMap<StatType, f64>>,
+    threshold : StatType
+) -> ArrivalDistribution {
+    let mut reached = 0.0f64;
+    let first_at = histograms
+        .into_iter()
+        .map(|histogram| {
+            let chance = histogram
+                .range(threshold..)
+                .map(|(_value, p)| p)
+                .sum::<f64>();
+            let arrived = (chance.min(1.0) - reached).max(0.0);
+            reached = reached.max(chance.min(1.0));
+            arrived
+        })
+        .collect();
+    ArrivalDistribution {
+        first_at,
+        never : (1.0 - reached).max(0.0)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    #[test]
+    fn histograms_are_compared_as_independent() {
+        let first = BTreeMap::from([(5, 0.5), (7, 0.5)]);
+        let second = BTreeMap::from([(6, 1.0)]);
+        let comparison = compare_histograms(&first, &second);
+        assert_eq!(comparison.mean_difference, 0.0);
+        assert_eq!(comparison.probability_at_least, 0.5);
+        assert_eq!(
+            compare_histograms(&second, &second).probability_at_least,
+            1.0
+        );
+    }
+
+    #[test]
+    fn arrivals_follow_from_the_cumulative_chances() {
+        let character = Character {
+            stats : BTreeMap::from([(
+                0u8,
+                Stat {
+                    base : 5,
+                    cap : 20,
+                    growth : 50,
+                    value : 5
+                }
+            )]),
+            name : "Test".to_owned(),
+            level : 1
+        };
+        let levels = (0..3)
+            .map(|_level| StatChange::LevelUp {
+                temporary_growth_override : None,
+                blank_avoidance : BlankAvoidance::NoAvoidance
+            })
+            .collect::<Vec<_>>();
+        let histograms = generate_histograms(&levels, &character, None);
+        let arrival = arrival_distribution(histograms.iter().map(|stats| &stats[&0]), 7);
+        // two of three coin flips, the second hit arriving at the second level
+        // with 1/4 and at the third with 2/8
+        assert_eq!(arrival.first_at, vec![0.0, 0.0, 0.25, 0.25]);
+        assert_eq!(arrival.never, 0.5);
+        assert_eq!(arrival.quantile(0.25), Some(2));
+        assert_eq!(arrival.quantile(0.5), Some(3));
+        assert_eq!(arrival.quantile(0.9), None);
+
+        let reached = arrival_distribution(histograms.iter().map(|stats| &stats[&0]), 5);
+        assert_eq!(reached.first_at, vec![1.0, 0.0, 0.0, 0.0]);
+        assert_eq!(reached.quantile(0.9), Some(0));
+
+        // a stat dropping back below the threshold doesn't arrive twice
+        let dropping = [
+            BTreeMap::from([(4, 0.5), (8, 0.5)]),
+            BTreeMap::from([(4, 1.0)]),
+            BTreeMap::from([(8, 1.0)])
+        ];
+        let arrival = arrival_distribution(&dropping, 8);
+        assert_eq!(arrival.first_at, vec![0.5, 0.0, 0.5]);
+        assert_eq!(arrival.never, 0.0);
+    }
 }
@@ -1,6 +1,14 @@
-/*use std::{collections::HashMap, fmt};
+use std::{
+    collections::HashMap,
+    fmt,
+    io::{self, Write},
+    path::PathBuf,
+    str::FromStr
+};
 
-use repl_rs::{Command, Parameter, Repl, Value};
+use fe_levels::Character;
+
+use repl_rs::{Command, Convert, Parameter, Repl, Value};
 
 mod repl;
 
@@ -9,9 +17,21 @@ pub enum Error {
     ReplError(repl_rs::Error),
     IoError(std::io::Error),
     JsonError(serde_json::Error),
+    CsvError(csv::Error),
     StatNotFound(String),
     NoUnit,
-    NoPromotionFound(String)
+    NoPromotionFound(String),
+    /// the input and the target classes of the promotions equally close to it
+    AmbiguousPromotion(String, Vec<String>),
+    /// the argument of the given name is missing or can't be parsed
+    BadArgument(String),
+    NotImplemented(&'static str),
+    UnknownGame(String),
+    UnknownCommand(String),
+    /// Ctrl+C was pressed during a computation
+    Interrupted,
+    /// the command, its parameters and how many of them are required
+    WrongArguments(String, &'static [&'static str], usize)
 }
 
 impl From<repl_rs::Error> for Error {
@@ -26,6 +46,10 @@ impl From<serde_json::Error> for Error {
     fn from(error : serde_json::Error) -> Self { Error::JsonError(error) }
 }
 
+impl From<csv::Error> for Error {
+    fn from(error : csv::Error) -> Self { Error::CsvError(error) }
+}
+
 impl std::error::Error for Error {}
 
 impl fmt::Display for Error {
@@ -34,16 +58,60 @@ impl fmt::Display for Error {
             Error::ReplError(error) => write!(f, "{error}"),
             Error::IoError(error) => write!(f, "{error}"),
             Error::JsonError(error) => write!(f, "{error}"),
+            Error::CsvError(error) => write!(f, "{error}"),
             Error::StatNotFound(input) => write!(f, "Failed to interpret the stat {input}."),
             Error::NoUnit => write!(f, "There's no unit currently loaded for editing."),
-            Error::NoPromotionFound(input) => write!(f, "No promotion found for the input {input}.")
+            Error::NoPromotionFound(input) => {
+                write!(f, "No promotion found for the input {input}.")
+            },
+            Error::AmbiguousPromotion(input, candidates) => write!(
+                f,
+                "The promotion {input} could be any of {}, be more specific.",
+                candidates.join(", ")
+            ),
+            Error::BadArgument(name) => write!(f, "The argument {name} is missing or invalid."),
+            Error::NotImplemented(command) => {
+                write!(f, "The command {command} isn't supported yet.")
+            },
+            Error::UnknownGame(game) => {
+                write!(
+                    f,
+                    "The game {game} isn't supported, try one of {}.",
+                    GAMES.join(", ")
+                )
+            },
+            Error::UnknownCommand(command) => write!(f, "There's no command {command}."),
+            Error::Interrupted => write!(f, "The computation was interrupted."),
+            Error::WrongArguments(command, [], _required) => {
+                write!(f, "The command {command} takes no arguments.")
+            },
+            Error::WrongArguments(command, parameters, required) => {
+                let optional = parameters[*required..]
+                    .iter()
+                    .map(|name| format!("[{name}]"));
+                let usage = parameters[..*required]
+                    .iter()
+                    .map(|name| name.to_string())
+                    .chain(optional)
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                write!(f, "The command {command} takes the arguments {usage}.")
+            }
         }
     }
 }
 
-type Arguments = HashMap<String, Value>;
+/// The arguments of a command by their names, as they were entered.
+type Arguments = HashMap<String, String>;
 type Return = Result<Option<String>, Error>;
 
+/// The argument of the given name parsed, e.g. as a number.
+fn argument<T : FromStr>(args : &Arguments, name : &str) -> Result<T, Error> {
+    args.get(name)
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| Error::BadArgument(name.to_string()))
+}
+
 trait FeRepl {
     fn new_unit(&mut self, args : Arguments) -> Return;
     fn update_base(&mut self, args : Arguments) -> Return;
@@ -53,34 +121,249 @@ trait FeRepl {
     fn new_promotion(&mut self, args : Arguments) -> Return;
     fn add_level(&mut self, args : Arguments) -> Return;
     fn add_promotion(&mut self, args : Arguments) -> Return;
+    fn list_promotions(&mut self, args : Arguments) -> Return;
     fn heat_map(&mut self, args : Arguments) -> Return;
+    fn compare(&mut self, args : Arguments) -> Return;
+    fn show(&mut self, args : Arguments) -> Return;
+    fn undo(&mut self, args : Arguments) -> Return;
     fn save_unit(&mut self, args : Arguments) -> Return;
     fn load_unit(&mut self, args : Arguments) -> Return;
     fn save_progression(&mut self, args : Arguments) -> Return;
     fn load_progression(&mut self, args : Arguments) -> Return;
     fn save_histograms(&mut self, args : Arguments) -> Return;
+    /// takes the unit being edited, e.g. to carry it over to another game
+    fn take_unit(&mut self) -> Option<Character<String>>;
+    fn set_unit(&mut self, unit : Character<String>);
+}
+
+/// the games the REPL can switch to
+const GAMES : [&str; 3] = ["fe6", "fe7", "fe8"];
+/// given after the game to keep editing the current unit in the new one
+const CARRY_UNIT_FLAG : &str = "--carry-unit";
+
+/// The game being analyzed, replaced by switch_game.
+struct Context {
+    game_id : String,
+    /// where units, progressions and promotions are saved, `./data` for the
+    /// REPL
+    data_dir : PathBuf,
+    game : Box<dyn FeRepl>
+}
+
+fn open_game(game_id : &str, data_dir : PathBuf) -> Result<Box<dyn FeRepl>, Error> {
+    if !GAMES.contains(&game_id) {
+        return Err(Error::UnknownGame(game_id.to_string()));
+    }
+    Ok(Box::new(repl::GbaFe::with_data_dir(game_id, data_dir)?))
+}
+
+impl Context {
+    fn new(game_id : &str, data_dir : PathBuf) -> Result<Self, Error> {
+        Ok(Context {
+            game_id : game_id.to_string(),
+            game : open_game(game_id, data_dir.clone())?,
+            data_dir
+        })
+    }
+
+    /// Replaces the game with a fresh one, keeping the current game if the new
+    /// one can't be opened.
+    fn switch_game(&mut self, args : Arguments) -> Return {
+        let game_id = argument::<String>(&args, "game")?.to_lowercase();
+        let carry_unit = args
+            .get("carry_unit")
+            .map_or(false, |flag| flag == CARRY_UNIT_FLAG);
+        let mut game = open_game(&game_id, self.data_dir.clone())?;
+
+        let mut message = format!("Switched from {} to {game_id}.", self.game_id);
+        if carry_unit {
+            if let Some(unit) = self.game.take_unit() {
+                message += &format!(
+                    " Carried {} over, whose caps are still those of {}, so check them.",
+                    unit.name, self.game_id
+                );
+                game.set_unit(unit);
+            }
+        }
+
+        self.game = game;
+        self.game_id = game_id;
+        Ok(Some(message))
+    }
+}
+
+/// The arguments as the commands take them.
+fn strings(args : HashMap<String, Value>) -> Result<Arguments, Error> {
+    args.into_iter()
+        .map(|(name, value)| Ok((name, value.convert()?)))
+        .collect()
+}
+
+/// The parameters of the commands, the required ones first, and how many are
+/// required. Scripts give the arguments in this order.
+const PARAMETERS : [(&str, &[&str], usize); 19] = [
+    ("switch_game", &["game", "carry_unit"], 1),
+    ("new_unit", &["name"], 1),
+    ("update_base", &["stat", "value"], 2),
+    ("update_stat", &["stat", "value"], 2),
+    ("update_growth", &["stat", "value"], 2),
+    ("update_cap", &["stat", "value"], 2),
+    ("show", &["stat"], 0),
+    ("undo", &[], 0),
+    ("new_promotion", &["target_class"], 1),
+    ("add_level", &[], 0),
+    ("add_promotion", &["target_class"], 1),
+    ("list_promotions", &[], 0),
+    ("heat_map", &["stat", "flags"], 0),
+    ("compare", &["unit_a", "unit_b", "level"], 2),
+    ("save_unit", &["format"], 0),
+    ("load_unit", &["unit_name", "format"], 1),
+    ("save_progression", &["filename"], 1),
+    ("load_progression", &["filename"], 1),
+    (
+        "save_histograms",
+        &["filename", "reduction", "reduction_param", "format"],
+        2
+    )
+];
+
+fn parameters(name : &str) -> Result<(&'static [&'static str], usize), Error> {
+    PARAMETERS
+        .iter()
+        .find(|(command, ..)| *command == name)
+        .map(|(_command, parameters, required)| (*parameters, *required))
+        .ok_or_else(|| Error::UnknownCommand(name.to_string()))
+}
+
+/// Runs the command, whether it was entered in the REPL or read from a script.
+fn dispatch(context : &mut Context, name : &str, args : Arguments) -> Return {
+    match name {
+        "switch_game" => context.switch_game(args),
+        "new_unit" => context.game.new_unit(args),
+        "update_base" => context.game.update_base(args),
+        "update_stat" => context.game.update_stat(args),
+        "update_growth" => context.game.update_growth(args),
+        "update_cap" => context.game.update_cap(args),
+        "show" => context.game.show(args),
+        "undo" => context.game.undo(args),
+        "new_promotion" => context.game.new_promotion(args),
+        "add_level" => context.game.add_level(args),
+        "add_promotion" => context.game.add_promotion(args),
+        "list_promotions" => context.game.list_promotions(args),
+        "heat_map" => context.game.heat_map(args),
+        "compare" => context.game.compare(args),
+        "save_unit" => context.game.save_unit(args),
+        "load_unit" => context.game.load_unit(args),
+        "save_progression" => context.game.save_progression(args),
+        "load_progression" => context.game.load_progression(args),
+        "save_histograms" => context.game.save_histograms(args),
+        _ => Err(Error::UnknownCommand(name.to_string()))
+    }
+}
+
+/// Reads a line of a script into the command and its arguments, `None` for
+/// blank lines and comments.
+fn parse_command(line : &str) -> Result<Option<(&str, Arguments)>, Error> {
+    let mut words = line.split_whitespace();
+    let name = match words.next() {
+        Some(name) if !name.starts_with('#') => name,
+        _ => return Ok(None)
+    };
+    let (parameters, required) = parameters(name)?;
+    let values = words.collect::<Vec<_>>();
+    if values.len() < required || values.len() > parameters.len() {
+        return Err(Error::WrongArguments(
+            name.to_string(),
+            parameters,
+            required
+        ));
+    }
+    let args = parameters
+        .iter()
+        .zip(values)
+        .map(|(parameter, value)| (parameter.to_string(), value.to_string()))
+        .collect();
+    Ok(Some((name, args)))
+}
+
+/// Runs the lines of the script one after another, writing their results to
+/// the output. Stops at the first line that fails, returning its number.
+fn run_script<'a>(
+    context : &mut Context,
+    lines : impl IntoIterator<Item = &'a str>,
+    output : &mut impl Write
+) -> Result<(), (usize, Error)> {
+    for (index, line) in lines.into_iter().enumerate() {
+        let fail = |error| (index + 1, error);
+        if let Some((name, args)) = parse_command(line).map_err(fail)? {
+            if let Some(result) = dispatch(context, name, args).map_err(fail)? {
+                writeln!(output, "{result}").map_err(|error| fail(error.into()))?;
+            }
+        }
+    }
+    Ok(())
 }
 
 fn exit<C>(_args : HashMap<String, Value>, _context : &mut C) -> Result<Option<String>, Error> {
     std::process::exit(0);
 }
 
+// the commands take their arguments as plain strings so that they can be
+// driven without the REPL, e.g. by scripts and tests
 macro_rules! callbacker {
     ($name : ident) => {
-        |args, context : &mut Box<dyn FeRepl>| context.$name(args)
+        |args : HashMap<String, Value>, context : &mut Context| {
+            dispatch(context, stringify!($name), strings(args)?)
+        }
     };
 }
 
+/// The command with the parameters listed in [`PARAMETERS`].
 macro_rules! command {
-    ($name : ident) => {
-        Command::new(stringify!($name), callbacker!($name))
-    };
-}*/
+    ($name : ident) => {{
+        let (parameters, required) = parameters(stringify!($name))?;
+        let mut command = Command::new(stringify!($name), callbacker!($name));
+        for (index, parameter) in parameters.iter().enumerate() {
+            let parameter = Parameter::new(parameter).set_required(index < required)?;
+            command = command.with_parameter(parameter)?;
+        }
+        command
+    }};
+}
+
+const USAGE : &str = "usage: fe-levels [--script <file> | --command \"<command>; <command>\"]";
+
+/// The commands to run instead of the REPL, given as `--script <file>` or as
+/// `--command "<command>; <command>"`.
+fn script_argument() -> Result<Option<String>, Error> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match args
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        [] => Ok(None),
+        ["--script", file] => Ok(Some(std::fs::read_to_string(file)?)),
+        ["--command", commands] => Ok(Some(commands.replace(';', "\n"))),
+        _ => {
+            eprintln!("{USAGE}");
+            std::process::exit(2);
+        }
+    }
+}
 
 pub fn main() -> Result<(), Error> {
-    Ok(())
-    /*
-    let context : Box<dyn FeRepl> = Box::new(repl::GbaFe::new("fe8").unwrap());
+    repl::handle_interrupts()?;
+    let mut context = Context::new("fe8", PathBuf::from("./data"))?;
+
+    if let Some(script) = script_argument()? {
+        if let Err((line, error)) = run_script(&mut context, script.lines(), &mut io::stdout()) {
+            eprintln!("line {line}: {error}");
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
 
     Ok(Repl::new(context)
         .use_completion(true)
@@ -88,60 +371,139 @@ pub fn main() -> Result<(), Error> {
         .with_version("0.1.0")
         .with_name("fe-levels")
         // general management
-        //.add_command(Command::new("switch_game", todo!()))
-        // specify unit
-        .add_command(command!(new_unit).with_parameter(Parameter::new("name").set_required(true)?)?)
         .add_command(
-            command!(update_base)
-                .with_parameter(Parameter::new("stat").set_required(true)?)?
-                .with_parameter(Parameter::new("value").set_required(true)?)?
-        )
-        .add_command(
-            command!(update_stat)
-                .with_parameter(Parameter::new("stat").set_required(true)?)?
-                .with_parameter(Parameter::new("value").set_required(true)?)?
-        )
-        .add_command(
-            command!(update_growth)
-                .with_parameter(Parameter::new("stat").set_required(true)?)?
-                .with_parameter(Parameter::new("value").set_required(true)?)?
+            command!(switch_game)
+                .with_help("Switches to fe6, fe7 or fe8, with --carry-unit to keep the unit.")
         )
+        // specify unit
+        .add_command(command!(new_unit))
+        .add_command(command!(update_base))
+        .add_command(command!(update_stat))
+        .add_command(command!(update_growth))
+        .add_command(command!(update_cap))
         .add_command(
-            command!(update_cap)
-                .with_parameter(Parameter::new("stat").set_required(true)?)?
-                .with_parameter(Parameter::new("value").set_required(true)?)?
+            command!(show).with_help("Shows the unit's stats, or the one given, and progression.")
         )
+        .add_command(command!(undo).with_help(
+            "Reverts the last change to the unit or their progression, e.g. by update_growth."
+        ))
         // specify promotions
         .add_command(command!(new_promotion))
-        // add new unit growth opportunities ("progression")
-        .add_command(command!(add_level))
         .add_command(
-            command!(add_promotion)
-                .with_parameter(Parameter::new("target_class").set_required(true)?)?
+            command!(list_promotions).with_help("Lists the promotions and their stat bonuses.")
         )
+        // add new unit growth opportunities ("progression")
+        .add_command(command!(add_level))
+        .add_command(command!(add_promotion).with_help(
+            "Adds the promotion whose target class is closest to the one given to the progression."
+        ))
         // perform analysis
-        .add_command(command!(heat_map))
+        .add_command(command!(heat_map).with_help(
+            "Shows how likely each value of the unit's stats is after every stat change. Takes an \
+             optional stat to only show that one and --wide for percentages."
+        ))
+        .add_command(command!(compare).with_help(
+            "Compares two saved units after the progressions saved under their names, or after \
+             the stat change of the level given, 0 being the units as saved."
+        ))
         // perform data management
-        .add_command(command!(save_unit))
-        .add_command(
-            command!(load_unit).with_parameter(Parameter::new("unit_name").set_required(true)?)?
-        )
+        .add_command(command!(save_unit).with_help("Saves the unit as json, or as csv if given."))
         .add_command(
-            command!(save_progression)
-                .with_parameter(Parameter::new("filename").set_required(true)?)?
-        )
-        .add_command(
-            command!(load_progression)
-                .with_parameter(Parameter::new("filename").set_required(true)?)?
-        )
-        .add_command(
-            command!(save_histograms)
-                .with_parameter(Parameter::new("filename").set_required(true)?)?
-                .with_parameter(Parameter::new("reduction").set_required(true)?)?
-                .with_parameter(Parameter::new("reduction_param").set_required(true)?)?
+            command!(load_unit).with_help("Loads a saved unit, from the csv if csv is given.")
         )
+        .add_command(command!(save_progression))
+        .add_command(command!(load_progression))
+        .add_command(command!(save_histograms).with_help(
+            "Saves the histograms of the unit after every stat change as json, reduced by none, \
+             by stat to the stat given or by level to the stat change of the index given. Saves \
+             csv in long format with the means in a file of their own if csv follows, after any \
+             placeholder for the reduction_param."
+        ))
         // general stuff
         .add_command(Command::new("exit", exit).with_help("Exits the program."))
         .add_command(Command::new("quit", exit).with_help("Exits the program."))
-        .run()?)*/
+        .run()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    fn arguments(pairs : &[(&str, &str)]) -> Arguments {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn switching_games_carries_the_unit_only_if_asked_to() {
+        let data_dir =
+            std::env::temp_dir().join(format!("fe-levels-switch-{}", std::process::id()));
+        let mut context = Context::new("fe8", data_dir).unwrap();
+        context
+            .game
+            .new_unit(arguments(&[("name", "Seth")]))
+            .unwrap();
+
+        context
+            .switch_game(arguments(&[
+                ("game", "FE7"),
+                ("carry_unit", CARRY_UNIT_FLAG)
+            ]))
+            .unwrap();
+        assert_eq!(context.game_id, "fe7");
+        assert_eq!(context.game.take_unit().unwrap().name, "Seth");
+
+        context
+            .game
+            .new_unit(arguments(&[("name", "Eliwood")]))
+            .unwrap();
+        context.switch_game(arguments(&[("game", "fe6")])).unwrap();
+        assert!(context.game.take_unit().is_none());
+
+        assert!(context.switch_game(arguments(&[("game", "fe99")])).is_err());
+        assert_eq!(context.game_id, "fe6");
+    }
+
+    #[test]
+    fn scripts_build_units_and_save_histograms() {
+        let data_dir =
+            std::env::temp_dir().join(format!("fe-levels-script-{}", std::process::id()));
+        let mut context = Context::new("fe8", data_dir.clone()).unwrap();
+        let script = "# Seth, always growing hp\nnew_unit Seth\nupdate_stat hp 5\nupdate_growth \
+                      hp 100\n\nadd_level\nadd_level\nsave_histograms seth stat hp";
+        let mut output = Vec::new();
+        run_script(&mut context, script.lines(), &mut output).unwrap();
+        assert!(String::from_utf8(output)
+            .unwrap()
+            .contains("Successfully saved the histograms"));
+
+        let path = data_dir.join("histograms").join("fe8").join("seth.json");
+        let histograms : Vec<BTreeMap<String, f64>> =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(histograms.len(), 3);
+        assert_eq!(histograms[2], BTreeMap::from([("7".to_string(), 1.0)]));
+    }
+
+    #[test]
+    fn scripts_stop_at_the_first_failing_line() {
+        let data_dir =
+            std::env::temp_dir().join(format!("fe-levels-failing-{}", std::process::id()));
+        let mut context = Context::new("fe8", data_dir).unwrap();
+        let mut output = Vec::new();
+        let script = "new_unit Seth; update_base hp; add_level".replace(';', "\n");
+        match run_script(&mut context, script.lines(), &mut output) {
+            Err((2, Error::WrongArguments(command, _parameters, 2))) => {
+                assert_eq!(command, "update_base")
+            },
+            _ => panic!("the second line should have failed")
+        }
+        assert!(matches!(
+            run_script(&mut context, ["fly"], &mut output),
+            Err((1, Error::UnknownCommand(_)))
+        ));
+    }
 }
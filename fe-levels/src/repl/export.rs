@@ -0,0 +1,146 @@
+//! Saving units and histograms as csv for spreadsheets, next to the json the
+//! REPL saves by default.
+
+use std::{collections::BTreeMap, fs, io, str::FromStr};
+
+use fe_levels::{mean, Character, GrowthType, Stat, StatType};
+use serde::{Deserialize, Serialize};
+
+use crate::{argument, Arguments, Error};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Format {
+    Json,
+    Csv
+}
+
+impl Format {
+    pub(crate) fn extension(self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::Csv => "csv"
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = ();
+
+    fn from_str(input : &str) -> Result<Self, Self::Err> {
+        match input.to_lowercase().as_str() {
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(())
+        }
+    }
+}
+
+/// The format given to the command, json if none is.
+pub(crate) fn format(args : &Arguments) -> Result<Format, Error> {
+    match args.get("format") {
+        Some(_format) => argument(args, "format"),
+        None => Ok(Format::Json)
+    }
+}
+
+/// Which of the histograms save_histograms saves: all of them, those of one
+/// stat or those after the stat change of the index.
+pub(crate) enum Reduction {
+    None,
+    Stat(&'static str),
+    Level(usize)
+}
+
+impl Reduction {
+    fn keeps(&self, level : usize, stat : &str) -> bool {
+        match self {
+            Reduction::None => true,
+            Reduction::Stat(kept) => stat == *kept,
+            Reduction::Level(kept) => level == *kept
+        }
+    }
+}
+
+/// A stat of a unit as a row of the csv they're saved in, the name and level
+/// of the unit repeated in every row.
+#[derive(Serialize, Deserialize)]
+struct UnitRow {
+    name : String,
+    level : usize,
+    stat : String,
+    base : StatType,
+    value : StatType,
+    growth : GrowthType,
+    cap : StatType
+}
+
+pub(crate) fn write_unit(file : fs::File, unit : &Character<String>) -> Result<(), Error> {
+    let mut writer = csv::Writer::from_writer(file);
+    for (stat, values) in &unit.stats {
+        writer.serialize(UnitRow {
+            name : unit.name.clone(),
+            level : unit.level,
+            stat : stat.clone(),
+            base : values.base,
+            value : values.value,
+            growth : values.growth,
+            cap : values.cap
+        })?;
+    }
+    Ok(writer.flush()?)
+}
+
+pub(crate) fn read_unit(file : fs::File) -> Result<Character<String>, Error> {
+    let rows = csv::Reader::from_reader(file)
+        .into_deserialize::<UnitRow>()
+        .collect::<Result<Vec<_>, _>>()?;
+    let first = rows
+        .first()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "the csv lists no stats"))?;
+    Ok(Character {
+        stats : rows
+            .iter()
+            .map(|row| {
+                (
+                    row.stat.clone(),
+                    Stat {
+                        base : row.base,
+                        cap : row.cap,
+                        growth : row.growth,
+                        value : row.value
+                    }
+                )
+            })
+            .collect(),
+        name : first.name.clone(),
+        level : first.level
+    })
+}
+
+/// Writes the histograms the reduction keeps in long format, i.e. a row per
+/// value of a stat after a stat change, and the means of the stats to a file
+/// of their own.
+pub(crate) fn write_histograms(
+    distributions : fs::File,
+    means : fs::File,
+    histograms : &[BTreeMap<String, BTreeMap<StatType, f64>>],
+    reduction : &Reduction
+) -> Result<(), Error> {
+    let mut distributions = csv::Writer::from_writer(distributions);
+    let mut means = csv::Writer::from_writer(means);
+    distributions.write_record(["level", "stat", "value", "probability"])?;
+    means.write_record(["level", "stat", "mean"])?;
+    for (level, histogram) in histograms.iter().enumerate() {
+        let kept = histogram
+            .iter()
+            .filter(|(stat, _distribution)| reduction.keeps(level, stat));
+        for (stat, distribution) in kept {
+            for (value, probability) in distribution {
+                distributions.serialize((level, stat, value, probability))?;
+            }
+            means.serialize((level, stat, mean(distribution)))?;
+        }
+    }
+    distributions.flush()?;
+    Ok(means.flush()?)
+}
@@ -1,15 +1,34 @@
 use itertools::Itertools;
-use repl_rs::Convert;
-use std::{collections::{HashMap, BTreeMap}, fs, io, path::Path, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+    sync::Arc
+};
 
-use fe_levels::{BlankAvoidance, Character, GrowthType, Stat, StatChange, StatType};
+use fe_levels::{
+    compare_histograms, stream_histograms, BlankAvoidance, Character, GrowthType, Stat, StatChange,
+    StatType
+};
 
-use crate::{Arguments, Error, FeRepl, Return};
+use super::{
+    export::{self, format, Format, Reduction},
+    interrupt::{interrupted, interruptible}
+};
+use crate::{argument, Arguments, Error, FeRepl, Return};
 
 type GBASIT = String;
+/// the distribution of every stat after a stat change
+type Histograms = BTreeMap<GBASIT, BTreeMap<StatType, f64>>;
+/// the unit and their progression as they were before a command changed them,
+/// the promotions by their target classes
+type Snapshot = (Option<Character<GBASIT>>, Vec<Option<String>>);
 
-#[derive(Clone, Debug)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// how many of the most recent changes undo can revert
+const UNDO_LIMIT : usize = 32;
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 struct GbaPromotion {
     growth_change : GrowthType,
     stat_bonus : HashMap<GBASIT, StatType>,
@@ -18,25 +37,91 @@ struct GbaPromotion {
 
 pub(crate) struct GbaFe {
     game : String,
+    /// where units, progressions and promotions are saved, `./data` for the
+    /// REPL
+    data_dir : PathBuf,
     unit : Option<Character<GBASIT>>,
     progressions : Vec<(Option<String>, StatChange<GBASIT>)>,
-    promotions : HashMap<String, GbaPromotion>
+    promotions : HashMap<String, GbaPromotion>,
+    /// the states before the most recent changes to the unit or their
+    /// progression, the latest last
+    history : Vec<Snapshot>
+}
+
+/// Creates the file to save to, together with the directories it's in.
+fn create_file(path : &Path) -> Result<fs::File, Error> {
+    fs::create_dir_all(
+        path.parent().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, format!("{}", path.display()))
+        })?
+    )?;
+
+    Ok(fs::OpenOptions::new()
+        .create(true)
+        .truncate(true)
+        .write(true)
+        .open(path)?)
 }
 
 impl GbaFe {
     pub(crate) fn new(game : &str) -> Result<Self, Error> {
-        let promotion_db = fs::OpenOptions::new()
-            .read(true)
-            .open(format!("./data/promotions/{game}.json"))?;
+        Self::with_data_dir(game, PathBuf::from("./data"))
+    }
+
+    pub(crate) fn with_data_dir(game : &str, data_dir : PathBuf) -> Result<Self, Error> {
+        let promotions =
+            match fs::File::open(data_dir.join("promotions").join(format!("{game}.json"))) {
+                Ok(promotion_db) => serde_json::from_reader(promotion_db)?,
+                // without a promotion database units can still level up
+                Err(error) if error.kind() == io::ErrorKind::NotFound => HashMap::new(),
+                Err(error) => return Err(error.into())
+            };
 
         Ok(GbaFe {
             game : game.to_string(),
+            data_dir,
             unit : None,
             progressions : vec![],
-            promotions : serde_json::from_reader(promotion_db)?
+            promotions,
+            history : vec![]
         })
     }
 
+    fn unit_path(&self, name : &str, format : Format) -> PathBuf {
+        self.data_dir
+            .join("characters")
+            .join(&self.game)
+            .join(format!("{}.{}", name.to_lowercase(), format.extension()))
+    }
+
+    fn progression_path(&self, filename : &str) -> PathBuf {
+        self.data_dir
+            .join("progressions")
+            .join(&self.game)
+            .join(format!("{}.json", filename.to_lowercase()))
+    }
+
+    fn histograms_path(&self, filename : &str, format : Format) -> PathBuf {
+        self.data_dir
+            .join("histograms")
+            .join(&self.game)
+            .join(format!(
+                "{}.{}",
+                filename.to_lowercase(),
+                format.extension()
+            ))
+    }
+
+    fn snapshot(&self) -> Snapshot { (self.unit.clone(), self.progression()) }
+
+    /// Keeps the state from before a command changed it for undo.
+    fn remember(&mut self, snapshot : Snapshot) {
+        if self.history.len() == UNDO_LIMIT {
+            self.history.remove(0);
+        }
+        self.history.push(snapshot);
+    }
+
     fn unit(&mut self) -> Result<&mut Character<String>, Error> {
         self.unit.as_mut().ok_or(Error::NoUnit)
     }
@@ -48,11 +133,12 @@ impl GbaFe {
         args : Arguments,
         extractor : impl Fn(&mut Stat) -> &mut StatType
     ) -> Result<(String, StatType, StatType), Error> {
-        let input : String = args["stat"].convert()?;
+        let input : String = argument(&args, "stat")?;
         let (_score, stat) =
             find_closest(&input, &GBA_STATS).ok_or_else(|| Error::StatNotFound(input.clone()))?;
-        let new_value : StatType = args["value"].convert()?;
+        let new_value : StatType = argument(&args, "value")?;
 
+        let snapshot = self.snapshot();
         let val_ref = extractor(
             self.unit()?
                 .stats
@@ -62,45 +148,403 @@ impl GbaFe {
 
         let old_value = *val_ref;
         *val_ref = new_value;
+        self.remember(snapshot);
 
         Ok((stat.to_string(), old_value, new_value))
     }
 
-    fn add_promotion_internal(&mut self, target_class : &str) -> Result<(), Error> {
-        let promotion = self
+    /// Writes the promotions back to the database they were read from.
+    fn save_promotions(&self) -> Result<(), Error> {
+        let path = self
+            .data_dir
+            .join("promotions")
+            .join(format!("{}.json", self.game));
+        let file = create_file(&path)?;
+        let sorted : BTreeMap<_, _> = self.promotions.iter().collect();
+        serde_json::to_writer_pretty(file, &sorted)?;
+        Ok(())
+    }
+
+    /// Asks for the stat bonuses, caps and growth change of the promotion and
+    /// saves it, replacing any promotion to the class.
+    fn new_promotion_with(
+        &mut self,
+        target_class : &str,
+        input : &mut impl BufRead,
+        output : &mut impl Write
+    ) -> Return {
+        let stat_bonus = read_stat_values(
+            input,
+            output,
+            &format!("Stat bonuses of promoting to {target_class}")
+        )?;
+        let new_caps = read_stat_values(input, output, "New caps")?;
+        write!(output, "Growth change (empty for none): ")?;
+        output.flush()?;
+        let mut line = String::new();
+        input.read_line(&mut line)?;
+        let growth_change = match line.trim() {
+            "" => 0,
+            change => change
+                .parse()
+                .map_err(|_| Error::BadArgument("growth change".to_string()))?
+        };
+
+        let replaced = self
             .promotions
-            .get(target_class)
-            .ok_or(Error::NoPromotionFound(target_class.to_string()))?
-            .clone();
-        self.progressions.push((
-            Some(target_class.to_string()),
-            StatChange::Promotion {
-                promo_changes : Arc::new(move |name, mut stat| {
-                    if !GBA_NON_GROWABLE_STATS.contains(&name.as_str()) {
-                        stat.growth += promotion.growth_change;
-                    }
-                    if let Some(bonus) = promotion.stat_bonus.get(name) {
-                        stat.base += bonus;
-                        stat.value += bonus;
-                    }
-                    if let Some(new_cap) = promotion.new_caps.get(name) {
-                        stat.cap = *new_cap;
-                    }
-                    stat
-                })
-            }
-        ));
+            .insert(
+                target_class.to_string(),
+                GbaPromotion {
+                    growth_change,
+                    stat_bonus,
+                    new_caps
+                }
+            )
+            .is_some();
+        self.save_promotions()?;
+
+        Ok(Some(format!(
+            "Successfully {} the promotion to {target_class}.",
+            if replaced { "replaced" } else { "added" }
+        )))
+    }
+
+    /// The target class of the promotion closest to the input, which has to be
+    /// the only one that close.
+    fn find_promotion(&self, input : &str) -> Result<String, Error> {
+        let classes = self
+            .promotions
+            .keys()
+            .map(String::as_str)
+            .sorted()
+            .collect_vec();
+        match closest_matches(input, &classes).as_slice() {
+            [] => Err(Error::NoPromotionFound(input.to_string())),
+            [(_score, target_class)] => Ok(target_class.to_string()),
+            candidates => Err(Error::AmbiguousPromotion(
+                input.to_string(),
+                candidates
+                    .iter()
+                    .map(|(_score, target_class)| target_class.to_string())
+                    .collect()
+            ))
+        }
+    }
+
+    fn add_promotion_internal(&mut self, target_class : &str) -> Result<(), Error> {
+        let promotion = promotion_change(&self.promotions, target_class)?;
+        self.progressions
+            .push((Some(target_class.to_string()), promotion));
         Ok(())
     }
+
+    /// The progression as it's saved, see [`changes_of`].
+    fn progression(&self) -> Vec<Option<String>> {
+        self.progressions
+            .iter()
+            .map(|(promotion, _change)| promotion.clone())
+            .collect()
+    }
+
+    /// The histograms of the unit after every stat change of the progression,
+    /// computed on a worker thread that shows its progress and stops on Ctrl+C.
+    fn compute_histograms(
+        &self,
+        unit : &Character<GBASIT>,
+        progression : Vec<Option<String>>,
+        command : &'static str
+    ) -> Result<Vec<Histograms>, Error> {
+        let unit = unit.clone();
+        let promotions = self.promotions.clone();
+        interruptible(move || {
+            // rebuilt on the worker as the stat changes can't be sent to it
+            let levels = changes_of(&promotions, &progression)?;
+            let stream = stream_histograms(&levels, &unit).ok_or(Error::NotImplemented(command))?;
+            let mut histograms = Vec::with_capacity(levels.len() + 1);
+            for histogram in stream {
+                if interrupted() {
+                    break;
+                }
+                histograms.push(histogram);
+                eprint!(
+                    "\rcomputed {} of {} levels",
+                    histograms.len(),
+                    levels.len() + 1
+                );
+                io::stderr().flush()?;
+            }
+            eprintln!();
+            Ok(histograms)
+        })
+    }
+
+    fn read_unit(&self, name : &str, format : Format) -> Result<Character<GBASIT>, Error> {
+        let path = self.unit_path(name, format);
+        let file = fs::File::open(&path).map_err(|error| match error.kind() {
+            io::ErrorKind::NotFound => io::Error::new(
+                error.kind(),
+                format!("no unit {name} is saved at {}", path.display())
+            ),
+            _ => error
+        })?;
+        match format {
+            Format::Json => Ok(serde_json::from_reader(&file)?),
+            Format::Csv => export::read_unit(file)
+        }
+    }
+
+    /// The saved progression, `None` if there's none of that name.
+    fn read_progression(&self, filename : &str) -> Result<Option<Vec<Option<String>>>, Error> {
+        match fs::File::open(self.progression_path(filename)) {
+            Ok(file) => Ok(Some(serde_json::from_reader(&file)?)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error.into())
+        }
+    }
+
+    /// The histograms of the saved unit after the progression saved under their
+    /// name, or of the unit as they are if there's none.
+    fn saved_unit_histograms(
+        &self,
+        name : &str
+    ) -> Result<(Character<GBASIT>, Vec<Histograms>), Error> {
+        let unit = self.read_unit(name, Format::Json)?;
+        let progression = self.read_progression(name)?.unwrap_or_default();
+        let histograms = self.compute_histograms(&unit, progression, "compare")?;
+        Ok((unit, histograms))
+    }
+
+    /// The labels of the rows of the heat map, i.e. the level the unit is at
+    /// after each stat change or the class they promoted to.
+    fn heat_map_labels(&self, unit : &Character<GBASIT>) -> Vec<String> {
+        let mut level = unit.level;
+        let mut labels = vec![format!("Lv {level}")];
+        for (promotion, _change) in &self.progressions {
+            labels.push(match promotion {
+                Some(target_class) => {
+                    level = 1;
+                    target_class.clone()
+                },
+                None => {
+                    level += 1;
+                    format!("Lv {level}")
+                }
+            });
+        }
+        labels
+    }
 }
 
-fn find_closest<'b>(input : &str, options : &[&'b str]) -> Option<(usize, &'b str)> {
-    let best_matches = options
+fn promotion_change(
+    promotions : &HashMap<String, GbaPromotion>,
+    target_class : &str
+) -> Result<StatChange<GBASIT>, Error> {
+    let promotion = promotions
+        .get(target_class)
+        .ok_or(Error::NoPromotionFound(target_class.to_string()))?
+        .clone();
+    Ok(StatChange::Promotion {
+        promo_changes : Arc::new(move |name : &GBASIT, mut stat : Stat| {
+            if !GBA_NON_GROWABLE_STATS.contains(&name.as_str()) {
+                stat.growth += promotion.growth_change;
+            }
+            if let Some(bonus) = promotion.stat_bonus.get(name) {
+                stat.base += bonus;
+                stat.value += bonus;
+            }
+            if let Some(new_cap) = promotion.new_caps.get(name) {
+                stat.cap = *new_cap;
+            }
+            stat
+        })
+    })
+}
+
+/// The stat changes of the progression as it's saved, i.e. the target classes
+/// of the promotions and `None` for level ups.
+fn changes_of(
+    promotions : &HashMap<String, GbaPromotion>,
+    progression : &[Option<String>]
+) -> Result<Vec<StatChange<GBASIT>>, Error> {
+    progression
+        .iter()
+        .map(|promotion| match promotion {
+            Some(target_class) => promotion_change(promotions, target_class),
+            None => Ok(GBA_REFERENCE_LEVEL_UP)
+        })
+        .collect()
+}
+
+/// Sorts the stats as the game lists them, any others last.
+fn gba_order(stat : &str) -> usize {
+    GBA_STATS
+        .iter()
+        .position(|known| *known == stat)
+        .unwrap_or(GBA_STATS.len())
+}
+
+/// The histograms after the stat change of the index, 0 being the unit as they
+/// are, or after the last one.
+fn snapshot(histograms : &[Histograms], index : Option<usize>) -> Result<&Histograms, Error> {
+    match index {
+        _ if histograms.is_empty() => Err(Error::NotImplemented("compare")),
+        Some(index) => histograms
+            .get(index)
+            .ok_or_else(|| Error::BadArgument("level".to_string())),
+        None => Ok(&histograms[histograms.len() - 1])
+    }
+}
+
+/// The table of how the stats of the first unit compare to those of the
+/// second, followed by the stats only one of them has.
+fn render_comparison(names : [&str; 2], first : &Histograms, second : &Histograms) -> String {
+    let rows = first
+        .iter()
+        .filter_map(|(stat, histogram)| Some((stat, histogram, second.get(stat)?)))
+        .sorted_by_key(|(stat, _first, _second)| gba_order(stat))
+        .map(|(stat, first, second)| {
+            let comparison = compare_histograms(first, second);
+            format!(
+                "{:<5}{:>+14.2}{:>11.3}%",
+                stat.to_uppercase(),
+                comparison.mean_difference,
+                comparison.probability_at_least * 100.0
+            )
+        });
+    let mut lines = vec![
+        format!("A: {}, B: {}", names[0], names[1]),
+        format!("{:<5}{:>14}{:>12}", "stat", "mean A - B", "P(A ≥ B)"),
+    ];
+    lines.extend(rows);
+    for (name, own, other) in [(names[0], first, second), (names[1], second, first)] {
+        let omitted = own
+            .keys()
+            .filter(|stat| !other.contains_key(*stat))
+            .map(|stat| stat.to_uppercase())
+            .join(", ");
+        if !omitted.is_empty() {
+            lines.push(format!(
+                "Only {name} has {omitted}, so they weren't compared."
+            ));
+        }
+    }
+    lines.join("\n")
+}
+
+/// Marks cells with their probability in the narrow heat map, see
+/// [`HEAT_MAP_LEGEND`].
+fn shade(probability : f64) -> char {
+    match probability {
+        p if p <= 0.0 => ' ',
+        p if p < 0.005 => '·',
+        p if p < 0.1 => '░',
+        p if p < 0.25 => '▒',
+        p if p < 0.5 => '▓',
+        _ => '█'
+    }
+}
+
+const HEAT_MAP_LEGEND : &str = "· below 0.5%  ░ below 10%  ▒ below 25%  ▓ below 50%  █ 50% or more";
+/// given as the stat or after it to show the probabilities as numbers
+const WIDE_FLAG : &str = "--wide";
+
+/// the reductions save_histograms takes: the histograms of every stat after
+/// every stat change, those of the stat given as the reduction_param, or those
+/// after the stat change of the index given, 0 being the unit as they are
+const HISTOGRAMS_UNREDUCED : &str = "none";
+const HISTOGRAMS_BY_STAT : &str = "stat";
+const HISTOGRAMS_BY_LEVEL : &str = "level";
+
+/// The distributions of the stat after each stat change, the levels as rows
+/// and the stat's values as columns.
+fn render_heat_map(
+    stat : &str,
+    labels : &[String],
+    histograms : &[BTreeMap<GBASIT, BTreeMap<StatType, f64>>],
+    wide : bool
+) -> String {
+    let distributions = histograms
+        .iter()
+        .map(|histogram| histogram.get(stat).cloned().unwrap_or_default())
+        .collect_vec();
+    let (lowest, highest) = match distributions
+        .iter()
+        .flat_map(|distribution| distribution.keys().copied())
+        .minmax()
+        .into_option()
+    {
+        Some(range) => range,
+        None => return format!("{} isn't a stat of the unit.", stat.to_uppercase())
+    };
+    let label_width = labels
+        .iter()
+        .map(|label| label.chars().count())
+        .max()
+        .unwrap_or(0);
+    let cell_width = if wide { 9 } else { 3 };
+
+    let mut rendered = format!("{:<label_width$}", stat.to_uppercase());
+    for value in lowest..=highest {
+        rendered += &format!("{value:>cell_width$}");
+    }
+    for (label, distribution) in labels.iter().zip(&distributions) {
+        rendered += &format!("\n{label:<label_width$}");
+        for value in lowest..=highest {
+            let probability = distribution.get(&value).copied().unwrap_or(0.0);
+            rendered += &if !wide {
+                format!(" {0}{0}", shade(probability))
+            }
+            else if probability > 0.0 {
+                format!(" {:>7.3}%", probability * 100.0)
+            }
+            else {
+                " ".repeat(cell_width)
+            };
+        }
+    }
+    rendered
+}
+
+/// Reads `stat=value` lines until an empty one, asking again for lines that
+/// aren't.
+fn read_stat_values(
+    input : &mut impl BufRead,
+    output : &mut impl Write,
+    prompt : &str
+) -> Result<HashMap<GBASIT, StatType>, Error> {
+    writeln!(
+        output,
+        "{prompt}, one stat=value per line and an empty line to finish:"
+    )?;
+    let mut values = HashMap::new();
+    loop {
+        output.flush()?;
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            return Ok(values);
+        }
+        let parsed = line.trim().split_once('=').and_then(|(stat, value)| {
+            let (_score, stat) = find_closest(stat.trim(), &GBA_STATS)?;
+            Some((stat, value.trim().parse::<StatType>().ok()?))
+        });
+        match parsed {
+            Some((stat, value)) => {
+                values.insert(stat.to_string(), value);
+            },
+            None => writeln!(output, "Expected a stat and a number like hp=2, try again.")?
+        }
+    }
+}
+
+/// The options closest to the input with their distance to it, several if
+/// they're equally close.
+fn closest_matches<'b>(input : &str, options : &[&'b str]) -> Vec<(usize, &'b str)> {
+    options
         .iter()
         .map(|vo| {
             (
                 strsim::damerau_levenshtein(&input.to_lowercase(), &vo.to_lowercase()),
-                vo
+                *vo
             )
         })
         .sorted_by_key(|(score, _value)| *score)
@@ -108,13 +552,17 @@ fn find_closest<'b>(input : &str, options : &[&'b str]) -> Option<(usize, &'b st
         .into_iter()
         .take(1)
         .flat_map(|(_score, group)| group)
-        .collect_vec();
+        .collect_vec()
+}
+
+fn find_closest<'b>(input : &str, options : &[&'b str]) -> Option<(usize, &'b str)> {
+    let best_matches = closest_matches(input, options);
 
     if best_matches.len() != 1 {
         None
     }
     else {
-        best_matches.first().map(|(score, value)| (*score, **value))
+        best_matches.first().copied()
     }
 }
 
@@ -141,13 +589,15 @@ impl FeRepl for GbaFe {
             baseline_stats.insert(stat.to_string(), GBA_REFERENCE_BASE_STAT);
         }
 
-        let name = args["name"].convert()?;
+        let name : String = argument(&args, "name")?;
 
         let output_message = format!("Successfully created empty unit {name}.");
 
+        self.remember(self.snapshot());
         self.unit = Some(Character {
             stats : baseline_stats,
-            name
+            name,
+            level : 1
         });
 
         Ok(Some(output_message))
@@ -189,9 +639,14 @@ impl FeRepl for GbaFe {
         )))
     }
 
-    fn new_promotion(&mut self, args : Arguments) -> Return { todo!() }
+    fn new_promotion(&mut self, args : Arguments) -> Return {
+        let target_class : String = argument(&args, "target_class")?;
+
+        self.new_promotion_with(&target_class, &mut io::stdin().lock(), &mut io::stdout())
+    }
 
     fn add_level(&mut self, _args : Arguments) -> Return {
+        self.remember(self.snapshot());
         self.progressions.push((None, GBA_REFERENCE_LEVEL_UP));
 
         Ok(Some(format!(
@@ -201,9 +656,12 @@ impl FeRepl for GbaFe {
     }
 
     fn add_promotion(&mut self, args : Arguments) -> Return {
-        let target_class : String = args["target_class"].convert()?;
+        let input : String = argument(&args, "target_class")?;
+        let target_class = self.find_promotion(&input)?;
 
+        let snapshot = self.snapshot();
         self.add_promotion_internal(&target_class)?;
+        self.remember(snapshot);
 
         Ok(Some(format!(
             "Successfully added a {target_class} promotion to {}'s progression.",
@@ -211,75 +669,188 @@ impl FeRepl for GbaFe {
         )))
     }
 
-    fn heat_map(&mut self, args : Arguments) -> Return { todo!() }
+    fn list_promotions(&mut self, _args : Arguments) -> Return {
+        if self.promotions.is_empty() {
+            return Ok(Some(
+                "There are no promotions yet, add them with new_promotion.".to_string()
+            ));
+        }
+        let lines = self
+            .promotions
+            .iter()
+            .sorted_by(|(first, _promotion), (second, _other)| first.cmp(second))
+            .map(|(target_class, promotion)| {
+                let mut bonuses = promotion
+                    .stat_bonus
+                    .iter()
+                    .sorted_by_key(|(stat, _bonus)| gba_order(stat))
+                    .map(|(stat, bonus)| format!("+{bonus} {}", stat.to_uppercase()))
+                    .collect_vec();
+                if promotion.growth_change != 0 {
+                    bonuses.push(format!("+{}% growths", promotion.growth_change));
+                }
+                if bonuses.is_empty() {
+                    bonuses.push("no bonuses".to_string());
+                }
+                format!("{target_class}: {}", bonuses.join(", "))
+            });
+        Ok(Some(lines.join("\n")))
+    }
 
-    fn save_unit(&mut self, _args : Arguments) -> Return {
-        let filename = format!(
-            "./data/characters/{}/{}.json",
-            self.game,
-            self.name()?.to_lowercase()
-        );
+    fn heat_map(&mut self, args : Arguments) -> Return {
+        let options = ["stat", "flags"]
+            .iter()
+            .filter_map(|name| args.get(*name))
+            .collect_vec();
+        let wide = options.iter().any(|option| *option == WIDE_FLAG);
+        let stats = match options.iter().find(|option| **option != WIDE_FLAG) {
+            Some(input) => {
+                let (_score, stat) = find_closest(input, &GBA_STATS)
+                    .ok_or_else(|| Error::StatNotFound(input.to_string()))?;
+                vec![stat]
+            },
+            None => GBA_STATS.to_vec()
+        };
+
+        let unit = self.unit.as_ref().ok_or(Error::NoUnit)?;
+        let labels = self.heat_map_labels(unit);
+        let histograms = self.compute_histograms(unit, self.progression(), "heat_map")?;
+
+        let blocks = stats
+            .into_iter()
+            .map(|stat| render_heat_map(stat, &labels, &histograms, wide))
+            .join("\n\n");
+        if wide {
+            Ok(Some(blocks))
+        }
+        else {
+            Ok(Some(format!("{HEAT_MAP_LEGEND}\n\n{blocks}")))
+        }
+    }
 
-        let path = Path::new(&filename);
+    fn compare(&mut self, args : Arguments) -> Return {
+        let first : String = argument(&args, "unit_a")?;
+        let second : String = argument(&args, "unit_b")?;
+        let level = match args.get("level") {
+            Some(_level) => Some(argument::<usize>(&args, "level")?),
+            None => None
+        };
+
+        let (first_unit, first_histograms) = self.saved_unit_histograms(&first)?;
+        let (second_unit, second_histograms) = self.saved_unit_histograms(&second)?;
+
+        Ok(Some(render_comparison(
+            [&first_unit.name, &second_unit.name],
+            snapshot(&first_histograms, level)?,
+            snapshot(&second_histograms, level)?
+        )))
+    }
 
-        std::fs::create_dir_all(&path.parent().ok_or(io::Error::new(
-            io::ErrorKind::NotFound,
-            format!("{}", path.display())
-        ))?)?;
+    fn show(&mut self, args : Arguments) -> Return {
+        let unit = self.unit.as_ref().ok_or(Error::NoUnit)?;
+        let shown = match args.get("stat") {
+            Some(input) => {
+                let (_score, stat) = find_closest(input, &GBA_STATS)
+                    .ok_or_else(|| Error::StatNotFound(input.to_string()))?;
+                Some(stat)
+            },
+            None => None
+        };
+
+        let mut lines = vec![
+            format!("{}, level {}", unit.name, unit.level),
+            format!(
+                "{:<5}{:>6}{:>6}{:>7}{:>5}",
+                "stat", "base", "value", "growth", "cap"
+            ),
+        ];
+        let rows = unit
+            .stats
+            .iter()
+            .filter(|(stat, _values)| shown.map_or(true, |shown| *stat == shown))
+            .sorted_by_key(|(stat, _values)| gba_order(stat))
+            .map(|(stat, values)| {
+                format!(
+                    "{:<5}{:>6}{:>6}{:>6}%{:>5}",
+                    stat.to_uppercase(),
+                    values.base,
+                    values.value,
+                    values.growth,
+                    values.cap
+                )
+            });
+        lines.extend(rows);
+
+        if self.progressions.is_empty() {
+            lines.push("No stat changes yet.".to_string());
+        }
+        else {
+            lines.push("Stat changes:".to_string());
+        }
+        // numbered as the level arguments of compare and save_histograms count them
+        for (index, (promotion, _change)) in self.progressions.iter().enumerate() {
+            lines.push(match promotion {
+                Some(target_class) => format!("{:>3}: promotion to {target_class}", index + 1),
+                None => format!("{:>3}: level up", index + 1)
+            });
+        }
+        Ok(Some(lines.join("\n")))
+    }
 
-        let file = std::fs::OpenOptions::new()
-            .create(true)
-            .truncate(true)
-            .write(true)
-            .open(&path)?;
+    fn undo(&mut self, _args : Arguments) -> Return {
+        let (unit, progression) = match self.history.pop() {
+            Some(snapshot) => snapshot,
+            None => return Ok(Some("There's nothing to undo.".to_string()))
+        };
+        let changes = changes_of(&self.promotions, &progression)?;
+        self.unit = unit;
+        self.progressions = progression.into_iter().zip(changes).collect();
+        Ok(Some(format!(
+            "Reverted the last change, {} more can be undone.",
+            self.history.len()
+        )))
+    }
+
+    fn save_unit(&mut self, args : Arguments) -> Return {
+        let format = format(&args)?;
+        let path = self.unit_path(self.name()?, format);
 
-        serde_json::to_writer_pretty(&file, &self.unit()?)?;
+        let file = create_file(&path)?;
+
+        match format {
+            Format::Json => serde_json::to_writer_pretty(&file, &self.unit()?)?,
+            Format::Csv => export::write_unit(file, self.unit()?)?
+        }
 
         Ok(Some(format!(
-            "Successfully saved {} to {filename}",
-            self.name()?
+            "Successfully saved {} to {}",
+            self.name()?,
+            path.display()
         )))
     }
 
     fn load_unit(&mut self, args : Arguments) -> Return {
-        let loaded_unit : String = args["unit_name"].convert()?;
+        let loaded_unit : String = argument(&args, "unit_name")?;
 
-        let filename = format!(
-            "./data/characters/{}/{}.json",
-            self.game,
-            loaded_unit.to_lowercase()
-        );
-
-        let file = std::fs::OpenOptions::new().read(true).open(&filename)?;
+        let format = format(&args)?;
+        let path = self.unit_path(&loaded_unit, format);
 
-        let unit : Character<String> = serde_json::from_reader(&file)?;
+        let unit = self.read_unit(&loaded_unit, format)?;
         let name = unit.name.clone();
 
+        self.remember(self.snapshot());
         self.unit = Some(unit);
 
-        Ok(Some(format!("Successfully read {name} from {filename}")))
+        Ok(Some(format!(
+            "Successfully read {name} from {}",
+            path.display()
+        )))
     }
 
     fn save_progression(&mut self, args : Arguments) -> Return {
-        let filename : String = args["filename"].convert()?;
-        let actual_filename = format!(
-            "./data/progressions/{}/{}.json",
-            self.game,
-            filename.to_lowercase()
-        );
+        let filename : String = argument(&args, "filename")?;
 
-        let path = Path::new(&actual_filename);
-
-        std::fs::create_dir_all(&path.parent().ok_or(io::Error::new(
-            io::ErrorKind::NotFound,
-            format!("{}", path.display())
-        ))?)?;
-
-        let file = std::fs::OpenOptions::new()
-            .create(true)
-            .truncate(true)
-            .write(true)
-            .open(&path)?;
+        let file = create_file(&self.progression_path(&filename))?;
 
         serde_json::to_writer_pretty(
             file,
@@ -297,19 +868,15 @@ impl FeRepl for GbaFe {
     }
 
     fn load_progression(&mut self, args : Arguments) -> Return {
-        let filename : String = args["filename"].convert()?;
-        let actual_filename = format!(
-            "./data/progressions/{}/{}.json",
-            self.game,
-            filename.to_lowercase()
-        );
+        let filename : String = argument(&args, "filename")?;
 
-        let file = std::fs::OpenOptions::new()
+        let file = fs::OpenOptions::new()
             .read(true)
-            .open(&actual_filename)?;
+            .open(self.progression_path(&filename))?;
 
         let progression : Vec<Option<String>> = serde_json::from_reader(&file)?;
 
+        let snapshot = self.snapshot();
         self.progressions.clear();
 
         for item in progression {
@@ -320,6 +887,7 @@ impl FeRepl for GbaFe {
                 self.progressions.push((None, GBA_REFERENCE_LEVEL_UP));
             }
         }
+        self.remember(snapshot);
 
         Ok(Some(format!(
             "Successfully loaded the current progression for {} from \"{filename}\".",
@@ -328,10 +896,372 @@ impl FeRepl for GbaFe {
     }
 
     fn save_histograms(&mut self, args : Arguments) -> Return {
-        // TODO: offer reduction to one stat type here and reduction to one specific
-        // level-up
-        // ... the latter one needs an index?
-        // also maybe we should track a base-level for a character?
-        todo!()
+        let filename : String = argument(&args, "filename")?;
+        let reduction : String = argument(&args, "reduction")?;
+        let format = format(&args)?;
+
+        let unit = self.unit.as_ref().ok_or(Error::NoUnit)?;
+        let histograms = self.compute_histograms(unit, self.progression(), "save_histograms")?;
+
+        // checked before anything is written so that bad arguments leave no
+        // empty file behind
+        let reduction = match reduction.as_str() {
+            HISTOGRAMS_UNREDUCED => Reduction::None,
+            HISTOGRAMS_BY_STAT => {
+                let input : String = argument(&args, "reduction_param")?;
+                let (_score, stat) = find_closest(&input, &GBA_STATS)
+                    .ok_or_else(|| Error::StatNotFound(input.clone()))?;
+                Reduction::Stat(stat)
+            },
+            HISTOGRAMS_BY_LEVEL => {
+                let index : usize = argument(&args, "reduction_param")?;
+                if index >= histograms.len() {
+                    return Err(Error::BadArgument("reduction_param".to_string()));
+                }
+                Reduction::Level(index)
+            },
+            _ => return Err(Error::BadArgument("reduction".to_string()))
+        };
+
+        let path = self.histograms_path(&filename, format);
+        let mut saved = path.display().to_string();
+        match format {
+            Format::Json => {
+                let json = match reduction {
+                    Reduction::None => serde_json::to_string_pretty(&histograms)?,
+                    Reduction::Stat(stat) => {
+                        let reduced = histograms
+                            .iter()
+                            .map(|histogram| histogram.get(stat).cloned().unwrap_or_default())
+                            .collect_vec();
+                        serde_json::to_string_pretty(&reduced)?
+                    },
+                    Reduction::Level(index) => serde_json::to_string_pretty(&histograms[index])?
+                };
+                create_file(&path)?.write_all(json.as_bytes())?;
+            },
+            Format::Csv => {
+                let means_path = self.histograms_path(&format!("{filename} means"), format);
+                export::write_histograms(
+                    create_file(&path)?,
+                    create_file(&means_path)?,
+                    &histograms,
+                    &reduction
+                )?;
+                saved += &format!(" and the means to {}", means_path.display());
+            }
+        }
+
+        Ok(Some(format!(
+            "Successfully saved the histograms for {} to {saved}",
+            self.name()?
+        )))
+    }
+
+    fn take_unit(&mut self) -> Option<Character<GBASIT>> { self.unit.take() }
+
+    fn set_unit(&mut self, unit : Character<GBASIT>) { self.unit = Some(unit) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arguments(pairs : &[(&str, &str)]) -> Arguments {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn units_and_progressions_are_saved_and_loaded() {
+        let data_dir = std::env::temp_dir().join(format!("fe-levels-repl-{}", std::process::id()));
+        fs::create_dir_all(data_dir.join("promotions")).unwrap();
+        fs::write(
+            data_dir.join("promotions").join("fe8.json"),
+            r#"{ "Paladin": { "growth_change": 0, "stat_bonus": { "hp": 2 }, "new_caps": {} } }"#
+        )
+        .unwrap();
+        let mut repl = GbaFe::with_data_dir("fe8", data_dir.clone()).unwrap();
+
+        repl.new_unit(arguments(&[("name", "Seth")])).unwrap();
+        repl.update_base(arguments(&[("stat", "hp"), ("value", "30")]))
+            .unwrap();
+        FeRepl::update_stat(&mut repl, arguments(&[("stat", "hp"), ("value", "31")])).unwrap();
+        repl.update_growth(arguments(&[("stat", "atk"), ("value", "50")]))
+            .unwrap();
+        repl.update_cap(arguments(&[("stat", "atk"), ("value", "25")]))
+            .unwrap();
+        repl.add_level(arguments(&[])).unwrap();
+        repl.add_level(arguments(&[])).unwrap();
+        repl.add_promotion(arguments(&[("target_class", "Paladin")]))
+            .unwrap();
+        repl.save_unit(arguments(&[])).unwrap();
+        repl.save_progression(arguments(&[("filename", "Seth Route")]))
+            .unwrap();
+
+        repl.new_unit(arguments(&[("name", "Franz")])).unwrap();
+        repl.progressions.clear();
+        repl.load_unit(arguments(&[("unit_name", "Seth")])).unwrap();
+        repl.load_progression(arguments(&[("filename", "seth route")]))
+            .unwrap();
+
+        let unit = repl.unit.as_ref().unwrap();
+        assert_eq!(unit.name, "Seth");
+        assert_eq!(unit.level, 1);
+        assert_eq!(unit.stats["hp"].base, 30);
+        assert_eq!(unit.stats["hp"].value, 31);
+        assert_eq!(unit.stats["atk"].growth, 50);
+        assert_eq!(unit.stats["atk"].cap, 25);
+        let classes = repl
+            .progressions
+            .iter()
+            .map(|(class, _change)| class.clone())
+            .collect_vec();
+        assert_eq!(classes, vec![None, None, Some("Paladin".to_string())]);
+
+        fs::remove_dir_all(data_dir).unwrap();
+    }
+
+    #[test]
+    fn unknown_stats_and_promotions_are_reported() {
+        let data_dir =
+            std::env::temp_dir().join(format!("fe-levels-repl-empty-{}", std::process::id()));
+        let mut repl = GbaFe::with_data_dir("fe8", data_dir).unwrap();
+
+        assert!(repl
+            .update_base(arguments(&[("stat", "hp"), ("value", "30")]))
+            .is_err());
+        repl.new_unit(arguments(&[("name", "Seth")])).unwrap();
+        assert!(repl
+            .update_base(arguments(&[("stat", "hp"), ("value", "lots")]))
+            .is_err());
+        assert!(repl
+            .add_promotion(arguments(&[("target_class", "Paladin")]))
+            .is_err());
+    }
+
+    #[test]
+    fn heat_map_shows_the_stat_after_every_level() {
+        let data_dir =
+            std::env::temp_dir().join(format!("fe-levels-repl-heat-{}", std::process::id()));
+        let mut repl = GbaFe::with_data_dir("fe8", data_dir).unwrap();
+        repl.new_unit(arguments(&[("name", "Seth")])).unwrap();
+        repl.update_growth(arguments(&[("stat", "hp"), ("value", "100")]))
+            .unwrap();
+        repl.add_level(arguments(&[])).unwrap();
+
+        let narrow = repl
+            .heat_map(arguments(&[("stat", "hp")]))
+            .unwrap()
+            .unwrap();
+        let lines = narrow.lines().collect_vec();
+        assert_eq!(lines[0], HEAT_MAP_LEGEND);
+        assert_eq!(lines[2], "HP    0  1");
+        assert_eq!(lines[3], "Lv 1 ██   ");
+        assert_eq!(lines[4], "Lv 2    ██");
+
+        let wide = repl
+            .heat_map(arguments(&[("stat", WIDE_FLAG), ("flags", "hp")]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(wide.lines().last().unwrap(), "Lv 2          100.000%");
+    }
+
+    #[test]
+    fn new_promotions_are_saved_and_can_be_added() {
+        let data_dir =
+            std::env::temp_dir().join(format!("fe-levels-repl-promotion-{}", std::process::id()));
+        let mut repl = GbaFe::with_data_dir("fe8", data_dir.clone()).unwrap();
+        repl.new_unit(arguments(&[("name", "Seth")])).unwrap();
+
+        let mut input = io::Cursor::new("hp=2\nskill=1\nnonsense\n\natk=25\n\n5\n");
+        let mut output = Vec::new();
+        repl.new_promotion_with("Paladin", &mut input, &mut output)
+            .unwrap();
+        assert!(String::from_utf8(output).unwrap().contains("try again"));
+        repl.add_promotion(arguments(&[("target_class", "Paladin")]))
+            .unwrap();
+
+        let reloaded = GbaFe::with_data_dir("fe8", data_dir.clone()).unwrap();
+        let paladin = &reloaded.promotions["Paladin"];
+        assert_eq!(paladin.growth_change, 5);
+        assert_eq!(paladin.stat_bonus["hp"], 2);
+        assert_eq!(paladin.stat_bonus["skl"], 1);
+        assert_eq!(paladin.new_caps["atk"], 25);
+
+        fs::remove_dir_all(data_dir).unwrap();
+    }
+
+    #[test]
+    fn saved_units_are_compared_after_their_progressions() {
+        let data_dir =
+            std::env::temp_dir().join(format!("fe-levels-repl-compare-{}", std::process::id()));
+        let mut repl = GbaFe::with_data_dir("fe8", data_dir.clone()).unwrap();
+        repl.new_unit(arguments(&[("name", "Seth")])).unwrap();
+        FeRepl::update_stat(&mut repl, arguments(&[("stat", "hp"), ("value", "5")])).unwrap();
+        repl.update_growth(arguments(&[("stat", "hp"), ("value", "100")]))
+            .unwrap();
+        repl.save_unit(arguments(&[])).unwrap();
+        repl.add_level(arguments(&[])).unwrap();
+        repl.save_progression(arguments(&[("filename", "seth")]))
+            .unwrap();
+        repl.new_unit(arguments(&[("name", "Franz")])).unwrap();
+        FeRepl::update_stat(&mut repl, arguments(&[("stat", "hp"), ("value", "5")])).unwrap();
+        repl.unit().unwrap().stats.remove("mov");
+        repl.save_unit(arguments(&[])).unwrap();
+
+        let table = repl
+            .compare(arguments(&[("unit_a", "seth"), ("unit_b", "franz")]))
+            .unwrap()
+            .unwrap();
+        let lines = table.lines().collect_vec();
+        assert_eq!(lines[0], "A: Seth, B: Franz");
+        assert_eq!(lines[2], "HP            +1.00    100.000%");
+        assert_eq!(
+            lines.last().unwrap(),
+            &"Only Seth has MOV, so they weren't compared."
+        );
+
+        let before = repl
+            .compare(arguments(&[
+                ("unit_a", "Seth"),
+                ("unit_b", "Franz"),
+                ("level", "0")
+            ]))
+            .unwrap()
+            .unwrap();
+        assert!(before.contains("HP            +0.00    100.000%"));
+        assert!(repl
+            .compare(arguments(&[("unit_a", "Seth"), ("unit_b", "Eirika")]))
+            .is_err());
+
+        fs::remove_dir_all(data_dir).unwrap();
+    }
+
+    #[test]
+    fn units_and_histograms_are_saved_as_csv() {
+        let data_dir =
+            std::env::temp_dir().join(format!("fe-levels-repl-csv-{}", std::process::id()));
+        let mut repl = GbaFe::with_data_dir("fe8", data_dir.clone()).unwrap();
+        repl.new_unit(arguments(&[("name", "Seth")])).unwrap();
+        repl.update_growth(arguments(&[("stat", "hp"), ("value", "100")]))
+            .unwrap();
+        // the stats of units loaded from json may be named anything
+        repl.unit()
+            .unwrap()
+            .stats
+            .insert("hp, \"max\"".to_string(), GBA_REFERENCE_BASE_STAT);
+        repl.save_unit(arguments(&[("format", "CSV")])).unwrap();
+        let saved = repl.unit.clone().unwrap();
+        repl.new_unit(arguments(&[("name", "Franz")])).unwrap();
+        repl.load_unit(arguments(&[("unit_name", "seth"), ("format", "csv")]))
+            .unwrap();
+        assert_eq!(repl.unit.as_ref().unwrap(), &saved);
+
+        repl.add_level(arguments(&[])).unwrap();
+        repl.save_histograms(arguments(&[
+            ("filename", "Seth"),
+            ("reduction", "stat"),
+            ("reduction_param", "hp"),
+            ("format", "csv")
+        ]))
+        .unwrap();
+        let histograms_dir = data_dir.join("histograms").join("fe8");
+        let distributions = fs::read_to_string(histograms_dir.join("seth.csv")).unwrap();
+        assert_eq!(
+            distributions.lines().collect_vec(),
+            vec!["level,stat,value,probability", "0,hp,0,1.0", "1,hp,1,1.0"]
+        );
+        let means = fs::read_to_string(histograms_dir.join("seth means.csv")).unwrap();
+        assert_eq!(
+            means.lines().collect_vec(),
+            vec!["level,stat,mean", "0,hp,0.0", "1,hp,1.0"]
+        );
+
+        assert!(repl.save_unit(arguments(&[("format", "xml")])).is_err());
+        fs::remove_dir_all(data_dir).unwrap();
+    }
+
+    #[test]
+    fn promotions_are_matched_fuzzily_and_listed() {
+        let data_dir =
+            std::env::temp_dir().join(format!("fe-levels-repl-fuzzy-{}", std::process::id()));
+        fs::create_dir_all(data_dir.join("promotions")).unwrap();
+        fs::write(
+            data_dir.join("promotions").join("fe8.json"),
+            r#"{
+                "Rogue": { "growth_change": 0, "stat_bonus": {}, "new_caps": {} },
+                "Ranger": {
+                    "growth_change": 5,
+                    "stat_bonus": { "def": 2, "hp": 3 },
+                    "new_caps": {}
+                }
+            }"#
+        )
+        .unwrap();
+        let mut repl = GbaFe::with_data_dir("fe8", data_dir.clone()).unwrap();
+        repl.new_unit(arguments(&[("name", "Colm")])).unwrap();
+
+        repl.add_promotion(arguments(&[("target_class", "rouge")]))
+            .unwrap();
+        assert_eq!(repl.progressions[0].0, Some("Rogue".to_string()));
+        match repl.add_promotion(arguments(&[("target_class", "roger")])) {
+            Err(Error::AmbiguousPromotion(input, candidates)) => {
+                assert_eq!(input, "roger");
+                assert_eq!(candidates, vec!["Ranger".to_string(), "Rogue".to_string()]);
+            },
+            _ => panic!("roger should be as close to Ranger as to Rogue")
+        }
+        assert_eq!(repl.progressions.len(), 1);
+
+        let listed = repl.list_promotions(arguments(&[])).unwrap().unwrap();
+        assert_eq!(
+            listed,
+            "Ranger: +3 HP, +2 DEF, +5% growths\nRogue: no bonuses"
+        );
+
+        fs::remove_dir_all(data_dir).unwrap();
+    }
+
+    #[test]
+    fn changes_are_shown_and_undone() {
+        let data_dir =
+            std::env::temp_dir().join(format!("fe-levels-repl-undo-{}", std::process::id()));
+        let mut repl = GbaFe::with_data_dir("fe8", data_dir).unwrap();
+        repl.new_unit(arguments(&[("name", "Seth")])).unwrap();
+        repl.update_growth(arguments(&[("stat", "hp"), ("value", "55")]))
+            .unwrap();
+        repl.update_growth(arguments(&[("stat", "hp"), ("value", "60")]))
+            .unwrap();
+        repl.add_level(arguments(&[])).unwrap();
+
+        let shown = repl.show(arguments(&[("stat", "hp")])).unwrap().unwrap();
+        assert_eq!(
+            shown.lines().collect_vec(),
+            vec![
+                "Seth, level 1",
+                "stat   base value growth  cap",
+                "HP        0     0    60%   20",
+                "Stat changes:",
+                "  1: level up"
+            ]
+        );
+
+        repl.undo(arguments(&[])).unwrap();
+        assert!(repl.progressions.is_empty());
+        repl.undo(arguments(&[])).unwrap();
+        assert_eq!(repl.unit.as_ref().unwrap().stats["hp"].growth, 55);
+        assert!(repl
+            .update_growth(arguments(&[("stat", "hp"), ("value", "lots")]))
+            .is_err());
+        repl.undo(arguments(&[])).unwrap();
+        repl.undo(arguments(&[])).unwrap();
+        assert!(repl.unit.is_none());
+        assert_eq!(
+            repl.undo(arguments(&[])).unwrap().unwrap(),
+            "There's nothing to undo."
+        );
     }
 }
@@ -0,0 +1,48 @@
+//! Long computations on a worker thread that Ctrl+C aborts, returning to the
+//! prompt rather than killing the REPL.
+
+use std::{
+    io,
+    sync::atomic::{AtomicBool, Ordering},
+    thread
+};
+
+use crate::Error;
+
+/// whether a computation is running, so that Ctrl+C aborts it
+static COMPUTING : AtomicBool = AtomicBool::new(false);
+static INTERRUPTED : AtomicBool = AtomicBool::new(false);
+
+/// Makes Ctrl+C abort the computation running or exit if there's none. The
+/// prompt handles Ctrl+C itself while it's waiting for input.
+pub(crate) fn handle_interrupts() -> Result<(), Error> {
+    ctrlc::set_handler(|| {
+        if COMPUTING.load(Ordering::SeqCst) {
+            INTERRUPTED.store(true, Ordering::SeqCst);
+        }
+        else {
+            std::process::exit(130);
+        }
+    })
+    .map_err(|error| io::Error::new(io::ErrorKind::Other, error.to_string()).into())
+}
+
+/// Whether Ctrl+C was pressed since the computation started, which it should
+/// check regularly to stop early.
+pub(crate) fn interrupted() -> bool { INTERRUPTED.load(Ordering::SeqCst) }
+
+/// Runs the computation on a worker thread while this one waits for it,
+/// failing with [`Error::Interrupted`] if Ctrl+C was pressed meanwhile.
+pub(crate) fn interruptible<T : Send + 'static>(
+    computation : impl FnOnce() -> Result<T, Error> + Send + 'static
+) -> Result<T, Error> {
+    INTERRUPTED.store(false, Ordering::SeqCst);
+    COMPUTING.store(true, Ordering::SeqCst);
+    let result = thread::spawn(computation).join();
+    COMPUTING.store(false, Ordering::SeqCst);
+    match result {
+        Err(panic) => std::panic::resume_unwind(panic),
+        Ok(_result) if interrupted() => Err(Error::Interrupted),
+        Ok(result) => result
+    }
+}
@@ -1,3 +1,6 @@
+mod export;
 mod gbafe;
+mod interrupt;
 pub use gbafe::*;
-pub(crate) use gbafe::*;
\ No newline at end of file
+pub(crate) use gbafe::*;
+pub(crate) use interrupt::handle_interrupts;
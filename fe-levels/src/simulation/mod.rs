@@ -1 +1,428 @@
+//! Monte Carlo sampling of progressions, rolling every level-up as the games
+//! do. It covers what the exact analysis can't: level-ups it has no handler
+//! for, and events over several stats at once, which blank avoidance ties
+//! together.
 
+use std::{
+    collections::BTreeMap,
+    ops::{Bound, RangeBounds},
+    sync::Arc
+};
+
+use rand::Rng;
+
+use crate::{
+    BlankAvoidance, Character, GrowthType, JointBenchmark, Stat, StatChange, StatIndexType,
+    StatType, GUARANTEED_STAT_POINT_GROWTH
+};
+
+/// the most passes over the stat order a level-up with guaranteed stats makes
+/// before settling for fewer stats, e.g. for growths of 0
+const MAX_GUARANTEED_PASSES : u32 = 100;
+
+/// the values of the stats at one point of a sampled run
+pub type StatValues<SIT> = BTreeMap<SIT, StatType>;
+
+type Event<'a, SIT> = Box<dyn Fn(&StatValues<SIT>) -> bool + 'a>;
+
+fn values<SIT : StatIndexType>(stats : &BTreeMap<SIT, Stat>) -> StatValues<SIT> {
+    stats
+        .iter()
+        .map(|(sit, stat)| (sit.clone(), stat.value))
+        .collect()
+}
+
+/// Rolls the growth, returning the points it gives and whether it counts as a
+/// hit for blank avoidance. Every 100 of the growth is a guaranteed point.
+fn roll(growth : GrowthType, rng : &mut impl Rng) -> (StatType, bool) {
+    let guaranteed = growth / GUARANTEED_STAT_POINT_GROWTH;
+    let chance = growth % GUARANTEED_STAT_POINT_GROWTH;
+    let hit = rng.gen_range(0..GUARANTEED_STAT_POINT_GROWTH) < chance;
+    (guaranteed + StatType::from(hit), hit || guaranteed > 0)
+}
+
+/// Rolls every growth once, returning the points and whether any roll hit.
+fn roll_all<SIT : StatIndexType>(
+    growths : &BTreeMap<SIT, GrowthType>,
+    rng : &mut impl Rng
+) -> (BTreeMap<SIT, StatType>, bool) {
+    let mut any_hit = false;
+    let points = growths
+        .iter()
+        .map(|(sit, growth)| {
+            let (points, hit) = roll(*growth, rng);
+            any_hit |= hit;
+            (sit.clone(), points)
+        })
+        .collect();
+    (points, any_hit)
+}
+
+fn below_maximum(range : &(Bound<u8>, Bound<u8>), grown : u8) -> bool {
+    match range.end_bound() {
+        Bound::Included(max) => grown < *max,
+        Bound::Excluded(max) => grown.saturating_add(1) < *max,
+        Bound::Unbounded => true
+    }
+}
+
+fn reached_minimum(range : &(Bound<u8>, Bound<u8>), grown : u8) -> bool {
+    match range.start_bound() {
+        Bound::Included(min) => grown >= *min,
+        Bound::Excluded(min) => grown > *min,
+        Bound::Unbounded => true
+    }
+}
+
+/// Rolls the stats in their order until the number of stats that grew lies in
+/// the range, as FE10's bonus experience and FE16's students do. Every stat
+/// grows at most once and capped stats are passed over. An empty order rolls
+/// the stats in their own order.
+fn guaranteed_rolls<SIT : StatIndexType>(
+    stats : &BTreeMap<SIT, Stat>,
+    growths : &BTreeMap<SIT, GrowthType>,
+    range : &(Bound<u8>, Bound<u8>),
+    order : &[SIT],
+    rng : &mut impl Rng
+) -> BTreeMap<SIT, StatType> {
+    let order : Vec<&SIT> = if order.is_empty() {
+        growths.keys().collect()
+    }
+    else {
+        order
+            .iter()
+            .filter(|sit| growths.contains_key(*sit))
+            .collect()
+    };
+    let mut points = BTreeMap::new();
+    let mut grown = 0u8;
+    for pass in 0..MAX_GUARANTEED_PASSES {
+        if pass > 0 && reached_minimum(range, grown) {
+            break;
+        }
+        for sit in &order {
+            if !below_maximum(range, grown) {
+                return points;
+            }
+            let capped = stats.get(*sit).map_or(true, |stat| stat.value >= stat.cap);
+            if capped || points.contains_key(*sit) {
+                continue;
+            }
+            let (gained, _hit) = roll(growths[*sit], rng);
+            if gained > 0 {
+                points.insert((*sit).clone(), gained);
+                grown = grown.saturating_add(1);
+            }
+        }
+    }
+    points
+}
+
+/// The points every stat gains on the level-up. None for level-ups the
+/// simulation doesn't know the rules of.
+fn level_up_points<SIT : StatIndexType>(
+    stats : &BTreeMap<SIT, Stat>,
+    temporary_growth_override : &Option<Arc<dyn Fn(&SIT, GrowthType) -> GrowthType>>,
+    blank_avoidance : &BlankAvoidance<SIT>,
+    rng : &mut impl Rng
+) -> Option<BTreeMap<SIT, StatType>> {
+    let growths : BTreeMap<SIT, GrowthType> = stats
+        .iter()
+        .map(|(sit, stat)| {
+            (
+                sit.clone(),
+                temporary_growth_override
+                    .as_ref()
+                    .map_or(stat.growth, |f| f(sit, stat.growth))
+            )
+        })
+        .collect();
+
+    match blank_avoidance {
+        BlankAvoidance::NoAvoidance => Some(roll_all(&growths, rng).0),
+        BlankAvoidance::RetriesForNoBlank(retries) => {
+            let (mut points, mut any_hit) = roll_all(&growths, rng);
+            for _retry in 0..*retries {
+                if any_hit {
+                    break;
+                }
+                (points, any_hit) = roll_all(&growths, rng);
+            }
+            Some(points)
+        },
+        BlankAvoidance::AwardFixedStatOnBlank(backup_stat) => {
+            let (mut points, any_hit) = roll_all(&growths, rng);
+            if !any_hit {
+                *points.entry(backup_stat.clone()).or_insert(0) += 1;
+            }
+            Some(points)
+        },
+        BlankAvoidance::GuaranteedStats(range, order) => {
+            Some(guaranteed_rolls(stats, &growths, range, order, rng))
+        },
+        BlankAvoidance::VariableGuaranteedStats => None
+    }
+}
+
+/// One sampled run of the progression: the stats' values before the first
+/// stat change and after every one, as [`crate::generate_histograms`] lists
+/// their distributions. None if a level-up can't be simulated.
+pub(crate) fn simulate_trajectory<SIT : StatIndexType>(
+    levels : &[StatChange<SIT>],
+    character : &Character<SIT>,
+    rng : &mut impl Rng
+) -> Option<Vec<StatValues<SIT>>> {
+    let mut stats = character.stats.clone();
+    let mut trajectory = vec![values(&stats)];
+    for level in levels {
+        match level {
+            StatChange::LevelUp {
+                temporary_growth_override,
+                blank_avoidance
+            } => {
+                let points =
+                    level_up_points(&stats, temporary_growth_override, blank_avoidance, rng)?;
+                for (sit, stat) in stats.iter_mut() {
+                    let gained = points.get(sit).copied().unwrap_or(0);
+                    stat.value = stat.value.saturating_add(gained).min(stat.cap);
+                }
+            },
+            StatChange::Promotion { promo_changes } => {
+                for (sit, stat) in stats.iter_mut() {
+                    *stat = promo_changes(sit, *stat);
+                }
+            },
+        }
+        trajectory.push(values(&stats));
+    }
+    Some(trajectory)
+}
+
+/// The distributions of [`crate::generate_histograms`] estimated from the
+/// sampled runs.
+pub(crate) fn simulate_histograms<SIT : StatIndexType>(
+    levels : &[StatChange<SIT>],
+    character : &Character<SIT>,
+    num_samples : u64,
+    rng : &mut impl Rng
+) -> Option<Vec<BTreeMap<SIT, BTreeMap<StatType, f64>>>> {
+    let mut histograms = vec![BTreeMap::<SIT, BTreeMap<StatType, f64>>::new(); levels.len() + 1];
+    let weight = 1.0 / num_samples.max(1) as f64;
+    for _sample in 0..num_samples {
+        let trajectory = simulate_trajectory(levels, character, rng)?;
+        for (histogram, values) in histograms.iter_mut().zip(trajectory) {
+            for (sit, value) in values {
+                *histogram
+                    .entry(sit)
+                    .or_default()
+                    .entry(value)
+                    .or_insert(0.0) += weight;
+            }
+        }
+    }
+    Some(histograms)
+}
+
+/// How often each event holds over the sampled runs, indexed by the stat
+/// change and then by the event. Every event sees the values of all stats of
+/// one run at once, so it may span several stats.
+pub(crate) fn event_frequencies<SIT, F>(
+    levels : &[StatChange<SIT>],
+    character : &Character<SIT>,
+    events : &[F],
+    num_samples : u64,
+    rng : &mut impl Rng
+) -> Option<Vec<Vec<f64>>>
+where
+    SIT : StatIndexType,
+    F : Fn(&StatValues<SIT>) -> bool
+{
+    let mut counts = vec![vec![0u64; events.len()]; levels.len() + 1];
+    for _sample in 0..num_samples {
+        let trajectory = simulate_trajectory(levels, character, rng)?;
+        for (counts, values) in counts.iter_mut().zip(&trajectory) {
+            for (count, event) in counts.iter_mut().zip(events) {
+                *count += u64::from(event(values));
+            }
+        }
+    }
+    let samples = num_samples.max(1) as f64;
+    Some(
+        counts
+            .into_iter()
+            .map(|counts| {
+                counts
+                    .into_iter()
+                    .map(|count| count as f64 / samples)
+                    .collect()
+            })
+            .collect()
+    )
+}
+
+fn reaches<SIT : StatIndexType>(
+    values : &StatValues<SIT>,
+    sit : &SIT,
+    benchmark : StatType
+) -> bool {
+    values.get(sit).map_or(false, |value| *value >= benchmark)
+}
+
+/// The chances of [`crate::joint_benchmark_chances`], the stats' own chances
+/// coming from the same runs as the joint one so that the sampling noise
+/// mostly cancels out of their difference.
+pub(crate) fn joint_benchmarks<SIT : StatIndexType>(
+    levels : &[StatChange<SIT>],
+    character : &Character<SIT>,
+    benchmarks : &BTreeMap<SIT, StatType>,
+    num_samples : u64,
+    rng : &mut impl Rng
+) -> Option<Vec<JointBenchmark>> {
+    let mut events : Vec<Event<SIT>> = benchmarks
+        .iter()
+        .map(|(sit, benchmark)| {
+            let reached = move |values : &StatValues<SIT>| reaches(values, sit, *benchmark);
+            Box::new(reached) as Event<SIT>
+        })
+        .collect();
+    events.push(Box::new(|values : &StatValues<SIT>| {
+        benchmarks
+            .iter()
+            .all(|(sit, benchmark)| reaches(values, sit, *benchmark))
+    }));
+    let frequencies = event_frequencies(levels, character, &events, num_samples, rng)?;
+    Some(
+        frequencies
+            .into_iter()
+            .map(|mut frequencies| {
+                let joint = frequencies.pop().unwrap_or(1.0);
+                JointBenchmark {
+                    joint,
+                    independent : frequencies.into_iter().product()
+                }
+            })
+            .collect()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::analysis::binomial_analysis;
+
+    const SAMPLES : u64 = 20_000;
+
+    fn character(growths : &[GrowthType]) -> Character<u8> {
+        Character {
+            stats : (0..)
+                .zip(growths)
+                .map(|(sit, growth)| {
+                    let stat = Stat {
+                        base : 5,
+                        cap : 20,
+                        growth : *growth,
+                        value : 5
+                    };
+                    (sit, stat)
+                })
+                .collect(),
+            name : "Test".to_owned(),
+            level : 1
+        }
+    }
+
+    fn level_up(blank_avoidance : BlankAvoidance<u8>) -> StatChange<u8> {
+        StatChange::LevelUp {
+            temporary_growth_override : None,
+            blank_avoidance
+        }
+    }
+
+    #[test]
+    fn simulated_histograms_match_the_analysis() {
+        let character = character(&[10, 45, 30]);
+        let promotion = StatChange::Promotion {
+            promo_changes : Arc::new(|_sit, stat| Stat {
+                value : stat.value + 2,
+                cap : 25,
+                ..stat
+            })
+        };
+        let boosted = StatChange::LevelUp {
+            temporary_growth_override : Some(Arc::new(|sit, growth| {
+                if *sit == 2 {
+                    130
+                }
+                else {
+                    growth
+                }
+            })),
+            blank_avoidance : BlankAvoidance::NoAvoidance
+        };
+        let levels = vec![
+            level_up(BlankAvoidance::RetriesForNoBlank(2)),
+            level_up(BlankAvoidance::AwardFixedStatOnBlank(0)),
+            promotion,
+            boosted,
+        ];
+        let exact = binomial_analysis(&levels, &character).unwrap();
+        let mut rng = StdRng::seed_from_u64(925);
+        let simulated = simulate_histograms(&levels, &character, SAMPLES, &mut rng).unwrap();
+        assert_eq!(simulated.len(), exact.len());
+        for (simulated, exact) in simulated.iter().zip(&exact) {
+            for (sit, spread) in exact {
+                for (value, probability) in spread {
+                    let sampled = simulated[sit].get(value).copied().unwrap_or(0.0);
+                    assert!((sampled - probability).abs() < 0.02, "{} {}", sit, value);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn guaranteed_stats_grow_as_many_stats_as_the_range_asks() {
+        let character = character(&[10, 20, 30, 40, 0]);
+        let levels = vec![level_up(BlankAvoidance::GuaranteedStats(
+            (Bound::Included(3), Bound::Included(3)),
+            vec![]
+        ))];
+        let mut rng = StdRng::seed_from_u64(925);
+        for _sample in 0..1_000 {
+            let trajectory = simulate_trajectory(&levels, &character, &mut rng).unwrap();
+            let gained : StatType = trajectory[1].values().map(|value| value - 5).sum();
+            assert_eq!(gained, 3);
+            assert_eq!(trajectory[1][&4], 5);
+        }
+        let levels = vec![level_up(BlankAvoidance::VariableGuaranteedStats)];
+        assert_eq!(simulate_trajectory(&levels, &character, &mut rng), None);
+    }
+
+    #[test]
+    fn retries_make_stats_growing_together_rarer() {
+        let character = character(&[10, 10]);
+        let levels = vec![level_up(BlankAvoidance::RetriesForNoBlank(2))];
+        let benchmarks = BTreeMap::from([(0, 6), (1, 6)]);
+        let mut rng = StdRng::seed_from_u64(925);
+        let chances =
+            joint_benchmarks(&levels, &character, &benchmarks, SAMPLES, &mut rng).unwrap();
+        assert_eq!(chances[0].joint, 0.0);
+        assert_eq!(chances[0].independent, 0.0);
+        // a re-roll only follows a level-up without any growth, so growing one
+        // stat makes the other less likely: 2.5% against 6.1% when independent
+        let after = chances[1];
+        assert!((after.joint - 0.0247).abs() < 0.005, "{:?}", after);
+        assert!((after.independent - 0.0608).abs() < 0.01, "{:?}", after);
+        assert!(after.correlation_delta() < -0.02, "{:?}", after);
+
+        let levels = vec![level_up(BlankAvoidance::NoAvoidance)];
+        let chances =
+            joint_benchmarks(&levels, &character, &benchmarks, SAMPLES, &mut rng).unwrap();
+        assert!(
+            chances[1].correlation_delta().abs() < 0.005,
+            "{:?}",
+            chances[1]
+        );
+    }
+}
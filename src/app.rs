@@ -1,49 +1,205 @@
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap},
     fmt::Display,
-    str::FromStr
+    str::FromStr,
+    sync::Mutex
 };
 
-use egui::{Button, TextEdit, Ui};
-use fe_levels::{Character, StatType};
+use egui::{emath::Numeric, Button, Rect, RichText, Slider, TextEdit, Ui};
+use fe_levels::{Character, GrowthType, Stat, StatType};
 use itertools::Itertools;
 
 use rand::random;
 use serde::{Deserialize, Serialize};
 
+#[cfg(target_arch = "wasm32")]
+pub use self::plotter::run_compute_worker;
 use self::{
+    autosave::Autosave,
+    backup::BackupDialog,
+    community::CommunityDialog,
+    database::{builtin_promotions, enemy_classes, ClassTier, DatabaseBrowser},
+    layout::{AppWindow, WindowVisibility},
+    locale::{tr, tr_with},
     manager::DataManaged,
+    migration::DiscardedState,
+    onboarding::Tour,
     plotter::PlotterManager,
-    progression::{ConcreteStatChange, ProgressionManager},
-    sit::StatIndexType,
-    weapon::{UsableWeapon, Weapon}
+    progression::{ConcreteStatChange, ProgressionManager, TransferDialog},
+    session_log::{log_action, SessionLog},
+    settings::Settings,
+    sit::{CustomStats, StatIndexType},
+    slots::{TabEdit, WorkingSlot},
+    stat_block::PasteDialog,
+    toasts::Toasts,
+    weapon::{
+        equipment_dialogue, forge_dialogue, weapon_rank_dialogue, EnemyEquipment, Forge,
+        ForgeLimits, UsableWeapon, Weapon, WeaponRanks
+    }
 };
 
+mod autosave;
+mod backup;
+mod combat;
+mod community;
+mod dashboard;
+mod database;
+mod files;
+mod growth_budget;
+mod help;
+mod hints;
+mod layout;
+mod locale;
 mod manager;
+mod migration;
+mod onboarding;
 mod plotter;
 mod progression;
+mod session_log;
+mod settings;
+#[cfg(target_arch = "wasm32")]
+mod share;
 mod sit;
+mod slots;
+mod stat_block;
+mod toasts;
 mod weapon;
 
 type CompleteData = Vec<BTreeMap<StatIndexType, BTreeMap<StatType, f64>>>;
+/// Growth bonuses a character has on top of their personal growths for their
+/// whole career, e.g. FE4's holy blood.
+pub type GrowthModifiers = BTreeMap<StatIndexType, GrowthType>;
+/// A saved character together with their progression and growth modifiers.
+type SavedCharacter = (
+    Character<StatIndexType>,
+    Vec<ConcreteStatChange>,
+    GrowthModifiers
+);
 
 #[derive(PartialEq, Default, Deserialize, Serialize, Hash, Eq, Clone, Copy, Debug)]
 pub enum GameKind {
     #[default]
     GbaFe,
-    PoR
+    PoR,
+    RD,
+    ShadowDragon,
+    SoV,
+    ThreeHouses,
+    /// a game whose stats are named by the user, e.g. for romhacks
+    Custom
 }
 
+/// every game, in the order the top bar lists them
+pub const ALL_GAMES : [GameKind; 7] = [
+    GameKind::GbaFe,
+    GameKind::PoR,
+    GameKind::RD,
+    GameKind::ShadowDragon,
+    GameKind::SoV,
+    GameKind::ThreeHouses,
+    GameKind::Custom
+];
+
+impl GameKind {
+    /// the name the top bar shows for the game
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameKind::GbaFe => "GBA-FE",
+            GameKind::PoR => "FE9",
+            GameKind::RD => "FE10",
+            GameKind::ShadowDragon => "FE11",
+            GameKind::SoV => "FE15",
+            GameKind::ThreeHouses => "FE16",
+            GameKind::Custom => "Custom"
+        }
+    }
+
+    /// the highest level a unit can reach before having to promote
+    pub fn level_cap(&self) -> usize {
+        match self {
+            GameKind::GbaFe => 20,
+            GameKind::PoR => 20,
+            GameKind::RD => 20,
+            GameKind::ShadowDragon => 20,
+            GameKind::SoV => 20,
+            // classes don't cap the level, only the game's maximum does
+            GameKind::ThreeHouses => 99,
+            GameKind::Custom => 20
+        }
+    }
+
+    /// the most the game's forge can raise a weapon by, `None` for games
+    /// without a forge or with fixed upgrade paths instead
+    pub fn forge_limits(&self) -> Option<ForgeLimits> {
+        match self {
+            GameKind::PoR | GameKind::RD => Some(ForgeLimits {
+                might : 5,
+                hit : 30,
+                crit : 30
+            }),
+            GameKind::ShadowDragon => Some(ForgeLimits {
+                might : 5,
+                hit : 50,
+                crit : 30
+            }),
+            GameKind::GbaFe | GameKind::SoV | GameKind::ThreeHouses | GameKind::Custom => None
+        }
+    }
+
+    /// whether the stat buffs of an equipped weapon can raise stats past their
+    /// caps, e.g. Durandal's +5 Atk showing above the cap in FE7
+    pub fn weapon_buffs_exceed_caps(&self) -> bool {
+        match self {
+            GameKind::GbaFe | GameKind::PoR | GameKind::RD | GameKind::Custom => true,
+            GameKind::ShadowDragon | GameKind::SoV | GameKind::ThreeHouses => false
+        }
+    }
+}
+
+/// Tells windows of the same kind apart for egui. Ids are drawn at random so
+/// that windows restored from other sessions keep theirs, but every id drawn or
+/// restored this session is taken, a draw that's taken moving on to the next
+/// free id. Windows thus never share an id even with a poor source of
+/// randomness.
 #[derive(Deserialize, Serialize, Hash, PartialEq, Eq, Clone, Copy)]
+#[serde(from = "SavedId")]
 struct UsefulId(u64);
 
-impl Default for UsefulId {
-    fn default() -> Self { Self(random()) }
+/// the ids of the windows created or restored this session
+static TAKEN_IDS : Mutex<BTreeSet<u64>> = Mutex::new(BTreeSet::new());
+
+/// the form ids are saved in, taking them once restored
+#[derive(Deserialize)]
+#[serde(rename = "UsefulId")]
+struct SavedId(u64);
+
+impl From<SavedId> for UsefulId {
+    fn from(SavedId(id) : SavedId) -> Self {
+        if let Ok(mut taken) = TAKEN_IDS.lock() {
+            taken.insert(id);
+        }
+        Self(id)
+    }
 }
 
-#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
-enum StatChangeTemplate {
-    LevelUp
+impl UsefulId {
+    /// The first free id from the drawn one on, which is then taken.
+    fn allocate(taken : &mut BTreeSet<u64>, draw : impl FnOnce() -> u64) -> Self {
+        let mut id = draw();
+        while !taken.insert(id) {
+            id = id.wrapping_add(1);
+        }
+        Self(id)
+    }
+}
+
+impl Default for UsefulId {
+    fn default() -> Self {
+        match TAKEN_IDS.lock() {
+            Ok(mut taken) => Self::allocate(&mut taken, random),
+            Err(_poisoned) => Self(random())
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize)]
@@ -52,22 +208,190 @@ pub struct GameData {
     plotter : PlotterManager,
 
     character : Character<StatIndexType>,
+    /// added to the personal growths of the character before any computation
+    growth_modifiers : GrowthModifiers,
+    /// whether the builder edits the current stats apart from the bases, e.g.
+    /// for units analyzed from the middle of a playthrough
+    current_values : bool,
+    /// the target of the personal growths' total the builder shows what's left
+    /// of, e.g. for romhacks balancing units by it
+    growth_budget : Option<u32>,
+    /// the weapon ranks of the character, gating the weapons they can wield
+    weapon_ranks : WeaponRanks,
     enemy : Option<Character<StatIndexType>>,
+    /// the equipment of the enemy being edited
+    enemy_equipment : EnemyEquipment,
+    enemy_scaling : Option<EnemyScaling>,
     weapon : Option<Weapon>,
+    /// the boosts of the weapon being forged
+    forge : Option<Forge>,
     game_option : GameKind,
 
     progression : ProgressionManager,
+    /// the units of the other tabs, the active one being the character and
+    /// progression above
+    slots : Vec<WorkingSlot>,
+    /// the position of the active unit among the tabs
+    active_slot : usize,
+    #[serde(skip)]
+    tab_edit : Option<TabEdit>,
+    /// overrides the game's level cap, e.g. for romhacks raising it
+    level_cap : usize,
+    /// allows promotions below their required level, e.g. for romhacks
+    ignore_promotion_levels : bool,
 
     promotions : DataManaged<Character<StatIndexType>>,
-    characters : DataManaged<(Character<StatIndexType>, Vec<ConcreteStatChange>)>,
+    custom_templates : DataManaged<ConcreteStatChange>,
+    characters : DataManaged<SavedCharacter>,
+    /// the weapon ranks of the saved characters by their names
+    character_weapon_ranks : BTreeMap<String, WeaponRanks>,
     enemies : DataManaged<Character<StatIndexType>>,
-    weapons : DataManaged<Weapon>
+    /// the equipment of the saved enemies by their names
+    equipment : BTreeMap<String, EnemyEquipment>,
+    weapons : DataManaged<Weapon>,
+
+    /// the stats of [`GameKind::Custom`], unused by all other games
+    custom_stats : CustomStats,
+    database_browser : DatabaseBrowser,
+    #[serde(skip)]
+    community : CommunityDialog,
+    #[serde(skip)]
+    stat_block : PasteDialog,
+    /// what was done to the data this session
+    #[serde(skip)]
+    session_log : SessionLog
 }
 
 impl Default for GameData {
     fn default() -> Self { generate_default_gamedata(Default::default()) }
 }
 
+impl GameData {
+    /// The character as the computations see them, i.e. with the growth
+    /// modifiers folded into their growths.
+    pub fn effective_character(&self) -> Character<StatIndexType> {
+        effective_character(&self.character, &self.growth_modifiers)
+    }
+
+    /// Gives the characters and enemies the stats added to the game since they
+    /// were saved, e.g. GBA Mov.
+    fn insert_missing_stats(&mut self) {
+        // custom stats are only known once published, and are removed by the user on
+        // purpose
+        if self.game_option == GameKind::Custom {
+            return;
+        }
+        let insert = |character : &mut Character<StatIndexType>,
+                      template : &Character<StatIndexType>| {
+            for (sit, stat) in template.stats.iter() {
+                character.stats.entry(*sit).or_insert(*stat);
+            }
+        };
+        let character_template = StatIndexType::new_default_character(self.game_option);
+        let enemy_template = StatIndexType::new_default_enemy(self.game_option);
+        insert(&mut self.character, &character_template);
+        for slot in self.slots.iter_mut() {
+            insert(&mut slot.character, &character_template);
+        }
+        for (character, _progression, _modifiers) in self.characters.values_mut() {
+            insert(character, &character_template);
+        }
+        for enemy in self.enemy.iter_mut().chain(self.enemies.values_mut()) {
+            insert(enemy, &enemy_template);
+        }
+    }
+}
+
+/// Raises the growths of the character by the given modifiers. Stats that
+/// can't grow stay constant whatever growth they were saved with.
+pub fn effective_character(
+    character : &Character<StatIndexType>,
+    growth_modifiers : &GrowthModifiers
+) -> Character<StatIndexType> {
+    let mut character = character.clone();
+    for (sit, stat) in character.stats.iter_mut() {
+        if !sit.is_growable() {
+            stat.growth = 0;
+        }
+        else if let Some(modifier) = growth_modifiers.get(sit) {
+            stat.growth = stat.growth.saturating_add(*modifier);
+        }
+    }
+    character
+}
+
+/// Sets the values of the enemy to their bases plus what their growths gained
+/// them since level 1, either rounding the expected gains or, as with fixed
+/// growths, only counting the points fully accumulated.
+fn autolevel(enemy : &mut Character<StatIndexType>, fixed_growths : bool) {
+    let level_ups = enemy.level.saturating_sub(1);
+    for stat in enemy.stats.values_mut() {
+        let accumulated = usize::from(stat.growth) * level_ups;
+        let gain = if fixed_growths {
+            accumulated / 100
+        }
+        else {
+            (accumulated + 50) / 100
+        };
+        let gain = StatType::try_from(gain).unwrap_or(StatType::MAX);
+        stat.value = stat.base.saturating_add(gain).min(stat.cap);
+    }
+}
+
+/// Copies of a saved enemy to generate at other levels, e.g. to see when the
+/// promoted enemies of a later chapter become beatable.
+#[derive(Deserialize, Serialize, Clone)]
+struct EnemyScaling {
+    /// the name of the saved enemy being scaled
+    enemy : String,
+    first_level : usize,
+    last_level : usize,
+    step : usize,
+    /// appended to the names of the copies, e.g. "HM"
+    difficulty : String,
+    /// the percentage the growths of the copies are scaled to
+    growth_multiplier : GrowthType,
+    fixed_growths : bool
+}
+
+impl EnemyScaling {
+    fn new(enemy : &Character<StatIndexType>) -> Self {
+        EnemyScaling {
+            enemy : enemy.name.clone(),
+            first_level : enemy.level,
+            last_level : enemy.level,
+            step : 1,
+            difficulty : String::new(),
+            growth_multiplier : 100,
+            fixed_growths : false
+        }
+    }
+
+    /// The autoleveled copies of the enemy, named after their level and the
+    /// difficulty, e.g. "Mercenary Lv12 (HM)".
+    fn copies(&self, enemy : &Character<StatIndexType>) -> Vec<Character<StatIndexType>> {
+        (self.first_level..=self.last_level)
+            .step_by(self.step.max(1))
+            .map(|level| {
+                let mut copy = enemy.clone();
+                copy.level = level;
+                copy.name = if self.difficulty.is_empty() {
+                    format!("{} Lv{level}", enemy.name)
+                }
+                else {
+                    format!("{} Lv{level} ({})", enemy.name, self.difficulty)
+                };
+                for stat in copy.stats.values_mut() {
+                    let growth = u32::from(stat.growth) * u32::from(self.growth_multiplier) / 100;
+                    stat.growth = GrowthType::try_from(growth).unwrap_or(GrowthType::MAX);
+                }
+                autolevel(&mut copy, self.fixed_growths);
+                copy
+            })
+            .collect()
+    }
+}
+
 /// We derive Deserialize/Serialize so we can persist app state on shutdown.
 #[derive(Deserialize, Serialize)]
 #[serde(default)] // if we add new fields, give them default values when deserializing old state
@@ -76,31 +400,143 @@ pub struct FeLevelGui {
 
     game_option : GameKind,
 
-    game_data : HashMap<GameKind, GameData>
+    game_data : HashMap<GameKind, GameData>,
+
+    /// the PoR character RD transfer bonuses are computed for
+    transfer : TransferDialog,
+
+    #[serde(skip)]
+    backup : BackupDialog,
+
+    /// the saved state that couldn't be restored
+    #[serde(skip)]
+    discarded : Option<DiscardedState>,
+
+    #[serde(skip)]
+    autosave : Autosave,
+
+    /// the key of the plotter data last written to the storage
+    #[serde(skip)]
+    persisted_data : Option<u64>,
+
+    /// whether the help window is open
+    help_open : bool,
+
+    /// whether the session log window is open
+    log_open : bool,
+
+    /// the tour of the windows a first run starts with
+    tour : Tour,
+
+    settings : Settings,
+    settings_open : bool,
+    /// the settings the interface is styled with, `None` before the first frame
+    #[serde(skip)]
+    applied_settings : Option<Settings>,
+
+    windows : WindowVisibility,
+
+    /// the errors and notes reported by the windows until they expire
+    #[serde(skip)]
+    toasts : Toasts,
+
+    /// the unit of the link the app was opened with until it's loaded or
+    /// ignored
+    #[cfg(target_arch = "wasm32")]
+    #[serde(skip)]
+    linked_unit : Option<share::SharedUnit>
 }
 
 impl Default for FeLevelGui {
     fn default() -> Self {
         Self {
-            version : 2,
+            version : migration::CURRENT_VERSION,
             game_option : Default::default(),
-            game_data : Default::default()
+            game_data : Default::default(),
+            transfer : Default::default(),
+            backup : Default::default(),
+            discarded : Default::default(),
+            autosave : Default::default(),
+            persisted_data : Default::default(),
+            help_open : Default::default(),
+            log_open : Default::default(),
+            tour : Default::default(),
+            settings : Default::default(),
+            settings_open : Default::default(),
+            applied_settings : Default::default(),
+            windows : Default::default(),
+            toasts : Default::default(),
+            #[cfg(target_arch = "wasm32")]
+            linked_unit : Default::default()
         }
     }
 }
 
 fn generate_default_gamedata(game_option : GameKind) -> GameData {
+    let custom_stats = CustomStats::default();
+    if game_option == GameKind::Custom {
+        // the default character is made of the published stats
+        custom_stats.publish();
+    }
     GameData {
         plotter : Default::default(),
         character : StatIndexType::new_default_character(game_option),
+        growth_modifiers : Default::default(),
+        current_values : false,
+        growth_budget : None,
+        weapon_ranks : Default::default(),
         game_option,
         progression : Default::default(),
+        slots : Default::default(),
+        active_slot : 0,
+        tab_edit : None,
+        level_cap : game_option.level_cap(),
+        ignore_promotion_levels : false,
         promotions : Default::default(),
+        custom_templates : Default::default(),
         characters : Default::default(),
+        character_weapon_ranks : Default::default(),
         enemy : Default::default(),
+        enemy_equipment : Default::default(),
+        enemy_scaling : Default::default(),
         enemies : Default::default(),
+        equipment : Default::default(),
         weapons : Default::default(),
-        weapon : Default::default()
+        weapon : Default::default(),
+        forge : Default::default(),
+        custom_stats,
+        database_browser : Default::default(),
+        community : Default::default(),
+        stat_block : Default::default(),
+        session_log : Default::default()
+    }
+}
+
+/// The sum of a part of every stat, e.g. the growth total.
+fn stat_total(character : &Character<StatIndexType>, part : impl Fn(&Stat) -> u16) -> u32 {
+    character
+        .stats
+        .values()
+        .map(|stat| u32::from(part(stat)))
+        .sum()
+}
+
+/// The current value of the stat, marked if it lies below the base or above
+/// the cap.
+fn current_value_box(ui : &mut Ui, stat : &mut Stat) {
+    let valid = (stat.base..=stat.cap).contains(&stat.value);
+    let color = (!valid).then(|| ui.visuals().error_fg_color);
+    let mut text = stat.value.to_string();
+    let response = ui.add(
+        TextEdit::singleline(&mut text)
+            .desired_width(ui.spacing().text_edit_width)
+            .text_color_opt(color)
+    );
+    if let Ok(parsed) = str::parse(&text) {
+        stat.value = parsed;
+    }
+    if !valid {
+        response.on_hover_text(tr("builder.value_out_of_range"));
     }
 }
 
@@ -113,152 +549,479 @@ fn numerical_text_box<T : Display + FromStr>(ui : &mut Ui, value : &mut T) {
     // do not write the result back / do anything in case of a bad parse
 }
 
+/// the least the cap sliders of the character builder reach, the highest cap
+/// of most games
+const CAP_SLIDER_MAX : StatType = 99;
+/// the least the growth sliders of the character builder reach
+const GROWTH_SLIDER_MAX : GrowthType = 100;
+
+/// A text box, or with `slider` a slider reaching up to at least `least_max`
+/// so that larger values aren't clamped.
+fn stat_number_box<T : Numeric + Display + FromStr>(
+    ui : &mut Ui,
+    value : &mut T,
+    least_max : T,
+    slider : bool
+) {
+    if slider {
+        let max = if *value > least_max {
+            *value
+        }
+        else {
+            least_max
+        };
+        ui.add(Slider::new(value, T::from_f64(0.0)..=max));
+    }
+    else {
+        numerical_text_box(ui, value);
+    }
+}
+
+/// The rect moved and shrunk onto the screen, so that modal windows pinned to
+/// a manager stay reachable when larger text pushes the manager off-screen.
+fn on_screen(ctx : &egui::Context, rect : Rect) -> Rect {
+    let screen = ctx.input().screen_rect();
+    let size = rect.size().min(screen.size());
+    Rect::from_min_size(rect.min.clamp(screen.min, screen.max - size), size)
+}
+
 impl FeLevelGui {
     /// Called once before the first frame.
     pub fn new(cc : &eframe::CreationContext<'_>) -> Self {
         // This is also where you can customized the look at feel of egui using
         // `cc.egui_ctx.set_visuals` and `cc.egui_ctx.set_fonts`.
 
+        let mut state = Self::restored(cc);
+        state.autosave = Autosave::load(cc.storage, &state.game_data);
+        if let (Some(storage), Some(data)) =
+            (cc.storage, state.game_data.get_mut(&state.game_option))
+        {
+            state.persisted_data = data.plotter.restore(storage);
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            state.linked_unit = share::linked_unit();
+        }
+        state
+    }
+
+    fn restored(cc : &eframe::CreationContext<'_>) -> Self {
         // Load previous app state (if any).
         // Note that you must enable the `persistence` feature for this to work.
-        if let Some(storage) = cc.storage {
-            let state : Self = eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
-            if state.version < Self::default().version {
-                return Default::default();
-            }
-            else {
-                return state;
+        let text = match cc
+            .storage
+            .and_then(|storage| storage.get_string(eframe::APP_KEY))
+        {
+            Some(text) => text,
+            None => return Self::first_run()
+        };
+        match migration::restore(&text) {
+            Ok(state) => state,
+            Err(reason) => Self {
+                discarded : Some(DiscardedState::new(reason, text)),
+                ..Default::default()
             }
         }
+    }
 
-        Default::default()
+    /// The state of a first run, starting the tour of the example unit.
+    fn first_run() -> Self {
+        let mut game_data = HashMap::new();
+        game_data.insert(GameKind::GbaFe, onboarding::example_gamedata());
+        Self {
+            game_option : GameKind::GbaFe,
+            game_data,
+            tour : Tour::start(),
+            ..Default::default()
+        }
     }
 
-    fn character_builder(data : &mut GameData, ctx : &egui::Context) {
-        egui::Window::new("Character Builder").show(ctx, |ui| {
+    fn character_builder(data : &mut GameData, settings : &Settings, ctx : &egui::Context) {
+        let window = AppWindow::CharacterBuilder.window().show(ctx, |ui| {
+            slots::slot_tabs(data, ui);
             ui.horizontal(|ui| {
-                ui.label("Name: ");
+                ui.label(tr("common.name"));
                 ui.add(
                     TextEdit::singleline(&mut data.character.name)
                         .desired_width(ui.spacing().slider_width * 1.5)
                 );
-                ui.label("Level: ");
+                ui.label(tr("common.level"));
                 numerical_text_box(ui, &mut data.character.level);
             });
+            let mut modified = !data.growth_modifiers.is_empty();
+            // units loaded with stats apart from their bases keep them
+            if data
+                .character
+                .stats
+                .values()
+                .any(|stat| stat.value != stat.base)
+            {
+                data.current_values = true;
+            }
+            ui.horizontal(|ui| {
+                if ui
+                    .checkbox(&mut modified, tr("builder.growth_modifiers"))
+                    .on_hover_text(tr("builder.growth_modifiers_hint"))
+                    .changed()
+                {
+                    data.growth_modifiers = if modified {
+                        data.character.stats.keys().map(|sit| (*sit, 0)).collect()
+                    }
+                    else {
+                        Default::default()
+                    };
+                }
+                ui.checkbox(&mut data.current_values, tr("builder.current_values"))
+                    .on_hover_text(tr("builder.current_values_hint"));
+                ui.toggle_value(&mut data.stat_block.open, tr("builder.paste_stats"))
+                    .on_hover_text(tr("builder.paste_stats_hint"));
+            });
+            if data.stat_block.open
+                && stat_block::paste_dialog(
+                    &mut data.stat_block,
+                    &mut data.character,
+                    data.game_option,
+                    ui
+                )
+            {
+                log_action(data, tr("builder.log_pasted_stats"));
+            }
+            let current_values = data.current_values;
             egui::Grid::new("Character Builder Table").show(ui, |ui| {
-                ui.label("Stat");
-                ui.label("Base");
-                ui.label("Cap");
-                ui.label("Growth");
+                ui.label(tr("common.stat"));
+                ui.label(tr("common.base"));
+                if current_values {
+                    ui.label(tr("builder.current"));
+                }
+                ui.label(tr("builder.cap"));
+                if modified {
+                    ui.label(tr("builder.personal_growth"));
+                    ui.label(tr("builder.modifier"));
+                    ui.label(tr("builder.effective_growth"));
+                }
+                else {
+                    ui.label(tr("common.growth"));
+                }
                 ui.end_row();
 
+                let growth_modifiers = &mut data.growth_modifiers;
                 data.character
                     .stats
                     .iter_mut()
-                    .sorted_by_key(|(key, _value)| **key)
+                    .sorted_by_key(|(key, _value)| key.display_order())
                     .for_each(|(key, stat)| {
-                        ui.label(key.to_string());
+                        locale::stat_label(ui, *key);
                         ui.add(egui::Slider::new(&mut stat.base, 0..=stat.cap));
-                        stat.value = stat.base;
-                        numerical_text_box(ui, &mut stat.cap);
-                        numerical_text_box(ui, &mut stat.growth);
+                        if current_values {
+                            current_value_box(ui, stat);
+                        }
+                        else {
+                            stat.value = stat.base;
+                        }
+                        stat_number_box(ui, &mut stat.cap, CAP_SLIDER_MAX, settings.stat_sliders);
+                        // stats like GBA Con only change with promotions and items
+                        if !key.is_growable() {
+                            stat.growth = 0;
+                            ui.label("-");
+                            if modified {
+                                ui.label("-");
+                                ui.label("-");
+                            }
+                        }
+                        else {
+                            let sliders = settings.stat_sliders;
+                            stat_number_box(ui, &mut stat.growth, GROWTH_SLIDER_MAX, sliders);
+                            if modified {
+                                let modifier = growth_modifiers.entry(*key).or_default();
+                                numerical_text_box(ui, modifier);
+                                ui.label(stat.growth.saturating_add(*modifier).to_string());
+                            }
+                        }
                         ui.end_row()
                     });
+
+                ui.label(RichText::new(tr("builder.total")).strong());
+                ui.label(stat_total(&data.character, |stat| stat.base).to_string());
+                if current_values {
+                    ui.label(stat_total(&data.character, |stat| stat.value).to_string());
+                }
+                ui.label("");
+                let personal = stat_total(&data.character, |stat| stat.growth);
+                let mut personal_total = RichText::new(personal.to_string());
+                if growth_budget::over_budget(data.growth_budget, personal) {
+                    personal_total = personal_total.color(ui.visuals().error_fg_color);
+                }
+                if modified {
+                    let effective = stat_total(
+                        &effective_character(&data.character, &data.growth_modifiers),
+                        |stat| stat.growth
+                    );
+                    ui.label(personal_total);
+                    ui.label(effective.saturating_sub(personal).to_string());
+                    ui.label(effective.to_string());
+                }
+                else {
+                    ui.label(personal_total);
+                }
+                ui.end_row();
             });
+            let budget = &mut data.growth_budget;
+            if let Some(points) = growth_budget::budget_row(budget, &mut data.character, ui) {
+                log_action(
+                    data,
+                    tr_with("builder.log_distributed", &[("points", &points)])
+                );
+            }
+            if data.game_option == GameKind::GbaFe {
+                ui.collapsing(tr("builder.weapon_ranks"), |ui| {
+                    weapon_rank_dialogue(&mut data.weapon_ranks, ui);
+                });
+            }
         });
+        if let Some(window) = window {
+            onboarding::mark_window(ctx, AppWindow::CharacterBuilder.id(), window.response.rect);
+        }
     }
 
     fn character_manager(data : &mut GameData, ctx : &egui::Context) {
+        let mut action = None;
         data.characters.management_dialogue(
             ctx,
             false,
-            "Character & Progression Manager",
-            |(c, _p)| c.name.clone(),
+            AppWindow::CharacterManager,
+            |(c, _p, _m)| c.name.clone(),
             |ui, characters| {
-                if characters.check_legal_name(&data.character.name) {
-                    if ui.button("save character & progression").clicked() {
+                let name = &data.character.name;
+                if characters.check_legal_name(name) {
+                    if ui.button(tr("manager.save_character")).clicked() {
+                        action = Some(tr_with("manager.log_saved_character", &[("name", name)]));
+                        data.character_weapon_ranks
+                            .insert(data.character.name.clone(), data.weapon_ranks.clone());
                         characters.insert(
                             data.character.name.clone(),
-                            (data.character.clone(), data.progression.clone())
+                            (
+                                data.character.clone(),
+                                data.progression.clone(),
+                                data.growth_modifiers.clone()
+                            )
                         );
                     }
                 }
                 else if ui
                     .add_enabled(
                         !data.character.name.is_empty(),
-                        Button::new("overwrite character & progression")
+                        Button::new(tr("manager.overwrite_character"))
                     )
                     .clicked()
                 {
+                    action = Some(tr_with(
+                        "manager.log_overwrote_character",
+                        &[("name", name)]
+                    ));
+                    data.character_weapon_ranks
+                        .insert(data.character.name.clone(), data.weapon_ranks.clone());
                     characters.insert(
                         data.character.name.clone(),
-                        (data.character.clone(), data.progression.clone())
+                        (
+                            data.character.clone(),
+                            data.progression.clone(),
+                            data.growth_modifiers.clone()
+                        )
                     );
                 }
 
                 ui.add_enabled_ui(characters.selected().is_some(), |ui| {
-                    if ui.button("load character").clicked() {
-                        data.character = characters.selected().unwrap().0.clone();
+                    if ui.button(tr("manager.load_character")).clicked() {
+                        let (character, _progression, growth_modifiers) =
+                            characters.selected().unwrap();
+                        let name = &character.name;
+                        action = Some(tr_with("manager.log_loaded_character", &[("name", name)]));
+                        data.character = character.clone();
+                        data.growth_modifiers = growth_modifiers.clone();
+                        data.weapon_ranks = data
+                            .character_weapon_ranks
+                            .get(&character.name)
+                            .cloned()
+                            .unwrap_or_default();
                     }
-                    if ui.button("load progression").clicked() {
-                        *data.progression = characters.selected().unwrap().1.clone();
+                    if ui.button(tr("manager.load_progression")).clicked() {
+                        let (character, progression, _growth_modifiers) =
+                            characters.selected().unwrap();
+                        action = Some(tr_with(
+                            "manager.log_loaded_progression",
+                            &[("name", &character.name), ("count", &progression.len())]
+                        ));
+                        data.progression.checkpoint();
+                        *data.progression = progression.clone();
                     }
                 });
+
+                #[cfg(target_arch = "wasm32")]
+                {
+                    if ui
+                        .button(tr("manager.share_link"))
+                        .on_hover_text(tr("manager.share_link_hint"))
+                        .clicked()
+                    {
+                        let unit = (
+                            data.game_option,
+                            data.character.clone(),
+                            data.progression.to_vec()
+                        );
+                        match share::share_link(&unit) {
+                            Ok(link) => ui.output().copied_text = link,
+                            Err(error) => toasts::error(ui.ctx(), error)
+                        }
+                    }
+                }
+
+                if let Some(character) = community::community_buttons(
+                    &mut data.community,
+                    &data.character,
+                    data.game_option,
+                    ui
+                ) {
+                    let name = &character.name;
+                    action = Some(tr_with("manager.log_loaded_community", &[("name", name)]));
+                    data.character = character;
+                    data.growth_modifiers = Default::default();
+                    data.weapon_ranks = Default::default();
+                    data.insert_missing_stats();
+                }
+
+                if data.game_option == GameKind::GbaFe {
+                    ui.collapsing(tr("manager.database"), |ui| {
+                        if let Some(character) = data.database_browser.show(ui) {
+                            let name = &character.name;
+                            action =
+                                Some(tr_with("manager.log_loaded_database", &[("name", name)]));
+                            data.character = character;
+                            data.growth_modifiers = Default::default();
+                            data.weapon_ranks = Default::default();
+                        }
+                    });
+                }
             }
         );
+        if let Some(action) = action {
+            log_action(data, action);
+        }
     }
 
     fn enemy_manager(data : &mut GameData, ctx : &egui::Context) {
         let modal_rect = data.enemies.management_dialogue(
             ctx,
-            data.enemy.is_some(),
-            "Enemy Manager",
+            data.enemy.is_some() || data.enemy_scaling.is_some(),
+            AppWindow::EnemyManager,
             |c| c.name.clone(),
             |ui, enemies| {
-                if ui.button("add").clicked() {
+                if ui.button(tr("manager.add")).clicked() {
                     data.enemy = Some(StatIndexType::new_default_enemy(data.game_option));
+                    data.enemy_equipment = Default::default();
                 }
 
                 ui.add_enabled_ui(enemies.selected().is_some(), |ui| {
-                    if ui.button("edit").clicked() {
+                    if ui.button(tr("manager.edit")).clicked() {
                         let selected_name = enemies.selected().unwrap().name.clone();
                         data.enemy = enemies.remove(&selected_name);
+                        data.enemy_equipment =
+                            data.equipment.remove(&selected_name).unwrap_or_default();
+                    }
+                    if ui
+                        .button(tr("manager.scale"))
+                        .on_hover_text(tr("manager.scale_hint"))
+                        .clicked()
+                    {
+                        data.enemy_scaling = Some(EnemyScaling::new(enemies.selected().unwrap()));
                     }
                 });
             }
         );
 
         if let Some(mut enemy) = std::mem::take(&mut data.enemy) {
-            egui::Window::new("Enemy Builder")
-                .fixed_rect(modal_rect.unwrap())
+            egui::Window::new(tr("enemy.builder"))
+                .id(egui::Id::new("Enemy Builder"))
+                .fixed_rect(on_screen(ctx, modal_rect.unwrap()))
                 .collapsible(false)
                 .show(ctx, |ui| {
                     ui.horizontal(|ui| {
-                        ui.label("Name: ");
+                        ui.label(tr("common.name"));
                         ui.text_edit_singleline(&mut enemy.name);
                     });
+                    ui.horizontal(|ui| {
+                        ui.label(tr("common.level"));
+                        numerical_text_box(ui, &mut enemy.level);
+                    });
+                    let classes = enemy_classes(data.game_option);
+                    if !classes.is_empty() {
+                        egui::containers::ComboBox::from_label(tr("enemy.class"))
+                            .selected_text(tr("enemy.load_class"))
+                            .show_ui(ui, |ui| {
+                                for class in classes {
+                                    if ui.selectable_label(false, class.label()).clicked() {
+                                        class.apply_to(&mut enemy);
+                                    }
+                                }
+                            });
+                    }
                     egui::Grid::new("Enemy Builder Table").show(ui, |ui| {
-                        ui.label("Stat");
-                        ui.label("Value");
+                        ui.label(tr("common.stat"));
+                        ui.label(tr("common.base"));
+                        ui.label(tr("common.growth"));
+                        ui.label(tr("enemy.value"));
                         ui.end_row();
 
                         enemy
                             .stats
                             .iter_mut()
-                            .sorted_by_key(|(key, _value)| **key)
+                            .sorted_by_key(|(key, _value)| key.display_order())
                             .for_each(|(key, stat)| {
-                                ui.label(key.to_string());
+                                locale::stat_label(ui, *key);
+                                numerical_text_box(ui, &mut stat.base);
+                                if key.is_growable() {
+                                    numerical_text_box(ui, &mut stat.growth);
+                                }
+                                else {
+                                    stat.growth = 0;
+                                    ui.label("-");
+                                }
                                 numerical_text_box(ui, &mut stat.value);
                                 ui.end_row()
                             });
                     });
+                    ui.horizontal(|ui| {
+                        if ui
+                            .button(tr("enemy.autolevel"))
+                            .on_hover_text(tr("enemy.autolevel_hint"))
+                            .clicked()
+                        {
+                            autolevel(&mut enemy, false);
+                        }
+                        if ui
+                            .button(tr("enemy.autolevel_fixed"))
+                            .on_hover_text(tr("enemy.autolevel_fixed_hint"))
+                            .clicked()
+                        {
+                            autolevel(&mut enemy, true);
+                        }
+                    });
+                    if !data.weapons.is_empty() {
+                        equipment_dialogue(&mut data.enemy_equipment, &data.weapons, ui);
+                    }
                     if ui
                         .add_enabled(
                             data.enemies.check_legal_name(&enemy.name),
-                            Button::new("confirm")
+                            Button::new(tr("common.confirm"))
                         )
                         .clicked()
                     {
+                        let equipment = std::mem::take(&mut data.enemy_equipment);
+                        if equipment != EnemyEquipment::default() {
+                            data.equipment.insert(enemy.name.clone(), equipment);
+                        }
+                        else {
+                            data.equipment.remove(&enemy.name);
+                        }
                         data.enemies.insert(enemy.name.clone(), enemy);
                     }
                     else {
@@ -266,14 +1029,192 @@ impl FeLevelGui {
                     }
                 });
         }
+
+        let scaling = std::mem::take(&mut data.enemy_scaling);
+        // the enemy may have been deleted in the meantime
+        if let Some((mut scaling, enemy)) = scaling.and_then(|scaling| {
+            let enemy = data.enemies.get(&scaling.enemy)?.clone();
+            Some((scaling, enemy))
+        }) {
+            egui::Window::new(tr("enemy.scaling"))
+                .id(egui::Id::new("Enemy Scaling"))
+                .fixed_rect(on_screen(ctx, modal_rect.unwrap()))
+                .collapsible(false)
+                .show(ctx, |ui| {
+                    ui.label(tr_with("enemy.scaling_name", &[("name", &enemy.name)]));
+                    ui.horizontal(|ui| {
+                        ui.label(tr("enemy.levels"));
+                        numerical_text_box(ui, &mut scaling.first_level);
+                        ui.label(tr("enemy.levels_to"));
+                        numerical_text_box(ui, &mut scaling.last_level);
+                        ui.label(tr("enemy.levels_every"));
+                        numerical_text_box(ui, &mut scaling.step);
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label(tr("enemy.difficulty"));
+                        ui.text_edit_singleline(&mut scaling.difficulty);
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label(tr("enemy.growth_multiplier"));
+                        numerical_text_box(ui, &mut scaling.growth_multiplier);
+                    });
+                    ui.checkbox(&mut scaling.fixed_growths, tr("enemy.fixed_growths"));
+
+                    let copies = scaling.copies(&enemy);
+                    let taken = copies
+                        .iter()
+                        .map(|copy| &copy.name)
+                        .filter(|name| !data.enemies.check_legal_name(name))
+                        .join(", ");
+                    ui.label(tr_with("enemy.copies", &[("count", &copies.len())]));
+                    let confirm = Button::new(tr("common.confirm"));
+                    let confirmed = ui
+                        .add_enabled(!copies.is_empty() && taken.is_empty(), confirm)
+                        .on_disabled_hover_text(
+                            if taken.is_empty() {
+                                tr("enemy.no_levels")
+                            }
+                            else {
+                                tr_with("enemy.names_taken", &[("names", &taken)])
+                            }
+                        )
+                        .clicked();
+                    let cancelled = ui.button(tr("common.cancel")).clicked();
+                    if confirmed {
+                        let equipment = data.equipment.get(&enemy.name).cloned();
+                        for copy in copies {
+                            if let Some(equipment) = equipment.clone() {
+                                data.equipment.insert(copy.name.clone(), equipment);
+                            }
+                            data.enemies.insert(copy.name.clone(), copy);
+                        }
+                    }
+                    else if !cancelled {
+                        data.enemy_scaling = Some(scaling);
+                    }
+                });
+        }
     }
 
     fn promotion_manager(data : &mut GameData, ctx : &egui::Context) {
+        let game_option = data.game_option;
         data.promotions.management_dialogue(
             ctx,
             false,
-            "Promotion Manager",
+            AppWindow::PromotionManager,
             |c| c.name.clone(),
+            |ui, promotions| {
+                if builtin_promotions(game_option, promotions, None).is_empty() {
+                    return;
+                }
+                ui.collapsing(tr("promotion.builtin"), |ui| {
+                    ui.label(tr("promotion.builtin_hint"));
+                    egui::ScrollArea::vertical()
+                        .max_height(ui.text_style_height(&egui::TextStyle::Body) * 12.0)
+                        .show(ui, |ui| {
+                            for (tier, heading) in [
+                                (ClassTier::Trainee, "promotion.trainee"),
+                                (ClassTier::Unpromoted, "promotion.unpromoted")
+                            ] {
+                                let builtins =
+                                    builtin_promotions(game_option, promotions, Some(tier));
+                                if builtins.is_empty() {
+                                    continue;
+                                }
+                                ui.strong(tr(heading));
+                                for (name, promotion) in builtins {
+                                    ui.horizontal(|ui| {
+                                        if ui
+                                            .small_button(tr("promotion.save_copy"))
+                                            .on_hover_text(tr("promotion.save_copy_hint"))
+                                            .clicked()
+                                        {
+                                            promotions.insert(name.clone(), promotion);
+                                        }
+                                        ui.label(RichText::new(name).italics());
+                                    });
+                                }
+                            }
+                        });
+                });
+            }
+        );
+    }
+
+    fn custom_stat_editor(data : &mut GameData, ctx : &egui::Context) {
+        enum Edit {
+            Add,
+            Remove(usize),
+            MoveUp(usize)
+        }
+        let mut edit = None;
+
+        egui::Window::new(tr("builder.custom_stats"))
+            .id(egui::Id::new("Custom Stats"))
+            .show(ctx, |ui| {
+                egui::Grid::new("Custom Stat Table").show(ui, |ui| {
+                    let stats = data.custom_stats.stats_mut();
+                    let num_stats = stats.len();
+                    for (index, (_id, name)) in stats.iter_mut().enumerate() {
+                        let width = ui.spacing().text_edit_width / 2.0;
+                        ui.add(TextEdit::singleline(name).desired_width(width));
+                        if ui.add_enabled(index > 0, Button::new("⏶")).clicked() {
+                            edit = Some(Edit::MoveUp(index));
+                        }
+                        if ui
+                            .add_enabled(index + 1 < num_stats, Button::new("⏷"))
+                            .clicked()
+                        {
+                            edit = Some(Edit::MoveUp(index + 1));
+                        }
+                        if ui
+                            .add_enabled(num_stats > 1, Button::new("🗑"))
+                            .on_hover_text(tr("builder.remove_stat_hint"))
+                            .clicked()
+                        {
+                            edit = Some(Edit::Remove(index));
+                        }
+                        ui.end_row();
+                    }
+                });
+                if ui.button(tr("builder.add_stat")).clicked() {
+                    edit = Some(Edit::Add);
+                }
+            });
+
+        match edit {
+            Some(Edit::Add) => {
+                let sit = data.custom_stats.add(tr("builder.new_stat"));
+                data.custom_stats.publish();
+                data.character.stats.insert(sit, sit.default_stat());
+                for slot in data.slots.iter_mut() {
+                    slot.character.stats.insert(sit, sit.default_stat());
+                }
+            },
+            Some(Edit::Remove(index)) => {
+                let sit = data.custom_stats.remove(index);
+                data.character.stats.remove(&sit);
+                data.growth_modifiers.remove(&sit);
+                for slot in data.slots.iter_mut() {
+                    slot.character.stats.remove(&sit);
+                    slot.growth_modifiers.remove(&sit);
+                }
+                data.custom_stats.publish();
+            },
+            Some(Edit::MoveUp(index)) => {
+                data.custom_stats.stats_mut().swap(index - 1, index);
+                data.custom_stats.publish();
+            },
+            None => {}
+        }
+    }
+
+    fn template_manager(data : &mut GameData, ctx : &egui::Context) {
+        data.custom_templates.management_dialogue(
+            ctx,
+            false,
+            AppWindow::TemplateManager,
+            ToString::to_string,
             |_, _| {}
         );
     }
@@ -281,26 +1222,36 @@ impl FeLevelGui {
     fn weapon_manager(data : &mut GameData, ctx : &egui::Context) {
         let modal_rect = data.weapons.management_dialogue(
             ctx,
-            data.weapon.is_some(),
-            "Weapon Manager",
+            data.weapon.is_some() || data.forge.is_some(),
+            AppWindow::WeaponManager,
             |w| w.name().to_owned(),
             |ui, weapons| {
-                if ui.button("add").clicked() {
+                if ui.button(tr("manager.add")).clicked() {
                     data.weapon = Some(Weapon::new(data.game_option));
                 }
 
                 ui.add_enabled_ui(weapons.selected().is_some(), |ui| {
-                    if ui.button("edit").clicked() {
+                    if ui.button(tr("manager.edit")).clicked() {
                         let selected_name = weapons.selected().unwrap().name().to_owned();
                         data.weapon = weapons.remove(&selected_name);
                     }
+                    let forgeable = data.game_option.forge_limits().is_some()
+                        && weapons.selected().map_or(false, Weapon::has_stats);
+                    if ui
+                        .add_enabled(forgeable, Button::new(tr("weapon.forge")))
+                        .on_disabled_hover_text(tr("weapon.forge_disabled"))
+                        .clicked()
+                    {
+                        data.forge = Some(Forge::new(weapons.selected().unwrap().name()));
+                    }
                 });
             }
         );
 
         if let Some(weapon) = std::mem::take(&mut data.weapon) {
-            egui::Window::new("Weapon Builder")
-                .fixed_rect(modal_rect.unwrap())
+            egui::Window::new(tr("weapon.builder"))
+                .id(egui::Id::new("Weapon Builder"))
+                .fixed_rect(on_screen(ctx, modal_rect.unwrap()))
                 .collapsible(false)
                 .show(ctx, |ui| {
                     let (weapon, ready) = weapon.clarification_dialogue(data, ui);
@@ -312,6 +1263,41 @@ impl FeLevelGui {
                     }
                 });
         }
+
+        let forge = std::mem::take(&mut data.forge);
+        // the base weapon may have been deleted in the meantime
+        if let Some((mut forge, limits)) = forge
+            .filter(|forge| data.weapons.contains_key(&forge.base))
+            .and_then(|forge| Some((forge, data.game_option.forge_limits()?)))
+        {
+            egui::Window::new(tr("weapon.forge_title"))
+                .id(egui::Id::new("Forge"))
+                .fixed_rect(on_screen(ctx, modal_rect.unwrap()))
+                .collapsible(false)
+                .show(ctx, |ui| {
+                    forge_dialogue(&mut forge, limits, ui);
+                    let confirmed = ui
+                        .add_enabled(
+                            data.weapons.check_legal_name(&forge.name),
+                            Button::new(tr("common.confirm"))
+                        )
+                        .on_disabled_hover_text(tr("weapon.forge_name"))
+                        .clicked();
+                    let cancelled = ui.button(tr("common.cancel")).clicked();
+                    if confirmed {
+                        let forged = data
+                            .weapons
+                            .get(&forge.base)
+                            .and_then(|base| base.forged(&forge));
+                        if let Some(forged) = forged {
+                            data.weapons.insert(forge.name, forged);
+                        }
+                    }
+                    else if !cancelled {
+                        data.forge = Some(forge);
+                    }
+                });
+        }
     }
 }
 
@@ -319,34 +1305,140 @@ impl eframe::App for FeLevelGui {
     /// Called by the frame work to save state before shutdown.
     fn save(&mut self, storage : &mut dyn eframe::Storage) {
         eframe::set_value(storage, eframe::APP_KEY, self);
+        if let Some(data) = self.game_data.get(&self.game_option) {
+            data.plotter.persist(&mut self.persisted_data, storage);
+        }
     }
 
     /// Called each time the UI needs repainting, which may be many times per
     /// second. Put your widgets into a `SidePanel`, `TopPanel`,
     /// `CentralPanel`, `Window` or `Area`.
-    fn update(&mut self, ctx : &egui::Context, _frame : &mut eframe::Frame) {
+    fn update(&mut self, ctx : &egui::Context, frame : &mut eframe::Frame) {
+        self.settings.apply(&mut self.applied_settings, ctx);
         egui::TopBottomPanel::top("Game Selector").show(ctx, |ui| {
             ui.horizontal(|ui| {
                 egui::global_dark_light_mode_switch(ui);
-                ui.label("Game Mechanics: ");
-                ui.selectable_value(&mut self.game_option, GameKind::GbaFe, "GBA-FE");
-                ui.selectable_value(&mut self.game_option, GameKind::PoR, "FE9");
+                ui.label(tr("app.game_mechanics"));
+                for game in ALL_GAMES {
+                    ui.selectable_value(&mut self.game_option, game, game.name());
+                }
+                ui.separator();
+                backup::backup_menu(&mut self.backup, &self.game_data, self.game_option, ui);
+                ui.separator();
+                let game_data = self
+                    .game_data
+                    .entry(self.game_option)
+                    .or_insert_with(|| generate_default_gamedata(self.game_option));
+                layout::windows_menu(&mut self.windows, game_data, ui);
+                ui.toggle_value(&mut self.help_open, tr("app.help"))
+                    .on_hover_text(tr("app.help_hint"));
+                ui.toggle_value(&mut self.log_open, tr("log.title"))
+                    .on_hover_text(tr("log.hint"));
+                ui.toggle_value(&mut self.settings_open, tr("settings.title"))
+                    .on_hover_text(tr("app.settings_hint"));
             });
         });
+        help::help_window(&mut self.help_open, self.game_option, ctx);
+        settings::settings_window(&mut self.settings_open, &mut self.settings, ctx);
+        backup::backup_window(&mut self.backup, &mut self.game_data, ctx);
+        migration::discarded_state_window(&mut self.discarded, ctx);
+        autosave::unsaved_work_window(
+            &mut self.autosave,
+            &mut self.game_option,
+            &mut self.game_data,
+            ctx
+        );
+        #[cfg(target_arch = "wasm32")]
+        share::linked_unit_window(
+            &mut self.linked_unit,
+            &mut self.game_option,
+            &mut self.game_data,
+            ctx
+        );
 
-        egui::CentralPanel::default().show(ctx, |_| {});
+        // shown before borrowing the current game's data as it reads PoR's
+        let transfer_bonus = if self.game_option == GameKind::RD {
+            progression::transfer_bonus_window(
+                &mut self.transfer,
+                self.game_data.get(&GameKind::PoR),
+                ctx
+            )
+        }
+        else {
+            None
+        };
 
         let game_data = self
             .game_data
             .entry(self.game_option)
             .or_insert_with(|| generate_default_gamedata(self.game_option));
+        game_data.apply_tab_edit();
+        if self.game_option == GameKind::Custom {
+            game_data.custom_stats.publish();
+            Self::custom_stat_editor(game_data, ctx);
+        }
+
+        if let Some(transfer_bonus) = transfer_bonus {
+            game_data.progression.checkpoint();
+            game_data.progression.insert(0, transfer_bonus);
+            log_action(game_data, tr("log.transfer_bonus"));
+        }
 
-        Self::character_builder(game_data, ctx);
-        progression::character_progression_builder(game_data, ctx);
+        let shown = |window| self.windows.shown(window);
+        if shown(AppWindow::CharacterBuilder) {
+            Self::character_builder(game_data, &self.settings, ctx);
+        }
+        if shown(AppWindow::ProgressionBuilder) {
+            progression::character_progression_builder(game_data, ctx);
+        }
         plotter::data_plotting_windows(game_data, ctx);
-        Self::character_manager(game_data, ctx);
-        Self::promotion_manager(game_data, ctx);
-        Self::enemy_manager(game_data, ctx);
-        Self::weapon_manager(game_data, ctx);
+        if shown(AppWindow::CharacterManager) {
+            Self::character_manager(game_data, ctx);
+        }
+        if shown(AppWindow::PromotionManager) {
+            Self::promotion_manager(game_data, ctx);
+        }
+        if shown(AppWindow::TemplateManager) {
+            Self::template_manager(game_data, ctx);
+        }
+        if shown(AppWindow::EnemyManager) {
+            Self::enemy_manager(game_data, ctx);
+        }
+        if shown(AppWindow::WeaponManager) {
+            Self::weapon_manager(game_data, ctx);
+        }
+        dashboard::dashboard(game_data, &mut self.windows, ctx);
+        if self.game_option == GameKind::GbaFe {
+            onboarding::tour_window(&mut self.tour, &mut self.windows, game_data, ctx);
+        }
+        session_log::collect(game_data, ctx);
+        session_log::session_log_window(&mut self.log_open, game_data, ctx);
+        self.autosave.track(self.game_option, game_data, ctx, frame);
+        self.toasts.show(ctx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_ids_never_collide() {
+        let mut taken = BTreeSet::new();
+        let constant = (0..10_000)
+            .map(|_| UsefulId::allocate(&mut taken, || u64::MAX - 5).0)
+            .collect::<BTreeSet<_>>();
+        assert_eq!(constant.len(), 10_000);
+        assert!(constant.contains(&0));
+
+        // saved ids restore as they were and are taken from then on
+        let restored : UsefulId = ron::from_str("(42)").unwrap();
+        assert!(restored == ron::from_str(&ron::to_string(&restored).unwrap()).unwrap());
+        assert_eq!(restored.0, 42);
+        let drawn = (0..10_000)
+            .map(|_| UsefulId::default().0)
+            .collect::<BTreeSet<_>>();
+        assert_eq!(drawn.len(), 10_000);
+        assert!(!drawn.contains(&42));
     }
 }
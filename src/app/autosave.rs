@@ -0,0 +1,296 @@
+//! Keeping the character and progression being built in every game safe from
+//! crashes and reloads between the periodic saves of the app's state.
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    time::Duration
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    generate_default_gamedata,
+    locale::{tr, tr_with},
+    manager::{age, now},
+    session_log::log_action,
+    GameData, GameKind, SavedCharacter, ALL_GAMES
+};
+
+/// The storage key of the unsaved work. It's kept apart from the app's state
+/// so that it can be written without everything else.
+const AUTOSAVE_KEY : &str = "unsaved_work";
+/// the least seconds between two writes, so that dragging a slider doesn't
+/// write every frame
+const WRITE_INTERVAL : f64 = 1.0;
+
+#[derive(Serialize, Deserialize)]
+struct UnsavedWork {
+    /// when the work was last changed, in seconds since the Unix epoch
+    time : u64,
+    unit : SavedCharacter
+}
+
+fn working_unit(data : &GameData) -> SavedCharacter {
+    (
+        data.character.clone(),
+        data.progression.to_vec(),
+        data.growth_modifiers.clone()
+    )
+}
+
+/// Compares the units by their saved form, which is all that's kept of them.
+fn same_unit(unit : &SavedCharacter, other : &SavedCharacter) -> bool {
+    ron::to_string(unit).ok() == ron::to_string(other).ok()
+}
+
+/// Whether the unit would be lost without restoring it, i.e. is neither being
+/// built in any tab nor saved in the character manager under its name.
+fn is_lost(unit : &SavedCharacter, data : &GameData) -> bool {
+    let saved = data.characters.get(&unit.0.name);
+    let in_tab = data.slots.iter().any(|slot| {
+        let parked = (
+            slot.character.clone(),
+            slot.progression.to_vec(),
+            slot.growth_modifiers.clone()
+        );
+        same_unit(unit, &parked)
+    });
+    !same_unit(unit, &working_unit(data))
+        && !in_tab
+        && !saved.map_or(false, |saved| same_unit(unit, saved))
+}
+
+#[derive(Default)]
+pub struct Autosave {
+    /// the unsaved work of every game as it's written
+    work : HashMap<GameKind, UnsavedWork>,
+    /// the hashes of the saved forms of the units being built, to tell when
+    /// they change
+    seen : HashMap<GameKind, u64>,
+    /// whether the work changed since it was last written
+    pending : bool,
+    /// when the work was last written, in the time of egui's input
+    last_write : f64,
+    /// the games whose work from an earlier session the restored state lacks
+    offered : Vec<GameKind>
+}
+
+impl Autosave {
+    /// Reads the work of the last session, offering to restore that of the
+    /// games whose restored state lacks it.
+    pub fn load(
+        storage : Option<&dyn eframe::Storage>,
+        game_data : &HashMap<GameKind, GameData>
+    ) -> Self {
+        let work : HashMap<GameKind, UnsavedWork> = storage
+            .and_then(|storage| storage.get_string(AUTOSAVE_KEY))
+            .and_then(|text| ron::from_str(&text).ok())
+            .unwrap_or_default();
+        let offered = ALL_GAMES
+            .into_iter()
+            .filter(|game| {
+                let unit = match work.get(game) {
+                    Some(work) => &work.unit,
+                    None => return false
+                };
+                match game_data.get(game) {
+                    Some(data) => is_lost(unit, data),
+                    None => is_lost(unit, &generate_default_gamedata(*game))
+                }
+            })
+            .collect();
+        Self {
+            work,
+            offered,
+            ..Default::default()
+        }
+    }
+
+    /// Keeps the unit being built in the game once it changed. Games whose
+    /// earlier work is still offered are left alone so as not to overwrite it.
+    fn note(&mut self, game : GameKind, data : &GameData) {
+        if self.offered.contains(&game) {
+            return;
+        }
+        let unit = working_unit(data);
+        let mut hasher = DefaultHasher::new();
+        ron::to_string(&unit).unwrap_or_default().hash(&mut hasher);
+        let hash = hasher.finish();
+        // the first frame only learns what's being built
+        if self
+            .seen
+            .insert(game, hash)
+            .map_or(false, |seen| seen != hash)
+        {
+            self.work.insert(game, UnsavedWork { time : now(), unit });
+            self.pending = true;
+        }
+    }
+
+    /// Writes the unit being built in the game once it changed.
+    pub fn track(
+        &mut self,
+        game : GameKind,
+        data : &GameData,
+        ctx : &egui::Context,
+        frame : &mut eframe::Frame
+    ) {
+        self.note(game, data);
+        if !self.pending {
+            return;
+        }
+        let time = ctx.input().time;
+        if time - self.last_write < WRITE_INTERVAL {
+            ctx.request_repaint_after(Duration::from_secs_f64(WRITE_INTERVAL));
+            return;
+        }
+        if let (Some(storage), Ok(text)) = (frame.storage_mut(), ron::to_string(&self.work)) {
+            storage.set_string(AUTOSAVE_KEY, text);
+            storage.flush();
+        }
+        self.pending = false;
+        self.last_write = time;
+    }
+}
+
+/// The window offering to restore the work of an earlier session, one game at
+/// a time.
+pub fn unsaved_work_window(
+    autosave : &mut Autosave,
+    game_option : &mut GameKind,
+    game_data : &mut HashMap<GameKind, GameData>,
+    ctx : &egui::Context
+) {
+    let (game, work) = match autosave.offered.first() {
+        Some(game) => match autosave.work.get(game) {
+            Some(work) => (*game, work),
+            None => {
+                autosave.offered.remove(0);
+                return;
+            }
+        },
+        None => return
+    };
+    let mut restored = false;
+    let mut discarded = false;
+    egui::Window::new(tr("autosave.title"))
+        .id(egui::Id::new("Unsaved Work"))
+        .collapsible(false)
+        .show(ctx, |ui| {
+            ui.label(tr_with(
+                "autosave.offer",
+                &[
+                    ("name", &work.unit.0.name),
+                    ("game", &game.name()),
+                    ("age", &age(work.time))
+                ]
+            ));
+            ui.horizontal(|ui| {
+                restored = ui.button(tr("autosave.restore")).clicked();
+                discarded = ui.button(tr("autosave.discard")).clicked();
+            });
+        });
+    if restored {
+        let (character, progression, growth_modifiers) = work.unit.clone();
+        let data = game_data
+            .entry(game)
+            .or_insert_with(|| generate_default_gamedata(game));
+        data.character = character;
+        data.growth_modifiers = growth_modifiers;
+        data.progression.checkpoint();
+        *data.progression = progression;
+        data.insert_missing_stats();
+        log_action(
+            data,
+            tr_with("log.restored_work", &[("name", &work.unit.0.name)])
+        );
+        *game_option = game;
+        autosave.offered.remove(0);
+    }
+    else if discarded {
+        autosave.work.remove(&game);
+        autosave.pending = true;
+        autosave.offered.remove(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct MemoryStorage(HashMap<String, String>);
+
+    impl eframe::Storage for MemoryStorage {
+        fn get_string(&self, key : &str) -> Option<String> { self.0.get(key).cloned() }
+
+        fn set_string(&mut self, key : &str, value : String) {
+            self.0.insert(key.to_owned(), value);
+        }
+
+        fn flush(&mut self) {}
+    }
+
+    /// the default data of the game building the named unit, and that unit
+    fn building(game : GameKind, name : &str) -> (GameData, SavedCharacter) {
+        let mut data = generate_default_gamedata(game);
+        data.character.name = name.to_owned();
+        let unit = working_unit(&data);
+        (data, unit)
+    }
+
+    #[test]
+    fn only_work_neither_built_nor_saved_is_lost() {
+        let (mut data, unit) = building(GameKind::GbaFe, "Eliwood");
+        assert!(!is_lost(&unit, &data));
+
+        data.character.name = "Hector".to_owned();
+        assert!(is_lost(&unit, &data));
+
+        data.characters.insert("Eliwood".to_owned(), unit.clone());
+        assert!(!is_lost(&unit, &data));
+    }
+
+    #[test]
+    fn games_missing_from_the_state_are_compared_to_their_defaults() {
+        let (_data, changed) = building(GameKind::ThreeHouses, "Byleth");
+        let default = working_unit(&generate_default_gamedata(GameKind::PoR));
+        let work : HashMap<GameKind, UnsavedWork> =
+            [(GameKind::ThreeHouses, changed), (GameKind::PoR, default)]
+                .into_iter()
+                .map(|(game, unit)| (game, UnsavedWork { time : 0, unit }))
+                .collect();
+        let mut storage = MemoryStorage::default();
+        storage
+            .0
+            .insert(AUTOSAVE_KEY.to_owned(), ron::to_string(&work).unwrap());
+
+        let autosave = Autosave::load(Some(&storage), &HashMap::new());
+        assert_eq!(autosave.offered, vec![GameKind::ThreeHouses]);
+    }
+
+    #[test]
+    fn offered_work_is_not_overwritten() {
+        let (mut data, unit) = building(GameKind::GbaFe, "Eliwood");
+        let mut autosave = Autosave {
+            work : [(GameKind::GbaFe, UnsavedWork { time : 0, unit })].into(),
+            offered : vec![GameKind::GbaFe],
+            ..Default::default()
+        };
+        for name in ["Hector", "Lyn"] {
+            data.character.name = name.to_owned();
+            autosave.note(GameKind::GbaFe, &data);
+        }
+        assert_eq!(autosave.work[&GameKind::GbaFe].unit.0.name, "Eliwood");
+        assert!(!autosave.pending);
+
+        autosave.offered.clear();
+        for name in ["Hector", "Lyn"] {
+            data.character.name = name.to_owned();
+            autosave.note(GameKind::GbaFe, &data);
+        }
+        assert_eq!(autosave.work[&GameKind::GbaFe].unit.0.name, "Lyn");
+        assert!(autosave.pending);
+    }
+}
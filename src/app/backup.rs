@@ -0,0 +1,421 @@
+//! Backing up the data of every game to a single file and restoring it.
+
+use std::collections::{BTreeMap, HashMap};
+
+use egui::{ScrollArea, Ui};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    files::{self, Upload},
+    locale::{tr, tr_with},
+    manager::DataManaged,
+    session_log::log_action,
+    toasts, GameData, GameKind, ALL_GAMES
+};
+
+/// The version of the backup format. Fields added to the game data are
+/// filled with their defaults when loading older backups, so this only has to
+/// be raised once old backups can't be read as they are anymore.
+const BACKUP_VERSION : u64 = 1;
+
+#[derive(Deserialize, Serialize)]
+struct Backup<G> {
+    version : u64,
+    games : HashMap<GameKind, G>
+}
+
+/// The saved entries of a game, i.e. what a backup is merged by.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Collection {
+    Characters,
+    Promotions,
+    Templates,
+    Enemies,
+    Weapons
+}
+
+impl Collection {
+    fn name(&self) -> String {
+        tr(match self {
+            Collection::Characters => "backup.character",
+            Collection::Promotions => "backup.promotion",
+            Collection::Templates => "backup.template",
+            Collection::Enemies => "backup.enemy",
+            Collection::Weapons => "backup.weapon"
+        })
+    }
+}
+
+/// A saved entry of the backup whose name is taken in the current data.
+struct Collision {
+    game : GameKind,
+    collection : Collection,
+    name : String,
+    overwrite : bool
+}
+
+struct ImportedBackup {
+    games : HashMap<GameKind, GameData>,
+    /// whether the games of the backup replace the current ones rather than
+    /// being merged into them
+    replace : bool,
+    collisions : Vec<Collision>
+}
+
+#[derive(Default)]
+pub struct BackupDialog {
+    upload : Option<Upload>,
+    imported : Option<ImportedBackup>
+}
+
+/// The name the entry is saved under in the managed data, compared the way
+/// the managers do.
+fn taken_name<V>(managed : &DataManaged<V>, name : &str) -> Option<String> {
+    managed
+        .keys()
+        .find(|taken| taken.to_lowercase() == name.to_lowercase())
+        .cloned()
+}
+
+fn collisions<V>(
+    game : GameKind,
+    collection : Collection,
+    current : &DataManaged<V>,
+    imported : &DataManaged<V>
+) -> Vec<Collision> {
+    imported
+        .keys()
+        .filter(|name| taken_name(current, name).is_some())
+        .map(|name| Collision {
+            game,
+            collection,
+            name : name.clone(),
+            overwrite : false
+        })
+        .collect()
+}
+
+/// Moves the imported entries into the current ones, returning the names of
+/// those moved. Entries whose names are taken are only moved if they're to
+/// overwrite the current ones.
+fn merge_entries<V>(
+    current : &mut DataManaged<V>,
+    imported : &mut DataManaged<V>,
+    overwrite : impl Fn(&str) -> bool
+) -> Vec<String> {
+    let mut moved = Vec::new();
+    for (name, entry) in imported.take_all() {
+        match taken_name(current, &name) {
+            Some(taken) if overwrite(&name) => {
+                current.remove(&taken);
+            },
+            Some(_taken) => continue,
+            None => {}
+        }
+        current.insert_managed(name.clone(), entry);
+        moved.push(name);
+    }
+    moved
+}
+
+/// Moves the extra data of the moved entries along, removing that of the
+/// overwritten entries if the imported ones have none.
+fn merge_extra<T>(
+    current : &mut BTreeMap<String, T>,
+    imported : &mut BTreeMap<String, T>,
+    moved : &[String]
+) {
+    for name in moved {
+        current.retain(|taken, _extra| taken.to_lowercase() != name.to_lowercase());
+        if let Some(extra) = imported.remove(name) {
+            current.insert(name.clone(), extra);
+        }
+    }
+}
+
+/// Merges the saved entries of the imported game into the current one, the
+/// collisions being those of this game. Everything else, e.g. the character
+/// being built, stays as it is.
+fn merge_game(current : &mut GameData, mut imported : GameData, collisions : &[&Collision]) {
+    let overwrite = |collection : Collection| {
+        move |name : &str| {
+            collisions.iter().any(|collision| {
+                collision.collection == collection && collision.name == name && collision.overwrite
+            })
+        }
+    };
+    let moved = merge_entries(
+        &mut current.characters,
+        &mut imported.characters,
+        overwrite(Collection::Characters)
+    );
+    merge_extra(
+        &mut current.character_weapon_ranks,
+        &mut imported.character_weapon_ranks,
+        &moved
+    );
+    merge_entries(
+        &mut current.promotions,
+        &mut imported.promotions,
+        overwrite(Collection::Promotions)
+    );
+    merge_entries(
+        &mut current.custom_templates,
+        &mut imported.custom_templates,
+        overwrite(Collection::Templates)
+    );
+    let moved = merge_entries(
+        &mut current.enemies,
+        &mut imported.enemies,
+        overwrite(Collection::Enemies)
+    );
+    merge_extra(&mut current.equipment, &mut imported.equipment, &moved);
+    merge_entries(
+        &mut current.weapons,
+        &mut imported.weapons,
+        overwrite(Collection::Weapons)
+    );
+}
+
+fn game_collisions(game : GameKind, current : &GameData, imported : &GameData) -> Vec<Collision> {
+    [
+        collisions(
+            game,
+            Collection::Characters,
+            &current.characters,
+            &imported.characters
+        ),
+        collisions(
+            game,
+            Collection::Promotions,
+            &current.promotions,
+            &imported.promotions
+        ),
+        collisions(
+            game,
+            Collection::Templates,
+            &current.custom_templates,
+            &imported.custom_templates
+        ),
+        collisions(
+            game,
+            Collection::Enemies,
+            &current.enemies,
+            &imported.enemies
+        ),
+        collisions(
+            game,
+            Collection::Weapons,
+            &current.weapons,
+            &imported.weapons
+        )
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+fn export(games : HashMap<GameKind, &GameData>) -> Result<String, String> {
+    let backup = Backup {
+        version : BACKUP_VERSION,
+        games
+    };
+    ron::ser::to_string_pretty(&backup, Default::default()).map_err(|error| error.to_string())
+}
+
+/// Reads the backup, refusing those of newer versions, whose games might not
+/// make sense to this one even where they can be read.
+fn parse(text : &str) -> Result<HashMap<GameKind, GameData>, String> {
+    #[derive(Deserialize)]
+    #[serde(rename = "Backup")]
+    struct Version {
+        version : u64
+    }
+    let not_a_backup =
+        |error : ron::error::SpannedError| tr_with("backup.not_a_backup", &[("error", &error)]);
+    let Version { version } = ron::from_str(text).map_err(not_a_backup)?;
+    if version > BACKUP_VERSION {
+        return Err(tr_with(
+            "backup.newer_version",
+            &[("version", &version), ("max", &BACKUP_VERSION)]
+        ));
+    }
+    let mut backup : Backup<GameData> = ron::from_str(text).map_err(not_a_backup)?;
+    backup
+        .games
+        .values_mut()
+        .for_each(GameData::insert_missing_stats);
+    Ok(backup.games)
+}
+
+impl BackupDialog {
+    fn import(
+        &mut self,
+        file : Result<Vec<u8>, String>,
+        current : &HashMap<GameKind, GameData>,
+        ctx : &egui::Context
+    ) {
+        let games = match file
+            .and_then(|bytes| String::from_utf8(bytes).map_err(|error| error.to_string()))
+            .and_then(|text| parse(&text))
+        {
+            Ok(games) => games,
+            Err(error) => {
+                toasts::error(ctx, tr_with("backup.import_failed", &[("error", &error)]));
+                return;
+            }
+        };
+        let collisions = games
+            .iter()
+            .filter_map(|(game, imported)| {
+                Some(game_collisions(*game, current.get(game)?, imported))
+            })
+            .flatten()
+            .collect();
+        self.imported = Some(ImportedBackup {
+            games,
+            replace : false,
+            collisions
+        });
+    }
+}
+
+fn save(file_name : &str, games : HashMap<GameKind, &GameData>, ctx : &egui::Context) {
+    if let Err(error) = export(games).and_then(|backup| files::save(file_name, files::RON, &backup))
+    {
+        toasts::error(ctx, tr_with("backup.export_failed", &[("error", &error)]));
+    }
+}
+
+/// The top bar menu exporting and importing backups.
+pub fn backup_menu(
+    dialog : &mut BackupDialog,
+    game_data : &HashMap<GameKind, GameData>,
+    current_game : GameKind,
+    ui : &mut Ui
+) {
+    ui.add_enabled_ui(dialog.imported.is_none(), |ui| {
+        ui.menu_button(tr("backup.menu"), |ui| {
+            if ui.button(tr("backup.export_all")).clicked() {
+                let games = game_data.iter().map(|(game, data)| (*game, data)).collect();
+                save("backup.ron", games, ui.ctx());
+                ui.close_menu();
+            }
+            if let Some(data) = game_data.get(&current_game) {
+                if ui
+                    .button(tr_with(
+                        "backup.export_game",
+                        &[("game", &current_game.name())]
+                    ))
+                    .clicked()
+                {
+                    save(
+                        &format!("backup {}.ron", current_game.name()),
+                        HashMap::from([(current_game, data)]),
+                        ui.ctx()
+                    );
+                    ui.close_menu();
+                }
+            }
+            if ui
+                .button(tr("backup.import"))
+                .on_hover_text(tr("backup.import_hint"))
+                .clicked()
+            {
+                dialog.upload = Some(files::pick(files::RON));
+                ui.close_menu();
+            }
+        });
+    });
+    if let Some(upload) = std::mem::take(&mut dialog.upload) {
+        match upload.try_take() {
+            Ok(Some(file)) => dialog.import(file, game_data, ui.ctx()),
+            Ok(None) => {},
+            Err(upload) => {
+                dialog.upload = Some(upload);
+                ui.ctx().request_repaint();
+            }
+        }
+    }
+}
+
+/// Adds the games of the backup, replacing those already present or merging
+/// into them.
+fn import_games(imported : ImportedBackup, game_data : &mut HashMap<GameKind, GameData>) {
+    for (game, data) in imported.games {
+        match game_data.get_mut(&game) {
+            Some(current) if !imported.replace => {
+                let collisions = imported
+                    .collisions
+                    .iter()
+                    .filter(|collision| collision.game == game)
+                    .collect_vec();
+                merge_game(current, data, &collisions);
+                log_action(current, tr("log.backup_merged"));
+            },
+            _ => {
+                let mut data = data;
+                // the log goes on, telling what the backup replaced
+                if let Some(replaced) = game_data.get_mut(&game) {
+                    data.session_log = std::mem::take(&mut replaced.session_log);
+                }
+                log_action(&mut data, tr("log.backup_replaced"));
+                game_data.insert(game, data);
+            }
+        }
+    }
+}
+
+/// The window asking how to import the loaded backup.
+pub fn backup_window(
+    dialog : &mut BackupDialog,
+    game_data : &mut HashMap<GameKind, GameData>,
+    ctx : &egui::Context
+) {
+    let mut imported = match std::mem::take(&mut dialog.imported) {
+        Some(imported) => imported,
+        None => return
+    };
+    let mut confirmed = false;
+    let mut cancelled = false;
+    egui::Window::new(tr("backup.title"))
+        .id(egui::Id::new("Import Backup"))
+        .collapsible(false)
+        .show(ctx, |ui| {
+            let games = ALL_GAMES
+                .iter()
+                .filter(|game| imported.games.contains_key(game))
+                .map(GameKind::name)
+                .join(", ");
+            ui.label(tr_with("backup.holds", &[("games", &games)]));
+            ui.radio_value(&mut imported.replace, false, tr("backup.merge"));
+            ui.radio_value(&mut imported.replace, true, tr("backup.replace"));
+            if !imported.replace && !imported.collisions.is_empty() {
+                ui.label(tr("backup.collisions"));
+                ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                    for collision in imported.collisions.iter_mut() {
+                        ui.checkbox(
+                            &mut collision.overwrite,
+                            format!(
+                                "{} {}: {}",
+                                collision.game.name(),
+                                collision.collection.name(),
+                                collision.name
+                            )
+                        );
+                    }
+                });
+            }
+            ui.horizontal(|ui| {
+                confirmed = ui.button(tr("backup.import_confirm")).clicked();
+                cancelled = ui.button(tr("common.cancel")).clicked();
+            });
+        });
+    if confirmed {
+        import_games(imported, game_data);
+    }
+    else if !cancelled {
+        dialog.imported = Some(imported);
+    }
+}
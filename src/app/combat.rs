@@ -0,0 +1,235 @@
+use std::collections::BTreeMap;
+
+use fe_levels::StatType;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::GameKind;
+
+/// How a game rolls the random numbers its hits are checked against, which
+/// makes the displayed hit rate differ from the actual chance to hit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HitRng {
+    /// a single random number, the displayed hit rate being the actual one,
+    /// e.g. FE5 and most romhacks of it
+    OneRn,
+    /// the average of two random numbers, inflating high hit rates and
+    /// deflating low ones
+    TwoRn,
+    /// a single random number below 50 displayed hit and a weighted average of
+    /// two random numbers from there on, as from Fates on
+    Hybrid
+}
+
+impl HitRng {
+    pub fn of(game_option : GameKind) -> Self {
+        match game_option {
+            GameKind::GbaFe | GameKind::PoR | GameKind::RD => HitRng::TwoRn,
+            GameKind::ShadowDragon | GameKind::Custom => HitRng::OneRn,
+            GameKind::SoV | GameKind::ThreeHouses => HitRng::Hybrid
+        }
+    }
+
+    /// The actual chance to hit of the displayed hit rate.
+    pub fn true_hit(self, displayed : u8) -> f64 {
+        let displayed = displayed.min(100);
+        match self {
+            HitRng::OneRn => displayed as f64 / 100.0,
+            HitRng::TwoRn => true_hit(displayed),
+            HitRng::Hybrid if displayed < 50 => displayed as f64 / 100.0,
+            // the first number weighs thrice as much as the second
+            HitRng::Hybrid => {
+                let bound = 4 * displayed as u32;
+                let hitting_pairs : u32 = (0..100u32)
+                    .map(|first| bound.saturating_sub(3 * first).min(100))
+                    .sum();
+                hitting_pairs as f64 / 10_000.0
+            }
+        }
+    }
+
+    /// The lowest displayed hit rate whose actual chance to hit reaches the
+    /// given one.
+    pub fn displayed_hit(self, true_hit : f64) -> u8 {
+        (0..=100)
+            .find(|displayed| self.true_hit(*displayed) >= true_hit - 1e-9)
+            .unwrap_or(100)
+    }
+}
+
+/// The actual chance to hit of the displayed hit rate if the game compares
+/// the average of two random numbers against it, e.g. 87.75% for 75.
+pub fn true_hit(displayed : u8) -> f64 {
+    let doubled = 2 * displayed.min(100) as u32;
+    let hitting_pairs : u32 = (0..100u32)
+        .map(|first| doubled.saturating_sub(first).min(100))
+        .sum();
+    hitting_pairs as f64 / 10_000.0
+}
+
+/// The strikes of one round of combat against the character, each landing
+/// with the same chance and dealing the same damage.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Strikes {
+    pub count : u32,
+    /// the actual chance to hit, not the displayed one
+    pub hit : f64,
+    pub damage : u32
+}
+
+/// The distribution of the total damage the rounds deal by its amount, all
+/// damage from the limit on lumped into the last entry.
+pub fn damage_distribution(rounds : &[Strikes], limit : usize) -> Vec<f64> {
+    let mut distribution = vec![0.0; limit + 1];
+    distribution[0] = 1.0;
+    for strikes in rounds {
+        for _strike in 0..strikes.count {
+            let mut next = vec![0.0; limit + 1];
+            for (damage, chance) in distribution.iter().enumerate() {
+                next[damage] += chance * (1.0 - strikes.hit);
+                next[(damage + strikes.damage as usize).min(limit)] += chance * strikes.hit;
+            }
+            distribution = next;
+        }
+    }
+    distribution
+}
+
+/// The chance to take less damage than the health over all rounds.
+pub fn survival_chance(rounds : &[Strikes], health : &BTreeMap<StatType, f64>) -> f64 {
+    let limit = health.keys().max().map_or(0, |max| *max as usize);
+    let damage = damage_distribution(rounds, limit);
+    health
+        .iter()
+        .map(|(health, chance)| chance * damage[..*health as usize].iter().sum::<f64>())
+        .sum()
+}
+
+/// The generator of a Monte Carlo simulation, reproducible when seeded and
+/// drawing from the platform's entropy otherwise.
+pub fn simulation_rng(seed : Option<u64>) -> StdRng {
+    seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64)
+}
+
+/// Draws a value from the distribution.
+pub fn sample(distribution : &BTreeMap<StatType, f64>, rng : &mut impl Rng) -> StatType {
+    let mut roll : f64 = rng.gen();
+    for (value, chance) in distribution {
+        if roll < *chance {
+            return *value;
+        }
+        roll -= chance;
+    }
+    distribution.keys().last().copied().unwrap_or_default()
+}
+
+/// Whether a simulated run of the rounds leaves the character standing.
+pub fn simulate_survival(rounds : &[Strikes], health : StatType, rng : &mut impl Rng) -> bool {
+    let damage : u32 = rounds
+        .iter()
+        .flat_map(|strikes| std::iter::repeat(strikes).take(strikes.count as usize))
+        .filter(|strikes| rng.gen::<f64>() < strikes.hit)
+        .map(|strikes| strikes.damage)
+        .sum();
+    damage < u32::from(health)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual : f64, expected : f64) {
+        assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn two_rn_hit_rates_match_the_known_table() {
+        for (displayed, expected) in [
+            (0, 0.0),
+            (1, 0.0003),
+            (10, 0.021),
+            (25, 0.1275),
+            (50, 0.505),
+            (60, 0.684),
+            (70, 0.823),
+            (75, 0.8775),
+            (80, 0.922),
+            (90, 0.981),
+            (99, 0.9999),
+            (100, 1.0)
+        ] {
+            assert_close(true_hit(displayed), expected);
+        }
+    }
+
+    #[test]
+    fn hybrid_hit_rates_only_change_from_50_on() {
+        assert_close(HitRng::Hybrid.true_hit(49), 0.49);
+        assert_close(HitRng::Hybrid.true_hit(50), 0.505);
+        assert_close(HitRng::Hybrid.true_hit(100), 1.0);
+    }
+
+    #[test]
+    fn displayed_hit_inverts_true_hit() {
+        for rng in [HitRng::OneRn, HitRng::TwoRn, HitRng::Hybrid] {
+            for displayed in 0..=100 {
+                assert_eq!(
+                    rng.displayed_hit(rng.true_hit(displayed)),
+                    displayed,
+                    "{rng:?}"
+                );
+            }
+        }
+        assert_eq!(HitRng::TwoRn.displayed_hit(0.8775), 75);
+    }
+
+    #[test]
+    fn survival_counts_the_hits_the_health_can_take() {
+        let rounds = [Strikes {
+            count : 2,
+            hit : 0.5,
+            damage : 10
+        }];
+        assert_close(survival_chance(&rounds, &BTreeMap::from([(15, 1.0)])), 0.75);
+        assert_close(survival_chance(&rounds, &BTreeMap::from([(20, 1.0)])), 0.75);
+        assert_close(survival_chance(&rounds, &BTreeMap::from([(21, 1.0)])), 1.0);
+        assert_close(
+            survival_chance(&rounds, &BTreeMap::from([(10, 0.5), (21, 0.5)])),
+            0.625
+        );
+    }
+
+    #[test]
+    fn simulated_survival_approaches_the_exact_chance() {
+        let rounds = [
+            Strikes {
+                count : 2,
+                hit : 0.7,
+                damage : 6
+            },
+            Strikes {
+                count : 1,
+                hit : 0.4,
+                damage : 9
+            }
+        ];
+        let health = BTreeMap::from([(12, 0.3), (16, 0.7)]);
+        let mut rng = simulation_rng(Some(0));
+        let runs = 20_000;
+        let survived = (0..runs)
+            .filter(|_run| simulate_survival(&rounds, sample(&health, &mut rng), &mut rng))
+            .count();
+        let exact = survival_chance(&rounds, &health);
+        assert!((survived as f64 / runs as f64 - exact).abs() < 0.02);
+    }
+
+    #[test]
+    fn seeded_simulations_repeat_and_unseeded_ones_differ() {
+        let draws = |seed| {
+            let mut rng = simulation_rng(seed);
+            (0..4).map(|_draw| rng.gen::<u64>()).collect::<Vec<_>>()
+        };
+        assert_eq!(draws(Some(7)), draws(Some(7)));
+        assert_ne!(draws(Some(7)), draws(Some(8)));
+        assert_ne!(draws(None), draws(None));
+    }
+}
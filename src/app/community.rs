@@ -0,0 +1,242 @@
+//! Units in the json of community spreadsheets and of the units the REPL saves
+//! to `./data/characters/<game>/<name>.json`. Stats are named by lowercase
+//! abbreviations rather than the GUI's indices:
+//!
+//! ```json
+//! {
+//!     "name": "Eliwood",
+//!     "level": 1,
+//!     "stats": {
+//!         "hp": { "base": 18, "growth": 80, "cap": 60 },
+//!         "str": { "base": 5, "growth": 45, "cap": 20 }
+//!     }
+//! }
+//! ```
+//!
+//! The stats may be listed as `[name, stat]` pairs instead, as the REPL saves
+//! them. Stats go by the names of the game or any of their aliases, e.g. "str"
+//! or "atk" for GBA Atk, and a stat's `value` defaults to its base.
+
+use std::collections::BTreeMap;
+
+use egui::Ui;
+use fe_levels::{Character, GrowthType, Stat, StatType};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    files::{self, Upload},
+    locale::{tr, tr_with},
+    sit::StatIndexType,
+    toasts, GameKind
+};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+struct CommunityStat {
+    base : StatType,
+    growth : GrowthType,
+    cap : StatType,
+    #[serde(default, skip_serializing)]
+    value : Option<StatType>
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum CommunityStats {
+    Named(BTreeMap<String, CommunityStat>),
+    /// as the REPL saves them
+    Listed(Vec<(String, CommunityStat)>)
+}
+
+#[derive(Serialize, Deserialize)]
+struct CommunityUnit {
+    #[serde(default)]
+    name : String,
+    #[serde(default = "first_level")]
+    level : usize,
+    stats : CommunityStats
+}
+
+fn first_level() -> usize { 1 }
+
+/// The unit with its stats named as in community json.
+fn encode(character : &Character<StatIndexType>) -> Result<String, String> {
+    let unit = CommunityUnit {
+        name : character.name.clone(),
+        level : character.level,
+        stats : CommunityStats::Named(
+            character
+                .stats
+                .iter()
+                .map(|(sit, stat)| {
+                    (
+                        sit.key(),
+                        CommunityStat {
+                            base : stat.base,
+                            growth : stat.growth,
+                            cap : stat.cap,
+                            value : None
+                        }
+                    )
+                })
+                .collect()
+        )
+    };
+    serde_json::to_string_pretty(&unit).map_err(|error| error.to_string())
+}
+
+/// The unit of the community json with the stats the game knows, together
+/// with the names of those it doesn't.
+fn decode(
+    game_option : GameKind,
+    text : &str
+) -> Result<(Character<StatIndexType>, Vec<String>), String> {
+    let unit : CommunityUnit = serde_json::from_str(text).map_err(|error| error.to_string())?;
+    let stats = match unit.stats {
+        CommunityStats::Named(stats) => stats.into_iter().collect_vec(),
+        CommunityStats::Listed(stats) => stats
+    };
+    let mut unknown = Vec::new();
+    let mut character = Character {
+        stats : BTreeMap::new(),
+        name : unit.name,
+        level : unit.level
+    };
+    for (key, stat) in stats {
+        match StatIndexType::from_key(game_option, &key) {
+            Some(sit) => {
+                character.stats.insert(
+                    sit,
+                    Stat {
+                        base : stat.base,
+                        cap : stat.cap,
+                        growth : stat.growth,
+                        value : stat.value.unwrap_or(stat.base)
+                    }
+                );
+            },
+            None => unknown.push(key)
+        }
+    }
+    Ok((character, unknown))
+}
+
+#[derive(Default)]
+pub struct CommunityDialog {
+    upload : Option<Upload>
+}
+
+/// The buttons saving the character as community json and loading it from
+/// there, returning the loaded character.
+pub fn community_buttons(
+    dialog : &mut CommunityDialog,
+    character : &Character<StatIndexType>,
+    game_option : GameKind,
+    ui : &mut Ui
+) -> Option<Character<StatIndexType>> {
+    ui.horizontal(|ui| {
+        ui.label(tr("community.json"));
+        if ui.button(tr("community.save")).clicked() {
+            let file_name = format!("{}.json", character.name.to_lowercase());
+            if let Err(error) =
+                encode(character).and_then(|json| files::save(&file_name, files::JSON, &json))
+            {
+                toasts::error(
+                    ui.ctx(),
+                    tr_with("community.save_failed", &[("error", &error)])
+                );
+            }
+        }
+        if ui
+            .button(tr("community.load"))
+            .on_hover_text(tr("community.load_hint"))
+            .clicked()
+        {
+            dialog.upload = Some(files::pick(files::JSON));
+        }
+    });
+    let mut loaded = None;
+    if let Some(upload) = std::mem::take(&mut dialog.upload) {
+        match upload.try_take() {
+            Ok(Some(file)) => {
+                let decoded = file
+                    .and_then(|bytes| String::from_utf8(bytes).map_err(|error| error.to_string()))
+                    .and_then(|text| decode(game_option, &text));
+                match decoded {
+                    Ok((character, unknown)) => {
+                        loaded = Some(character);
+                        if !unknown.is_empty() {
+                            let unknown = unknown.join(", ");
+                            toasts::info(
+                                ui.ctx(),
+                                tr_with("community.unknown_stats", &[("unknown", &unknown)])
+                            );
+                        }
+                    },
+                    Err(error) => {
+                        toasts::error(
+                            ui.ctx(),
+                            tr_with("community.load_failed", &[("error", &error)])
+                        );
+                    }
+                }
+            },
+            Ok(None) => {},
+            Err(upload) => {
+                dialog.upload = Some(upload);
+                ui.ctx().request_repaint();
+            }
+        }
+    }
+    loaded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn named_stats_and_aliases_are_read() {
+        let text = r#"{
+            "name": "Eliwood",
+            "stats": {
+                "hp": { "base": 18, "growth": 80, "cap": 60 },
+                "str": { "base": 5, "growth": 45, "cap": 20 }
+            }
+        }"#;
+        let (character, unknown) = decode(GameKind::GbaFe, text).unwrap();
+        assert!(unknown.is_empty());
+        assert_eq!(character.name, "Eliwood");
+        assert_eq!(character.level, 1);
+        let atk = StatIndexType::from_key(GameKind::GbaFe, "atk").unwrap();
+        assert_eq!(character.stats[&atk].base, 5);
+        assert_eq!(character.stats[&atk].value, 5);
+    }
+
+    #[test]
+    fn listed_stats_are_read_and_unknown_ones_reported() {
+        let text = r#"{
+            "name": "Ike",
+            "level": 4,
+            "stats": [
+                ["hp", { "base": 19, "growth": 80, "cap": 40, "value": 21 }],
+                ["bravery", { "base": 1, "growth": 1, "cap": 1 }]
+            ]
+        }"#;
+        let (character, unknown) = decode(GameKind::PoR, text).unwrap();
+        assert_eq!(unknown, vec!["bravery".to_owned()]);
+        assert_eq!(character.stats.len(), 1);
+        let hp = StatIndexType::from_key(GameKind::PoR, "HP").unwrap();
+        assert_eq!(character.stats[&hp].value, 21);
+    }
+
+    #[test]
+    fn encoded_units_round_trip() {
+        let mut character = StatIndexType::new_default_character(GameKind::ThreeHouses);
+        character.name = "Byleth".to_owned();
+        let (decoded, unknown) =
+            decode(GameKind::ThreeHouses, &encode(&character).unwrap()).unwrap();
+        assert!(unknown.is_empty());
+        assert_eq!(decoded, character);
+    }
+}
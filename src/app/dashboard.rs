@@ -0,0 +1,120 @@
+//! The overview of the current game's unit filling the background behind the
+//! windows.
+
+use egui::{Id, LayerId, Order, Ui};
+use itertools::Itertools;
+
+use super::{
+    layout::{AppWindow, WindowVisibility},
+    locale::{tr, tr_with},
+    GameData
+};
+
+/// The windows the dashboard brings to the front, by the key of their button
+/// label.
+const QUICK_WINDOWS : [(&str, AppWindow); 7] = [
+    ("dashboard.builder", AppWindow::CharacterBuilder),
+    ("dashboard.progression", AppWindow::ProgressionBuilder),
+    ("dashboard.characters", AppWindow::CharacterManager),
+    ("dashboard.promotions", AppWindow::PromotionManager),
+    ("dashboard.templates", AppWindow::TemplateManager),
+    ("dashboard.enemies", AppWindow::EnemyManager),
+    ("dashboard.weapons", AppWindow::WeaponManager)
+];
+
+fn focus_button(ui : &mut Ui, label_key : &str, window : Id) -> bool {
+    let clicked = ui.button(tr(label_key)).clicked();
+    if clicked {
+        ui.ctx().move_to_top(LayerId::new(Order::Middle, window));
+    }
+    clicked
+}
+
+/// The expected stats at the end of the progression, or a spinner while they
+/// are still being computed.
+fn expected_stats(data : &GameData, ui : &mut Ui) {
+    let character = data.effective_character();
+    let averages = match data.plotter.final_averages(&character, &data.progression) {
+        Some(averages) => averages,
+        None => {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(tr("dashboard.computing"));
+            });
+            return;
+        }
+    };
+    egui::Grid::new("Dashboard Stat Table")
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label(tr("common.stat"));
+            ui.label(tr("common.base"));
+            ui.label(tr("dashboard.expected"));
+            ui.label(tr("builder.cap"));
+            ui.end_row();
+
+            for (sit, stat) in character
+                .stats
+                .iter()
+                .sorted_by_key(|(sit, _stat)| sit.display_order())
+            {
+                ui.label(sit.to_string());
+                ui.label(stat.base.to_string());
+                match averages.get(sit) {
+                    Some(average) => ui.label(format!("{average:.2}")),
+                    None => ui.label("-")
+                };
+                ui.label(stat.cap.to_string());
+                ui.end_row();
+            }
+        });
+}
+
+/// Shows the character being built, what they're expected to end up with and
+/// how much is saved in the current game. The buttons showing the windows
+/// bring back hidden ones as well.
+pub fn dashboard(data : &mut GameData, visibility : &mut WindowVisibility, ctx : &egui::Context) {
+    egui::CentralPanel::default().show(ctx, |ui| {
+        let name = match data.character.name.as_str() {
+            "" => tr("dashboard.unnamed"),
+            name => name.to_owned()
+        };
+        ui.heading(tr_with(
+            "dashboard.heading",
+            &[("name", &name), ("level", &data.character.level)]
+        ));
+        ui.label(tr_with(
+            "dashboard.summary",
+            &[
+                ("game", &data.game_option.name()),
+                ("count", &data.progression.len())
+            ]
+        ));
+        ui.separator();
+
+        ui.label(tr("dashboard.expected_stats"));
+        expected_stats(data, ui);
+        ui.separator();
+
+        ui.label(tr_with(
+            "dashboard.saved",
+            &[
+                ("characters", &data.characters.len()),
+                ("promotions", &data.promotions.len()),
+                ("enemies", &data.enemies.len()),
+                ("weapons", &data.weapons.len())
+            ]
+        ));
+        ui.horizontal_wrapped(|ui| {
+            ui.label(tr("dashboard.show"));
+            for (label, window) in QUICK_WINDOWS {
+                if focus_button(ui, label, window.id()) {
+                    visibility.show(window);
+                }
+            }
+            if focus_button(ui, "dashboard.plotter", data.plotter.window_id()) {
+                data.plotter.show_windows();
+            }
+        });
+    });
+}
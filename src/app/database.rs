@@ -0,0 +1,274 @@
+use std::collections::BTreeMap;
+
+use fe_levels::{Character, GrowthType, Stat, StatType};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use super::{locale::tr, manager::DataManaged, sit::StatIndexType, GameKind};
+
+/// The playable casts of FE7 and FE8, save for FE8's Orson and Tana, and the
+/// characters joining in the first chapters of FE6.
+const GBA_CHARACTERS : &str = include_str!("database/gba_characters.json");
+/// The promotions of FE7 and FE8, FE8's branching promotions listed once per
+/// target class.
+const GBA_PROMOTIONS : &str = include_str!("database/gba_promotions.json");
+/// The generic enemy classes of FE7.
+const GBA_ENEMY_CLASSES : &str = include_str!("database/gba_enemy_classes.json");
+
+/// A playable character as they join, every stat list following the
+/// iteration order of [`GameKind::GbaFe`], i.e. HP, Atk, Skl, Spd, Lck, Def,
+/// Res, Con and Mov.
+#[derive(Deserialize, Clone, Debug)]
+pub struct DatabaseCharacter {
+    /// the game the character is from, e.g. "FE8"
+    pub game : String,
+    pub name : String,
+    pub class_name : String,
+    /// the level the character joins at
+    pub level : usize,
+    bases : Vec<StatType>,
+    growths : Vec<GrowthType>,
+    caps : Vec<StatType>
+}
+
+impl DatabaseCharacter {
+    pub fn to_character(&self) -> Character<StatIndexType> {
+        Character {
+            stats : StatIndexType::new(GameKind::GbaFe)
+                .into_iter()
+                .zip(self.bases.iter().zip(&self.growths).zip(&self.caps))
+                .map(|(sit, ((base, growth), cap))| {
+                    (
+                        sit,
+                        Stat {
+                            base : *base,
+                            cap : *cap,
+                            growth : *growth,
+                            value : *base
+                        }
+                    )
+                })
+                .collect(),
+            name : self.name.clone(),
+            level : self.level
+        }
+    }
+}
+
+pub fn gba_characters() -> Vec<DatabaseCharacter> {
+    serde_json::from_str(GBA_CHARACTERS).unwrap_or_default()
+}
+
+/// The class tiers of the GBA games, FE8's trainees starting one tier below
+/// the usual unpromoted classes.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ClassTier {
+    /// capped at level 10, e.g. Ross' Journeyman
+    Trainee,
+    Unpromoted,
+    Promoted
+}
+
+/// A promotion with its gains and the caps of the target class, both following
+/// the iteration order of [`GameKind::GbaFe`].
+#[derive(Deserialize, Clone, Debug)]
+struct DatabasePromotion {
+    game : String,
+    from_class : String,
+    to_class : String,
+    /// the tier of the class promoted out of
+    from_tier : ClassTier,
+    gains : Vec<StatType>,
+    caps : Vec<StatType>
+}
+
+impl DatabasePromotion {
+    /// e.g. "Cavalier to Paladin (FE8)"
+    fn name(&self) -> String { format!("{} to {} ({})", self.from_class, self.to_class, self.game) }
+
+    fn to_promotion(&self) -> Character<StatIndexType> {
+        Character {
+            stats : StatIndexType::new(GameKind::GbaFe)
+                .into_iter()
+                .zip(self.gains.iter().zip(&self.caps))
+                .map(|(sit, (gain, cap))| {
+                    (
+                        sit,
+                        Stat {
+                            base : 0,
+                            cap : *cap,
+                            growth : 0,
+                            value : *gain
+                        }
+                    )
+                })
+                .collect(),
+            name : self.name(),
+            // every GBA promotion requires level 10
+            level : 10
+        }
+    }
+}
+
+/// An enemy class by its bases at level 1 and the growths its enemies are
+/// autoleveled with, both following the iteration order of [`GameKind::GbaFe`].
+#[derive(Deserialize, Clone, Debug)]
+pub struct DatabaseEnemyClass {
+    pub game : String,
+    pub name : String,
+    bases : Vec<StatType>,
+    growths : Vec<GrowthType>
+}
+
+impl DatabaseEnemyClass {
+    /// e.g. "Fighter (FE7)"
+    pub fn label(&self) -> String { format!("{} ({})", self.name, self.game) }
+
+    /// Replaces the bases and growths of the enemy with the class', keeping
+    /// their level and values until they're autoleveled.
+    pub fn apply_to(&self, enemy : &mut Character<StatIndexType>) {
+        let stats = StatIndexType::new(GameKind::GbaFe);
+        for (sit, (base, growth)) in stats.into_iter().zip(self.bases.iter().zip(&self.growths)) {
+            if let Some(stat) = enemy.stats.get_mut(&sit) {
+                stat.base = *base;
+                stat.growth = *growth;
+            }
+        }
+    }
+}
+
+/// The enemy classes shipped for the given game.
+pub fn enemy_classes(game_option : GameKind) -> Vec<DatabaseEnemyClass> {
+    if game_option != GameKind::GbaFe {
+        return vec![];
+    }
+    serde_json::from_str(GBA_ENEMY_CLASSES).unwrap_or_default()
+}
+
+/// The promotions shipped for the given game by name, leaving out those the
+/// user saved an equally named promotion of and, if given, those not promoting
+/// out of the given tier.
+pub fn builtin_promotions(
+    game_option : GameKind,
+    saved : &DataManaged<Character<StatIndexType>>,
+    from_tier : Option<ClassTier>
+) -> BTreeMap<String, Character<StatIndexType>> {
+    if game_option != GameKind::GbaFe {
+        return BTreeMap::new();
+    }
+    serde_json::from_str::<Vec<DatabasePromotion>>(GBA_PROMOTIONS)
+        .unwrap_or_default()
+        .iter()
+        .filter(|promotion| from_tier.map_or(true, |tier| promotion.from_tier == tier))
+        .map(|promotion| (promotion.name(), promotion.to_promotion()))
+        .filter(|(name, _promotion)| !saved.contains_key(name))
+        .collect()
+}
+
+/// The state of the "Load from game database" section of the character
+/// manager.
+#[derive(Deserialize, Serialize, Default)]
+pub struct DatabaseBrowser {
+    filter : String,
+    /// game and name of the selected character
+    selected : Option<(String, String)>,
+    /// parsed on first use
+    #[serde(skip)]
+    characters : Vec<DatabaseCharacter>
+}
+
+impl DatabaseBrowser {
+    /// Lists the characters grouped by game, returning the one the user chose
+    /// to load.
+    pub fn show(&mut self, ui : &mut egui::Ui) -> Option<Character<StatIndexType>> {
+        if self.characters.is_empty() {
+            self.characters = gba_characters();
+        }
+
+        ui.weak(tr("database.partial"));
+        ui.horizontal(|ui| {
+            ui.label(tr("database.filter"));
+            ui.text_edit_singleline(&mut self.filter);
+        });
+        let filter = self.filter.to_lowercase();
+        egui::ScrollArea::vertical()
+            .max_height(ui.text_style_height(&egui::TextStyle::Body) * 12.0)
+            .show(ui, |ui| {
+                let matching = self
+                    .characters
+                    .iter()
+                    .filter(|character| character.name.to_lowercase().contains(&filter))
+                    .group_by(|character| character.game.clone());
+                for (game, characters) in &matching {
+                    ui.collapsing(game, |ui| {
+                        for character in characters {
+                            let key = Some((character.game.clone(), character.name.clone()));
+                            let label = format!(
+                                "{} (Lv. {} {})",
+                                character.name, character.level, character.class_name
+                            );
+                            ui.selectable_value(&mut self.selected, key, label);
+                        }
+                    });
+                }
+            });
+
+        let selected = self.selected.as_ref().and_then(|(game, name)| {
+            self.characters
+                .iter()
+                .find(|character| &character.game == game && &character.name == name)
+        });
+        ui.add_enabled(selected.is_some(), egui::Button::new(tr("database.load")))
+            .on_disabled_hover_text(tr("database.load_hint"))
+            .clicked()
+            .then(|| selected.map(DatabaseCharacter::to_character))
+            .flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_database_character_has_all_stats() {
+        let characters = gba_characters();
+        assert!(!characters.is_empty());
+        let num_stats = StatIndexType::new(GameKind::GbaFe).len();
+        for character in characters {
+            assert_eq!(character.bases.len(), num_stats, "{}", character.name);
+            assert_eq!(character.growths.len(), num_stats, "{}", character.name);
+            assert_eq!(character.caps.len(), num_stats, "{}", character.name);
+            assert!(
+                character
+                    .bases
+                    .iter()
+                    .zip(&character.caps)
+                    .all(|(base, cap)| base <= cap),
+                "{}",
+                character.name
+            );
+        }
+    }
+
+    #[test]
+    fn every_database_promotion_has_all_stats() {
+        let promotions = builtin_promotions(GameKind::GbaFe, &Default::default(), None);
+        assert!(!promotions.is_empty());
+        let num_stats = StatIndexType::new(GameKind::GbaFe).len();
+        for (name, promotion) in promotions {
+            assert_eq!(promotion.stats.len(), num_stats, "{name}");
+        }
+    }
+
+    #[test]
+    fn every_database_enemy_class_has_all_stats() {
+        let classes = enemy_classes(GameKind::GbaFe);
+        assert!(!classes.is_empty());
+        let num_stats = StatIndexType::new(GameKind::GbaFe).len();
+        for class in classes {
+            assert_eq!(class.bases.len(), num_stats, "{}", class.name);
+            assert_eq!(class.growths.len(), num_stats, "{}", class.name);
+        }
+    }
+}
@@ -0,0 +1,114 @@
+//! Saving and loading files, natively through the file dialogs and on the web
+//! as downloads and uploads.
+
+use egui::Rect;
+use poll_promise::Promise;
+
+/// A file being picked and read, `None` if the user cancelled.
+pub type Upload = Promise<Option<Result<Vec<u8>, String>>>;
+
+/// The name shown for and the extension of the files a dialog accepts.
+pub type FileFilter = (&'static str, &'static str);
+
+pub const JSON : FileFilter = ("JSON", "json");
+pub const RON : FileFilter = ("RON", "ron");
+pub const PNG : FileFilter = ("PNG", "png");
+
+/// Asks where to save the file and saves it there. Returns whether it was
+/// saved rather than the dialog cancelled.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save(
+    file_name : &str,
+    (name, extension) : FileFilter,
+    contents : impl AsRef<[u8]>
+) -> Result<bool, String> {
+    let path = match rfd::FileDialog::new()
+        .add_filter(name, &[extension])
+        .set_file_name(file_name)
+        .save_file()
+    {
+        Some(path) => path,
+        None => return Ok(false)
+    };
+    std::fs::write(&path, contents)
+        .map(|()| true)
+        .map_err(|error| format!("{}: {error}", path.display()))
+}
+
+/// Offers the file as a download, there being no file system to save to.
+#[cfg(target_arch = "wasm32")]
+pub fn save(
+    file_name : &str,
+    _filter : FileFilter,
+    contents : impl AsRef<[u8]>
+) -> Result<bool, String> {
+    use wasm_bindgen::{JsCast, JsValue};
+
+    let js_error = |error : JsValue| format!("{error:?}");
+    let bytes = js_sys::Uint8Array::from(contents.as_ref());
+    let blob =
+        web_sys::Blob::new_with_u8_array_sequence(&js_sys::Array::of1(&bytes)).map_err(js_error)?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob).map_err(js_error)?;
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or("no document to download from")?;
+    let anchor : web_sys::HtmlAnchorElement = document
+        .create_element("a")
+        .map_err(js_error)?
+        .dyn_into()
+        .map_err(|_element| "no link to download with")?;
+    anchor.set_href(&url);
+    anchor.set_download(file_name);
+    anchor.click();
+    web_sys::Url::revoke_object_url(&url)
+        .map(|()| true)
+        .map_err(js_error)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn pick((name, extension) : FileFilter) -> Upload {
+    Promise::from_ready(
+        rfd::FileDialog::new()
+            .add_filter(name, &[extension])
+            .pick_file()
+            .map(|path| {
+                std::fs::read(&path).map_err(|error| format!("{}: {error}", path.display()))
+            })
+    )
+}
+
+/// Asks the browser for a file, which is ready once it's read.
+#[cfg(target_arch = "wasm32")]
+pub fn pick((name, extension) : FileFilter) -> Upload {
+    Promise::spawn_async(async move {
+        let file = rfd::AsyncFileDialog::new()
+            .add_filter(name, &[extension])
+            .pick_file()
+            .await?;
+        Some(Ok(file.read().await))
+    })
+}
+
+/// The contents of the files dropped onto the window this frame.
+pub fn dropped(ctx : &egui::Context, window : Rect) -> Vec<Result<Vec<u8>, String>> {
+    let input = ctx.input();
+    if !input
+        .pointer
+        .hover_pos()
+        .map_or(false, |pointer| window.contains(pointer))
+    {
+        return Vec::new();
+    }
+    input
+        .raw
+        .dropped_files
+        .iter()
+        .filter_map(|file| match (&file.bytes, &file.path) {
+            (Some(bytes), _) => Some(Ok(bytes.to_vec())),
+            (None, Some(path)) => {
+                Some(std::fs::read(path).map_err(|error| format!("{}: {error}", path.display())))
+            },
+            (None, None) => None
+        })
+        .collect()
+}
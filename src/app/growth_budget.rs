@@ -0,0 +1,137 @@
+//! A target for the total of the personal growths, as romhacks often balance
+//! their units by, e.g. 330% spread over the stats. The budget only helps
+//! entering the growths, the analysis never looks at it.
+
+use egui::{Button, DragValue, Ui};
+use fe_levels::{Character, GrowthType};
+
+use super::{
+    locale::{tr, tr_with},
+    sit::StatIndexType,
+    stat_total
+};
+
+/// whether the growth total lies above the budget, if there is one
+pub fn over_budget(budget : Option<u32>, total : u32) -> bool {
+    budget.map_or(false, |budget| total > budget)
+}
+
+/// Raises the growths by the points, as evenly as the growths allow: every
+/// growth gets the same share, the first ones a point more for what doesn't
+/// divide evenly, and growths at their maximum pass their share on. Returns
+/// the points given out.
+fn distribute_evenly(growths : &mut [&mut GrowthType], points : u32) -> u32 {
+    let mut left = points;
+    loop {
+        let open = growths
+            .iter()
+            .filter(|growth| ***growth < GrowthType::MAX)
+            .count();
+        if left == 0 || open == 0 {
+            return points - left;
+        }
+        let open = u32::try_from(open).unwrap_or(u32::MAX);
+        let (share, mut extra) = (left / open, left % open);
+        for growth in growths
+            .iter_mut()
+            .filter(|growth| ***growth < GrowthType::MAX)
+        {
+            let wanted = share + u32::from(extra > 0);
+            extra = extra.saturating_sub(1);
+            let given = wanted.min(u32::from(GrowthType::MAX - **growth));
+            **growth += GrowthType::try_from(given).unwrap_or(0);
+            left -= given;
+        }
+    }
+}
+
+/// Spreads the points over the growable stats in their order.
+fn distribute(character : &mut Character<StatIndexType>, points : u32) -> u32 {
+    let mut growths = character
+        .stats
+        .iter_mut()
+        .filter(|(sit, _stat)| sit.is_growable())
+        .map(|(_sit, stat)| &mut stat.growth)
+        .collect::<Vec<_>>();
+    distribute_evenly(&mut growths, points)
+}
+
+/// The budget's checkbox, target and what's left of it, with a button spreading
+/// the rest over the growths. Returns the points spread this frame, if any.
+pub fn budget_row(
+    budget : &mut Option<u32>,
+    character : &mut Character<StatIndexType>,
+    ui : &mut Ui
+) -> Option<u32> {
+    let total = stat_total(character, |stat| stat.growth);
+    let mut enabled = budget.is_some();
+    let mut distributed = None;
+    ui.horizontal(|ui| {
+        if ui
+            .checkbox(&mut enabled, tr("builder.growth_budget"))
+            .on_hover_text(tr("builder.growth_budget_hint"))
+            .changed()
+        {
+            // the budget starts out as the growths are
+            *budget = enabled.then_some(total);
+        }
+        let target = match budget {
+            Some(target) => target,
+            None => return
+        };
+        ui.add(DragValue::new(target).suffix("%"));
+        if total > *target {
+            let over = total - *target;
+            ui.colored_label(
+                ui.visuals().error_fg_color,
+                tr_with("builder.budget_over", &[("points", &over)])
+            );
+        }
+        else {
+            ui.label(tr_with(
+                "builder.budget_left",
+                &[("points", &(*target - total))]
+            ));
+        }
+        let distribute_button = Button::new(tr("builder.distribute"));
+        if ui
+            .add_enabled(total < *target, distribute_button)
+            .on_hover_text(tr("builder.distribute_hint"))
+            .clicked()
+        {
+            let given = distribute(character, *target - total);
+            distributed = (given > 0).then_some(given);
+        }
+    });
+    distributed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distributed(mut growths : Vec<GrowthType>, points : u32) -> (Vec<GrowthType>, u32) {
+        let given = distribute_evenly(&mut growths.iter_mut().collect::<Vec<_>>(), points);
+        (growths, given)
+    }
+
+    #[test]
+    fn remaining_points_are_spread_evenly() {
+        assert_eq!(distributed(vec![10, 20, 30], 30), (vec![20, 30, 40], 30));
+        // the first growths get the points that don't divide evenly
+        assert_eq!(
+            distributed(vec![0; 7], 100),
+            (vec![15, 15, 14, 14, 14, 14, 14], 100)
+        );
+        assert_eq!(distributed(vec![50, 50, 50], 2), (vec![51, 51, 50], 2));
+        // full growths pass their share on, and what fits nowhere is kept
+        let max = GrowthType::MAX;
+        assert_eq!(distributed(vec![max - 1, 0, 0], 10), (vec![max, 5, 4], 10));
+        assert_eq!(distributed(vec![max, max - 2], 5), (vec![max, max], 2));
+        assert_eq!(distributed(Vec::new(), 5), (Vec::new(), 0));
+
+        assert!(over_budget(Some(330), 331));
+        assert!(!over_budget(Some(330), 330));
+        assert!(!over_budget(None, 900));
+    }
+}
@@ -0,0 +1,105 @@
+//! The window explaining what the analysis models, i.e. the level-up
+//! mechanics of every game and what each chart shows. The entries are
+//! explained by the stat changes themselves so that the help can't fall
+//! behind them.
+
+use egui::{CollapsingHeader, RichText, Ui};
+use itertools::Itertools;
+
+use super::{
+    locale::{tr, tr_with},
+    plotter::chart_help,
+    progression::{game_help, ConcreteStatChange, UsefulStatChange},
+    GameKind, ALL_GAMES
+};
+
+/// the most names listed for entries sharing an explanation, e.g. the
+/// classes of Three Houses
+const MAX_LISTED_NAMES : usize = 6;
+
+/// The names of the game's progression entries grouped by their explanations,
+/// in the order the templates list them.
+fn explained_entries(game_option : GameKind) -> Vec<(Vec<String>, String)> {
+    let mut entries : Vec<(Vec<String>, String)> = Vec::new();
+    for template in ConcreteStatChange::generate_templates(game_option) {
+        let name = template.to_string();
+        let explanation = template.explanation();
+        match entries
+            .iter_mut()
+            .find(|(_names, known)| *known == explanation)
+        {
+            Some((names, _explanation)) if names.contains(&name) => {},
+            Some((names, _explanation)) => names.push(name),
+            None => entries.push((vec![name], explanation))
+        }
+    }
+    entries
+}
+
+fn listed_names(names : &[String]) -> String {
+    if names.len() <= MAX_LISTED_NAMES {
+        names.join(", ")
+    }
+    else {
+        tr_with(
+            "help.more_names",
+            &[
+                ("names", &names[..MAX_LISTED_NAMES].iter().join(", ")),
+                ("count", &(names.len() - MAX_LISTED_NAMES))
+            ]
+        )
+    }
+}
+
+fn game_section(game_option : GameKind, ui : &mut Ui) {
+    ui.label(game_help(game_option));
+    ui.add_space(4.0);
+    for (names, explanation) in explained_entries(game_option) {
+        ui.label(RichText::new(listed_names(&names)).strong());
+        ui.indent(names.join(","), |ui| ui.label(explanation));
+    }
+}
+
+/// The help window, open while `open` is set. The section of the current
+/// game starts out expanded.
+pub fn help_window(open : &mut bool, game_option : GameKind, ctx : &egui::Context) {
+    egui::Window::new(tr("app.help"))
+        .id(egui::Id::new("Help"))
+        .open(open)
+        .vscroll(true)
+        .default_width(400.0)
+        .show(ctx, |ui| {
+            ui.heading(tr("help.mechanics"));
+            for game in ALL_GAMES {
+                CollapsingHeader::new(game.name())
+                    .id_source(("Help", game))
+                    .default_open(game == game_option)
+                    .show(ui, |ui| game_section(game, ui));
+            }
+            ui.heading(tr("help.charts"));
+            for (name, explanation) in chart_help() {
+                ui.label(RichText::new(name).strong());
+                ui.indent(("Help", &explanation), |ui| ui.label(&explanation));
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_sharing_an_explanation_are_grouped() {
+        for game in ALL_GAMES {
+            let entries = explained_entries(game);
+            let explanations = entries.iter().map(|(_names, explanation)| explanation);
+            assert_eq!(explanations.clone().unique().count(), entries.len());
+        }
+        let gba = explained_entries(GameKind::GbaFe);
+        let promotions = gba
+            .iter()
+            .find(|(names, _explanation)| names.contains(&"Promotion".to_owned()))
+            .unwrap();
+        assert!(promotions.0.contains(&"Trainee Promotion".to_owned()));
+    }
+}
@@ -0,0 +1,166 @@
+//! The hover hints explaining values and entries whose meaning lies in the
+//! games' mechanics. The keys of their texts are kept in one table per game,
+//! next to the hints every game shares, rather than at the widgets showing
+//! them.
+
+use egui::Ui;
+use fe_levels::StatType;
+
+use super::{
+    locale::{tr, tr_with},
+    progression::find_booster,
+    sit::StatIndexType,
+    GameKind
+};
+
+/// What a hint explains, independently of the game.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Topic {
+    AverageStat,
+    BenchmarkChance,
+    BoxRange,
+    LevelUp,
+    StatBooster
+}
+
+/// A value or an entry with a hint.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HintKey {
+    /// the average stats the plotter reduces the distributions to
+    AverageStat,
+    /// the chances to hit a benchmark the plotter reduces the distributions to
+    BenchmarkChance,
+    /// the share of the outcomes the boxes of the box plot span
+    BoxRange,
+    /// a plain level-up of the game
+    LevelUp(GameKind),
+    /// raising the stat by the amount with an item
+    StatBooster(StatIndexType, StatType)
+}
+
+impl HintKey {
+    fn game(&self) -> Option<GameKind> {
+        match self {
+            HintKey::LevelUp(game) => Some(*game),
+            HintKey::StatBooster(stat, _amount) => Some(stat.game()),
+            HintKey::AverageStat | HintKey::BenchmarkChance | HintKey::BoxRange => None
+        }
+    }
+
+    fn topic(&self) -> Topic {
+        match self {
+            HintKey::AverageStat => Topic::AverageStat,
+            HintKey::BenchmarkChance => Topic::BenchmarkChance,
+            HintKey::BoxRange => Topic::BoxRange,
+            HintKey::LevelUp(_game) => Topic::LevelUp,
+            HintKey::StatBooster(..) => Topic::StatBooster
+        }
+    }
+}
+
+/// the keys of the hints of the charts, alike for every game
+const CHART_HINTS : [(Topic, &str); 3] = [
+    (Topic::AverageStat, "hint.average_stat"),
+    (Topic::BenchmarkChance, "hint.benchmark_chance"),
+    (Topic::BoxRange, "hint.box_range")
+];
+
+const BOOSTER_HINT : &str = "hint.booster";
+
+const GBA_FE_HINTS : [(Topic, &str); 2] = [
+    (Topic::LevelUp, "hint.gba_level_up"),
+    (Topic::StatBooster, BOOSTER_HINT)
+];
+const POR_HINTS : [(Topic, &str); 2] = [
+    (Topic::LevelUp, "hint.por_level_up"),
+    (Topic::StatBooster, BOOSTER_HINT)
+];
+const RD_HINTS : [(Topic, &str); 2] = [
+    (Topic::LevelUp, "hint.rd_level_up"),
+    (Topic::StatBooster, BOOSTER_HINT)
+];
+const SHADOW_DRAGON_HINTS : [(Topic, &str); 1] = [(Topic::LevelUp, "hint.shadow_dragon_level_up")];
+const SOV_HINTS : [(Topic, &str); 1] = [(Topic::LevelUp, "hint.sov_level_up")];
+const THREE_HOUSES_HINTS : [(Topic, &str); 1] = [(Topic::LevelUp, "hint.three_houses_level_up")];
+const CUSTOM_HINTS : [(Topic, &str); 2] = [
+    (Topic::LevelUp, "hint.custom_level_up"),
+    (Topic::StatBooster, "hint.custom_booster")
+];
+
+fn game_hints(game : GameKind) -> &'static [(Topic, &'static str)] {
+    match game {
+        GameKind::GbaFe => &GBA_FE_HINTS,
+        GameKind::PoR => &POR_HINTS,
+        GameKind::RD => &RD_HINTS,
+        GameKind::ShadowDragon => &SHADOW_DRAGON_HINTS,
+        GameKind::SoV => &SOV_HINTS,
+        GameKind::ThreeHouses => &THREE_HOUSES_HINTS,
+        GameKind::Custom => &CUSTOM_HINTS
+    }
+}
+
+/// The text of the hint, naming the in-game item of a booster. None if the
+/// tables lack the hint.
+pub fn hint_text(key : HintKey) -> Option<String> {
+    let table = key.game().map_or(&CHART_HINTS[..], game_hints);
+    let (_topic, text_key) = table.iter().find(|(topic, _key)| *topic == key.topic())?;
+    let text = tr(text_key);
+    match key {
+        HintKey::StatBooster(stat, amount) => match find_booster(stat, amount) {
+            Some(item) => Some(tr_with(
+                "hint.booster_item",
+                &[
+                    ("item", &item.name),
+                    ("amount", &amount),
+                    ("stat", &stat),
+                    ("hint", &text)
+                ]
+            )),
+            None => Some(text)
+        },
+        _ => Some(text)
+    }
+}
+
+/// A small marker showing the hint when hovered.
+pub fn hint(ui : &mut Ui, key : HintKey) {
+    if let Some(text) = hint_text(key) {
+        ui.weak("ⓘ").on_hover_text(text);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{
+        progression::{ConcreteStatChange, UsefulStatChange},
+        ALL_GAMES
+    };
+
+    #[test]
+    fn every_hinted_entry_has_a_text() {
+        for key in [
+            HintKey::AverageStat,
+            HintKey::BenchmarkChance,
+            HintKey::BoxRange
+        ] {
+            assert!(hint_text(key).is_some(), "{key:?}");
+        }
+        for game in ALL_GAMES {
+            for template in ConcreteStatChange::generate_templates(game) {
+                if let Some(key) = template.hint_key() {
+                    assert!(hint_text(key).is_some(), "{key:?} of {template}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn boosters_name_their_item() {
+        let energy_ring = StatIndexType::from_key(GameKind::GbaFe, "str").unwrap();
+        let text = hint_text(HintKey::StatBooster(energy_ring, 2)).unwrap();
+        assert!(text.starts_with("The Energy Ring, +2 Atk."), "{text}");
+        let unlisted = hint_text(HintKey::StatBooster(energy_ring, 5)).unwrap();
+        assert_eq!(unlisted, tr(BOOSTER_HINT));
+    }
+}
@@ -0,0 +1,146 @@
+//! Showing and hiding the windows and putting them back where they started.
+
+use std::collections::BTreeSet;
+
+use egui::{collapsing_header::CollapsingState, Id, Ui};
+use serde::{Deserialize, Serialize};
+
+use super::{locale::tr, GameData};
+
+/// The windows every game has exactly one of. The plotter windows are hidden
+/// by their own flags as there can be any number of them.
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum AppWindow {
+    CharacterBuilder,
+    ProgressionBuilder,
+    CharacterManager,
+    PromotionManager,
+    TemplateManager,
+    EnemyManager,
+    WeaponManager
+}
+
+/// every window, in the order the menu lists them
+pub const ALL_WINDOWS : [AppWindow; 7] = [
+    AppWindow::CharacterBuilder,
+    AppWindow::ProgressionBuilder,
+    AppWindow::CharacterManager,
+    AppWindow::PromotionManager,
+    AppWindow::TemplateManager,
+    AppWindow::EnemyManager,
+    AppWindow::WeaponManager
+];
+
+impl AppWindow {
+    fn english_title(&self) -> &'static str {
+        match self {
+            AppWindow::CharacterBuilder => "Character Builder",
+            AppWindow::ProgressionBuilder => "Character Progression Builder",
+            AppWindow::CharacterManager => "Character & Progression Manager",
+            AppWindow::PromotionManager => "Promotion Manager",
+            AppWindow::TemplateManager => "Template Manager",
+            AppWindow::EnemyManager => "Enemy Manager",
+            AppWindow::WeaponManager => "Weapon Manager"
+        }
+    }
+
+    pub fn title(&self) -> String {
+        tr(match self {
+            AppWindow::CharacterBuilder => "window.character_builder",
+            AppWindow::ProgressionBuilder => "window.progression_builder",
+            AppWindow::CharacterManager => "window.character_manager",
+            AppWindow::PromotionManager => "window.promotion_manager",
+            AppWindow::TemplateManager => "window.template_manager",
+            AppWindow::EnemyManager => "window.enemy_manager",
+            AppWindow::WeaponManager => "window.weapon_manager"
+        })
+    }
+
+    /// the id egui knows the window by, taken from the English title so that
+    /// a change of the language keeps where the window was moved
+    pub fn id(&self) -> Id { Id::new(self.english_title()) }
+
+    /// the window showing its title in the selected language
+    pub fn window(&self) -> egui::Window<'static> { egui::Window::new(self.title()).id(self.id()) }
+}
+
+/// The windows the user hid, every other one being shown.
+#[derive(Deserialize, Serialize, Default, Clone, PartialEq, Eq, Debug)]
+pub struct WindowVisibility {
+    hidden : BTreeSet<AppWindow>
+}
+
+impl WindowVisibility {
+    pub fn shown(&self, window : AppWindow) -> bool { !self.hidden.contains(&window) }
+
+    pub fn show(&mut self, window : AppWindow) { self.hidden.remove(&window); }
+
+    fn set_shown(&mut self, window : AppWindow, shown : bool) {
+        if shown {
+            self.hidden.remove(&window);
+        }
+        else {
+            self.hidden.insert(window);
+        }
+    }
+}
+
+/// Opens the window again should it have been collapsed, egui keeping that
+/// under the window's id.
+fn expand(ctx : &egui::Context, id : Id) {
+    let mut collapsing = CollapsingState::load_with_default_open(ctx, id.with("collapsing"), true);
+    collapsing.set_open(true);
+    collapsing.store(ctx);
+}
+
+/// Forgets where the windows were moved, opens the collapsed ones and shows
+/// them all. The sizes they were resized to stay, as egui keeps them under a
+/// type it doesn't expose.
+fn reset_layout(visibility : &mut WindowVisibility, data : &mut GameData, ctx : &egui::Context) {
+    ctx.memory().reset_areas();
+    let windows = ALL_WINDOWS.iter().map(AppWindow::id);
+    for id in windows.chain(data.plotter.window_ids()) {
+        expand(ctx, id);
+    }
+    *visibility = Default::default();
+    data.plotter.show_windows();
+}
+
+/// The top bar menu listing the windows to show or hide.
+pub fn windows_menu(visibility : &mut WindowVisibility, data : &mut GameData, ui : &mut Ui) {
+    ui.menu_button(tr("window.menu"), |ui| {
+        for window in ALL_WINDOWS {
+            let mut shown = visibility.shown(window);
+            if ui.checkbox(&mut shown, window.title()).changed() {
+                visibility.set_shown(window, shown);
+            }
+        }
+        ui.separator();
+        data.plotter.windows_menu(ui);
+        ui.separator();
+        if ui
+            .button(tr("window.reset_layout"))
+            .on_hover_text(tr("window.reset_layout_hint"))
+            .clicked()
+        {
+            reset_layout(visibility, data, ui.ctx());
+            ui.close_menu();
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hidden_windows_are_saved() {
+        let mut visibility = WindowVisibility::default();
+        assert!(ALL_WINDOWS.iter().all(|window| visibility.shown(*window)));
+        visibility.set_shown(AppWindow::EnemyManager, false);
+        let restored : WindowVisibility =
+            ron::from_str(&ron::to_string(&visibility).unwrap()).unwrap();
+        assert!(!restored.shown(AppWindow::EnemyManager));
+        assert!(restored.shown(AppWindow::WeaponManager));
+    }
+}
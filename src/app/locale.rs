@@ -0,0 +1,245 @@
+//! The texts of the windows in the user's language. Every language is a flat
+//! table of keys to texts embedded from `locale/`, English being complete
+//! and filling in whatever another table lacks. Texts name their arguments
+//! in braces, e.g. `{name}`.
+//!
+//! Stat abbreviations stay as the games print them, with their long names in
+//! the user's language as hover texts.
+
+use std::{collections::BTreeMap, fmt::Display, sync::RwLock};
+
+use egui::{Response, Ui};
+use serde::{Deserialize, Serialize};
+
+use super::sit::StatIndexType;
+
+const ENGLISH : &str = include_str!("locale/en.json");
+const GERMAN : &str = include_str!("locale/de.json");
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum Language {
+    #[default]
+    English,
+    German
+}
+
+/// every language, in the order the settings list them
+pub const ALL_LANGUAGES : [Language; 2] = [Language::English, Language::German];
+
+impl Language {
+    /// the name of the language in itself, as the settings list it
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::German => "Deutsch"
+        }
+    }
+
+    fn source(&self) -> &'static str {
+        match self {
+            Language::English => ENGLISH,
+            Language::German => GERMAN
+        }
+    }
+}
+
+type Table = BTreeMap<String, String>;
+
+fn parse(source : &str) -> Table { serde_json::from_str(source).unwrap_or_default() }
+
+/// The language's texts on top of the English ones.
+fn table(language : Language) -> Table {
+    let mut table = parse(ENGLISH);
+    if language != Language::English {
+        table.extend(parse(language.source()));
+    }
+    table
+}
+
+/// The texts of the selected language, parsed once when it's selected.
+struct Texts {
+    language : Option<Language>,
+    table : Table
+}
+
+static TEXTS : RwLock<Texts> = RwLock::new(Texts {
+    language : None,
+    table : BTreeMap::new()
+});
+
+/// makes the language the one every window's texts are in
+pub fn set_language(language : Language) {
+    if let Ok(mut texts) = TEXTS.write() {
+        if texts.language != Some(language) {
+            *texts = Texts {
+                language : Some(language),
+                table : table(language)
+            };
+        }
+    }
+}
+
+/// The text of the key in the selected language, or the key itself should no
+/// table have it.
+pub fn tr(key : &str) -> String {
+    if TEXTS.read().map_or(false, |texts| texts.language.is_none()) {
+        set_language(Language::default());
+    }
+    TEXTS
+        .read()
+        .ok()
+        .and_then(|texts| texts.table.get(key).cloned())
+        .unwrap_or_else(|| key.to_owned())
+}
+
+fn fill(text : String, args : &[(&str, &dyn Display)]) -> String {
+    args.iter().fold(text, |text, (name, value)| {
+        text.replace(&format!("{{{name}}}"), &value.to_string())
+    })
+}
+
+/// The text of the key with its arguments filled in.
+pub fn tr_with(key : &str, args : &[(&str, &dyn Display)]) -> String { fill(tr(key), args) }
+
+/// The long name of the stat in the selected language, e.g. "Strength" for
+/// GBA Atk. None for custom stats named unlike any of the games' stats.
+pub fn stat_name(stat : StatIndexType) -> Option<String> {
+    let key = format!("stat.{}", stat.canonical_key()?);
+    Some(tr(&key))
+}
+
+/// The stat's abbreviation with its long name as the hover text.
+pub fn stat_label(ui : &mut Ui, stat : StatIndexType) -> Response {
+    let label = ui.label(stat.to_string());
+    match stat_name(stat) {
+        Some(name) => label.on_hover_text(name),
+        None => label
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use super::*;
+    use crate::app::{GameKind, ALL_GAMES};
+
+    /// the names in braces of the text
+    fn arguments(text : &str) -> Vec<&str> {
+        let mut arguments = text
+            .split('{')
+            .skip(1)
+            .filter_map(|rest| rest.split_once('}'))
+            .map(|(name, _rest)| name)
+            .collect::<Vec<_>>();
+        arguments.sort_unstable();
+        arguments
+    }
+
+    #[test]
+    fn every_language_has_every_text() {
+        let english = parse(ENGLISH);
+        assert!(!english.is_empty());
+        for language in ALL_LANGUAGES {
+            let translated = parse(language.source());
+            assert_eq!(
+                translated.keys().collect::<Vec<_>>(),
+                english.keys().collect::<Vec<_>>(),
+                "{language:?}"
+            );
+            for (key, text) in &translated {
+                assert_eq!(
+                    arguments(text),
+                    arguments(&english[key]),
+                    "{language:?} {key}"
+                );
+            }
+        }
+    }
+
+    /// the keys the source looks up literally, skipping e.g. `from_str("…")`
+    fn looked_up(source : &str) -> Vec<&str> {
+        ["tr(", "tr_with("]
+            .into_iter()
+            .flat_map(|call| {
+                source
+                    .match_indices(call)
+                    .filter_map(move |(index, _call)| {
+                        let before = source[..index].chars().next_back();
+                        if before.map_or(false, |c| c.is_alphanumeric() || c == '_') {
+                            return None;
+                        }
+                        let rest = source[index + call.len()..]
+                            .trim_start()
+                            .strip_prefix('"')?;
+                        rest.split_once('"').map(|(key, _rest)| key)
+                    })
+            })
+            .filter(|key| key.contains('.') && !key.contains(char::is_whitespace))
+            .collect()
+    }
+
+    /// the keys of the texts the sources below the directory look up
+    fn used_keys(directory : &Path, keys : &mut Vec<String>) {
+        for entry in fs::read_dir(directory).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                used_keys(&path, keys);
+            }
+            else if path
+                .extension()
+                .map_or(false, |extension| extension == "rs")
+            {
+                let source = fs::read_to_string(&path).unwrap();
+                keys.extend(looked_up(&source).into_iter().map(str::to_owned));
+            }
+        }
+    }
+
+    #[test]
+    fn every_looked_up_text_exists() {
+        let english = parse(ENGLISH);
+        let mut keys = Vec::new();
+        used_keys(
+            &Path::new(env!("CARGO_MANIFEST_DIR")).join("src"),
+            &mut keys
+        );
+        assert!(!keys.is_empty());
+        for key in keys {
+            assert!(english.contains_key(&key), "{key}");
+        }
+    }
+
+    #[test]
+    fn every_stat_of_the_games_has_a_long_name() {
+        let english = parse(ENGLISH);
+        for game in ALL_GAMES
+            .into_iter()
+            .filter(|game| *game != GameKind::Custom)
+        {
+            for stat in StatIndexType::new(game) {
+                let key = stat.canonical_key().map(|key| format!("stat.{key}"));
+                assert!(
+                    key.map_or(false, |key| english.contains_key(&key)),
+                    "{game:?} {stat}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn texts_fall_back_to_english() {
+        let german = table(Language::German);
+        assert_eq!(german["settings.language"], "Sprache");
+        assert_eq!(german.len(), parse(ENGLISH).len());
+        let source = "from_str(\"x\") + tr(\"a.b\") + tr_with(\n \"c.d\", &[]) + tr(key)";
+        assert_eq!(looked_up(source), vec!["a.b", "c.d"]);
+        assert_eq!(
+            fill(
+                "{count} of {total}".to_owned(),
+                &[("count", &3), ("total", &8)]
+            ),
+            "3 of 8"
+        );
+    }
+}
@@ -1,12 +1,22 @@
 use std::{
-    collections::BTreeMap,
-    ops::{Deref, DerefMut}
+    cmp::Reverse,
+    collections::{btree_map::ValuesMut, BTreeMap, BTreeSet, VecDeque},
+    ops::Deref
 };
 
-use egui::{Button, Rect, ScrollArea, TextEdit, Ui};
+use egui::{Button, ComboBox, Id, Rect, ScrollArea, TextEdit, Ui};
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
+use super::{
+    files::{self, Upload},
+    layout::AppWindow,
+    locale::{tr, tr_with},
+    on_screen, onboarding, session_log, toasts
+};
+#[cfg(target_arch = "wasm32")]
+use poll_promise::Promise;
+
 #[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq, Default)]
 enum CodeEditMode {
     #[default]
@@ -14,21 +24,220 @@ enum CodeEditMode {
     Importing(String)
 }
 
+/// The order the entries are listed in.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+enum SortOrder {
+    #[default]
+    Alphabetical,
+    RecentlyModified,
+    RecentlyCreated
+}
+
+impl SortOrder {
+    fn name(&self) -> String {
+        tr(match self {
+            SortOrder::Alphabetical => "manager.sort_alphabetical",
+            SortOrder::RecentlyModified => "manager.sort_modified",
+            SortOrder::RecentlyCreated => "manager.sort_created"
+        })
+    }
+}
+
+/// how many deletions and renames can be undone
+const UNDO_LIMIT : usize = 10;
+
+/// What's kept about an entry besides its value.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct EntryInfo {
+    /// written by the user, e.g. where the entry comes from
+    note : String,
+    /// when the entry was last saved, in seconds since the Unix epoch
+    modified : u64,
+    /// when the entry was first saved, in seconds since the Unix epoch
+    created : u64
+}
+
+impl EntryInfo {
+    /// what's kept about an entry saved just now
+    fn new() -> Self {
+        Self {
+            note : String::new(),
+            modified : now(),
+            created : now()
+        }
+    }
+}
+
+/// An entry as exported, together with what's kept about it.
+#[derive(Serialize, Deserialize)]
+pub struct Managed<V> {
+    value : V,
+    #[serde(flatten)]
+    info : EntryInfo
+}
+
+/// the current time in seconds since the Unix epoch
+#[cfg(not(target_arch = "wasm32"))]
+pub fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
+
+/// the current time in seconds since the Unix epoch, the standard library's
+/// clock not being available in the browser
+#[cfg(target_arch = "wasm32")]
+pub fn now() -> u64 { (js_sys::Date::now() / 1000.0) as u64 }
+
+/// How long ago the time was, roughly.
+pub fn age(time : u64) -> String {
+    let seconds = now().saturating_sub(time);
+    match seconds {
+        0..=59 => tr("manager.just_now"),
+        60..=3_599 => tr_with("manager.minutes_ago", &[("count", &(seconds / 60))]),
+        3_600..=86_399 => tr_with("manager.hours_ago", &[("count", &(seconds / 3_600))]),
+        _ => tr_with("manager.days_ago", &[("count", &(seconds / 86_400))])
+    }
+}
+
+/// Reads an exported entry. Bare values, as exported before entries had notes,
+/// are read as well and count as saved just now.
+fn parse_entry<V : for<'a> Deserialize<'a>>(text : &str) -> serde_json::Result<Managed<V>> {
+    serde_json::from_str(text).or_else(|_| {
+        serde_json::from_str(text).map(|value| Managed {
+            value,
+            info : EntryInfo::new()
+        })
+    })
+}
+
+/// Reads exported entries, with or without what's kept about them.
+fn parse_entries<V : for<'a> Deserialize<'a>>(
+    text : &str
+) -> serde_json::Result<BTreeMap<String, Managed<V>>> {
+    serde_json::from_str(text).or_else(|_| {
+        serde_json::from_str::<BTreeMap<String, V>>(text).map(|values| {
+            values
+                .into_iter()
+                .map(|(name, value)| {
+                    (
+                        name,
+                        Managed {
+                            value,
+                            info : EntryInfo::new()
+                        }
+                    )
+                })
+                .collect()
+        })
+    })
+}
+
+/// Reads an array of exported entries, with or without what's kept about
+/// them, as exported for the checked entries.
+fn parse_list<V : for<'a> Deserialize<'a>>(text : &str) -> serde_json::Result<Vec<Managed<V>>> {
+    serde_json::from_str(text).or_else(|_| {
+        serde_json::from_str::<Vec<V>>(text).map(|values| {
+            values
+                .into_iter()
+                .map(|value| Managed {
+                    value,
+                    info : EntryInfo::new()
+                })
+                .collect()
+        })
+    })
+}
+
+/// An entry taken from under its name, remembered so that it can be restored.
+enum Removal<V> {
+    Deleted(String, Managed<V>),
+    /// the checked entries were deleted together
+    DeletedChecked(Vec<(String, Managed<V>)>),
+    /// the entry was renamed from the first name to the second
+    Renamed(String, String)
+}
+
+/// Logs the action on the entries of the manager's window.
+fn log(ctx : &egui::Context, window : AppWindow, action : String) {
+    let title = window.title();
+    session_log::report(
+        ctx,
+        tr_with(
+            "manager.log_action",
+            &[("window", &title), ("action", &action)]
+        )
+    );
+}
+
+/// The browser's clipboard, `None` if it can't be used, e.g. outside of
+/// secure contexts.
+#[cfg(target_arch = "wasm32")]
+fn web_clipboard() -> Option<web_sys::Clipboard> { web_sys::window()?.navigator().clipboard() }
+
 #[derive(Serialize, Deserialize)]
 pub struct DataManaged<V> {
     data : BTreeMap<String, V>,
+    /// what's kept about the entries besides their values, kept apart so that
+    /// the entries of older versions can still be read
+    #[serde(default)]
+    info : BTreeMap<String, EntryInfo>,
     selected : String,
     renamed : Option<(String, V)>,
-    edit_mode : CodeEditMode
+    /// what's kept about the entry being renamed
+    #[serde(skip)]
+    renamed_info : EntryInfo,
+    edit_mode : CodeEditMode,
+    /// whether "save to file" writes all entries rather than the selected one
+    #[serde(default)]
+    save_all : bool,
+    /// the file being loaded
+    #[serde(skip)]
+    upload : Option<Upload>,
+    /// only the entries whose names contain this are listed
+    #[serde(default)]
+    filter : String,
+    #[serde(default)]
+    sort : SortOrder,
+    /// the name the entry being renamed had, `None` for imported entries
+    #[serde(skip)]
+    renamed_from : Option<String>,
+    /// the latest deletions and renames first
+    #[serde(skip)]
+    removals : VecDeque<Removal<V>>,
+    /// the name to import the pasted json under if its own is taken
+    #[serde(skip)]
+    import_name : String,
+    /// the entries ticked to be deleted or exported together
+    #[serde(skip)]
+    checked : BTreeSet<String>,
+    /// the text being read from the browser's clipboard, `None` if that's not
+    /// permitted
+    #[cfg(target_arch = "wasm32")]
+    #[serde(skip)]
+    clipboard_read : Option<Promise<Option<String>>>
 }
 
 impl<V> Default for DataManaged<V> {
     fn default() -> Self {
         Self {
             data : Default::default(),
+            info : Default::default(),
             selected : Default::default(),
             renamed : Default::default(),
-            edit_mode : Default::default()
+            renamed_info : Default::default(),
+            edit_mode : Default::default(),
+            save_all : Default::default(),
+            upload : Default::default(),
+            filter : Default::default(),
+            sort : Default::default(),
+            renamed_from : Default::default(),
+            removals : Default::default(),
+            import_name : Default::default(),
+            checked : Default::default(),
+            #[cfg(target_arch = "wasm32")]
+            clipboard_read : Default::default()
         }
     }
 }
@@ -39,180 +248,757 @@ impl<V> Deref for DataManaged<V> {
     fn deref(&self) -> &Self::Target { &self.data }
 }
 
-impl<V> DerefMut for DataManaged<V> {
-    fn deref_mut(&mut self) -> &mut Self::Target { &mut self.data }
-}
-
 impl<V> DataManaged<V> {
     pub fn selected(&self) -> Option<&V> { self.data.get(&self.selected) }
 
+    /// Saves the value under the name, keeping the note of the entry it
+    /// replaces.
+    pub fn insert(&mut self, name : String, value : V) -> Option<V> {
+        let info = self.info.entry(name.clone()).or_insert_with(EntryInfo::new);
+        info.modified = now();
+        self.data.insert(name, value)
+    }
+
+    /// Takes the value out, e.g. to edit it. Its note is kept for when it's
+    /// saved under the same name again.
+    pub fn remove(&mut self, name : &str) -> Option<V> { self.data.remove(name) }
+
+    /// The values to be changed in place, e.g. to add new stats. This doesn't
+    /// count as saving them.
+    pub fn values_mut(&mut self) -> ValuesMut<'_, String, V> { self.data.values_mut() }
+
+    /// the entry together with what's kept about it
+    fn managed(&self, name : &str) -> Option<Managed<&V>> {
+        Some(Managed {
+            value : self.data.get(name)?,
+            info : self.info.get(name).cloned().unwrap_or_default()
+        })
+    }
+
+    pub fn insert_managed(&mut self, name : String, entry : Managed<V>) {
+        self.info.insert(name.clone(), entry.info);
+        self.data.insert(name, entry.value);
+    }
+
+    fn take_managed(&mut self, name : &str) -> Option<Managed<V>> {
+        let info = self.info.remove(name).unwrap_or_default();
+        Some(Managed {
+            value : self.data.remove(name)?,
+            info
+        })
+    }
+
+    /// Takes every entry, e.g. to merge them into others.
+    pub fn take_all(&mut self) -> Vec<(String, Managed<V>)> {
+        let info = std::mem::take(&mut self.info);
+        std::mem::take(&mut self.data)
+            .into_iter()
+            .map(|(name, value)| {
+                let info = info.get(&name).cloned().unwrap_or_default();
+                (name, Managed { value, info })
+            })
+            .collect()
+    }
+
+    /// The same entries with their values converted, e.g. from an older
+    /// version of the saved state.
+    pub fn map_values<W>(self, convert : impl Fn(V) -> W) -> DataManaged<W> {
+        DataManaged {
+            data : self
+                .data
+                .into_iter()
+                .map(|(name, value)| (name, convert(value)))
+                .collect(),
+            info : self.info,
+            selected : self.selected,
+            renamed : self.renamed.map(|(name, value)| (name, convert(value))),
+            edit_mode : self.edit_mode,
+            save_all : self.save_all,
+            filter : self.filter,
+            sort : self.sort,
+            ..Default::default()
+        }
+    }
+
+    fn remember(&mut self, removal : Removal<V>) {
+        self.removals.push_front(removal);
+        self.removals.truncate(UNDO_LIMIT);
+    }
+
+    /// Restores the entry removed last. If its name has been taken since, it
+    /// goes through renaming instead. Of entries deleted together, those whose
+    /// names have been taken are left to be restored one by one.
+    fn undo_removal(&mut self) {
+        let (name, entry) = match self.removals.pop_front() {
+            Some(Removal::Deleted(name, entry)) => (name, entry),
+            Some(Removal::DeletedChecked(entries)) => {
+                for (name, entry) in entries.into_iter().rev() {
+                    if self.check_legal_name(&name) {
+                        self.insert_managed(name, entry);
+                    }
+                    else {
+                        self.removals.push_front(Removal::Deleted(name, entry));
+                    }
+                }
+                return;
+            },
+            Some(Removal::Renamed(from, to)) => match self.take_managed(&to) {
+                Some(entry) => (from, entry),
+                None => return
+            },
+            None => return
+        };
+        if self.check_legal_name(&name) {
+            self.selected = name.clone();
+            self.insert_managed(name, entry);
+        }
+        else {
+            self.renamed = Some((name, entry.value));
+            self.renamed_info = entry.info;
+        }
+    }
+
+    /// "Name (copy)", or "Name (copy 2)" and so on if that's taken as well
+    fn duplicate_name(&self, name : &str) -> String {
+        std::iter::once(format!("{name} (copy)"))
+            .chain((2..).map(|count| format!("{name} (copy {count})")))
+            .find(|duplicate| self.check_legal_name(duplicate))
+            .unwrap()
+    }
+
+    /// the names of the entries the filter lets through, in the chosen order
+    fn filtered_names(&self) -> Vec<&String> {
+        let filter = self.filter.to_lowercase();
+        let mut names = self
+            .data
+            .keys()
+            .filter(|name| name.to_lowercase().contains(&filter))
+            .collect_vec();
+        let time : fn(&EntryInfo) -> u64 = match self.sort {
+            SortOrder::Alphabetical => return names,
+            SortOrder::RecentlyModified => |info| info.modified,
+            SortOrder::RecentlyCreated => |info| info.created
+        };
+        // entries saved before their times were kept come last, and the sort
+        // being stable, those of the same time stay alphabetical
+        names.sort_by_key(|name| Reverse(self.info.get(*name).map_or(0, time)));
+        names
+    }
+
+    /// the names of the checked entries, or of the selected one if none are
+    /// checked
+    fn chosen_names(&self) -> String {
+        if self.checked.is_empty() {
+            self.selected.clone()
+        }
+        else {
+            self.checked.iter().join(", ")
+        }
+    }
+
+    /// Deletes the checked entries, or the selected one if none are checked.
+    fn delete(&mut self) {
+        if self.checked.is_empty() {
+            if let Some(entry) = self.take_managed(&self.selected.clone()) {
+                self.remember(Removal::Deleted(self.selected.clone(), entry));
+            }
+            return;
+        }
+        let entries = std::mem::take(&mut self.checked)
+            .into_iter()
+            .filter_map(|name| Some((name.clone(), self.take_managed(&name)?)))
+            .collect_vec();
+        self.remember(Removal::DeletedChecked(entries));
+    }
+
     pub fn check_legal_name(&self, name : &str) -> bool {
         !name.is_empty()
             && !self
                 .data
-                .iter()
-                .map(|(name, _data)| name.to_lowercase())
+                .keys()
+                .map(|name| name.to_lowercase())
                 .contains(&name.to_lowercase())
     }
 }
 
-impl<V : Serialize + for<'a> Deserialize<'a>> DataManaged<V> {
+impl<V : Clone + Serialize + for<'a> Deserialize<'a>> DataManaged<V> {
+    /// The checked entries as an array, or the selected one if none are
+    /// checked.
     fn extract(&self) -> Option<String> {
-        serde_json::to_string(self.data.get(&self.selected)?).ok()
+        if self.checked.is_empty() {
+            serde_json::to_string(&self.managed(&self.selected)?).ok()
+        }
+        else {
+            let entries = self
+                .checked
+                .iter()
+                .filter_map(|name| self.managed(name))
+                .collect_vec();
+            serde_json::to_string(&entries).ok()
+        }
+    }
+
+    fn export_file_name(&self) -> String {
+        if self.save_all {
+            "all.json".to_owned()
+        }
+        else {
+            format!("{}.json", self.selected)
+        }
+    }
+
+    /// The selected entry, or all of them, as pretty JSON.
+    fn export_file(&self) -> Result<String, String> {
+        let json = if self.save_all {
+            let entries : BTreeMap<_, _> = self
+                .data
+                .keys()
+                .filter_map(|name| Some((name, self.managed(name)?)))
+                .collect();
+            serde_json::to_string_pretty(&entries)
+        }
+        else {
+            serde_json::to_string_pretty(&self.managed(&self.selected).ok_or("nothing selected")?)
+        };
+        json.map_err(|error| error.to_string())
+    }
+
+    /// Adds the entry, array of entries or named entries of the text, logging
+    /// their names with the given action. The first one whose name is taken
+    /// goes through renaming, any further ones are skipped.
+    fn import_file(
+        &mut self,
+        ctx : &egui::Context,
+        window : AppWindow,
+        text : &str,
+        deserialize_name : &impl Fn(&V) -> String,
+        action_key : &str
+    ) -> Result<(), String> {
+        let entries = if let Ok(entry) = parse_entry::<V>(text) {
+            vec![(deserialize_name(&entry.value), entry)]
+        }
+        else if let Ok(entries) = parse_list::<V>(text) {
+            entries
+                .into_iter()
+                .map(|entry| (deserialize_name(&entry.value), entry))
+                .collect()
+        }
+        else {
+            parse_entries(text)
+                .map_err(|error| tr_with("manager.not_entries", &[("error", &error)]))?
+                .into_iter()
+                .collect()
+        };
+        let mut imported = Vec::new();
+        let mut skipped = Vec::new();
+        for (name, entry) in entries {
+            if self.check_legal_name(&name) {
+                imported.push(name.clone());
+                self.insert_managed(name, entry);
+            }
+            else if self.renamed.is_none() {
+                self.renamed = Some((name, entry.value));
+                self.renamed_info = entry.info;
+            }
+            else {
+                skipped.push(name);
+            }
+        }
+        if !imported.is_empty() {
+            let names = imported.join(", ");
+            log(ctx, window, tr_with(action_key, &[("names", &names)]));
+        }
+        if skipped.is_empty() {
+            Ok(())
+        }
+        else {
+            Err(tr_with(
+                "manager.skipped",
+                &[("names", &skipped.join(", "))]
+            ))
+        }
+    }
+
+    fn import_loaded(
+        &mut self,
+        ctx : &egui::Context,
+        window : AppWindow,
+        file : Result<Vec<u8>, String>,
+        deserialize_name : &impl Fn(&V) -> String
+    ) {
+        if let Err(error) = file
+            .and_then(|bytes| String::from_utf8(bytes).map_err(|error| error.to_string()))
+            .and_then(|text| {
+                self.import_file(ctx, window, &text, deserialize_name, "manager.log_imported")
+            })
+        {
+            toasts::error(ctx, tr_with("manager.load_failed", &[("error", &error)]));
+        }
+    }
+
+    /// Whether the text holds entries [`Self::import_file`] can add. The
+    /// clipboard of every platform is checked the same way.
+    fn holds_entries(text : &str) -> bool {
+        parse_entry::<V>(text).is_ok()
+            || parse_list::<V>(text).is_ok()
+            || parse_entries::<V>(text).is_ok()
+    }
+
+    /// Imports the entries of the clipboard's text the way a loaded file's
+    /// are, so that taken names go through renaming or are skipped.
+    fn paste(
+        &mut self,
+        ctx : &egui::Context,
+        window : AppWindow,
+        text : Option<String>,
+        deserialize_name : &impl Fn(&V) -> String
+    ) {
+        match text.filter(|text| Self::holds_entries(text)) {
+            Some(text) => {
+                if let Err(error) =
+                    self.import_file(ctx, window, &text, deserialize_name, "manager.log_pasted")
+                {
+                    toasts::error(ctx, tr_with("manager.paste_failed", &[("error", &error)]));
+                }
+            },
+            None => toasts::error(ctx, tr("manager.clipboard_empty"))
+        }
+    }
+
+    /// Imports the entry read from the browser's clipboard once it's read.
+    #[cfg(target_arch = "wasm32")]
+    fn poll_clipboard(
+        &mut self,
+        ui : &Ui,
+        window : AppWindow,
+        deserialize_name : &impl Fn(&V) -> String
+    ) {
+        if let Some(read) = std::mem::take(&mut self.clipboard_read) {
+            match read.try_take() {
+                Ok(text) => self.paste(ui.ctx(), window, text, deserialize_name),
+                Err(read) => {
+                    self.clipboard_read = Some(read);
+                    ui.ctx().request_repaint();
+                }
+            }
+        }
+    }
+
+    fn file_buttons(
+        &mut self,
+        ui : &mut Ui,
+        window : AppWindow,
+        deserialize_name : &impl Fn(&V) -> String
+    ) {
+        ui.checkbox(&mut self.save_all, tr("manager.save_all"));
+        let savable = self.save_all || self.data.contains_key(&self.selected);
+        if ui
+            .add_enabled(savable, Button::new(tr("manager.save_file")))
+            .clicked()
+        {
+            let file = self.export_file_name();
+            match self
+                .export_file()
+                .and_then(|json| files::save(&file, files::JSON, &json))
+            {
+                Ok(true) => log(
+                    ui.ctx(),
+                    window,
+                    tr_with("manager.log_saved_file", &[("file", &file)])
+                ),
+                Ok(false) => {},
+                Err(error) => toasts::error(
+                    ui.ctx(),
+                    tr_with("manager.save_failed", &[("error", &error)])
+                )
+            }
+        }
+        if ui
+            .button(tr("manager.load_file"))
+            .on_hover_text(tr("manager.load_file_hint"))
+            .clicked()
+        {
+            self.upload = Some(files::pick(files::JSON));
+        }
+        if let Some(upload) = std::mem::take(&mut self.upload) {
+            match upload.try_take() {
+                Ok(Some(file)) => self.import_loaded(ui.ctx(), window, file, deserialize_name),
+                Ok(None) => {},
+                Err(upload) => {
+                    self.upload = Some(upload);
+                    ui.ctx().request_repaint();
+                }
+            }
+        }
+    }
+
+    /// Imports the files dropped onto the window.
+    fn import_dropped(
+        &mut self,
+        ctx : &egui::Context,
+        window : AppWindow,
+        rect : Rect,
+        deserialize_name : &impl Fn(&V) -> String
+    ) {
+        for file in files::dropped(ctx, rect) {
+            self.import_loaded(ctx, window, file, deserialize_name);
+        }
     }
 
     pub fn management_dialogue(
         &mut self,
         ctx : &egui::Context,
         external_modal_open : bool,
-        window_title : &str,
+        window : AppWindow,
         deserialize_name : impl Fn(&V) -> String,
         buttons : impl FnOnce(&mut Ui, &mut Self)
     ) -> Option<Rect> {
         let modal_open = external_modal_open || self.renamed.is_some();
-        let window_response = egui::Window::new(window_title)
-            .collapsible(!modal_open)
-            .show(ctx, |ui| {
-                ui.set_enabled(!modal_open);
-                let out_rect = ui.available_rect_before_wrap();
-                ui.columns(3, |uis| {
-                    let ui = &mut uis[1];
-
-                    buttons(ui, self);
-
-                    ui.add_enabled_ui(self.data.contains_key(&self.selected), |ui| {
-                        if ui.button("delete").clicked() {
-                            self.data.remove(&self.selected);
-                        }
-                        if ui.button("rename").clicked() {
-                            self.renamed = self
-                                .data
-                                .remove(&self.selected)
-                                .map(|v| (self.selected.clone(), v));
-                        }
-                        #[cfg(not(target_arch = "wasm32"))]
-                        {
-                            if ui.button("copy to clipboard").clicked() {
-                                if let Ok(mut clipboard) = arboard::Clipboard::new() {
-                                    let _best_effort = clipboard.set_text(
-                                        serde_json::to_string(
-                                            &self.data.get(&self.selected).unwrap()
-                                        )
-                                        .unwrap()
+        let window_response = window.window().collapsible(!modal_open).show(ctx, |ui| {
+            ui.set_enabled(!modal_open);
+            let out_rect = ui.available_rect_before_wrap();
+            let data = &self.data;
+            self.checked.retain(|name| data.contains_key(name));
+            ui.columns(3, |uis| {
+                let ui = &mut uis[1];
+
+                buttons(ui, self);
+
+                let chosen = self.data.contains_key(&self.selected) || !self.checked.is_empty();
+                ui.add_enabled_ui(chosen, |ui| {
+                    let delete = match self.checked.len() {
+                        0 => tr("manager.delete"),
+                        checked => tr_with("manager.delete_checked", &[("count", &checked)])
+                    };
+                    if ui.button(delete).clicked() {
+                        let names = self.chosen_names();
+                        log(
+                            ctx,
+                            window,
+                            tr_with("manager.log_deleted", &[("names", &names)])
+                        );
+                        self.delete();
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    {
+                        if ui.button(tr("manager.copy")).clicked() {
+                            if let Some(json) = self.extract() {
+                                let names = self.chosen_names();
+                                let action = tr_with("manager.log_copied", &[("names", &names)]);
+                                log(ctx, window, action);
+                                if let Err(error) = arboard::Clipboard::new()
+                                    .and_then(|mut clipboard| clipboard.set_text(json))
+                                {
+                                    toasts::error(
+                                        ctx,
+                                        tr_with("manager.copy_failed", &[("error", &error)])
                                     );
                                 }
                             }
                         }
-                    });
-
-                    #[cfg(not(target_arch = "wasm32"))]
+                    }
+                    #[cfg(target_arch = "wasm32")]
                     {
-                        let mut clipboard_copied_promotion : Option<V> = None;
-
-                        if let Ok(mut clipboard) = arboard::Clipboard::new() {
-                            if let Ok(text) = clipboard.get_text() {
-                                if let Ok(parse) = serde_json::from_str::<V>(&text) {
-                                    if !self.data.contains_key(&deserialize_name(&parse)) {
-                                        clipboard_copied_promotion = Some(parse);
-                                    }
+                        if let Some(clipboard) = web_clipboard() {
+                            if ui.button(tr("manager.copy")).clicked() {
+                                if let Some(json) = self.extract() {
+                                    let names = self.chosen_names();
+                                    let action =
+                                        tr_with("manager.log_copied", &[("names", &names)]);
+                                    log(ctx, window, action);
+                                    let written = clipboard.write_text(&json);
+                                    let ctx = ctx.clone();
+                                    wasm_bindgen_futures::spawn_local(async move {
+                                        let written =
+                                            wasm_bindgen_futures::JsFuture::from(written).await;
+                                        if let Err(error) = written {
+                                            let error = format!("{error:?}");
+                                            toasts::error(
+                                                &ctx,
+                                                tr_with(
+                                                    "manager.copy_failed",
+                                                    &[("error", &error)]
+                                                )
+                                            );
+                                        }
+                                    });
                                 }
                             }
-                            ui.add_enabled_ui(clipboard_copied_promotion.is_some(), |ui| {
-                                if ui.button("import from clipboard").clicked() {
-                                    if let Some(clipboard_copied_promotion) =
-                                        clipboard_copied_promotion
-                                    {
-                                        self.data.insert(
-                                            deserialize_name(&clipboard_copied_promotion),
-                                            clipboard_copied_promotion
-                                        );
-                                    }
-                                }
-                            });
                         }
                     }
+                });
 
-                    if ui
-                        .add_enabled(
-                            self.edit_mode != CodeEditMode::Export,
-                            Button::new("export json")
-                        )
-                        .clicked()
-                    {
-                        self.edit_mode = CodeEditMode::Export;
+                // renaming and duplicating act on the selected entry only
+                ui.add_enabled_ui(self.data.contains_key(&self.selected), |ui| {
+                    if ui.button(tr("manager.rename")).clicked() {
+                        if let Some(entry) = self.take_managed(&self.selected.clone()) {
+                            self.renamed_from = Some(self.selected.clone());
+                            self.renamed = Some((self.selected.clone(), entry.value));
+                            self.renamed_info = entry.info;
+                        }
                     }
-
-                    if ui
-                        .add_enabled(
-                            matches!(self.edit_mode, CodeEditMode::Export)
-                                || self.check_importable_text(&deserialize_name),
-                            Button::new("import json")
-                        )
-                        .clicked()
-                    {
-                        match &mut self.edit_mode {
-                            CodeEditMode::Export => {
-                                self.edit_mode = CodeEditMode::Importing("".to_string());
-                            },
-                            CodeEditMode::Importing(s) => {
-                                let read_value : V = serde_json::from_str(s).unwrap();
-                                self.data.insert(deserialize_name(&read_value), read_value);
-                                s.clear();
+                    if ui.button(tr("manager.duplicate")).clicked() {
+                        let name = self.duplicate_name(&self.selected);
+                        let duplicate = Managed {
+                            value : self.data[&self.selected].clone(),
+                            info : EntryInfo {
+                                note : self
+                                    .info
+                                    .get(&self.selected)
+                                    .map(|info| info.note.clone())
+                                    .unwrap_or_default(),
+                                ..EntryInfo::new()
                             }
-                        }
+                        };
+                        let action = tr_with(
+                            "manager.log_duplicated",
+                            &[("name", &self.selected), ("copy", &name)]
+                        );
+                        log(ctx, window, action);
+                        self.insert_managed(name.clone(), duplicate);
+                        self.selected = name;
                     }
+                });
 
-                    let ui = &mut uis[0];
-                    ScrollArea::vertical().show_rows(
-                        ui,
-                        ui.text_style_height(&egui::TextStyle::Body),
-                        self.data.len(),
-                        |ui, range| {
-                            for name in self.data.keys().take(range.end).skip(range.start) {
-                                ui.selectable_value(&mut self.selected, name.to_owned(), name);
+                if let Some(removal) = self.removals.front() {
+                    let label = match removal {
+                        Removal::Deleted(name, _entry) => {
+                            tr_with("manager.undo_delete", &[("name", name)])
+                        },
+                        Removal::DeletedChecked(entries) => {
+                            tr_with("manager.undo_delete_checked", &[("count", &entries.len())])
+                        },
+                        Removal::Renamed(from, to) => {
+                            tr_with("manager.undo_rename", &[("from", from), ("to", to)])
+                        },
+                    };
+                    if ui.button(&label).clicked() {
+                        log(ctx, window, label);
+                        self.undo_removal();
+                    }
+                }
+
+                #[cfg(not(target_arch = "wasm32"))]
+                {
+                    if let Ok(mut clipboard) = arboard::Clipboard::new() {
+                        let text = clipboard
+                            .get_text()
+                            .ok()
+                            .filter(|text| Self::holds_entries(text));
+                        ui.add_enabled_ui(text.is_some(), |ui| {
+                            if ui.button(tr("manager.paste")).clicked() {
+                                self.paste(ctx, window, text, &deserialize_name);
                             }
+                        });
+                    }
+                }
+
+                // reading the browser's clipboard asks for permission, so
+                // it's only read once asked to rather than every frame
+                #[cfg(target_arch = "wasm32")]
+                {
+                    if let Some(clipboard) = web_clipboard() {
+                        if ui
+                            .add_enabled(
+                                self.clipboard_read.is_none(),
+                                Button::new(tr("manager.paste"))
+                            )
+                            .clicked()
+                        {
+                            let text = clipboard.read_text();
+                            self.clipboard_read = Some(Promise::spawn_async(async move {
+                                wasm_bindgen_futures::JsFuture::from(text)
+                                    .await
+                                    .ok()?
+                                    .as_string()
+                            }));
                         }
-                    );
+                    }
+                    self.poll_clipboard(ui, window, &deserialize_name);
+                }
 
-                    let ui = &mut uis[2];
-                    match &mut self.edit_mode {
+                self.file_buttons(ui, window, &deserialize_name);
+
+                if ui
+                    .add_enabled(
+                        self.edit_mode != CodeEditMode::Export,
+                        Button::new(tr("manager.export_json"))
+                    )
+                    .clicked()
+                {
+                    self.edit_mode = CodeEditMode::Export;
+                }
+
+                let importable = match &self.edit_mode {
+                    CodeEditMode::Export => true,
+                    CodeEditMode::Importing(s) => {
+                        self.import_target(s, &deserialize_name).is_some()
+                            || parse_list::<V>(s).is_ok()
+                    },
+                };
+                let import = Button::new(tr("manager.import_json"));
+                if ui.add_enabled(importable, import).clicked() {
+                    match &self.edit_mode {
                         CodeEditMode::Export => {
-                            let copied_export = self.extract().unwrap_or_default();
-                            ui.add(
-                                TextEdit::multiline(&mut copied_export.as_str())
-                                    .code_editor()
-                                    .desired_width(0.0)
-                            );
+                            self.edit_mode = CodeEditMode::Importing("".to_string());
                         },
                         CodeEditMode::Importing(s) => {
-                            ui.label(
-                                "Paste the json here and then confirm by clicking \"import json\" \
-                                 again:"
-                            );
-                            ui.add(TextEdit::multiline(s).code_editor().desired_width(0.0));
+                            let text = s.clone();
+                            if let Some((name, entry)) =
+                                self.import_target(&text, &deserialize_name)
+                            {
+                                let action = tr_with("manager.log_imported", &[("names", &name)]);
+                                log(ctx, window, action);
+                                self.insert_managed(name, entry);
+                            }
+                            else if let Err(error) = self.import_file(
+                                ctx,
+                                window,
+                                &text,
+                                &deserialize_name,
+                                "manager.log_imported"
+                            ) {
+                                toasts::error(
+                                    ctx,
+                                    tr_with("manager.import_failed", &[("error", &error)])
+                                );
+                            }
+                            self.edit_mode = CodeEditMode::Importing("".to_string());
+                            self.import_name.clear();
                         }
                     }
-                });
-                out_rect
+                }
+
+                let ui = &mut uis[0];
+                let filter = TextEdit::singleline(&mut self.filter);
+                ui.add(filter.hint_text(tr("manager.filter")));
+                ComboBox::from_label(tr("manager.sort"))
+                    .selected_text(self.sort.name())
+                    .show_ui(ui, |ui| {
+                        for sort in [
+                            SortOrder::Alphabetical,
+                            SortOrder::RecentlyModified,
+                            SortOrder::RecentlyCreated
+                        ] {
+                            ui.selectable_value(&mut self.sort, sort, sort.name());
+                        }
+                    });
+                let uncheck = tr_with("manager.uncheck", &[("count", &self.checked.len())]);
+                if !self.checked.is_empty() && ui.button(uncheck).clicked() {
+                    self.checked.clear();
+                }
+                let names = self.filtered_names();
+                // delete and rename mustn't act on an entry that isn't shown
+                let mut selected = if names.contains(&&self.selected) {
+                    self.selected.clone()
+                }
+                else {
+                    String::new()
+                };
+                let mut toggled = Vec::new();
+                ScrollArea::vertical().show_rows(
+                    ui,
+                    // the rows are as high as their checkboxes
+                    ui.spacing().interact_size.y,
+                    names.len(),
+                    |ui, range| {
+                        for name in &names[range] {
+                            ui.horizontal(|ui| {
+                                let mut checked = self.checked.contains(*name);
+                                if ui.checkbox(&mut checked, "").changed() {
+                                    toggled.push((*name).clone());
+                                }
+                                let response =
+                                    ui.selectable_value(&mut selected, name.to_string(), *name);
+                                let info = self.info.get(*name);
+                                if let Some(info) = info.filter(|info| !info.note.is_empty()) {
+                                    response.on_hover_text(&info.note);
+                                }
+                            });
+                        }
+                    }
+                );
+                self.selected = selected;
+                for name in toggled {
+                    if !self.checked.remove(&name) {
+                        self.checked.insert(name);
+                    }
+                }
+
+                let ui = &mut uis[2];
+                if self.data.contains_key(&self.selected) {
+                    let info = self.info.entry(self.selected.clone()).or_default();
+                    // entries saved before their times were kept have none
+                    if info.modified == 0 {
+                        ui.label(tr("manager.note"));
+                    }
+                    else {
+                        let age = age(info.modified);
+                        ui.label(tr_with("manager.note_saved", &[("age", &age)]));
+                    }
+                    ui.add(
+                        TextEdit::multiline(&mut info.note)
+                            .desired_rows(2)
+                            .desired_width(f32::INFINITY)
+                    );
+                }
+                if self.edit_mode == CodeEditMode::Export {
+                    let copied_export = self.extract().unwrap_or_default();
+                    ui.add(
+                        TextEdit::multiline(&mut copied_export.as_str())
+                            .code_editor()
+                            .desired_width(0.0)
+                    );
+                }
+                else {
+                    self.import_editor(ui, &deserialize_name);
+                }
             });
+            out_rect
+        });
         let modal_rect = window_response.map(|response| response.response.rect);
+        if let Some(rect) = modal_rect {
+            onboarding::mark_window(ctx, window.id(), rect);
+        }
+        if let (Some(rect), false) = (modal_rect, modal_open) {
+            self.import_dropped(ctx, window, rect, &deserialize_name);
+        }
 
-        let copy_rect = modal_rect.clone();
+        let copy_rect = modal_rect;
 
         if let Some((mut name, item)) = std::mem::take(&mut self.renamed) {
-            egui::Window::new("Renaming Promotion")
+            egui::Window::new(tr("manager.renaming"))
+                .id(Id::new("Renaming Promotion"))
                 .collapsible(false)
-                .fixed_rect(modal_rect.unwrap())
+                .fixed_rect(on_screen(ctx, modal_rect.unwrap()))
                 .show(ctx, |ui| {
                     ui.horizontal(|ui| {
-                        ui.label("Promotion name: ");
+                        ui.label(tr("manager.new_name"));
                         ui.text_edit_singleline(&mut name);
                     });
+                    let confirm = Button::new(tr("common.confirm"));
                     if ui
-                        .add_enabled(self.check_legal_name(&name), Button::new("confirm"))
+                        .add_enabled(self.check_legal_name(&name), confirm)
                         .clicked()
                     {
-                        self.data.insert(name, item);
+                        match self.renamed_from.take() {
+                            Some(from) if from != name => {
+                                let action = tr_with(
+                                    "manager.log_renamed",
+                                    &[("from", &from), ("to", &name)]
+                                );
+                                log(ctx, window, action);
+                                self.remember(Removal::Renamed(from, name.clone()));
+                            },
+                            Some(_unchanged) => {},
+                            // imported entries and restored ones whose names were taken
+                            None => {
+                                let action = tr_with("manager.log_added", &[("names", &name)]);
+                                log(ctx, window, action);
+                            }
+                        }
+                        let info = std::mem::take(&mut self.renamed_info);
+                        self.insert_managed(name, Managed { value : item, info });
                     }
                     else {
                         self.renamed = Some((name, item));
@@ -223,12 +1009,58 @@ impl<V : Serialize + for<'a> Deserialize<'a>> DataManaged<V> {
         copy_rect
     }
 
-    fn check_importable_text(&self, deserialize_name : &impl Fn(&V) -> String) -> bool {
-        if let CodeEditMode::Importing(s) = &self.edit_mode {
-            if let Ok(parsed) = serde_json::from_str(s) {
-                return self.check_legal_name(&deserialize_name(&parsed));
+    /// The pasted entry and the name to import it under, `None` if it isn't an
+    /// entry or neither its own name nor the one entered instead is free.
+    fn import_target(
+        &self,
+        text : &str,
+        deserialize_name : &impl Fn(&V) -> String
+    ) -> Option<(String, Managed<V>)> {
+        let entry = parse_entry::<V>(text).ok()?;
+        let name = deserialize_name(&entry.value);
+        if self.check_legal_name(&name) {
+            Some((name, entry))
+        }
+        else if self.check_legal_name(&self.import_name) {
+            Some((self.import_name.clone(), entry))
+        }
+        else {
+            None
+        }
+    }
+
+    /// The text box the json is pasted into, telling why it can't be imported
+    /// and asking for another name if its own is taken.
+    fn import_editor(&mut self, ui : &mut Ui, deserialize_name : &impl Fn(&V) -> String) {
+        let text = match &mut self.edit_mode {
+            CodeEditMode::Importing(text) => text,
+            CodeEditMode::Export => return
+        };
+        ui.label(tr("manager.paste_hint"));
+        ui.add(TextEdit::multiline(text).code_editor().desired_width(0.0));
+        if text.trim().is_empty() {
+            return;
+        }
+        let name = match parse_entry::<V>(text) {
+            Ok(entry) => deserialize_name(&entry.value),
+            Err(error) => {
+                let error = tr_with("manager.not_an_entry", &[("error", &error)]);
+                ui.colored_label(ui.visuals().error_fg_color, error);
+                return;
             }
+        };
+        if self.check_legal_name(&name) {
+            return;
+        }
+        if name.is_empty() {
+            ui.label(tr("manager.unnamed"));
+        }
+        else {
+            ui.label(tr_with("manager.name_taken", &[("name", &name)]));
+        }
+        ui.text_edit_singleline(&mut self.import_name);
+        if !self.import_name.is_empty() && !self.check_legal_name(&self.import_name) {
+            ui.colored_label(ui.visuals().error_fg_color, tr("manager.also_taken"));
         }
-        false
     }
 }
@@ -0,0 +1,269 @@
+//! Loading the state saved by older versions of the app. Every version whose
+//! layout can't be read as the current one keeps its own structs here
+//! together with their conversion into the next version.
+
+use std::collections::HashMap;
+
+use fe_levels::Character;
+use serde::Deserialize;
+
+use super::{
+    database::ClassTier,
+    files, generate_default_gamedata,
+    locale::{tr, tr_with},
+    manager::DataManaged,
+    plotter::PlotterManager,
+    progression::{afas_drops, ConcreteStatChange, GbaFeStatChange, ProgressionManager},
+    sit::StatIndexType,
+    toasts,
+    weapon::Weapon,
+    FeLevelGui, GameData, GameKind
+};
+
+/// The version of the saved state, to be raised once per release whenever the
+/// state of the previous release can't be read anymore, e.g. as a saved type
+/// changed.
+pub const CURRENT_VERSION : u64 = 3;
+
+/// The state saved by version 2, the last released one, which only knew the
+/// GBA games and PoR.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct FeLevelGuiV2 {
+    game_option : GameKind,
+    game_data : HashMap<GameKind, GameDataV2>
+}
+
+#[derive(Deserialize)]
+struct GameDataV2 {
+    plotter : PlotterManager,
+    character : Character<StatIndexType>,
+    enemy : Option<Character<StatIndexType>>,
+    weapon : Option<Weapon>,
+    game_option : GameKind,
+    progression : ProgressionManagerV2,
+    promotions : DataManaged<Character<StatIndexType>>,
+    characters : DataManaged<(Character<StatIndexType>, Vec<ConcreteStatChangeV2>)>,
+    enemies : DataManaged<Character<StatIndexType>>,
+    weapons : DataManaged<Weapon>
+}
+
+/// Only the progression is kept, the templates are generated anew.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct ProgressionManagerV2 {
+    progression : Vec<ConcreteStatChangeV2>
+}
+
+#[derive(Deserialize)]
+enum ConcreteStatChangeV2 {
+    GbaFeStatChange(GbaFeStatChangeV2)
+}
+
+/// Promotions didn't know their tier yet and boosters always granted the
+/// amount of the GBA items.
+#[derive(Deserialize)]
+enum GbaFeStatChangeV2 {
+    Promotion(Character<StatIndexType>),
+    LevelUp,
+    GrowthBooster,
+    StatBooster(StatIndexType)
+}
+
+impl From<ConcreteStatChangeV2> for ConcreteStatChange {
+    fn from(stat_change : ConcreteStatChangeV2) -> Self {
+        let ConcreteStatChangeV2::GbaFeStatChange(stat_change) = stat_change;
+        ConcreteStatChange::GbaFeStatChange(match stat_change {
+            GbaFeStatChangeV2::Promotion(promotion) => {
+                GbaFeStatChange::Promotion(ClassTier::Unpromoted, promotion)
+            },
+            GbaFeStatChangeV2::LevelUp => GbaFeStatChange::LevelUp,
+            GbaFeStatChangeV2::GrowthBooster => afas_drops(),
+            GbaFeStatChangeV2::StatBooster(stat) => {
+                GbaFeStatChange::StatBooster(stat, if stat.is_hp() { 7 } else { 2 })
+            },
+        })
+    }
+}
+
+fn convert_progression(progression : Vec<ConcreteStatChangeV2>) -> Vec<ConcreteStatChange> {
+    progression.into_iter().map(Into::into).collect()
+}
+
+impl From<GameDataV2> for GameData {
+    fn from(data : GameDataV2) -> Self {
+        let mut progression = ProgressionManager::default();
+        *progression = convert_progression(data.progression.progression);
+        GameData {
+            plotter : data.plotter,
+            character : data.character,
+            enemy : data.enemy,
+            weapon : data.weapon,
+            game_option : data.game_option,
+            progression,
+            promotions : data.promotions,
+            characters : data.characters.map_values(|(character, progression)| {
+                (
+                    character,
+                    convert_progression(progression),
+                    Default::default()
+                )
+            }),
+            enemies : data.enemies,
+            weapons : data.weapons,
+            ..generate_default_gamedata(data.game_option)
+        }
+    }
+}
+
+impl From<FeLevelGuiV2> for FeLevelGui {
+    fn from(state : FeLevelGuiV2) -> Self {
+        FeLevelGui {
+            game_option : state.game_option,
+            game_data : state
+                .game_data
+                .into_iter()
+                .map(|(game, data)| (game, data.into()))
+                .collect(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Reads the saved state of the current or an older version.
+pub fn restore(text : &str) -> Result<FeLevelGui, String> {
+    #[derive(Deserialize)]
+    #[serde(rename = "FeLevelGui")]
+    struct Version {
+        #[serde(default)]
+        version : u64
+    }
+    let unreadable =
+        |error : ron::error::SpannedError| tr_with("migration.unreadable", &[("error", &error)]);
+    let Version { version } = ron::from_str(text).map_err(unreadable)?;
+    let mut state : FeLevelGui = match version {
+        CURRENT_VERSION => ron::from_str(text).map_err(unreadable)?,
+        2 => ron::from_str::<FeLevelGuiV2>(text)
+            .map_err(unreadable)?
+            .into(),
+        _ => {
+            return Err(tr_with(
+                "migration.unknown_version",
+                &[("version", &version)]
+            ))
+        },
+    };
+    state
+        .game_data
+        .values_mut()
+        .for_each(GameData::insert_missing_stats);
+    Ok(state)
+}
+
+/// Saved state that couldn't be restored, kept until the user has had the
+/// chance to save it.
+pub struct DiscardedState {
+    reason : String,
+    text : String
+}
+
+impl DiscardedState {
+    pub fn new(reason : String, text : String) -> Self { Self { reason, text } }
+}
+
+pub fn discarded_state_window(discarded : &mut Option<DiscardedState>, ctx : &egui::Context) {
+    let mut dismissed = false;
+    if let Some(state) = discarded {
+        egui::Window::new(tr("migration.title"))
+            .id(egui::Id::new("Saved State Reset"))
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(tr_with("migration.reset", &[("reason", &state.reason)]));
+                ui.horizontal(|ui| {
+                    if ui.button(tr("migration.save")).clicked() {
+                        if let Err(error) = files::save("old state.ron", files::RON, &state.text) {
+                            toasts::error(
+                                ctx,
+                                tr_with("migration.save_failed", &[("error", &error)])
+                            );
+                        }
+                    }
+                    dismissed = ui.button(tr("migration.dismiss")).clicked();
+                });
+            });
+    }
+    if dismissed {
+        *discarded = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STATE_V2 : &str = include_str!("migration/state_v2.ron");
+
+    #[test]
+    fn version_2_stat_changes_gain_their_tiers_and_amounts() {
+        let state = restore(STATE_V2).unwrap();
+        assert_eq!(state.version, CURRENT_VERSION);
+        let data = &state.game_data[&GameKind::GbaFe];
+        let stats = StatIndexType::new(GameKind::GbaFe);
+        let (character, progression, growth_modifiers) = &data.characters["Eliwood"];
+        assert_eq!(character.stats[&stats[0]].base, 18);
+        assert!(growth_modifiers.is_empty());
+        assert_eq!(progression, &*data.progression);
+        assert_eq!(progression.len(), 8);
+        let gba = |stat_change| ConcreteStatChange::GbaFeStatChange(stat_change);
+        assert_eq!(
+            progression[2],
+            gba(GbaFeStatChange::StatBooster(stats[0], 7))
+        );
+        assert_eq!(
+            progression[3],
+            gba(GbaFeStatChange::StatBooster(stats[3], 2))
+        );
+        assert_eq!(progression[4], gba(afas_drops()));
+        match &progression[6] {
+            ConcreteStatChange::GbaFeStatChange(GbaFeStatChange::Promotion(tier, promotion)) => {
+                assert_eq!(*tier, ClassTier::Unpromoted);
+                assert_eq!(promotion.name, "Knight Lord");
+            },
+            other => panic!("{other} isn't the saved promotion")
+        }
+    }
+
+    #[test]
+    fn version_2_keeps_the_rest_of_the_state() {
+        let state = restore(STATE_V2).unwrap();
+        let data = &state.game_data[&GameKind::GbaFe];
+        let default_stats = StatIndexType::new_default_character(GameKind::GbaFe)
+            .stats
+            .len();
+        assert_eq!(data.character.name, "Eliwood");
+        assert_eq!(data.character.stats.len(), default_stats);
+        assert_eq!(data.promotions["Knight Lord"].level, 20);
+        assert!(matches!(data.weapon, Some(Weapon::GbaFeWeapon(_))));
+        assert_eq!(data.level_cap, GameKind::GbaFe.level_cap());
+    }
+
+    #[test]
+    fn current_state_round_trips() {
+        let mut state = FeLevelGui::default();
+        let mut data = generate_default_gamedata(GameKind::PoR);
+        data.character.name = "Ike".to_owned();
+        state.game_data.insert(GameKind::PoR, data);
+        state.game_option = GameKind::PoR;
+
+        let restored = restore(&ron::to_string(&state).unwrap()).unwrap();
+        assert_eq!(restored.version, CURRENT_VERSION);
+        assert_eq!(restored.game_option, GameKind::PoR);
+        assert_eq!(restored.game_data[&GameKind::PoR].character.name, "Ike");
+    }
+
+    #[test]
+    fn newer_versions_are_refused() {
+        assert!(restore("(version: 4)").is_err());
+        assert!(restore("not a saved state").is_err());
+    }
+}
@@ -0,0 +1,220 @@
+//! What a first run starts with: a worked example of a GBA unit and a tour
+//! pointing at the windows working on it one after another. The example is
+//! kept as a fixture, so the tests check that it still loads.
+
+use egui::{vec2, Align2, Context, Id, LayerId, Order, Rect, Stroke};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    database::{builtin_promotions, DatabaseCharacter},
+    generate_default_gamedata,
+    layout::{AppWindow, WindowVisibility},
+    locale::{tr, tr_with},
+    progression::parse_progression,
+    GameData, GameKind
+};
+
+const EXAMPLE_UNIT : &str = include_str!("onboarding/example_unit.json");
+
+/// A unit with everything the windows show of it.
+#[derive(Deserialize)]
+struct ExampleUnit {
+    character : DatabaseCharacter,
+    /// the built-in promotion saved to the promotion manager
+    promotion : String,
+    /// the progression as the progression builder's text editor writes it
+    progression : String
+}
+
+fn load_example(data : &mut GameData) -> Result<(), String> {
+    let example : ExampleUnit =
+        serde_json::from_str(EXAMPLE_UNIT).map_err(|error| error.to_string())?;
+    let promotion = builtin_promotions(GameKind::GbaFe, &data.promotions, None)
+        .remove(&example.promotion)
+        .ok_or_else(|| format!("{} is not a built-in promotion", example.promotion))?;
+    data.promotions.insert(example.promotion, promotion);
+    *data.progression = parse_progression(&example.progression, GameKind::GbaFe, &data.promotions)
+        .map_err(|error| error.to_string())?;
+    data.character = example.character.to_character();
+    data.plotter.show_averages();
+    Ok(())
+}
+
+/// The data of the GBA games with the example unit loaded, or the usual
+/// default data should the example not load.
+pub fn example_gamedata() -> GameData {
+    let mut data = generate_default_gamedata(GameKind::GbaFe);
+    match load_example(&mut data) {
+        Ok(()) => data,
+        Err(_reason) => generate_default_gamedata(GameKind::GbaFe)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum TourStop {
+    Window(AppWindow),
+    /// the first plotter window
+    Plotter
+}
+
+/// the stops of the tour in their order, with the key of what the tour tells
+/// there
+const TOUR : [(TourStop, &str); 5] = [
+    (
+        TourStop::Window(AppWindow::CharacterBuilder),
+        "tour.character_builder"
+    ),
+    (
+        TourStop::Window(AppWindow::ProgressionBuilder),
+        "tour.progression_builder"
+    ),
+    (
+        TourStop::Window(AppWindow::PromotionManager),
+        "tour.promotion_manager"
+    ),
+    (TourStop::Plotter, "tour.plotter"),
+    (
+        TourStop::Window(AppWindow::CharacterManager),
+        "tour.character_manager"
+    )
+];
+
+/// How far the user got through the tour, which only a first run starts.
+#[derive(Deserialize, Serialize, Default)]
+#[serde(default)]
+pub struct Tour {
+    /// the stop being shown, `None` once the tour is done or dismissed
+    stop : Option<usize>,
+    /// the stop whose window was last brought to the front
+    #[serde(skip)]
+    focused : Option<usize>
+}
+
+impl Tour {
+    pub fn start() -> Self {
+        Self {
+            stop : Some(0),
+            focused : None
+        }
+    }
+}
+
+fn window_mark(id : Id) -> Id { id.with("Tour Mark") }
+
+/// Notes where the window was drawn this frame, for the tour to point at.
+pub fn mark_window(ctx : &Context, id : Id, rect : Rect) {
+    ctx.data().insert_temp(window_mark(id), rect);
+}
+
+fn stop_id(stop : TourStop, data : &GameData) -> Id {
+    match stop {
+        TourStop::Window(window) => window.id(),
+        TourStop::Plotter => data.plotter.window_id()
+    }
+}
+
+/// Shows the window of the stop and brings it to the front.
+fn focus(
+    stop : TourStop,
+    visibility : &mut WindowVisibility,
+    data : &mut GameData,
+    ctx : &Context
+) {
+    match stop {
+        TourStop::Window(window) => visibility.show(window),
+        TourStop::Plotter => data.plotter.show_windows()
+    }
+    ctx.move_to_top(LayerId::new(Order::Middle, stop_id(stop, data)));
+}
+
+/// The card explaining the current stop, with an arrow pointing from it at
+/// the stop's window. Drawn after the windows so that it sees where they are.
+pub fn tour_window(
+    tour : &mut Tour,
+    visibility : &mut WindowVisibility,
+    data : &mut GameData,
+    ctx : &Context
+) {
+    let (index, (stop, text)) = match tour.stop.and_then(|index| Some((index, TOUR.get(index)?))) {
+        Some(stop) => stop,
+        None => {
+            tour.stop = None;
+            return;
+        }
+    };
+    if tour.focused != Some(index) {
+        focus(*stop, visibility, data, ctx);
+        tour.focused = Some(index);
+    }
+    let id = stop_id(*stop, data);
+
+    let mut next_stop = Some(index);
+    let card = egui::Window::new(tr_with(
+        "tour.title",
+        &[("stop", &(index + 1)), ("count", &TOUR.len())]
+    ))
+    .id(Id::new("Tour"))
+    .anchor(Align2::CENTER_BOTTOM, vec2(0.0, -24.0))
+    .collapsible(false)
+    .resizable(false)
+    .show(ctx, |ui| {
+        ui.set_max_width(320.0);
+        ui.label(tr(text));
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(index > 0, egui::Button::new(tr("tour.back")))
+                .clicked()
+            {
+                next_stop = Some(index - 1);
+            }
+            let last = index + 1 == TOUR.len();
+            if ui
+                .button(tr(if last { "tour.done" } else { "tour.next" }))
+                .clicked()
+            {
+                next_stop = (!last).then_some(index + 1);
+            }
+            if !last && ui.button(tr("tour.skip")).clicked() {
+                next_stop = None;
+            }
+        });
+    });
+    if next_stop != tour.stop {
+        tour.stop = next_stop;
+        ctx.request_repaint();
+        return;
+    }
+
+    // the mark is taken so that a window no longer drawn isn't pointed at
+    let target = ctx.data().get_temp::<Rect>(window_mark(id));
+    ctx.data().remove::<Rect>(window_mark(id));
+    if let (Some(card), Some(target)) = (card, target) {
+        let stroke = Stroke::new(2.0, ctx.style().visuals.warn_fg_color);
+        let painter = ctx.layer_painter(LayerId::new(Order::Foreground, Id::new("Tour Arrow")));
+        let target = target.expand(4.0);
+        let origin = card.response.rect.center_top();
+        painter.rect_stroke(target, 4.0, stroke);
+        painter.arrow(origin, target.clamp(origin) - origin, stroke);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::progression::{early_promotions, row_levels};
+
+    #[test]
+    fn the_example_unit_loads() {
+        let mut data = generate_default_gamedata(GameKind::GbaFe);
+        assert_eq!(load_example(&mut data), Ok(()));
+        assert_eq!(data.character.name, "Eliwood");
+        assert_eq!(data.progression.len(), 39);
+        assert_eq!(data.promotions.len(), 1);
+        let levels = row_levels(data.character.level, &data.progression).collect::<Vec<_>>();
+        assert_eq!(levels[18], 20);
+        assert_eq!(levels.last(), Some(&20));
+        assert!(early_promotions(data.character.level, &data.progression)
+            .iter()
+            .all(Option::is_none));
+    }
+}
@@ -1,33 +1,89 @@
 use std::{
-    collections::{BTreeMap, BTreeSet},
+    collections::{hash_map::DefaultHasher, BTreeMap, BTreeSet, VecDeque},
     fmt,
-    ops::Deref
+    hash::{Hash, Hasher},
+    panic::AssertUnwindSafe,
+    sync::Arc
 };
 
 use super::{
-    progression::{ConcreteStatChange, UsefulStatChange},
+    combat::HitRng,
+    hints::{hint, hint_text, HintKey},
+    locale::{self, tr, tr_with},
+    onboarding,
+    progression::{
+        compile_progression, early_promotions, row_levels, snapshot_caps, BranchName,
+        ConcreteStatChange, ProgressionManager, UsefulStatChange
+    },
+    session_log::log_action,
     sit::StatIndexType,
+    toasts,
+    weapon::{Opponent, Weapon},
     CompleteData, GameData, UsefulId
 };
-use cached::proc_macro::cached;
 use egui::{
     plot::{
-        uniform_grid_spacer, Bar, BarChart, BoxElem, BoxPlot, BoxSpread, GridMark, Legend, Line,
-        Plot, PlotPoint, PlotPoints
+        uniform_grid_spacer, Bar, BarChart, BoxElem, BoxPlot, BoxSpread, GridMark, HLine, Legend,
+        Line, LineStyle, Plot, PlotPoint, PlotPoints, PlotUi, Polygon
     },
-    reset_button_with, Align, Id, Layout, Slider, Ui
+    reset_button_with, Align, Color32, DragValue, Id, Layout, SelectableLabel, Slider, Ui
 };
-use fe_levels::{Character, StatType};
+use fe_levels::{ArrivalDistribution, Character, StatType};
 use itertools::Itertools;
 use poll_promise::Promise;
 use serde::{Deserialize, Serialize};
 
-#[derive(PartialEq, Default, Deserialize, Serialize)]
+use self::export::Sketch;
+#[cfg(target_arch = "wasm32")]
+pub use self::worker::run_compute_worker;
+
+mod export;
+#[cfg(target_arch = "wasm32")]
+mod worker;
+
+#[derive(PartialEq, Default, Deserialize, Serialize, Hash, Clone, Copy)]
 enum ChartKind {
     IntraLevelDist,
     InterLevelDist,
     #[default]
-    BoxPlots
+    BoxPlots,
+    Envelope,
+    ArrivalLevel,
+    OrkoChance,
+    SurvivalChance
+}
+
+/// every chart, in the order the plotter lists them
+const ALL_CHARTS : [ChartKind; 7] = [
+    ChartKind::IntraLevelDist,
+    ChartKind::InterLevelDist,
+    ChartKind::BoxPlots,
+    ChartKind::Envelope,
+    ChartKind::ArrivalLevel,
+    ChartKind::OrkoChance,
+    ChartKind::SurvivalChance
+];
+
+impl ChartKind {
+    fn explanation(&self) -> String {
+        tr(match self {
+            ChartKind::IntraLevelDist => "chart.focus_one_level_hint",
+            ChartKind::InterLevelDist => "chart.multiple_levels_hint",
+            ChartKind::BoxPlots => "chart.box_plot_hint",
+            ChartKind::Envelope => "chart.envelope_hint",
+            ChartKind::ArrivalLevel => "chart.arrival_hint",
+            ChartKind::OrkoChance => "chart.orko_hint",
+            ChartKind::SurvivalChance => "chart.survival_hint"
+        })
+    }
+}
+
+/// The names of the charts with what they show, for the help window.
+pub fn chart_help() -> Vec<(String, String)> {
+    ALL_CHARTS
+        .iter()
+        .map(|chart| (chart.to_string(), chart.explanation()))
+        .collect()
 }
 
 impl fmt::Display for ChartKind {
@@ -35,163 +91,970 @@ impl fmt::Display for ChartKind {
         write!(
             f,
             "{}",
-            match self {
-                ChartKind::IntraLevelDist => "Focus One Level",
-                ChartKind::InterLevelDist => "Show Multiple Levels",
-                ChartKind::BoxPlots => "Box Plot"
-            }
+            tr(match self {
+                ChartKind::IntraLevelDist => "chart.focus_one_level",
+                ChartKind::InterLevelDist => "chart.multiple_levels",
+                ChartKind::BoxPlots => "chart.box_plot",
+                ChartKind::Envelope => "chart.envelope",
+                ChartKind::ArrivalLevel => "chart.arrival",
+                ChartKind::OrkoChance => "chart.orko",
+                ChartKind::SurvivalChance => "chart.survival"
+            })
         )
     }
 }
 
-#[derive(PartialEq, Default, Deserialize, Serialize)]
+#[derive(PartialEq, Default, Deserialize, Serialize, Hash)]
 enum ReductionKind {
     #[default]
     AverageReduction,
     BenchmarkReduction
 }
 
-#[derive(PartialEq, Default, Deserialize, Serialize)]
+#[derive(PartialEq, Default, Deserialize, Serialize, Hash)]
 enum IntraLevelDetails {
     #[default]
     DensityData,
     CumulativeData
 }
 
+#[derive(PartialEq, Default, Deserialize, Serialize, Clone, Copy, Hash)]
+enum XAxisKind {
+    #[default]
+    ProgressionIndex,
+    InGameLevel
+}
+
+impl fmt::Display for XAxisKind {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            tr(match self {
+                XAxisKind::ProgressionIndex => "plotter.progression_index",
+                XAxisKind::InGameLevel => "plotter.in_game_level"
+            })
+        )
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 #[serde(default)] // if we add new fields, give them default values when deserializing old state
 pub struct PlotterData {
     chart_type : ChartKind,
     benchmark : StatType,
     box_range : u8,
+    envelope_range : (u8, u8),
     inspected_level : usize,
     selected_stat : StatIndexType,
     intra_level_option : IntraLevelDetails,
     reduction_option : ReductionKind,
-    window_id : UsefulId
+    x_axis : XAxisKind,
+    /// Con, Mov and the other stats that don't grow on level-ups in the stat
+    /// charts, which otherwise leave them out
+    show_constant_stats : bool,
+    /// the names of the saved enemy and weapon the combat related series are
+    /// computed for
+    enemy : String,
+    weapon : String,
+    /// the saved enemies attacking one after another in the survival chart by
+    /// how often each of them attacks
+    gauntlet : BTreeMap<String, usize>,
+    /// the name of the saved weapon whose stat buffs are added to the shown
+    /// stats, none being equipped if empty
+    equipped_weapon : String,
+    view : PlotView,
+    window_id : UsefulId,
+    /// hidden through the windows menu rather than closed
+    hidden : bool
 }
 
+/// the lower and upper percentile shown in the envelope chart
+const DEFAULT_ENVELOPE_RANGE : (u8, u8) = (10, 90);
+
 impl Default for PlotterData {
     fn default() -> Self {
         Self {
             chart_type : Default::default(),
             benchmark : Default::default(),
             box_range : 50,
+            envelope_range : DEFAULT_ENVELOPE_RANGE,
             inspected_level : Default::default(),
             selected_stat : StatIndexType::arbitrary_valid(Default::default()),
             intra_level_option : Default::default(),
             reduction_option : Default::default(),
-            window_id : Default::default()
+            x_axis : Default::default(),
+            show_constant_stats : false,
+            enemy : Default::default(),
+            weapon : Default::default(),
+            gauntlet : Default::default(),
+            equipped_weapon : Default::default(),
+            view : Default::default(),
+            window_id : Default::default(),
+            hidden : false
         }
     }
 }
 
 impl PlotterData {
     pub fn id(&self) -> Id { Id::new(self.window_id) }
+
+    /// Identifies what the chart currently shows, so that a stored view is
+    /// only restored onto the same kind of data.
+    fn view_signature(&self, context : &GameData) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.chart_type.hash(&mut hasher);
+        self.intra_level_option.hash(&mut hasher);
+        self.reduction_option.hash(&mut hasher);
+        self.selected_stat.hash(&mut hasher);
+        self.x_axis.hash(&mut hasher);
+        self.equipped_weapon.hash(&mut hasher);
+        context.progression.len().hash(&mut hasher);
+        context.character.name.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy)]
+struct ViewBounds {
+    min : [f64; 2],
+    max : [f64; 2]
+}
+
+impl ViewBounds {
+    /// The view every chart starts out with, showing everything from the
+    /// origin up to the given corner.
+    fn up_to(x : f64, y : f64) -> Self {
+        Self {
+            min : [-0.2, -0.5],
+            max : [x, y]
+        }
+    }
+
+    fn approx_eq(&self, other : &ViewBounds) -> bool {
+        self.min
+            .iter()
+            .chain(self.max.iter())
+            .zip(other.min.iter().chain(other.max.iter()))
+            .all(|(lhs, rhs)| (lhs - rhs).abs() < 1e-6)
+    }
+}
+
+/// The zoom and pan state of a chart, kept across sessions for as long as the
+/// chart keeps showing the same kind of data.
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default)]
+struct PlotView {
+    signature : u64,
+    bounds : Option<ViewBounds>,
+    #[serde(skip)]
+    reset : bool,
+    /// the view shown last frame, stored or not
+    #[serde(skip)]
+    shown : Option<ViewBounds>
+}
+
+impl PlotView {
+    fn update(&mut self, signature : u64) {
+        if self.signature != signature {
+            self.signature = signature;
+            self.clear();
+        }
+    }
+
+    fn clear(&mut self) {
+        self.bounds = None;
+        self.reset = true;
+    }
+
+    /// Shows the plot with the stored view if there is one, remembering the
+    /// view the user leaves it in. Views matching the default are not stored,
+    /// so that untouched charts keep fitting their data.
+    fn show(
+        &mut self,
+        plot : Plot,
+        default : ViewBounds,
+        ui : &mut Ui,
+        build_fn : impl FnOnce(&mut PlotUi)
+    ) {
+        let view = self.bounds.unwrap_or(default);
+        let mut plot = plot
+            .include_x(view.min[0])
+            .include_x(view.max[0])
+            .include_y(view.min[1])
+            .include_y(view.max[1]);
+        if std::mem::take(&mut self.reset) {
+            plot = plot.reset();
+        }
+        let bounds = plot
+            .show(ui, |plot_ui| {
+                build_fn(plot_ui);
+                plot_ui.plot_bounds()
+            })
+            .inner;
+        self.shown = bounds.is_valid().then(|| ViewBounds {
+            min : bounds.min(),
+            max : bounds.max()
+        });
+        self.bounds = self.shown.filter(|bounds| !bounds.approx_eq(&default));
+    }
+}
+
+/// Horizontal placement and tick labels of the data points, the base
+/// character being the first point followed by one point per progression
+/// entry. Promotions taken below their required level are starred so that
+/// screenshots show it too.
+#[derive(Clone)]
+struct XAxis {
+    kind : XAxisKind,
+    points : Vec<(f64, String)>
+}
+
+impl XAxis {
+    fn new(
+        kind : XAxisKind,
+        character : &Character<StatIndexType>,
+        progression : &[ConcreteStatChange],
+        ignore_promotion_levels : bool
+    ) -> Self {
+        let name = &character.name;
+        let base_level = character.level;
+        let mut points : Vec<(f64, String)> = match kind {
+            XAxisKind::ProgressionIndex => {
+                let base_label = tr_with("plotter.base", &[("name", name)]);
+                std::iter::once((1.0, base_label))
+                    .chain(progression.iter().enumerate().map(|(index, sc)| {
+                        (
+                            (index + 2) as f64,
+                            tr_with("plotter.after", &[("entry", sc)])
+                        )
+                    }))
+                    .collect()
+            },
+            XAxisKind::InGameLevel => {
+                // promotions reset the level, so every tier continues where the previous
+                // one ended to keep the axis monotonic
+                let mut tier_offset = 0;
+                let mut previous_level = base_level;
+                let base_label = tr_with(
+                    "plotter.base_level",
+                    &[("name", name), ("level", &base_level)]
+                );
+                let unspread = std::iter::once((base_level, base_label))
+                    .chain(
+                        progression
+                            .iter()
+                            .zip(row_levels(base_level, progression))
+                            .map(|(sc, level)| {
+                                if sc.resets_level_counter() {
+                                    tier_offset += previous_level;
+                                }
+                                previous_level = level;
+                                let label = if sc.resets_level_counter() {
+                                    tr_with("plotter.level_promoted", &[("level", &level)])
+                                }
+                                else if sc.increases_level_counter() {
+                                    tr_with("plotter.level", &[("level", &level)])
+                                }
+                                else {
+                                    tr_with(
+                                        "plotter.level_entry",
+                                        &[("level", &level), ("entry", sc)]
+                                    )
+                                };
+                                (tier_offset + level, label)
+                            })
+                    )
+                    .collect_vec();
+
+                // entries that don't change the level (e.g. boosters) share a position, so
+                // spread them out a little
+                let mut occurences = BTreeMap::new();
+                for (position, _label) in unspread.iter() {
+                    *occurences.entry(*position).or_insert(0usize) += 1;
+                }
+                let mut seen = BTreeMap::new();
+                unspread
+                    .into_iter()
+                    .map(|(position, label)| {
+                        let index = seen.entry(position).or_insert(0usize);
+                        let offset = *index as f64 * 0.5 / occurences[&position] as f64;
+                        *index += 1;
+                        (position as f64 + offset, label)
+                    })
+                    .collect()
+            }
+        };
+        if !ignore_promotion_levels {
+            let early = early_promotions(base_level, progression);
+            for ((_position, label), early) in points.iter_mut().skip(1).zip(early) {
+                if early.is_some() {
+                    label.push('*');
+                }
+            }
+        }
+        Self { kind, points }
+    }
+
+    fn position(&self, snapshot : usize) -> f64 {
+        self.points
+            .get(snapshot)
+            .map(|(position, _label)| *position)
+            .unwrap_or((snapshot + 1) as f64)
+    }
+
+    fn label(&self, value : f64) -> String {
+        self.points
+            .iter()
+            .find(|(position, _label)| (position - value).abs() < 1e-6)
+            .map(|(_position, label)| label.clone())
+            .unwrap_or_default()
+    }
+
+    fn last_position(&self) -> f64 {
+        self.points
+            .last()
+            .map(|(position, _label)| *position)
+            .unwrap_or(1.0)
+    }
+
+    /// level based positions are meaningless without their labels, index based
+    /// ones keep the plain numbering
+    fn label_plot(&self, plot : Plot) -> Plot {
+        match self.kind {
+            XAxisKind::ProgressionIndex => plot,
+            XAxisKind::InGameLevel => {
+                let axis = self.clone();
+                plot.x_axis_formatter(move |value, _visible_range| axis.label(value))
+            }
+        }
+    }
+
+    /// Labels the exported chart as [`Self::label_plot`] labels the plot.
+    fn label_sketch(&self, sketch : &mut Sketch) {
+        if self.kind == XAxisKind::InGameLevel {
+            sketch.label_x(&self.points);
+        }
+    }
+
+    /// width of bars and boxes such that neighboring points don't overlap
+    fn element_width(&self) -> f64 {
+        self.points
+            .iter()
+            .tuple_windows()
+            .map(|((a, _), (b, _))| b - a)
+            .fold(1.0, f64::min)
+            * 0.8
+    }
+}
+
+/// number of samples drawn when a progression can't be analyzed exactly
+const SIMULATION_SAMPLES : u64 = 1 << 20;
+
+/// rough seconds a browser spends simulating one sample of a plain level-up
+#[cfg(target_arch = "wasm32")]
+const BROWSER_SECONDS_PER_SAMPLE : f64 = 2e-6;
+
+/// how many computed progressions are kept around so that reverting an edit
+/// doesn't trigger a recomputation
+const DERIVED_DATA_CACHE_SIZE : usize = 32;
+
+/// the storage key of the data last computed for the current game
+const PERSISTED_DATA_KEY : &str = "plotter_data";
+/// the most bytes of computed data saved, as browsers only grant the app a few
+/// megabytes of storage in total
+const MAX_PERSISTED_BYTES : usize = 2_000_000;
+
+/// how the displayed data was obtained
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash, Serialize, Deserialize)]
+enum ComputationKind {
+    Exact,
+    Simulated { samples : u64 }
+}
+
+impl fmt::Display for ComputationKind {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComputationKind::Exact => write!(f, "{}", tr("plotter.exact")),
+            ComputationKind::Simulated { samples } => {
+                write!(
+                    f,
+                    "{}",
+                    tr_with("plotter.simulated", &[("samples", samples)])
+                )
+            }
+        }
+    }
+}
+
+/// result of [`compute`] together with how it was obtained
+#[derive(Serialize, Deserialize)]
+pub struct ComputedData {
+    data : CompleteData,
+    /// the data of the branch not being edited, if the progression is forked
+    other_branch : Option<CompleteData>,
+    kind : ComputationKind,
+    /// how long the computation took
+    #[serde(default)]
+    seconds : f64
+}
+
+impl ComputedData {
+    /// no data at all, which the plotter windows show as not computable
+    fn failed() -> Self {
+        ComputedData {
+            data : Vec::new(),
+            other_branch : None,
+            kind : ComputationKind::Exact,
+            seconds : 0.0
+        }
+    }
+
+    /// A rough estimate of the bytes the distributions take up, counting
+    /// every value with its probability.
+    fn footprint(&self) -> usize {
+        let values = std::iter::once(&self.data)
+            .chain(self.other_branch.iter())
+            .flatten()
+            .flat_map(BTreeMap::values)
+            .map(BTreeMap::len)
+            .sum::<usize>();
+        values * std::mem::size_of::<(StatType, f64)>()
+    }
+}
+
+/// Times the computations, with the performance clock in the browser where
+/// [`std::time::Instant`] isn't available.
+#[cfg(not(target_arch = "wasm32"))]
+struct Stopwatch(std::time::Instant);
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Stopwatch {
+    fn start() -> Self { Self(std::time::Instant::now()) }
+
+    fn seconds(&self) -> f64 { self.0.elapsed().as_secs_f64() }
+}
+
+#[cfg(target_arch = "wasm32")]
+struct Stopwatch(f64);
+
+#[cfg(target_arch = "wasm32")]
+impl Stopwatch {
+    /// milliseconds by the clock of the page or the worker, whichever runs
+    /// the computation
+    fn now() -> f64 {
+        js_sys::Reflect::get(&js_sys::global(), &"performance".into())
+            .ok()
+            .and_then(|performance| {
+                wasm_bindgen::JsCast::dyn_into::<web_sys::Performance>(performance).ok()
+            })
+            .map_or_else(js_sys::Date::now, |performance| performance.now())
+    }
+
+    fn start() -> Self { Self(Self::now()) }
+
+    fn seconds(&self) -> f64 { (Self::now() - self.0) / 1000.0 }
+}
+
+type DerivedData = Promise<(u64, Arc<ComputedData>)>;
+
+/// Rough seconds the browser needs to simulate the progression.
+#[cfg(target_arch = "wasm32")]
+fn estimated_browser_seconds<'a>(
+    progression : impl Iterator<Item = &'a ConcreteStatChange>,
+    num_samples : u64
+) -> f64 {
+    progression
+        .map(ConcreteStatChange::simulation_cost)
+        .sum::<f64>()
+        * num_samples as f64
+        * BROWSER_SECONDS_PER_SAMPLE
+}
+
+/// Tells which entries can only be simulated and how long the worker will
+/// take for them, leaving the page usable in the meantime.
+#[cfg(target_arch = "wasm32")]
+fn warn_of_slow_simulation(
+    ctx : &egui::Context,
+    progression : &[ConcreteStatChange],
+    other_branch : Option<&[ConcreteStatChange]>,
+    num_samples : u64
+) {
+    let entries = progression.iter().chain(other_branch.into_iter().flatten());
+    let estimate = estimated_browser_seconds(entries.clone(), num_samples);
+    let slow = entries
+        .filter(|sc| !sc.cheap_to_execute())
+        .map(ToString::to_string)
+        .unique()
+        .join(", ");
+    let estimate = format!("{estimate:.0}");
+    toasts::info(
+        ctx,
+        tr_with(
+            "plotter.slow_simulation",
+            &[("entries", &slow), ("seconds", &estimate)]
+        )
+    );
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Default)]
+enum DataOrigin {
+    #[default]
+    Recomputed,
+    Cached,
+    /// saved when the app was last closed
+    Restored
+}
+
+impl fmt::Display for DataOrigin {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            tr(match self {
+                DataOrigin::Recomputed => "plotter.recomputed",
+                DataOrigin::Cached => "plotter.cached",
+                DataOrigin::Restored => "plotter.restored"
+            })
+        )
+    }
+}
+
+/// Least-recently-used store of computed data, keyed by
+/// [`derived_data_key`].
+#[derive(Default)]
+struct DerivedDataCache {
+    entries : VecDeque<(u64, Arc<ComputedData>)>
+}
+
+impl DerivedDataCache {
+    fn get(&mut self, key : u64) -> Option<Arc<ComputedData>> {
+        let position = self.entries.iter().position(|(k, _data)| *k == key)?;
+        let entry = self.entries.remove(position)?;
+        let data = entry.1.clone();
+        self.entries.push_front(entry);
+        Some(data)
+    }
+
+    fn insert(&mut self, key : u64, data : Arc<ComputedData>) {
+        if self
+            .entries
+            .front()
+            .map(|(k, _data)| *k == key)
+            .unwrap_or(false)
+        {
+            return;
+        }
+        self.entries.retain(|(k, _data)| *k != key);
+        self.entries.push_front((key, data));
+        self.entries.truncate(DERIVED_DATA_CACHE_SIZE);
+    }
+
+    fn clear(&mut self) { self.entries.clear(); }
+
+    fn len(&self) -> usize { self.entries.len() }
 }
 
 #[derive(Deserialize, Serialize, Default)]
 pub struct PlotterManager {
     #[serde(skip)]
-    derived_data : Option<
-        Promise<(
-            Vec<ConcreteStatChange>,
-            Character<StatIndexType>,
-            CompleteData
-        )>
-    >,
+    derived_data : Option<DerivedData>,
+    #[serde(skip)]
+    cache : DerivedDataCache,
+    #[serde(skip)]
+    data_origin : DataOrigin,
+    #[cfg(target_arch = "wasm32")]
+    #[serde(skip)]
+    worker : Option<worker::WorkerComputation>,
+    /// whether a computation was started whose end isn't logged yet
+    #[serde(skip)]
+    computing : bool,
     plotter_windows : Vec<PlotterData>
 }
 
+impl PlotterManager {
+    /// Writes the computed data to the storage as ron text, tagged with the
+    /// app version since the keys and the computations may change with it.
+    /// Data larger than [`MAX_PERSISTED_BYTES`] isn't kept. `persisted_key` is
+    /// the key of the data last written, which isn't written again.
+    pub fn persist(&self, persisted_key : &mut Option<u64>, storage : &mut dyn eframe::Storage) {
+        let (key, data) = match self.derived_data.as_ref().and_then(Promise::ready) {
+            Some((key, data)) => (*key, data),
+            None => return
+        };
+        if *persisted_key == Some(key) {
+            return;
+        }
+        let text = ron::to_string(&(env!("CARGO_PKG_VERSION"), key, &**data))
+            .ok()
+            .filter(|text| text.len() <= MAX_PERSISTED_BYTES)
+            .unwrap_or_default();
+        storage.set_string(PERSISTED_DATA_KEY, text);
+        *persisted_key = Some(key);
+    }
+
+    /// Reads the data written by the last session. Data of an unchanged
+    /// progression is shown right away, while a changed one is recomputed as
+    /// usual. Returns the key of the restored data.
+    pub fn restore(&mut self, storage : &dyn eframe::Storage) -> Option<u64> {
+        let restored = storage
+            .get_string(PERSISTED_DATA_KEY)
+            .and_then(|text| ron::from_str::<(String, u64, ComputedData)>(&text).ok())
+            .filter(|(version, _key, _data)| version == env!("CARGO_PKG_VERSION"));
+        let (_version, key, data) = restored?;
+        self.derived_data = Some(Promise::from_ready((key, Arc::new(data))));
+        self.data_origin = DataOrigin::Restored;
+        Some(key)
+    }
+
+    /// The average of every stat after each number of progression entries.
+    /// Returns `None` unless the data of the current progression has finished
+    /// computing.
+    pub fn snapshot_averages(
+        &self,
+        character : &Character<StatIndexType>,
+        progression : &ProgressionManager
+    ) -> Option<Vec<BTreeMap<StatIndexType, f64>>> {
+        let (key, _num_samples) = current_data_key(character, progression);
+        let (computed_key, actual_data) = self.derived_data.as_ref()?.ready()?;
+        if *computed_key != key {
+            return None;
+        }
+        Some(
+            actual_data
+                .data
+                .iter()
+                .map(|stats| {
+                    stats
+                        .iter()
+                        .map(|(stat, distribution)| (*stat, expected_value(distribution)))
+                        .collect()
+                })
+                .collect()
+        )
+    }
+
+    /// The stat values after the given number of progression entries, either
+    /// the rounded expected values or the given percentile. Returns `None`
+    /// unless the data of the current progression has finished computing.
+    pub fn snapshot_values(
+        &self,
+        character : &Character<StatIndexType>,
+        progression : &ProgressionManager,
+        snapshot : usize,
+        percentile : Option<u8>
+    ) -> Option<BTreeMap<StatIndexType, StatType>> {
+        let (key, _num_samples) = current_data_key(character, progression);
+        let (computed_key, actual_data) = self.derived_data.as_ref()?.ready()?;
+        if *computed_key != key {
+            return None;
+        }
+        actual_data
+            .data
+            .get(snapshot)?
+            .iter()
+            .map(|(stat, distribution)| {
+                let value = match percentile {
+                    Some(percentile) => find_percentile(distribution, percentile as f64 / 100.0)?,
+                    None => expected_value(distribution).round()
+                };
+                Some((*stat, value as StatType))
+            })
+            .collect()
+    }
+
+    /// The average of every stat at the end of the progression, `None` while
+    /// the data of the current progression is still being computed.
+    pub fn final_averages(
+        &self,
+        character : &Character<StatIndexType>,
+        progression : &ProgressionManager
+    ) -> Option<BTreeMap<StatIndexType, f64>> {
+        let (key, _num_samples) = current_data_key(character, progression);
+        let (computed_key, actual_data) = self.derived_data.as_ref()?.ready()?;
+        if *computed_key != key {
+            return None;
+        }
+        Some(
+            actual_data
+                .data
+                .last()?
+                .iter()
+                .map(|(stat, distribution)| (*stat, expected_value(distribution)))
+                .collect()
+        )
+    }
+
+    /// The id of the first plotter window, the one the dashboard focuses.
+    pub fn window_id(&self) -> Id {
+        self.plotter_windows
+            .first()
+            .map_or_else(|| Id::new("Data Plotter"), PlotterData::id)
+    }
+
+    /// Checkboxes showing and hiding each plotter window and a button opening
+    /// another one.
+    pub fn windows_menu(&mut self, ui : &mut Ui) {
+        for (index, window) in self.plotter_windows.iter_mut().enumerate() {
+            let mut shown = !window.hidden;
+            let title = tr_with("plotter.numbered", &[("number", &(index + 1))]);
+            if ui.checkbox(&mut shown, title).changed() {
+                window.hidden = !shown;
+            }
+        }
+        if ui.button(tr("plotter.new")).clicked() {
+            self.plotter_windows.push(Default::default());
+            ui.close_menu();
+        }
+    }
+
+    /// Shows the averages of every stat in the first plotter window.
+    pub fn show_averages(&mut self) {
+        if self.plotter_windows.is_empty() {
+            self.plotter_windows.push(Default::default());
+        }
+        let window = &mut self.plotter_windows[0];
+        window.chart_type = ChartKind::InterLevelDist;
+        window.reduction_option = ReductionKind::AverageReduction;
+        window.hidden = false;
+    }
+
+    /// the ids of every plotter window, the one shown while computing included
+    pub fn window_ids(&self) -> Vec<Id> {
+        self.plotter_windows
+            .iter()
+            .map(PlotterData::id)
+            .chain([Id::new("Data Plotter")])
+            .collect()
+    }
+
+    pub fn show_windows(&mut self) {
+        self.plotter_windows
+            .iter_mut()
+            .for_each(|window| window.hidden = false);
+    }
+
+    /// the stat shown by the first plotter window
+    pub fn focused_stat(&self) -> Option<StatIndexType> {
+        self.plotter_windows
+            .first()
+            .map(|window| window.selected_stat)
+    }
+}
+
 pub fn actual_data_display(
     context : &GameData,
     data : &mut PlotterData,
     ui : &mut Ui,
     actual_data : &CompleteData,
+    other_branch : Option<&CompleteData>,
     new_window : &mut Option<PlotterData>
 ) {
+    // the library yields nothing for level-ups it can neither analyze nor
+    // simulate yet, e.g. FE10's
+    if actual_data.is_empty() {
+        ui.label(tr("plotter.not_computable"));
+        return;
+    }
     if let Some(first) = actual_data.first() {
         if first.get(&data.selected_stat).is_none() {
             data.selected_stat = *first.iter().next().unwrap().0;
         }
     }
     data.inspected_level = data.inspected_level.clamp(1, actual_data.len());
+    // the combat charts apply the buffs of the weapon they are computed for
+    // themselves, so they keep the unbuffed stats
+    let raw_data = actual_data;
+    let raw_other_branch = other_branch;
+    let buffs = context
+        .weapons
+        .get(&data.equipped_weapon)
+        .map(|weapon| weapon.stat_buffs())
+        .unwrap_or_default();
+    let buffed_data;
+    let buffed_other_branch;
+    let (actual_data, other_branch) = if buffs.is_empty() {
+        (actual_data, other_branch)
+    }
+    else {
+        let exceed_caps = context.game_option.weapon_buffs_exceed_caps();
+        let caps = snapshot_caps(&context.character, &context.progression);
+        buffed_data = apply_buffs(actual_data, &caps, &buffs, exceed_caps);
+        buffed_other_branch = other_branch.zip(context.progression.other_branch()).map(
+            |(other_data, progression)| {
+                let caps = snapshot_caps(&context.character, &progression);
+                apply_buffs(other_data, &caps, &buffs, exceed_caps)
+            }
+        );
+        (&buffed_data, buffed_other_branch.as_ref())
+    };
+    let equipped_weapon = data.equipped_weapon.clone();
+    let buff_note = |sit : StatIndexType| {
+        buffs
+            .get(&sit)
+            .map(|buff| {
+                tr_with(
+                    "plotter.buff",
+                    &[("buff", buff), ("weapon", &equipped_weapon)]
+                )
+            })
+            .unwrap_or_default()
+    };
+    let ignore_promotion_levels = context.ignore_promotion_levels;
+    let axis = XAxis::new(
+        data.x_axis,
+        &context.character,
+        &context.progression,
+        ignore_promotion_levels
+    );
+    let editing_branch = context.progression.editing_branch();
+    let branch_suffix =
+        |name : Option<BranchName>| name.map(|name| format!(" ({name})")).unwrap_or_default();
+    let own_suffix = branch_suffix(editing_branch);
+    let other_suffix = branch_suffix(editing_branch.map(BranchName::other));
+    // the other branch may have a different number of entries, so it gets its own
+    // axis
+    let other_branch =
+        other_branch
+            .zip(context.progression.other_branch())
+            .map(|(other_data, progression)| {
+                let other_axis = XAxis::new(
+                    data.x_axis,
+                    &context.character,
+                    &progression,
+                    ignore_promotion_levels
+                );
+                (other_data, other_axis)
+            });
+    let last_position = other_branch
+        .as_ref()
+        .map_or(axis.last_position(), |(_data, other_axis)| {
+            other_axis.last_position().max(axis.last_position())
+        });
+    let signature = data.view_signature(context);
+    data.view.update(signature);
 
+    let mut exporting = false;
     ui.horizontal_top(|ui| {
-        egui::containers::ComboBox::from_label("Data to Display")
+        egui::containers::ComboBox::from_label(tr("plotter.chart"))
             .selected_text(data.chart_type.to_string())
             .show_ui(ui, |ui| {
-                ui.selectable_value(
-                    &mut data.chart_type,
-                    ChartKind::IntraLevelDist,
-                    ChartKind::IntraLevelDist.to_string()
-                );
-                ui.selectable_value(
-                    &mut data.chart_type,
-                    ChartKind::InterLevelDist,
-                    ChartKind::InterLevelDist.to_string()
-                );
-                ui.selectable_value(
-                    &mut data.chart_type,
-                    ChartKind::BoxPlots,
-                    ChartKind::BoxPlots.to_string()
-                );
+                for chart in ALL_CHARTS {
+                    ui.selectable_value(&mut data.chart_type, chart, chart.to_string())
+                        .on_hover_text(chart.explanation());
+                }
             });
         match data.chart_type {
             ChartKind::IntraLevelDist => {
                 ui.radio_value(
                     &mut data.intra_level_option,
                     IntraLevelDetails::DensityData,
-                    "Chance to hit the stat exactly"
+                    tr("plotter.density")
                 );
                 ui.radio_value(
                     &mut data.intra_level_option,
                     IntraLevelDetails::CumulativeData,
-                    "Chance to hit at least the stat"
+                    tr("plotter.cumulative")
                 );
             },
             ChartKind::InterLevelDist => {
                 ui.radio_value(
                     &mut data.reduction_option,
                     ReductionKind::AverageReduction,
-                    "Average Stat"
+                    tr("plotter.average")
                 );
+                hint(ui, HintKey::AverageStat);
                 ui.radio_value(
                     &mut data.reduction_option,
                     ReductionKind::BenchmarkReduction,
-                    "% to hit Benchmark"
+                    tr("plotter.benchmark")
                 );
+                hint(ui, HintKey::BenchmarkChance);
+                if data.reduction_option == ReductionKind::AverageReduction {
+                    combat_pickers(context, data, ui);
+                }
             },
+            ChartKind::OrkoChance => combat_pickers(context, data, ui),
+            ChartKind::SurvivalChance => gauntlet_pickers(context, data, ui),
             _ => {}
         };
+        if data.chart_type != ChartKind::IntraLevelDist {
+            egui::containers::ComboBox::from_label(tr("plotter.x_axis"))
+                .selected_text(data.x_axis.to_string())
+                .show_ui(ui, |ui| {
+                    for kind in [XAxisKind::ProgressionIndex, XAxisKind::InGameLevel] {
+                        ui.selectable_value(&mut data.x_axis, kind, kind.to_string());
+                    }
+                });
+        }
+        let picks_weapon = matches!(
+            data.chart_type,
+            ChartKind::OrkoChance | ChartKind::SurvivalChance
+        );
+        let has_constant_stats = context.character.stats.keys().any(|sit| !sit.is_growable());
+        if !picks_weapon && has_constant_stats {
+            ui.checkbox(&mut data.show_constant_stats, tr("plotter.constant_stats"))
+                .on_hover_text(tr("plotter.constant_stats_hint"));
+        }
+        if !picks_weapon && !context.weapons.is_empty() {
+            egui::containers::ComboBox::from_label(tr("plotter.equipped_weapon"))
+                .selected_text(data.equipped_weapon.as_str())
+                .show_ui(ui, |ui| {
+                    let none = tr("plotter.none");
+                    ui.selectable_value(&mut data.equipped_weapon, String::new(), none);
+                    for (name, weapon) in context.weapons.iter() {
+                        weapon_option(context, &mut data.equipped_weapon, name, weapon, ui);
+                    }
+                });
+        }
         ui.with_layout(Layout::right_to_left(Align::Min), |ui| {
-            if ui.button("Add Plotter").clicked() {
+            if ui.button(tr("plotter.add")).clicked() {
                 *new_window = Some(Default::default());
             }
+            if ui.button(tr("plotter.reset_view")).clicked() {
+                data.view.clear();
+            }
+            if ui
+                .button(tr("plotter.export_image"))
+                .on_hover_text(tr("plotter.export_image_hint"))
+                .clicked()
+            {
+                exporting = true;
+            }
         });
     });
-    if !matches!(
+    let picks_stat = !matches!(
         (&data.reduction_option, &data.chart_type),
         (&ReductionKind::AverageReduction, &ChartKind::InterLevelDist)
-    ) {
+            | (_, &ChartKind::OrkoChance)
+            | (_, &ChartKind::SurvivalChance)
+    );
+    let picks_benchmark = data.chart_type == ChartKind::ArrivalLevel
+        || (data.chart_type == ChartKind::InterLevelDist
+            && data.reduction_option == ReductionKind::BenchmarkReduction);
+    let show_constant_stats = data.show_constant_stats;
+    if picks_stat {
         ui.horizontal(|ui| {
-            egui::containers::ComboBox::from_label("Stat to Display")
+            egui::containers::ComboBox::from_label(tr("plotter.stat"))
                 .selected_text(format!("{}", data.selected_stat))
                 .show_ui(ui, |ui| {
                     context
                         .character
                         .stats
                         .iter()
-                        .sorted_by_key(|(key, _value)| **key)
+                        .filter(|(key, _value)| charted(**key, show_constant_stats))
+                        .sorted_by_key(|(key, _value)| key.display_order())
                         .for_each(|(key, _stat)| {
-                            ui.selectable_value(&mut data.selected_stat, *key, key.to_string());
+                            let option =
+                                ui.selectable_value(&mut data.selected_stat, *key, key.to_string());
+                            if let Some(name) = locale::stat_name(*key) {
+                                option.on_hover_text(name);
+                            }
                         });
                 });
 
             match data.chart_type {
-                ChartKind::InterLevelDist
-                    if matches!(data.reduction_option, ReductionKind::BenchmarkReduction) =>
-                {
+                _ if picks_benchmark => {
                     ui.add(
                         egui::Slider::new(
                             &mut data.benchmark,
@@ -200,25 +1063,29 @@ pub fn actual_data_display(
                                 .unwrap()
                                 .get(&data.selected_stat)
                                 .unwrap()
-                                .iter()
-                                .map(|(stat, _prob)| *stat)
+                                .keys()
+                                .copied()
                                 .max()
                                 .unwrap()
                         )
-                        .text("Stat Benchmark to hit")
+                        .text(tr("plotter.benchmark_slider"))
                     );
                 },
                 ChartKind::BoxPlots => {
-                    ui.add(
-                        Slider::new(&mut data.box_range, 0..=100)
-                            .text("Range of stats to be included in the boxes")
-                    );
+                    ui.add(Slider::new(&mut data.box_range, 0..=100).text(tr("plotter.box_range")));
+                    hint(ui, HintKey::BoxRange);
                     reset_button_with(ui, &mut data.box_range, 50);
                 },
+                ChartKind::Envelope => {
+                    let (low, high) = &mut data.envelope_range;
+                    ui.add(Slider::new(low, 0..=50).text(tr("plotter.worst_case")));
+                    ui.add(Slider::new(high, 50..=100).text(tr("plotter.best_case")));
+                    reset_button_with(ui, &mut data.envelope_range, DEFAULT_ENVELOPE_RANGE);
+                },
                 ChartKind::IntraLevelDist => {
                     ui.add(
                         Slider::new(&mut data.inspected_level, 1..=actual_data.len())
-                            .text("Level to focus on")
+                            .text(tr("plotter.focus_level"))
                     );
                 },
                 _ => {}
@@ -226,6 +1093,17 @@ pub fn actual_data_display(
         });
     }
 
+    let mut title = format!("{} – {}", context.character.name, data.chart_type);
+    if picks_stat {
+        title.push_str(&format!(" – {}", data.selected_stat));
+    }
+    if data.chart_type == ChartKind::IntraLevelDist {
+        let entry = tr_with("plotter.export_entry", &[("entry", &data.inspected_level)]);
+        title.push_str(&format!(" – {entry}"));
+    }
+    let mut sketch = Sketch::new(exporting, title);
+
+    let selected_note = buff_note(data.selected_stat);
     match data.chart_type {
         ChartKind::IntraLevelDist
             if matches!(data.intra_level_option, IntraLevelDetails::DensityData) =>
@@ -236,24 +1114,21 @@ pub fn actual_data_display(
             let bars = selected_data_range
                 .iter()
                 .map(|(points, prob)| Bar::new(*points as f64, *prob * 100.0))
-                .collect();
-            let max = selected_data_range
-                .iter()
-                .map(|(value, _p)| value)
-                .max()
-                .unwrap();
+                .collect_vec();
+            let max = selected_data_range.keys().max().unwrap();
+            let formatter =
+                stat_probability_formatter(data.selected_stat, data.inspected_level, "=");
+            let name = format!("{}{selected_note}", tr("plotter.density_series"));
+            sketch.bars(&name, &bars);
 
-            Plot::new("Exact Plot")
-                .legend(Legend::default())
-                .include_x(-0.2)
-                .include_x(*max as f64 + 0.5)
-                .include_y(-0.5)
-                .include_y(110.0)
-                .show(ui, |ui| {
-                    ui.bar_chart(
-                        BarChart::new(bars).name("Probability in % to hit the stat exactly")
-                    );
-                });
+            data.view.show(
+                Plot::new("Exact Plot").legend(Legend::default()),
+                ViewBounds::up_to(*max as f64 + 0.5, 110.0),
+                ui,
+                |ui| {
+                    ui.bar_chart(BarChart::new(bars).name(name).element_formatter(formatter));
+                }
+            );
         },
         ChartKind::IntraLevelDist
             if matches!(data.intra_level_option, IntraLevelDetails::CumulativeData) =>
@@ -261,6 +1136,9 @@ pub fn actual_data_display(
             let selected_data_range = &actual_data[data.inspected_level - 1]
                 .get(&data.selected_stat)
                 .unwrap();
+            let formatter =
+                stat_probability_formatter(data.selected_stat, data.inspected_level, "≥");
+            let view = &mut data.view;
             let data = selected_data_range
                 .iter()
                 .rev()
@@ -269,92 +1147,118 @@ pub fn actual_data_display(
                     Some((*points, *acc))
                 })
                 .chain(
-                    (0..*selected_data_range
-                        .iter()
-                        .map(|(stat, _prob)| stat)
-                        .min()
-                        .unwrap())
+                    (0..*selected_data_range.keys().min().unwrap())
                         .map(|guaranteed| (guaranteed, 1.0))
                 )
                 .map(|(points, prob)| Bar::new(points as f64, prob * 100.0))
-                .collect();
-            let max = selected_data_range
-                .iter()
-                .map(|(value, _p)| value)
-                .max()
-                .unwrap();
+                .collect_vec();
+            let max = selected_data_range.keys().max().unwrap();
+            let name = format!("{}{selected_note}", tr("plotter.cumulative_series"));
+            sketch.bars(&name, &data);
 
-            Plot::new("Cumulative Plot")
-                .legend(Legend::default())
-                .include_x(-0.2)
-                .include_x(*max as f64 + 0.5)
-                .include_y(-0.5)
-                .include_y(110.0)
-                .show(ui, |ui| {
-                    ui.bar_chart(
-                        BarChart::new(data).name("Probability in % to hit at least the stat")
-                    )
-                });
+            view.show(
+                Plot::new("Cumulative Plot").legend(Legend::default()),
+                ViewBounds::up_to(*max as f64 + 0.5, 110.0),
+                ui,
+                |ui| ui.bar_chart(BarChart::new(data).name(name).element_formatter(formatter))
+            );
         },
         ChartKind::InterLevelDist
             if matches!(data.reduction_option, ReductionKind::AverageReduction) =>
         {
-            let data = actual_data
-                .iter()
-                .map(|stats| {
-                    stats
-                        .iter()
-                        .map(|(name, map)| {
-                            (
-                                name,
-                                map.iter()
-                                    .fold(0.0, |acc, (points, prob)| acc + *points as f64 * *prob)
-                            )
-                        })
-                        .collect::<BTreeMap<_, _>>()
-                })
-                .collect::<Vec<_>>();
-            let data = StatIndexType::new(context.game_option)
-                .into_iter()
-                .map(|stat_type| {
-                    (
-                        stat_type,
-                        data.iter()
-                            .map(|stats| *stats.get(&stat_type).unwrap())
-                            .enumerate()
-                            .map(|(level, average)| PlotPoint::new((level + 1) as f64, average))
-                            .collect::<Vec<_>>()
-                    )
-                })
-                .collect::<BTreeMap<_, _>>();
+            let view = &mut data.view;
+            let averages = |complete_data : &CompleteData, axis : &XAxis| {
+                StatIndexType::new(context.game_option)
+                    .into_iter()
+                    .filter(|stat_type| charted(*stat_type, show_constant_stats))
+                    .map(|stat_type| {
+                        (
+                            stat_type,
+                            complete_data
+                                .iter()
+                                .enumerate()
+                                .map(|(level, stats)| {
+                                    PlotPoint::new(
+                                        axis.position(level),
+                                        expected_value(stats.get(&stat_type).unwrap())
+                                    )
+                                })
+                                .collect::<Vec<_>>()
+                        )
+                    })
+                    .collect::<BTreeMap<_, _>>()
+            };
+            let weapon = context.weapons.get(&data.weapon);
+            let attack_speeds = |complete_data : &CompleteData, axis : &XAxis| {
+                let weapon = weapon?;
+                complete_data
+                    .iter()
+                    .enumerate()
+                    .map(|(level, stats)| {
+                        let attack_speed = weapon.attack_speed(stats)?;
+                        Some(PlotPoint::new(
+                            axis.position(level),
+                            expected_value(&attack_speed)
+                        ))
+                    })
+                    .collect::<Option<Vec<_>>>()
+            };
+            let attack_speed = attack_speeds(raw_data, &axis);
+            let other_attack_speed = raw_other_branch.zip(other_branch.as_ref()).and_then(
+                |(other_data, (_buffed, other_axis))| attack_speeds(other_data, other_axis)
+            );
+            let weapon_name = data.weapon.clone();
+            // the enemy is doubled once the attack speed reaches their speed plus four
+            let doubling_threshold = opponent(context, &data.enemy, &data.weapon)
+                .filter(|_opponent| attack_speed.is_some())
+                .map(|opponent| {
+                    let speed = opponent.attack_speed() as f64;
+                    (opponent.character.name.clone(), speed + 4.0)
+                });
+            let data = averages(actual_data, &axis);
+            let other_data = other_branch
+                .as_ref()
+                .map(|(other_data, other_axis)| averages(other_data, other_axis));
 
-            let max = &actual_data
-                .last()
-                .unwrap()
-                .iter()
-                .map(|(_sit, tree)| tree.keys().max().unwrap())
+            let max = std::iter::once(actual_data)
+                .chain(other_branch.as_ref().map(|(other_data, _axis)| *other_data))
+                .filter_map(|complete_data| complete_data.last())
+                .flat_map(|stats| stats.values())
+                .filter_map(|tree| tree.keys().max())
                 .max()
                 .unwrap();
 
-            let copied_progression = context.progression.clone();
-            let copied_name = context.character.name.clone();
             let important_marks : BTreeSet<_> = context
                 .progression
                 .iter()
                 .map(UsefulStatChange::marking_worthy)
                 .enumerate()
                 .filter(|(_index, val)| *val)
-                .map(|(index, _truthy)| index + 2)
-                .chain(std::iter::once(1))
-                .chain(std::iter::once(context.progression.len() + 1))
+                .map(|(index, _truthy)| index + 1)
+                .chain(std::iter::once(0))
+                .chain(std::iter::once(context.progression.len()))
                 .collect();
+            let is_important = {
+                let important_positions = important_marks
+                    .into_iter()
+                    .map(|snapshot| axis.position(snapshot))
+                    .collect_vec();
+                move |mark : f64| {
+                    important_positions
+                        .iter()
+                        .any(|position| (position - mark).abs() < 1e-6)
+                }
+            };
+            let level_positions = (axis.kind == XAxisKind::InGameLevel).then(|| {
+                axis.points
+                    .iter()
+                    .map(|(position, _label)| *position)
+                    .collect_vec()
+            });
+            let label_axis = axis.clone();
 
-            Plot::new("Average Plot")
+            let plot = Plot::new("Average Plot")
                 .legend(Legend::default())
-                .include_x(-0.2)
-                .include_x(actual_data.len() as f64 + 0.5)
-                .include_y(-0.5)
-                .include_y(**max as f64 * 1.2)
                 .label_formatter(|name, point| {
                     if !name.is_empty() {
                         format!("{name}: {:.1}", point.y)
@@ -363,86 +1267,182 @@ pub fn actual_data_display(
                         "".to_owned()
                     }
                 })
-                .x_axis_formatter(move |value, _visible_range| {
-                    if value == 1.0 {
-                        format!("Base {}", copied_name)
-                    }
-                    else if value >= 2.0 {
-                        copied_progression
-                            .get((value - 2.0) as usize)
-                            .map(|sc| format!("after {sc}"))
-                            .unwrap_or_else(|| "".to_owned())
-                    }
-                    else {
-                        "".to_owned()
-                    }
-                })
+                .x_axis_formatter(move |value, _visible_range| label_axis.label(value))
                 .x_grid_spacer(move |grid_input| {
                     let (lower, upper) = grid_input.bounds;
-                    let mut current = lower.round();
-                    std::iter::from_fn(|| {
-                        let out = current;
-                        current += 1.0;
-                        (out <= upper).then_some(out)
-                    })
-                    .filter(|x| x >= &lower)
-                    .map(|mark| GridMark {
-                        value : mark,
-                        step_size : if important_marks.contains(&(mark as usize)) {
-                            10.0
-                        }
-                        else {
-                            1.0
+                    let marks = match &level_positions {
+                        Some(positions) => positions
+                            .iter()
+                            .copied()
+                            .filter(|x| x >= &lower && x <= &upper)
+                            .collect_vec(),
+                        None => {
+                            let mut current = lower.round();
+                            std::iter::from_fn(|| {
+                                let out = current;
+                                current += 1.0;
+                                (out <= upper).then_some(out)
+                            })
+                            .filter(|x| x >= &lower)
+                            .collect_vec()
                         }
-                    })
-                    .collect()
+                    };
+                    marks
+                        .into_iter()
+                        .map(|mark| GridMark {
+                            value : mark,
+                            step_size : if is_important(mark) { 10.0 } else { 1.0 }
+                        })
+                        .collect()
                 })
-                .y_grid_spacer(uniform_grid_spacer(|_grid_input| [10.0, 1.0, 0.1]))
-                .show(ui, |ui| {
-                    data.into_iter().for_each(|(name, averages)| {
-                        ui.line(
-                            Line::new(PlotPoints::Owned(averages)).name(format!("Average {name}"))
+                .y_grid_spacer(uniform_grid_spacer(|_grid_input| [10.0, 1.0, 0.1]));
+            sketch.label_x(&axis.points);
+            view.show(
+                plot,
+                ViewBounds::up_to(last_position + 0.5, *max as f64 * 1.2),
+                ui,
+                |ui| {
+                    let average = |name : StatIndexType| {
+                        tr_with("plotter.average_series", &[("stat", &name)])
+                    };
+                    let mut line = |name : String, points : Vec<PlotPoint>, style : LineStyle| {
+                        sketch.line(&name, &points, style);
+                        ui.line(Line::new(PlotPoints::Owned(points)).style(style).name(name));
+                    };
+                    let (solid, dashed) = (LineStyle::Solid, LineStyle::dashed_loose());
+                    data.into_iter().for_each(|(stat, averages)| {
+                        let name = format!("{}{}{own_suffix}", average(stat), buff_note(stat));
+                        line(name, averages, solid);
+                    });
+                    other_data
+                        .into_iter()
+                        .flatten()
+                        .for_each(|(stat, averages)| {
+                            let name =
+                                format!("{}{}{other_suffix}", average(stat), buff_note(stat));
+                            line(name, averages, dashed);
+                        });
+                    let attack_speed_name =
+                        tr_with("plotter.attack_speed_series", &[("weapon", &weapon_name)]);
+                    if let Some(attack_speed) = attack_speed {
+                        line(
+                            format!("{attack_speed_name}{own_suffix}"),
+                            attack_speed,
+                            solid
                         );
-                    })
-                });
+                    }
+                    if let Some(attack_speed) = other_attack_speed {
+                        line(
+                            format!("{attack_speed_name}{other_suffix}"),
+                            attack_speed,
+                            dashed
+                        );
+                    }
+                    if let Some((enemy_name, threshold)) = doubling_threshold {
+                        let name = tr_with("plotter.doubling_series", &[("enemy", &enemy_name)]);
+                        let dotted = LineStyle::dotted_loose();
+                        sketch.hline(&name, threshold, dotted);
+                        ui.hline(HLine::new(threshold).style(dotted).name(name));
+                    }
+                }
+            );
         },
         ChartKind::InterLevelDist
             if matches!(data.reduction_option, ReductionKind::BenchmarkReduction) =>
         {
-            let data = actual_data
-                .iter()
-                .enumerate()
-                .map(|(level, stats)| {
-                    let stat = stats.get(&data.selected_stat).unwrap();
-                    Bar::new(
-                        (level + 1) as f64,
-                        stat.iter()
-                            .filter(|(points, _prob)| points >= &&data.benchmark)
-                            .map(|(_points, prob)| 100.0 * prob)
-                            .sum()
-                    )
-                })
-                .collect();
+            let selected_stat = data.selected_stat;
+            let benchmark = data.benchmark;
+            // bars of the two branches are placed side by side
+            let width = match &other_branch {
+                Some((_other_data, other_axis)) => {
+                    axis.element_width().min(other_axis.element_width()) / 2.0
+                },
+                None => axis.element_width()
+            };
+            let offset = if other_branch.is_some() {
+                width / 2.0
+            }
+            else {
+                0.0
+            };
+            let reduction = hint_text(HintKey::BenchmarkChance).unwrap_or_default();
+            let series_name = tr("plotter.benchmark_series");
+            let mut benchmark_chart =
+                |complete_data : &CompleteData, axis : &XAxis, offset : f64, suffix : &str| {
+                    let bars = complete_data
+                        .iter()
+                        .enumerate()
+                        .map(|(level, stats)| {
+                            let stat = stats.get(&selected_stat).unwrap();
+                            Bar::new(
+                                axis.position(level) + offset,
+                                stat.iter()
+                                    .filter(|(points, _prob)| points >= &&benchmark)
+                                    .map(|(_points, prob)| 100.0 * prob)
+                                    .sum()
+                            )
+                            .width(width)
+                        })
+                        .collect_vec();
+                    let name = format!("{series_name}{selected_note}{suffix}.");
+                    sketch.bars(&name, &bars);
+                    let positions = axis
+                        .points
+                        .iter()
+                        .map(|(position, _label)| *position + offset)
+                        .collect_vec();
+                    let reduction = reduction.clone();
+                    BarChart::new(bars).name(name).element_formatter(Box::new(
+                        move |bar, _chart| {
+                            let entry = positions
+                                .iter()
+                                .position(|position| (position - bar.argument).abs() < 1e-6)
+                                .unwrap_or_default()
+                                + 1;
+                            let chance = format_percentage(bar.value / 100.0);
+                            let probability = tr_with(
+                                "plotter.probability",
+                                &[
+                                    ("stat", &selected_stat),
+                                    ("relation", &"≥"),
+                                    ("value", &benchmark),
+                                    ("entry", &entry),
+                                    ("chance", &chance)
+                                ]
+                            );
+                            format!("{probability}\n{reduction}")
+                        }
+                    ))
+                };
+            let chart = benchmark_chart(actual_data, &axis, -offset, &own_suffix);
+            let other_chart = other_branch.as_ref().map(|(other_data, other_axis)| {
+                benchmark_chart(other_data, other_axis, offset, &other_suffix)
+            });
 
-            Plot::new("Benchmark Plot")
-                .legend(Legend::default())
-                .include_x(-0.2)
-                .include_x(actual_data.len() as f64 + 0.5)
-                .include_y(-0.5)
-                .include_y(110.0)
-                .show(ui, |ui| {
-                    ui.bar_chart(BarChart::new(data).name("Probability in % to hit the benchmark."))
-                });
+            let plot = Plot::new("Benchmark Plot").legend(Legend::default());
+            axis.label_sketch(&mut sketch);
+            data.view.show(
+                axis.label_plot(plot),
+                ViewBounds::up_to(last_position + 0.5, 110.0),
+                ui,
+                |ui| {
+                    ui.bar_chart(chart);
+                    if let Some(other_chart) = other_chart {
+                        ui.bar_chart(other_chart);
+                    }
+                }
+            );
         },
         ChartKind::BoxPlots => {
-            let (boxes, series) = actual_data
+            let box_width = (axis.element_width() * 0.3).min(0.25);
+            let (boxes, series) : (Vec<_>, Vec<_>) = actual_data
                 .iter()
                 .enumerate()
                 .map(|(level, stats)| {
                     let stat = stats.get(&data.selected_stat).unwrap();
                     (
                         BoxElem::new(
-                            (level + 1) as f64,
+                            axis.position(level),
                             BoxSpread::new(
                                 *stat.keys().min().unwrap_or(&1) as f64,
                                 find_percentile(stat, 0.5 - (data.box_range as f64) / 200.0)
@@ -452,38 +1452,601 @@ pub fn actual_data_display(
                                     .unwrap_or(15.0),
                                 *stat.keys().max().unwrap_or(&20) as f64
                             )
-                        ),
-                        PlotPoint::new(
-                            (level + 1) as f64,
-                            stat.iter()
-                                .fold(0.0, |acc, (points, prob)| acc + *points as f64 * *prob)
                         )
+                        .box_width(box_width)
+                        .whisker_width(box_width * 0.6),
+                        PlotPoint::new(axis.position(level), expected_value(stat))
                     )
                 })
                 .unzip();
             let max = &actual_data
                 .last()
                 .unwrap()
+                .values()
+                .map(|tree| tree.keys().max().unwrap())
+                .max()
+                .unwrap();
+            let box_name = format!("{}{selected_note}", tr("plotter.box_series"));
+            let averages_name = format!("{}{selected_note}", tr("plotter.averages_series"));
+            sketch.boxes(&box_name, &boxes);
+            sketch.line(&averages_name, &series, LineStyle::Solid);
+            axis.label_sketch(&mut sketch);
+            data.view.show(
+                axis.label_plot(Plot::new("Box Plot").legend(Legend::default())),
+                ViewBounds::up_to(axis.last_position() + 0.5, **max as f64 * 1.2),
+                ui,
+                |ui| {
+                    ui.box_plot(BoxPlot::new(boxes).name(box_name));
+                    ui.line(Line::new(PlotPoints::Owned(series)).name(averages_name))
+                }
+            );
+        },
+        ChartKind::Envelope => {
+            let (low, high) = data.envelope_range;
+            let percentile_series = |percentile : u8| {
+                actual_data
+                    .iter()
+                    .enumerate()
+                    .map(|(level, stats)| {
+                        let stat = stats.get(&data.selected_stat).unwrap();
+                        PlotPoint::new(
+                            axis.position(level),
+                            find_percentile(stat, percentile as f64 / 100.0).unwrap_or_default()
+                        )
+                    })
+                    .collect_vec()
+            };
+            let worst = percentile_series(low);
+            let median = percentile_series(50);
+            let best = percentile_series(high);
+
+            // the band is made up of one quad per segment, as egui only fills convex
+            // polygons
+            let band_name = tr_with("plotter.band_series", &[("low", &low), ("high", &high)]);
+            let (band_color, band_alpha) = (Color32::from_rgb(100, 150, 250), 0.15);
+            sketch.band(&band_name, &worst, &best, band_color, band_alpha);
+            let band = worst
                 .iter()
-                .map(|(_sit, tree)| tree.keys().max().unwrap())
+                .zip(best.iter())
+                .tuple_windows()
+                .map(|((worst_from, best_from), (worst_to, best_to))| {
+                    Polygon::new(PlotPoints::Owned(vec![
+                        *worst_from,
+                        *worst_to,
+                        *best_to,
+                        *best_from,
+                    ]))
+                    .color(band_color)
+                    .width(0.0)
+                    .fill_alpha(band_alpha)
+                    .name(&band_name)
+                })
+                .collect_vec();
+
+            let max = actual_data
+                .last()
+                .unwrap()
+                .get(&data.selected_stat)
+                .unwrap()
+                .keys()
                 .max()
                 .unwrap();
-            Plot::new("Box Plot")
+            axis.label_sketch(&mut sketch);
+            data.view.show(
+                axis.label_plot(Plot::new("Envelope Plot").legend(Legend::default())),
+                ViewBounds::up_to(axis.last_position() + 0.5, *max as f64 * 1.2),
+                ui,
+                |ui| {
+                    band.into_iter().for_each(|quad| ui.polygon(quad));
+                    let blessed = tr_with("plotter.blessed_series", &[("percentile", &high)]);
+                    let screwed = tr_with("plotter.screwed_series", &[("percentile", &low)]);
+                    let median_name = tr("plotter.median_series");
+                    for (name, points) in [(blessed, best), (median_name, median), (screwed, worst)]
+                    {
+                        let name = format!("{name}{selected_note}");
+                        sketch.line(&name, &points, LineStyle::Solid);
+                        ui.line(Line::new(PlotPoints::Owned(points)).name(name));
+                    }
+                }
+            );
+        },
+        ChartKind::ArrivalLevel => {
+            let selected_stat = data.selected_stat;
+            let threshold = data.benchmark;
+            // bars of the two branches are placed side by side
+            let width = match &other_branch {
+                Some((_other_data, other_axis)) => {
+                    axis.element_width().min(other_axis.element_width()) / 2.0
+                },
+                None => axis.element_width()
+            };
+            let offset = if other_branch.is_some() {
+                width / 2.0
+            }
+            else {
+                0.0
+            };
+            // the chance of never getting there follows the last entry after a gap
+            let never_position = last_position + 1.0;
+            let mut summaries = Vec::new();
+            let mut arrival_charts =
+                |complete_data : &CompleteData, axis : &XAxis, offset : f64, suffix : &str| {
+                    let arrival = fe_levels::arrival_distribution(
+                        complete_data.iter().map(|stats| &stats[&selected_stat]),
+                        threshold
+                    );
+                    let summary = arrival_summary(&arrival, axis);
+                    summaries.push(format!("{selected_stat} ≥ {threshold}{suffix}: {summary}"));
+                    let bars = arrival
+                        .first_at
+                        .iter()
+                        .enumerate()
+                        .map(|(level, chance)| {
+                            Bar::new(axis.position(level) + offset, chance * 100.0).width(width)
+                        })
+                        .collect_vec();
+                    let never_bar = Bar::new(never_position + offset, arrival.never * 100.0);
+                    let never = vec![never_bar.width(width)];
+                    let name = format!("{}{selected_note}{suffix}", tr("plotter.arrival_series"));
+                    let never_name =
+                        format!("{}{selected_note}{suffix}", tr("plotter.never_series"));
+                    sketch.bars(&name, &bars);
+                    sketch.bars(&never_name, &never);
+                    let positions = axis
+                        .points
+                        .iter()
+                        .map(|(position, _label)| *position + offset)
+                        .collect_vec();
+                    let chart = BarChart::new(bars).name(name).element_formatter(Box::new(
+                        move |bar, _chart| {
+                            let entry = positions
+                                .iter()
+                                .position(|position| (position - bar.argument).abs() < 1e-6)
+                                .unwrap_or_default()
+                                + 1;
+                            let chance = format_percentage(bar.value / 100.0);
+                            tr_with(
+                                "plotter.arrival_probability",
+                                &[
+                                    ("stat", &selected_stat),
+                                    ("value", &threshold),
+                                    ("entry", &entry),
+                                    ("chance", &chance)
+                                ]
+                            )
+                        }
+                    ));
+                    let never_chart =
+                        BarChart::new(never)
+                            .name(never_name)
+                            .element_formatter(Box::new(move |bar, _chart| {
+                                let chance = format_percentage(bar.value / 100.0);
+                                tr_with(
+                                    "plotter.never_probability",
+                                    &[
+                                        ("stat", &selected_stat),
+                                        ("value", &threshold),
+                                        ("chance", &chance)
+                                    ]
+                                )
+                            }));
+                    [chart, never_chart]
+                };
+            let charts = arrival_charts(actual_data, &axis, -offset, &own_suffix);
+            let other_charts = other_branch.as_ref().map(|(other_data, other_axis)| {
+                arrival_charts(other_data, other_axis, offset, &other_suffix)
+            });
+            for summary in summaries {
+                ui.label(summary);
+            }
+
+            let plot = Plot::new("Arrival Plot").legend(Legend::default());
+            axis.label_sketch(&mut sketch);
+            data.view.show(
+                axis.label_plot(plot),
+                ViewBounds::up_to(never_position + 0.5, 110.0),
+                ui,
+                |ui| {
+                    for chart in charts.into_iter().chain(other_charts.into_iter().flatten()) {
+                        ui.bar_chart(chart);
+                    }
+                }
+            );
+        },
+        ChartKind::OrkoChance => {
+            let (enemy, weapon) = match (
+                opponent(context, &data.enemy, &data.weapon),
+                context.weapons.get(&data.weapon)
+            ) {
+                (Some(enemy), Some(weapon)) => (enemy, weapon),
+                _ => {
+                    ui.label(tr("plotter.pick_enemy"));
+                    return;
+                }
+            };
+            if weapon.heals() {
+                ui.label(tr_with("plotter.heals", &[("weapon", &data.weapon)]));
+                return;
+            }
+            let orko_series = |complete_data : &CompleteData, axis : &XAxis| {
+                complete_data
+                    .iter()
+                    .enumerate()
+                    .map(|(level, stats)| {
+                        let chance = weapon.orko_chance(stats, &enemy)?;
+                        Some(PlotPoint::new(axis.position(level), chance * 100.0))
+                    })
+                    .collect::<Option<Vec<_>>>()
+            };
+            let series = match orko_series(raw_data, &axis) {
+                Some(series) => series,
+                None => {
+                    ui.label(tr("plotter.no_combat"));
+                    return;
+                }
+            };
+            let other_series = raw_other_branch.zip(other_branch.as_ref()).and_then(
+                |(other_data, (_buffed, other_axis))| orko_series(other_data, other_axis)
+            );
+            // the average of the displayed hit rates next to the chance they
+            // actually hit with
+            let hit_rng = HitRng::of(context.game_option);
+            let (displayed_hit, true_hit) : (Vec<_>, Vec<_>) = raw_data
+                .iter()
+                .enumerate()
+                .filter_map(|(level, stats)| {
+                    let hit_rates = weapon.hit_rates(stats, &enemy)?;
+                    let position = axis.position(level);
+                    let (displayed, actual) = hit_rates.iter().fold(
+                        (0.0, 0.0),
+                        |(displayed, actual), (hit_rate, chance)| {
+                            (
+                                displayed + *hit_rate as f64 * chance,
+                                actual + hit_rng.true_hit(*hit_rate) * chance
+                            )
+                        }
+                    );
+                    Some((
+                        PlotPoint::new(position, displayed * 100.0),
+                        PlotPoint::new(position, actual * 100.0)
+                    ))
+                })
+                .unzip();
+
+            let true_hit_name = tr("plotter.true_hit_series");
+            let true_hit_label = true_hit_name.clone();
+            let plot = Plot::new("ORKO Plot")
                 .legend(Legend::default())
-                .include_x(-0.2)
-                .include_x(actual_data.len() as f64 + 0.5)
-                .include_y(-0.5)
-                .include_y(**max as f64 * 1.2)
-                .show(ui, |ui| {
-                    ui.box_plot(BoxPlot::new(boxes).name("Medians, Percentiles & Extremes"));
-                    ui.line(Line::new(PlotPoints::Owned(series)).name("Averages"))
+                .label_formatter(move |name, point| {
+                    if name.is_empty() {
+                        "".to_owned()
+                    }
+                    else if name == true_hit_label {
+                        let displayed = hit_rng.displayed_hit(point.y / 100.0);
+                        let chance = format_percentage(point.y / 100.0);
+                        tr_with(
+                            "plotter.true_hit_label",
+                            &[
+                                ("name", &name),
+                                ("chance", &chance),
+                                ("displayed", &displayed)
+                            ]
+                        )
+                    }
+                    else {
+                        format!("{name}: {}", format_percentage(point.y / 100.0))
+                    }
                 });
+            axis.label_sketch(&mut sketch);
+            data.view.show(
+                axis.label_plot(plot),
+                ViewBounds::up_to(last_position + 0.5, 110.0),
+                ui,
+                |ui| {
+                    let mut line = |name : String, points : Vec<PlotPoint>, style : LineStyle| {
+                        sketch.line(&name, &points, style);
+                        ui.line(Line::new(PlotPoints::Owned(points)).style(style).name(name));
+                    };
+                    let orko = tr_with("plotter.orko_series", &[("enemy", &enemy.character.name)]);
+                    line(format!("{orko}{own_suffix}"), series, LineStyle::Solid);
+                    if let Some(other_series) = other_series {
+                        let dashed = LineStyle::dashed_loose();
+                        line(format!("{orko}{other_suffix}"), other_series, dashed);
+                    }
+                    let dotted = LineStyle::dotted_loose();
+                    line(tr("plotter.displayed_hit_series"), displayed_hit, dotted);
+                    line(true_hit_name, true_hit, dotted);
+                }
+            );
+        },
+        ChartKind::SurvivalChance => {
+            let weapon = match context.weapons.get(&data.weapon) {
+                Some(weapon) => weapon,
+                None => {
+                    ui.label(tr("plotter.pick_weapon"));
+                    return;
+                }
+            };
+            let gauntlet = data
+                .gauntlet
+                .iter()
+                .filter_map(|(name, count)| Some((opponent(context, name, &data.weapon)?, *count)))
+                .collect_vec();
+            if gauntlet.is_empty() {
+                ui.label(tr("plotter.empty_gauntlet"));
+                return;
+            }
+            let enemy_list = gauntlet
+                .iter()
+                .map(|(enemy, count)| match count {
+                    1 => enemy.character.name.clone(),
+                    _ => format!("{count}× {}", enemy.character.name)
+                })
+                .join(", ");
+            // bars of the two branches are placed side by side
+            let width = match &other_branch {
+                Some((_other_data, other_axis)) => {
+                    axis.element_width().min(other_axis.element_width()) / 2.0
+                },
+                None => axis.element_width()
+            };
+            let offset = if other_branch.is_some() {
+                width / 2.0
+            }
+            else {
+                0.0
+            };
+            let mut survival_chart = |complete_data : &CompleteData,
+                                      axis : &XAxis,
+                                      offset : f64,
+                                      suffix : &str| {
+                let bars = complete_data
+                    .iter()
+                    .enumerate()
+                    .map(|(level, stats)| {
+                        let chance = weapon.survival_chance(stats, &gauntlet)?;
+                        Some(Bar::new(axis.position(level) + offset, chance * 100.0).width(width))
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                let positions = axis
+                    .points
+                    .iter()
+                    .map(|(position, _label)| *position + offset)
+                    .collect_vec();
+                let enemy_list = enemy_list.clone();
+                let series_name = tr_with("plotter.survival_series", &[("enemies", &enemy_list)]);
+                let name = format!("{series_name}{suffix}");
+                sketch.bars(&name, &bars);
+                Some(BarChart::new(bars).name(name).element_formatter(Box::new(
+                    move |bar, _chart| {
+                        let entry = positions
+                            .iter()
+                            .position(|position| (position - bar.argument).abs() < 1e-6)
+                            .unwrap_or_default()
+                            + 1;
+                        let chance = format_percentage(bar.value / 100.0);
+                        tr_with(
+                            "plotter.survival_probability",
+                            &[
+                                ("enemies", &enemy_list),
+                                ("entry", &entry),
+                                ("chance", &chance)
+                            ]
+                        )
+                    }
+                )))
+            };
+            let chart = match survival_chart(raw_data, &axis, -offset, &own_suffix) {
+                Some(chart) => chart,
+                None => {
+                    ui.label(tr("plotter.no_combat"));
+                    return;
+                }
+            };
+            let other_chart = raw_other_branch.zip(other_branch.as_ref()).and_then(
+                |(other_data, (_buffed, other_axis))| {
+                    survival_chart(other_data, other_axis, offset, &other_suffix)
+                }
+            );
+
+            let plot = Plot::new("Survival Plot").legend(Legend::default());
+            axis.label_sketch(&mut sketch);
+            data.view.show(
+                axis.label_plot(plot),
+                ViewBounds::up_to(last_position + 0.5, 110.0),
+                ui,
+                |ui| {
+                    ui.bar_chart(chart);
+                    if let Some(other_chart) = other_chart {
+                        ui.bar_chart(other_chart);
+                    }
+                }
+            );
         },
         _ => {}
     }
+    sketch.save(data.view.shown.map(|view| (view.min, view.max)), ui.ctx());
 }
 
-fn find_percentile(stat : &BTreeMap<StatType, f64>, percentile : f64) -> Option<f64> {
+/// Whether the stat charts show the stat, leaving out those that never grow on
+/// level-ups unless asked to show them.
+fn charted(sit : StatIndexType, show_constant_stats : bool) -> bool {
+    show_constant_stats || sit.is_growable()
+}
+
+/// Shifts the distributions of the buffed stats by their buffs, clamping them
+/// at the caps of the snapshot unless the game lets buffs exceed them.
+fn apply_buffs(
+    complete_data : &CompleteData,
+    caps : &[BTreeMap<StatIndexType, StatType>],
+    buffs : &BTreeMap<StatIndexType, StatType>,
+    exceed_caps : bool
+) -> CompleteData {
+    complete_data
+        .iter()
+        .zip(caps)
+        .map(|(stats, caps)| {
+            stats
+                .iter()
+                .map(|(sit, distribution)| {
+                    let buff = buffs.get(sit).copied().unwrap_or_default();
+                    let cap = caps
+                        .get(sit)
+                        .copied()
+                        .filter(|_cap| !exceed_caps)
+                        .unwrap_or(StatType::MAX);
+                    let mut shifted = BTreeMap::new();
+                    for (value, chance) in distribution {
+                        let value = value.saturating_add(buff).min(cap);
+                        *shifted.entry(value).or_insert(0.0) += chance;
+                    }
+                    (*sit, shifted)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// The saved enemy with the equipment saved for them, weak to the weapon
+/// fighting them if it's effective against them.
+fn opponent<'a>(context : &'a GameData, enemy : &str, weapon : &str) -> Option<Opponent<'a>> {
+    let character = context.enemies.get(enemy)?;
+    let equipment = context.equipment.get(enemy);
+    Some(Opponent {
+        character,
+        weapon : equipment.and_then(|equipment| context.weapons.get(&equipment.weapon)),
+        weak : equipment.map_or(false, |equipment| equipment.weak_to.contains(weapon))
+    })
+}
+
+/// Lets the user pick the saved enemy and weapon of the combat related series.
+fn combat_pickers(context : &GameData, data : &mut PlotterData, ui : &mut Ui) {
+    egui::containers::ComboBox::from_label(tr("plotter.enemy"))
+        .selected_text(data.enemy.as_str())
+        .show_ui(ui, |ui| {
+            for name in context.enemies.keys() {
+                ui.selectable_value(&mut data.enemy, name.clone(), name);
+            }
+        });
+    weapon_picker(context, data, ui);
+}
+
+/// Lets the user pick the saved enemies of the survival chart, how often each
+/// of them attacks and the saved weapon faced with.
+fn gauntlet_pickers(context : &GameData, data : &mut PlotterData, ui : &mut Ui) {
+    ui.menu_button(tr("plotter.gauntlet"), |ui| {
+        for name in context.enemies.keys() {
+            ui.horizontal(|ui| {
+                let count = data.gauntlet.entry(name.clone()).or_default();
+                ui.add(DragValue::new(count).clamp_range(0..=MAX_GAUNTLET_ATTACKS));
+                ui.label(name);
+            });
+        }
+        data.gauntlet.retain(|_name, count| *count > 0);
+    });
+    weapon_picker(context, data, ui);
+}
+
+/// the most attacks per enemy the gauntlet editor offers
+const MAX_GAUNTLET_ATTACKS : usize = 10;
+
+fn weapon_picker(context : &GameData, data : &mut PlotterData, ui : &mut Ui) {
+    egui::containers::ComboBox::from_label(tr("plotter.weapon"))
+        .selected_text(data.weapon.as_str())
+        .show_ui(ui, |ui| {
+            for (name, weapon) in context.weapons.iter() {
+                weapon_option(context, &mut data.weapon, name, weapon, ui);
+            }
+        });
+}
+
+/// Offers the saved weapon to be picked, greyed out if the character can't
+/// wield it.
+fn weapon_option(
+    context : &GameData,
+    picked : &mut String,
+    name : &str,
+    weapon : &Weapon,
+    ui : &mut Ui
+) {
+    let usable = weapon.usable_with(&context.weapon_ranks);
+    let mut response = ui.add_enabled(usable, SelectableLabel::new(*picked == name, name));
+    if let Some(requirement) = weapon.requirement() {
+        response = response
+            .on_hover_text(requirement.clone())
+            .on_disabled_hover_text(requirement);
+    }
+    if response.clicked() {
+        *picked = name.to_owned();
+    }
+}
+
+/// Formats a probability as a percentage with three significant digits, so
+/// that sub-percent tails remain distinguishable.
+fn format_percentage(probability : f64) -> String {
+    let percent = probability * 100.0;
+    if percent <= 0.0 {
+        return "0%".to_owned();
+    }
+    let decimals = (2 - percent.log10().floor() as i32).clamp(1, 8) as usize;
+    format!("{percent:.decimals$}%")
+}
+
+type BarFormatter = Box<dyn Fn(&Bar, &BarChart) -> String>;
+
+/// By which entries half and nine in ten of the runs reached the threshold the
+/// arrivals are of, for the text next to their chart.
+fn arrival_summary(arrival : &ArrivalDistribution, axis : &XAxis) -> String {
+    let by_entry = |chance : f64| {
+        let snapshot = arrival.quantile(chance)?;
+        let (_position, label) = axis.points.get(snapshot)?;
+        Some((snapshot + 1, label))
+    };
+    let median = match by_entry(0.5) {
+        Some((entry, label)) => tr_with(
+            "plotter.arrival_median",
+            &[("entry", &entry), ("label", label)]
+        ),
+        None => tr("plotter.arrival_median_never")
+    };
+    let likely = match by_entry(0.9) {
+        Some((entry, label)) => tr_with(
+            "plotter.arrival_likely",
+            &[("entry", &entry), ("label", label)]
+        ),
+        None => tr("plotter.arrival_likely_never")
+    };
+    format!("{median} {likely}")
+}
+
+/// hover text for bars whose argument is a stat value at a given entry
+fn stat_probability_formatter(
+    stat : StatIndexType,
+    entry : usize,
+    relation : &'static str
+) -> BarFormatter {
+    Box::new(move |bar, _chart| {
+        let value = bar.argument.round();
+        let chance = format_percentage(bar.value / 100.0);
+        tr_with(
+            "plotter.probability",
+            &[
+                ("stat", &stat),
+                ("relation", &relation),
+                ("value", &value),
+                ("entry", &entry),
+                ("chance", &chance)
+            ]
+        )
+    })
+}
+
+fn expected_value(stat : &BTreeMap<StatType, f64>) -> f64 {
+    stat.iter()
+        .fold(0.0, |acc, (points, prob)| acc + *points as f64 * *prob)
+}
+
+pub fn find_percentile(stat : &BTreeMap<StatType, f64>, percentile : f64) -> Option<f64> {
     stat.iter()
         .scan(0.0, |acc, (points, prob)| {
             *acc += prob;
@@ -493,130 +2056,387 @@ fn find_percentile(stat : &BTreeMap<StatType, f64>, percentile : f64) -> Option<
         .map(|(points, _prob)| points as f64)
 }
 
+/// The sample count is part of the key so that exact and simulated results for
+/// the same inputs never collide.
+fn derived_data_key(
+    character : &Character<StatIndexType>,
+    progression : &[ConcreteStatChange],
+    other_branch : Option<&[ConcreteStatChange]>,
+    num_samples : Option<u64>
+) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    character.hash(&mut hasher);
+    progression.hash(&mut hasher);
+    other_branch.hash(&mut hasher);
+    num_samples.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn required_samples<'a>(
+    mut progression : impl Iterator<Item = &'a ConcreteStatChange>
+) -> Option<u64> {
+    if progression.all(ConcreteStatChange::cheap_to_execute) {
+        None
+    }
+    else {
+        Some(SIMULATION_SAMPLES)
+    }
+}
+
+/// The key and sample count of the data needed for the current progression.
+fn current_data_key(
+    character : &Character<StatIndexType>,
+    progression : &ProgressionManager
+) -> (u64, Option<u64>) {
+    let other_branch = progression.other_branch();
+    let num_samples = required_samples(progression.iter().chain(other_branch.iter().flatten()));
+    let key = derived_data_key(character, progression, other_branch.as_deref(), num_samples);
+    (key, num_samples)
+}
+
+fn format_seconds(seconds : f64) -> String {
+    if seconds < 1.0 {
+        format!("{:.0} ms", seconds * 1000.0)
+    }
+    else {
+        format!("{seconds:.1} s")
+    }
+}
+
+fn format_bytes(bytes : usize) -> String {
+    let kib = bytes as f64 / 1024.0;
+    if kib < 1024.0 {
+        format!("{kib:.0} KiB")
+    }
+    else {
+        format!("{:.1} MiB", kib / 1024.0)
+    }
+}
+
+/// The strip along the bottom of a plotter window telling how its data was
+/// obtained, true on return indicating the user cleared the cache.
+fn status_strip(
+    plotter : &PlotterManager,
+    computed : &ComputedData,
+    id : Id,
+    ui : &mut Ui
+) -> bool {
+    let mut clear_cache = false;
+    egui::TopBottomPanel::bottom(id.with("Status Strip")).show_inside(ui, |ui| {
+        ui.horizontal_wrapped(|ui| {
+            ui.small(computed.kind.to_string())
+                .on_hover_text(tr("plotter.computation_hint"));
+            ui.separator();
+            let seconds = format_seconds(computed.seconds);
+            ui.small(tr_with(
+                "plotter.data_origin",
+                &[("origin", &plotter.data_origin), ("time", &seconds)]
+            ));
+            ui.separator();
+            ui.small(tr_with(
+                "plotter.cache_slots",
+                &[
+                    ("used", &plotter.cache.len()),
+                    ("total", &DERIVED_DATA_CACHE_SIZE)
+                ]
+            ))
+            .on_hover_text(tr("plotter.cache_hint"));
+            if ui.small_button(tr("plotter.clear_cache")).clicked() {
+                clear_cache = true;
+            }
+            ui.separator();
+            let size = format_bytes(computed.footprint());
+            ui.small(tr_with("plotter.footprint", &[("size", &size)]))
+                .on_hover_text(tr("plotter.footprint_hint"));
+        });
+    });
+    clear_cache
+}
+
+/// Logs the end of the computation started last once its data is ready.
+fn log_finished(context : &mut GameData, computed : &ComputedData) {
+    if !std::mem::take(&mut context.plotter.computing) {
+        return;
+    }
+    let seconds = format_seconds(computed.seconds);
+    let text = if computed.data.is_empty() {
+        tr_with("plotter.log_no_data", &[("time", &seconds)])
+    }
+    else {
+        tr_with(
+            "plotter.log_finished",
+            &[("time", &seconds), ("kind", &computed.kind)]
+        )
+    };
+    log_action(context, text);
+}
+
+/// The plotter window while its data is being computed, known by the id the
+/// first plotter window falls back to.
+fn processing_window(ctx : &egui::Context) {
+    egui::Window::new(tr("plotter.title"))
+        .id(Id::new("Data Plotter"))
+        .show(ctx, |ui| {
+            ui.spinner();
+            ui.label(tr("plotter.processing"));
+        });
+}
+
 pub fn data_plotting_windows(context : &mut GameData, ctx : &egui::Context) {
+    let other_branch = context.progression.other_branch();
+    let character = context.effective_character();
+    let (key, num_samples) = current_data_key(&character, &context.progression);
     let copy = std::mem::take(&mut context.plotter.derived_data);
 
     if let Some(promise) = copy {
         match promise.ready() {
             None => {
-                egui::Window::new("Data Plotter").show(ctx, |ui| {
-                    ui.spinner();
-                    ui.label("Processing...");
-                });
+                processing_window(ctx);
                 context.plotter.derived_data = Some(promise);
             },
-            Some((parameters, character, actual_data))
-                if parameters == context.progression.deref() && character == &context.character =>
-            {
+            Some((computed_key, actual_data)) if *computed_key == key => {
+                log_finished(context, actual_data);
+                context
+                    .plotter
+                    .cache
+                    .insert(*computed_key, actual_data.clone());
+
                 if context.plotter.plotter_windows.is_empty() {
                     context.plotter.plotter_windows.push(Default::default());
                 }
+                let mut clear_cache = false;
                 let moved_out = std::mem::take(&mut context.plotter.plotter_windows);
                 context.plotter.plotter_windows = moved_out
                     .into_iter()
                     .flat_map(|mut state| {
+                        if state.hidden {
+                            return vec![Some(state), None];
+                        }
                         let mut currently_open = true;
                         let mut new_instance = None;
-                        egui::Window::new("Data Plotter")
+                        let window = egui::Window::new(tr("plotter.title"))
                             .id(state.id())
                             .open(&mut currently_open)
                             .show(ctx, |ui| {
+                                clear_cache |=
+                                    status_strip(&context.plotter, actual_data, state.id(), ui);
                                 actual_data_display(
                                     context,
                                     &mut state,
                                     ui,
-                                    actual_data,
+                                    &actual_data.data,
+                                    actual_data.other_branch.as_ref(),
                                     &mut new_instance
                                 );
                             });
+                        if let Some(window) = window {
+                            onboarding::mark_window(ctx, state.id(), window.response.rect);
+                        }
                         vec![currently_open.then_some(state), new_instance]
                     })
                     .flatten()
                     .collect();
 
+                if clear_cache {
+                    context.plotter.cache.clear();
+                }
+
                 context.plotter.derived_data = Some(promise);
             },
-            Some((parameters, character, _actual_data))
-                if parameters != context.progression.deref() || character != &context.character =>
-            {
-                egui::Window::new("Data Plotter").show(ctx, |ui| {
-                    ui.spinner();
-                    ui.label("Processing...");
-                });
-                context.plotter.derived_data = None;
-            },
-            _ => unreachable!()
+            Some((computed_key, actual_data)) => {
+                log_finished(context, actual_data);
+                // keep the outdated result around in case the user reverts their edit
+                context
+                    .plotter
+                    .cache
+                    .insert(*computed_key, actual_data.clone());
+                processing_window(ctx);
+            }
         }
     }
     if context.plotter.derived_data.is_none() {
-        if context
-            .progression
-            .iter()
-            .all(ConcreteStatChange::cheap_to_execute)
-        {
-            let (sender, promise) = Promise::new();
-            let character = context.character.clone();
-            let progression = context.progression.clone();
-            sender.send((
-                progression.clone(),
-                character.clone(),
-                compute(character, progression, None)
-            ));
-            context.plotter.derived_data = Some(promise);
+        if let Some(actual_data) = context.plotter.cache.get(key) {
+            context.plotter.derived_data = Some(Promise::from_ready((key, actual_data)));
+            context.plotter.data_origin = DataOrigin::Cached;
         }
         else {
+            let progression = context.progression.clone();
+            let count = progression.len();
+            let text = match num_samples {
+                Some(samples) => tr_with(
+                    "plotter.log_started_simulation",
+                    &[("count", &count), ("samples", &samples)]
+                ),
+                None => tr_with("plotter.log_started", &[("count", &count)])
+            };
+            log_action(context, text);
+            context.plotter.computing = true;
             #[cfg(target_arch = "wasm32")]
             {
-                egui::Window::new("Error")
-                    .collapsible(false)
-                    .resizable(false)
-                    .show(ctx, |ui| {
-                        ui.label(format!(
-                            "Unfortunately, operation in a browser environment is slow and \
-                             time-constrained. Therefore certain slow stat changing progressions \
-                             cannot reasonably be computed. Please remove the following listed \
-                             progressions entries or use the native version of this app."
-                        ));
-                        context
-                            .progression
-                            .iter()
-                            .filter(|sc| sc.cheap_to_execute())
-                            .for_each(|sc| {
-                                ui.label(sc.to_string());
-                            });
-                    });
+                if let Some(samples) = num_samples {
+                    warn_of_slow_simulation(ctx, &progression, other_branch.as_deref(), samples);
+                }
+                let (worker, promise) =
+                    worker::spawn(ctx, key, character, progression, other_branch, num_samples);
+                context.plotter.worker = worker;
+                context.plotter.derived_data = Some(promise);
             }
             #[cfg(not(target_arch = "wasm32"))]
             {
-                let character = context.character.clone();
-                let progression = context.progression.clone();
-                context.plotter.derived_data = Some(Promise::spawn_thread(
-                    "Background Compute Thread",
-                    move || {
-                        (
-                            progression.clone(),
-                            character.clone(),
-                            compute(character, progression, Some(1u64 << 20))
-                        )
-                    }
-                ));
+                if num_samples.is_none() {
+                    let (sender, promise) = Promise::new();
+                    let computed =
+                        compute_or_report(ctx, character, progression, other_branch, None);
+                    sender.send((key, Arc::new(computed)));
+                    context.plotter.derived_data = Some(promise);
+                }
+                else {
+                    let ctx = ctx.clone();
+                    context.plotter.derived_data = Some(Promise::spawn_thread(
+                        "Background Compute Thread",
+                        move || {
+                            let computed = compute_or_report(
+                                &ctx,
+                                character,
+                                progression,
+                                other_branch,
+                                num_samples
+                            );
+                            (key, Arc::new(computed))
+                        }
+                    ));
+                }
             }
+            context.plotter.data_origin = DataOrigin::Recomputed;
         }
     }
 }
 
-#[cached(size = 1000)]
+/// [`compute`], reporting a panic of the library rather than taking the app
+/// down with it. A failed computation yields no data, which the plotter
+/// windows say as well.
+fn compute_or_report(
+    ctx : &egui::Context,
+    character : Character<StatIndexType>,
+    stat_changes : Vec<ConcreteStatChange>,
+    other_branch : Option<Vec<ConcreteStatChange>>,
+    num_samples : Option<u64>
+) -> ComputedData {
+    std::panic::catch_unwind(AssertUnwindSafe(move || {
+        compute(character, stat_changes, other_branch, num_samples)
+    }))
+    .unwrap_or_else(|panic| {
+        let reason = panic
+            .downcast_ref::<&str>()
+            .map(|reason| reason.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| tr("plotter.compute_failed_reason"));
+        toasts::error(
+            ctx,
+            tr_with("plotter.compute_failed", &[("reason", &reason)])
+        );
+        ComputedData::failed()
+    })
+}
+
 fn compute(
     character : Character<StatIndexType>,
     stat_changes : Vec<ConcreteStatChange>,
+    other_branch : Option<Vec<ConcreteStatChange>>,
     num_samples : Option<u64>
-) -> CompleteData {
-    fe_levels::generate_histograms(
-        &stat_changes
+) -> ComputedData {
+    let stopwatch = Stopwatch::start();
+    let compiled = compile_progression(&stat_changes);
+    let other_compiled = other_branch.as_deref().map(compile_progression);
+    let kind = if std::iter::once(&compiled)
+        .chain(other_compiled.iter())
+        .all(|compiled| fe_levels::supports_exact_analysis(compiled))
+    {
+        ComputationKind::Exact
+    }
+    else {
+        ComputationKind::Simulated {
+            samples : num_samples.unwrap_or_default()
+        }
+    };
+    let data = fe_levels::generate_histograms(&compiled, &character, num_samples);
+    let other_branch = other_compiled
+        .map(|compiled| fe_levels::generate_histograms(&compiled, &character, num_samples));
+    ComputedData {
+        data,
+        other_branch,
+        kind,
+        seconds : stopwatch.seconds()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::app::GameKind;
+
+    #[derive(Default)]
+    struct MemoryStorage(HashMap<String, String>);
+
+    impl eframe::Storage for MemoryStorage {
+        fn get_string(&self, key : &str) -> Option<String> { self.0.get(key).cloned() }
+
+        fn set_string(&mut self, key : &str, value : String) {
+            self.0.insert(key.to_owned(), value);
+        }
+
+        fn flush(&mut self) {}
+    }
+
+    #[test]
+    fn computed_data_is_restored_once_written() {
+        let mut plotter = PlotterManager::default();
+        let computed = ComputedData {
+            data : vec![BTreeMap::new()],
+            other_branch : None,
+            kind : ComputationKind::Exact,
+            seconds : 0.0
+        };
+        plotter.derived_data = Some(Promise::from_ready((7, Arc::new(computed))));
+        let mut storage = MemoryStorage::default();
+        let mut persisted_key = None;
+        plotter.persist(&mut persisted_key, &mut storage);
+        assert_eq!(persisted_key, Some(7));
+
+        let mut restored = PlotterManager::default();
+        assert_eq!(restored.restore(&storage), Some(7));
+        assert!(restored.data_origin == DataOrigin::Restored);
+        let (key, data) = restored
+            .derived_data
+            .as_ref()
+            .and_then(Promise::ready)
+            .unwrap();
+        assert_eq!((*key, data.data.len()), (7, 1));
+
+        // unchanged data isn't written again
+        storage.0.clear();
+        plotter.persist(&mut persisted_key, &mut storage);
+        assert!(storage.0.is_empty());
+    }
+
+    #[test]
+    fn constant_stats_are_only_charted_on_request() {
+        let names = |show_constant_stats : bool| {
+            StatIndexType::new(GameKind::GbaFe)
+                .into_iter()
+                .filter(|sit| charted(*sit, show_constant_stats))
+                .map(|sit| sit.to_string())
+                .collect_vec()
+        };
+        assert_eq!(
+            names(false),
+            ["HP", "Atk", "Skl", "Spd", "Lck", "Def", "Res"]
+        );
+        assert_eq!(names(true).len(), 9);
+        assert!(StatIndexType::new(GameKind::RD)
             .into_iter()
-            .map(ConcreteStatChange::compile)
-            .collect_vec(),
-        &character,
-        num_samples
-    )
+            .all(|sit| charted(sit, false)));
+    }
 }
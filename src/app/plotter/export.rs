@@ -0,0 +1,669 @@
+//! Charts exported as PNG images, for sharing results without screenshots.
+//! egui can't draw a plot anywhere but on screen, so the chart records what
+//! it adds to its plot while it's exported and a small renderer draws the
+//! same series again, colored as egui colors them. The renderer is plain Rust,
+//! so exported images come out alike natively and on the web.
+
+use std::borrow::Cow;
+
+use ab_glyph::{point, Font, FontRef, ScaleFont};
+use egui::{
+    epaint::Hsva,
+    plot::{Bar, BoxElem, LineStyle, PlotPoint},
+    pos2, Color32, Pos2, Rect
+};
+
+use crate::app::{files, locale::tr_with, session_log, toasts};
+
+/// the size of exported images in pixels
+const WIDTH : usize = 1000;
+const HEIGHT : usize = 600;
+/// the space around the plot for the title and the axes' labels
+const MARGIN_TOP : f32 = 40.0;
+const MARGIN_LEFT : f32 = 52.0;
+const MARGIN_BOTTOM : f32 = 30.0;
+const MARGIN_RIGHT : f32 = 16.0;
+const TITLE_SIZE : f32 = 18.0;
+const LABEL_SIZE : f32 = 13.0;
+/// the width of lines as egui draws them by default
+const LINE_WIDTH : f32 = 1.5;
+/// how strongly egui fills bars and boxes with their color
+const FILL_ALPHA : f32 = 0.2;
+
+type Rgb = [u8; 3];
+
+const WHITE : Rgb = [255, 255, 255];
+const TEXT : Rgb = [40, 40, 40];
+const GRID : Rgb = [225, 225, 225];
+const FRAME : Rgb = [160, 160, 160];
+
+fn rgb(color : Color32) -> Rgb { [color.r(), color.g(), color.b()] }
+
+/// The color egui gives the plot's item of the index that has none set.
+fn auto_color(index : usize) -> Rgb {
+    let golden_ratio = (5.0_f32.sqrt() - 1.0) / 2.0;
+    rgb(Hsva::new(index as f32 * golden_ratio, 0.85, 0.5, 1.0).into())
+}
+
+enum Shape {
+    Line {
+        points : Vec<[f64; 2]>,
+        style : LineStyle
+    },
+    HLine {
+        y : f64,
+        style : LineStyle
+    },
+    /// the argument, value and width of every bar
+    Bars(Vec<[f64; 3]>),
+    Boxes(Vec<BoxElem>),
+    /// quads between the lower and the upper series, filled with the alpha
+    Band {
+        lower : Vec<[f64; 2]>,
+        upper : Vec<[f64; 2]>,
+        alpha : f32
+    }
+}
+
+struct Item {
+    name : String,
+    color : Rgb,
+    shape : Shape
+}
+
+/// What a chart added to its plot this frame, recorded only while it's being
+/// exported.
+pub struct Sketch {
+    recording : bool,
+    /// the character, chart and stat shown, heading the image
+    title : String,
+    items : Vec<Item>,
+    /// the labels of the x axis by their positions, plain numbers if empty
+    x_labels : Vec<(f64, String)>,
+    /// the items egui colored so far, as it colors them one after another
+    auto_colors : usize
+}
+
+fn coordinates(points : &[PlotPoint]) -> Vec<[f64; 2]> {
+    points.iter().map(|point| [point.x, point.y]).collect()
+}
+
+impl Sketch {
+    pub fn new(recording : bool, title : String) -> Self {
+        Self {
+            recording,
+            title,
+            items : Vec::new(),
+            x_labels : Vec::new(),
+            auto_colors : 0
+        }
+    }
+
+    fn push(&mut self, name : &str, color : Option<Rgb>, shape : impl FnOnce() -> Shape) {
+        if !self.recording {
+            return;
+        }
+        let color = color.unwrap_or_else(|| {
+            self.auto_colors += 1;
+            auto_color(self.auto_colors - 1)
+        });
+        self.items.push(Item {
+            name : name.to_owned(),
+            color,
+            shape : shape()
+        });
+    }
+
+    /// Labels the x axis as the plot's axis formatter does.
+    pub fn label_x(&mut self, labels : &[(f64, String)]) {
+        if self.recording {
+            self.x_labels = labels.to_vec();
+        }
+    }
+
+    pub fn line(&mut self, name : &str, points : &[PlotPoint], style : LineStyle) {
+        self.push(name, None, || Shape::Line {
+            points : coordinates(points),
+            style
+        });
+    }
+
+    pub fn hline(&mut self, name : &str, y : f64, style : LineStyle) {
+        self.push(name, None, || Shape::HLine { y, style });
+    }
+
+    pub fn bars(&mut self, name : &str, bars : &[Bar]) {
+        self.push(name, None, || {
+            Shape::Bars(
+                bars.iter()
+                    .map(|bar| [bar.argument, bar.value, bar.bar_width])
+                    .collect()
+            )
+        });
+    }
+
+    pub fn boxes(&mut self, name : &str, boxes : &[BoxElem]) {
+        self.push(name, None, || Shape::Boxes(boxes.to_vec()));
+    }
+
+    pub fn band(
+        &mut self,
+        name : &str,
+        lower : &[PlotPoint],
+        upper : &[PlotPoint],
+        color : Color32,
+        alpha : f32
+    ) {
+        self.push(name, Some(rgb(color)), || Shape::Band {
+            lower : coordinates(lower),
+            upper : coordinates(upper),
+            alpha
+        });
+    }
+
+    /// Renders the chart within the lower left and upper right corner the plot
+    /// showed and offers it as a PNG file. Does nothing unless the chart is
+    /// being exported.
+    pub fn save(&self, view : Option<([f64; 2], [f64; 2])>, ctx : &egui::Context) {
+        let view = match view {
+            Some(view) if self.recording && !self.items.is_empty() => view,
+            _ => return
+        };
+        let file_name = format!("{}.png", self.title.replace(['/', '\\', ':'], "_"));
+        match render(self, view)
+            .encode()
+            .and_then(|image| files::save(&file_name, files::PNG, &image))
+        {
+            Ok(true) => {
+                let text = tr_with("plotter.log_exported", &[("file", &file_name)]);
+                session_log::report(ctx, text);
+            },
+            Ok(false) => {},
+            Err(error) => {
+                toasts::error(ctx, tr_with("plotter.export_failed", &[("error", &error)]));
+            }
+        }
+    }
+}
+
+/// A step of 1, 2 or 5 times a power of ten dividing the range into about
+/// the count of steps.
+fn nice_step(range : f64, count : f64) -> f64 {
+    let raw = (range / count).max(f64::EPSILON);
+    let magnitude = 10_f64.powf(raw.log10().floor());
+    let step = match raw / magnitude {
+        normalized if normalized <= 1.0 => 1.0,
+        normalized if normalized <= 2.0 => 2.0,
+        normalized if normalized <= 5.0 => 5.0,
+        _ => 10.0
+    };
+    step * magnitude
+}
+
+/// The multiples of the step within the range.
+fn ticks(min : f64, max : f64, step : f64) -> Vec<f64> {
+    let first = (min / step).ceil() as i64;
+    let last = (max / step).floor() as i64;
+    (first..=last).map(|index| index as f64 * step).collect()
+}
+
+/// The value with as many decimals as the step needs.
+fn tick_label(value : f64, step : f64) -> String {
+    let decimals = if step >= 1.0 {
+        0
+    }
+    else {
+        (-step.log10()).ceil() as usize
+    };
+    let value = if value.abs() < step * 1e-6 {
+        0.0
+    }
+    else {
+        value
+    };
+    format!("{value:.decimals$}")
+}
+
+/// The fonts egui draws proportional text with, a char being drawn with the
+/// first of them that has it.
+struct Fonts(Vec<FontRef<'static>>);
+
+impl Fonts {
+    fn load() -> Self {
+        let definitions = egui::FontDefinitions::default();
+        let fonts = definitions
+            .families
+            .get(&egui::FontFamily::Proportional)
+            .into_iter()
+            .flatten()
+            .filter_map(|name| match &definitions.font_data.get(name)?.font {
+                Cow::Borrowed(bytes) => FontRef::try_from_slice(*bytes).ok(),
+                Cow::Owned(_bytes) => None
+            })
+            .collect();
+        Self(fonts)
+    }
+
+    fn font_for(&self, char : char) -> Option<&FontRef<'static>> {
+        self.0
+            .iter()
+            .find(|font| font.glyph_id(char).0 != 0)
+            .or_else(|| self.0.first())
+    }
+
+    fn width(&self, text : &str, size : f32) -> f32 {
+        text.chars()
+            .filter_map(|char| {
+                let font = self.font_for(char)?;
+                Some(font.as_scaled(size).h_advance(font.glyph_id(char)))
+            })
+            .sum()
+    }
+}
+
+struct Raster {
+    pixels : Vec<Rgb>
+}
+
+impl Raster {
+    fn new() -> Self {
+        Self {
+            pixels : vec![WHITE; WIDTH * HEIGHT]
+        }
+    }
+
+    /// Mixes the color into the pixel by the alpha, ignoring pixels outside
+    /// of the clip.
+    fn blend(&mut self, x : i64, y : i64, color : Rgb, alpha : f32, clip : Rect) {
+        let center = pos2(x as f32 + 0.5, y as f32 + 0.5);
+        if alpha <= 0.0 || !clip.contains(center) || x < 0 || y < 0 {
+            return;
+        }
+        let (x, y) = (x as usize, y as usize);
+        if x >= WIDTH || y >= HEIGHT {
+            return;
+        }
+        let pixel = &mut self.pixels[y * WIDTH + x];
+        for (channel, target) in pixel.iter_mut().zip(color) {
+            let mixed = f32::from(*channel) * (1.0 - alpha) + f32::from(target) * alpha;
+            *channel = mixed.round() as u8;
+        }
+    }
+
+    /// Calls back with every pixel whose center lies within the rect.
+    fn pixels_in(rect : Rect, mut pixel : impl FnMut(i64, i64, Pos2)) {
+        let (left, top) = (rect.left().floor() as i64, rect.top().floor() as i64);
+        let (right, bottom) = (rect.right().ceil() as i64, rect.bottom().ceil() as i64);
+        for y in top.max(0)..bottom.min(HEIGHT as i64) {
+            for x in left.max(0)..right.min(WIDTH as i64) {
+                pixel(x, y, pos2(x as f32 + 0.5, y as f32 + 0.5));
+            }
+        }
+    }
+
+    fn fill_rect(&mut self, rect : Rect, color : Rgb, alpha : f32, clip : Rect) {
+        Self::pixels_in(rect.intersect(clip), |x, y, center| {
+            if rect.contains(center) {
+                self.blend(x, y, color, alpha, clip);
+            }
+        });
+    }
+
+    /// Fills the convex polygon, whichever way round its corners go.
+    fn fill_convex(&mut self, corners : &[Pos2], color : Rgb, alpha : f32, clip : Rect) {
+        let bounds = Rect::from_points(corners);
+        Self::pixels_in(bounds.intersect(clip), |x, y, center| {
+            let (mut positive, mut negative) = (false, false);
+            for (from, to) in corners.iter().zip(corners.iter().cycle().skip(1)) {
+                let (edge, offset) = (*to - *from, center - *from);
+                let side = edge.x * offset.y - edge.y * offset.x;
+                positive |= side > 0.0;
+                negative |= side < 0.0;
+            }
+            if !(positive && negative) {
+                self.blend(x, y, color, alpha, clip);
+            }
+        });
+    }
+
+    /// Strokes the line through the points, its dashes or dots continuing from
+    /// one segment into the next.
+    fn polyline(&mut self, points : &[Pos2], color : Rgb, style : LineStyle, clip : Rect) {
+        // the lengths drawn and skipped along the line
+        let pattern = match style {
+            LineStyle::Solid => None,
+            LineStyle::Dashed { length } => Some((length, length / 2.0)),
+            LineStyle::Dotted { spacing } => Some((LINE_WIDTH, spacing))
+        };
+        let mut along = 0.0;
+        for (from, to) in points.iter().zip(points.iter().skip(1)) {
+            let (from, to) = (*from, *to);
+            let length = from.distance(to);
+            let reach = LINE_WIDTH / 2.0 + 1.0;
+            let bounds = Rect::from_two_pos(from, to).expand(reach);
+            Self::pixels_in(bounds.intersect(clip), |x, y, center| {
+                let t = if length > 0.0 {
+                    ((center - from).dot(to - from) / (length * length)).clamp(0.0, 1.0)
+                }
+                else {
+                    0.0
+                };
+                if let Some((on, off)) = pattern {
+                    if (along + t * length) % (on + off) > on {
+                        return;
+                    }
+                }
+                let distance = center.distance(from + (to - from) * t);
+                let coverage = (LINE_WIDTH / 2.0 + 0.5 - distance).clamp(0.0, 1.0);
+                self.blend(x, y, color, coverage, clip);
+            });
+            along += length;
+        }
+    }
+
+    /// Writes the text with its top left corner at the position.
+    fn text(&mut self, fonts : &Fonts, position : Pos2, size : f32, text : &str, color : Rgb) {
+        let everywhere = Rect::EVERYTHING;
+        let mut x = position.x;
+        for char in text.chars() {
+            let font = match fonts.font_for(char) {
+                Some(font) => font,
+                None => continue
+            };
+            let scaled = font.as_scaled(size);
+            let id = font.glyph_id(char);
+            let glyph = id.with_scale_and_position(size, point(x, position.y + scaled.ascent()));
+            x += scaled.h_advance(id);
+            if let Some(outline) = font.outline_glyph(glyph) {
+                let corner = outline.px_bounds().min;
+                outline.draw(|glyph_x, glyph_y, coverage| {
+                    let pixel_x = corner.x as i64 + i64::from(glyph_x);
+                    let pixel_y = corner.y as i64 + i64::from(glyph_y);
+                    self.blend(pixel_x, pixel_y, color, coverage, everywhere);
+                });
+            }
+        }
+    }
+
+    /// The image as a PNG file.
+    fn encode(&self) -> Result<Vec<u8>, String> {
+        let mut file = Vec::new();
+        let mut encoder = png::Encoder::new(&mut file, WIDTH as u32, HEIGHT as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(|error| error.to_string())?;
+        writer
+            .write_image_data(&self.pixels.concat())
+            .map_err(|error| error.to_string())?;
+        writer.finish().map_err(|error| error.to_string())?;
+        Ok(file)
+    }
+}
+
+/// Where the plot's coordinates lie in the image.
+struct Transform {
+    /// the lower left and upper right corner of the plot's view
+    min : [f64; 2],
+    max : [f64; 2],
+    plot : Rect
+}
+
+impl Transform {
+    fn x(&self, x : f64) -> f32 {
+        let (min, max) = (self.min[0], self.max[0]);
+        self.plot.left() + ((x - min) / (max - min)) as f32 * self.plot.width()
+    }
+
+    fn y(&self, y : f64) -> f32 {
+        let (min, max) = (self.min[1], self.max[1]);
+        self.plot.bottom() - ((y - min) / (max - min)) as f32 * self.plot.height()
+    }
+
+    fn pos(&self, [x, y] : [f64; 2]) -> Pos2 { pos2(self.x(x), self.y(y)) }
+
+    /// The rect spanned by the two corners in plot coordinates.
+    fn rect(&self, a : [f64; 2], b : [f64; 2]) -> Rect {
+        Rect::from_two_pos(self.pos(a), self.pos(b))
+    }
+}
+
+fn draw_item(raster : &mut Raster, item : &Item, transform : &Transform) {
+    let clip = transform.plot;
+    let color = item.color;
+    match &item.shape {
+        Shape::Line { points, style } => {
+            let points = points
+                .iter()
+                .map(|point| transform.pos(*point))
+                .collect::<Vec<_>>();
+            raster.polyline(&points, color, *style, clip);
+        },
+        Shape::HLine { y, style } => {
+            let y = transform.y(*y);
+            let ends = [pos2(clip.left(), y), pos2(clip.right(), y)];
+            raster.polyline(&ends, color, *style, clip);
+        },
+        Shape::Bars(bars) => {
+            for [argument, value, width] in bars {
+                let rect = transform.rect(
+                    [argument - width / 2.0, 0.0],
+                    [argument + width / 2.0, *value]
+                );
+                raster.fill_rect(rect, color, FILL_ALPHA, clip);
+                outline(raster, rect, color, clip);
+            }
+        },
+        Shape::Boxes(boxes) => {
+            for elem in boxes {
+                let spread = &elem.spread;
+                let (x, half_box) = (elem.argument, elem.box_width / 2.0);
+                let half_whisker = elem.whisker_width / 2.0;
+                let rect = transform.rect(
+                    [x - half_box, spread.quartile1],
+                    [x + half_box, spread.quartile3]
+                );
+                raster.fill_rect(rect, color, FILL_ALPHA, clip);
+                outline(raster, rect, color, clip);
+                let (upper, lower) = (spread.upper_whisker, spread.lower_whisker);
+                let segments = [
+                    ([x - half_box, spread.median], [x + half_box, spread.median]),
+                    ([x, spread.quartile3], [x, upper]),
+                    ([x, spread.quartile1], [x, lower]),
+                    ([x - half_whisker, upper], [x + half_whisker, upper]),
+                    ([x - half_whisker, lower], [x + half_whisker, lower])
+                ];
+                for (from, to) in segments {
+                    let ends = [transform.pos(from), transform.pos(to)];
+                    raster.polyline(&ends, color, LineStyle::Solid, clip);
+                }
+            }
+        },
+        Shape::Band {
+            lower,
+            upper,
+            alpha
+        } => {
+            let corners = lower
+                .iter()
+                .zip(upper.iter())
+                .map(|(low, high)| (transform.pos(*low), transform.pos(*high)));
+            for ((low_from, high_from), (low_to, high_to)) in corners.clone().zip(corners.skip(1)) {
+                let quad = [low_from, low_to, high_to, high_from];
+                raster.fill_convex(&quad, color, *alpha, clip);
+            }
+        }
+    }
+}
+
+fn outline(raster : &mut Raster, rect : Rect, color : Rgb, clip : Rect) {
+    let corners = [
+        rect.left_top(),
+        rect.right_top(),
+        rect.right_bottom(),
+        rect.left_bottom()
+    ];
+    let closed = [&corners[..], &corners[..1]].concat();
+    raster.polyline(&closed, color, LineStyle::Solid, clip);
+}
+
+/// The grid with the labels of the axes, the x axis labelled as the chart
+/// labels it. Labels that would overlap the previous one are left out.
+fn draw_axes(raster : &mut Raster, sketch : &Sketch, fonts : &Fonts, transform : &Transform) {
+    let plot = transform.plot;
+    let mut grid_line = |raster : &mut Raster, from : Pos2, to : Pos2| {
+        raster.polyline(&[from, to], GRID, LineStyle::Solid, plot);
+    };
+    let [min_x, min_y] = transform.min;
+    let [max_x, max_y] = transform.max;
+    let y_step = nice_step(max_y - min_y, 8.0);
+    for y in ticks(min_y, max_y, y_step) {
+        let pixel = transform.y(y);
+        grid_line(raster, pos2(plot.left(), pixel), pos2(plot.right(), pixel));
+        let label = tick_label(y, y_step);
+        let width = fonts.width(&label, LABEL_SIZE);
+        let position = pos2(plot.left() - width - 6.0, pixel - LABEL_SIZE / 2.0);
+        raster.text(fonts, position, LABEL_SIZE, &label, TEXT);
+    }
+    let x_labels = if sketch.x_labels.is_empty() {
+        let x_step = nice_step(max_x - min_x, 10.0).max(1.0);
+        ticks(min_x, max_x, x_step)
+            .into_iter()
+            .map(|x| (x, tick_label(x, x_step)))
+            .collect()
+    }
+    else {
+        sketch
+            .x_labels
+            .iter()
+            .filter(|(x, _label)| (min_x..=max_x).contains(x))
+            .cloned()
+            .collect()
+    };
+    let mut free_from = f32::MIN;
+    for (x, label) in x_labels {
+        let pixel = transform.x(x);
+        grid_line(raster, pos2(pixel, plot.top()), pos2(pixel, plot.bottom()));
+        let width = fonts.width(&label, LABEL_SIZE);
+        let left = pixel - width / 2.0;
+        if left >= free_from {
+            raster.text(
+                fonts,
+                pos2(left, plot.bottom() + 6.0),
+                LABEL_SIZE,
+                &label,
+                TEXT
+            );
+            free_from = left + width + 8.0;
+        }
+    }
+}
+
+/// The names of the items with their colors, items of the same name sharing
+/// an entry as in egui's legend.
+fn draw_legend(raster : &mut Raster, sketch : &Sketch, fonts : &Fonts, plot : Rect) {
+    let mut entries : Vec<(&str, Rgb)> = Vec::new();
+    for item in &sketch.items {
+        if !item.name.is_empty() && entries.iter().all(|(name, _color)| *name != item.name) {
+            entries.push((item.name.as_str(), item.color));
+        }
+    }
+    if entries.is_empty() {
+        return;
+    }
+    let row = LABEL_SIZE + 6.0;
+    let widest = entries
+        .iter()
+        .map(|(name, _color)| fonts.width(name, LABEL_SIZE))
+        .fold(0.0, f32::max);
+    let legend = Rect::from_min_size(
+        plot.left_top() + egui::vec2(8.0, 8.0),
+        egui::vec2(widest + 36.0, row * entries.len() as f32 + 8.0)
+    );
+    raster.fill_rect(legend, WHITE, 0.85, plot);
+    outline(raster, legend, FRAME, plot);
+    for (index, (name, color)) in entries.into_iter().enumerate() {
+        let top = legend.top() + 4.0 + row * index as f32;
+        let swatch = Rect::from_min_size(
+            pos2(legend.left() + 6.0, top + 3.0),
+            egui::vec2(14.0, LABEL_SIZE - 4.0)
+        );
+        raster.fill_rect(swatch, color, 1.0, plot);
+        raster.text(
+            fonts,
+            pos2(swatch.right() + 8.0, top),
+            LABEL_SIZE,
+            name,
+            TEXT
+        );
+    }
+}
+
+fn render(sketch : &Sketch, (min, max) : ([f64; 2], [f64; 2])) -> Raster {
+    let mut raster = Raster::new();
+    let fonts = Fonts::load();
+    let plot = Rect::from_min_max(
+        pos2(MARGIN_LEFT, MARGIN_TOP),
+        pos2(WIDTH as f32 - MARGIN_RIGHT, HEIGHT as f32 - MARGIN_BOTTOM)
+    );
+    let transform = Transform { min, max, plot };
+    raster.text(
+        &fonts,
+        pos2(MARGIN_LEFT, 10.0),
+        TITLE_SIZE,
+        &sketch.title,
+        TEXT
+    );
+    draw_axes(&mut raster, sketch, &fonts, &transform);
+    for item in &sketch.items {
+        draw_item(&mut raster, item, &transform);
+    }
+    outline(&mut raster, plot, FRAME, plot.expand(1.0));
+    draw_legend(&mut raster, sketch, &fonts, plot);
+    raster
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixel(raster : &Raster, x : f32, y : f32) -> Rgb {
+        raster.pixels[y as usize * WIDTH + x as usize]
+    }
+
+    #[test]
+    fn ticks_fall_on_round_numbers() {
+        assert_eq!(nice_step(100.0, 8.0), 20.0);
+        assert_eq!(nice_step(7.0, 8.0), 1.0);
+        assert_eq!(nice_step(0.3, 8.0), 0.05);
+        assert_eq!(
+            ticks(-0.5, 110.0, 20.0),
+            vec![0.0, 20.0, 40.0, 60.0, 80.0, 100.0]
+        );
+        assert_eq!(tick_label(0.15, 0.05), "0.15");
+        assert_eq!(tick_label(-1e-12, 1.0), "0");
+    }
+
+    #[test]
+    fn exported_bars_cover_their_values() {
+        let mut sketch = Sketch::new(true, "Eliwood".to_owned());
+        sketch.bars("", &[Bar::new(1.0, 50.0).width(1.0)]);
+        let raster = render(&sketch, ([0.0, 0.0], [2.0, 100.0]));
+        // the bar spans the middle half of the plot, reaching halfway up, and
+        // is tinted as egui tints the first item's bars
+        let x = (MARGIN_LEFT + (WIDTH as f32 - MARGIN_RIGHT)) / 2.0 + 30.0;
+        let half_height = (MARGIN_TOP + (HEIGHT as f32 - MARGIN_BOTTOM)) / 2.0;
+        let mut tinted = Raster::new();
+        tinted.blend(0, 0, auto_color(0), FILL_ALPHA, Rect::EVERYTHING);
+        assert_eq!(
+            pixel(&raster, x, half_height + 20.0),
+            pixel(&tinted, 0.0, 0.0)
+        );
+        assert_eq!(pixel(&raster, x, half_height - 20.0), WHITE);
+        assert_eq!(&raster.encode().unwrap()[..4], b"\x89PNG");
+
+        // nothing is recorded unless exporting
+        let mut idle = Sketch::new(false, String::new());
+        idle.bars("", &[Bar::new(1.0, 50.0)]);
+        assert!(idle.items.is_empty());
+    }
+}
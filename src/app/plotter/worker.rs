@@ -0,0 +1,178 @@
+//! Computing the plotter's data in a web worker, as the page has only the one
+//! thread that also draws it. The worker is the `compute_worker` binary trunk
+//! builds next to the app, requests and results passing between the two as
+//! ron text.
+
+use std::{cell::RefCell, rc::Rc, sync::Arc};
+
+use fe_levels::Character;
+use poll_promise::{Promise, Sender};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::{DedicatedWorkerGlobalScope, ErrorEvent, MessageEvent, Worker};
+
+use super::{compute, compute_or_report, ComputedData, DerivedData};
+use crate::app::{locale::tr_with, progression::ConcreteStatChange, sit::StatIndexType, toasts};
+
+/// the script loading the worker's wasm, which trunk copies next to the app
+const LOADER : &str = "./compute_worker_loader.js";
+/// what the worker posts once it listens, as requests posted before are lost
+const READY : &str = "ready";
+
+#[derive(Serialize, Deserialize)]
+struct Request {
+    character : Character<StatIndexType>,
+    progression : Vec<ConcreteStatChange>,
+    other_branch : Option<Vec<ConcreteStatChange>>,
+    num_samples : Option<u64>
+}
+
+type Response = Result<ComputedData, String>;
+
+fn js_error(error : JsValue) -> String { format!("{error:?}") }
+
+fn respond(request : &str) -> Response {
+    let request : Request = ron::from_str(request).map_err(|error| error.to_string())?;
+    Ok(compute(
+        request.character,
+        request.progression,
+        request.other_branch,
+        request.num_samples
+    ))
+}
+
+/// Runs in the worker, answering every request with the computed data.
+pub fn run_compute_worker() {
+    let scope : DedicatedWorkerGlobalScope = js_sys::global().unchecked_into();
+    let responder = scope.clone();
+    let on_message = Closure::wrap(Box::new(move |event : MessageEvent| {
+        let response = match event.data().as_string() {
+            Some(request) => respond(&request),
+            None => Err("the request isn't text".to_owned())
+        };
+        // the page hears of a worker that can't answer by its error event
+        if let Ok(text) = ron::to_string(&response) {
+            responder.post_message(&JsValue::from_str(&text)).ok();
+        }
+    }) as Box<dyn FnMut(MessageEvent)>);
+    scope.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    // the worker listens for as long as it lives
+    on_message.forget();
+    scope.post_message(&JsValue::from_str(READY)).ok();
+}
+
+/// where the result goes, taken by whichever of the result, an error or the
+/// computation being dropped comes first
+type Delivery = Rc<RefCell<Option<Sender<(u64, Arc<ComputedData>)>>>>;
+
+fn deliver(delivery : &Delivery, key : u64, computed : ComputedData) {
+    if let Some(sender) = delivery.borrow_mut().take() {
+        sender.send((key, Arc::new(computed)));
+    }
+}
+
+fn fail(ctx : &egui::Context, delivery : &Delivery, key : u64, reason : &str) {
+    if delivery.borrow().is_some() {
+        toasts::error(
+            ctx,
+            tr_with("plotter.compute_failed", &[("reason", &reason)])
+        );
+        deliver(delivery, key, ComputedData::failed());
+    }
+}
+
+/// A computation running in its own worker. The worker is terminated once it
+/// answered or the computation is dropped, e.g. with the tab of its unit.
+pub struct WorkerComputation {
+    worker : Worker,
+    key : u64,
+    delivery : Delivery,
+    _on_message : Closure<dyn FnMut(MessageEvent)>,
+    _on_error : Closure<dyn FnMut(ErrorEvent)>
+}
+
+impl Drop for WorkerComputation {
+    fn drop(&mut self) {
+        self.worker.terminate();
+        // a promise whose sender is dropped panics when polled
+        deliver(&self.delivery, self.key, ComputedData::failed());
+    }
+}
+
+fn start(
+    ctx : &egui::Context,
+    key : u64,
+    request : &Request
+) -> Result<(WorkerComputation, DerivedData), String> {
+    let text = ron::to_string(request).map_err(|error| error.to_string())?;
+    let worker = Worker::new(LOADER).map_err(js_error)?;
+    let (sender, promise) = Promise::new();
+    let delivery : Delivery = Rc::new(RefCell::new(Some(sender)));
+
+    let on_message = {
+        let (ctx, delivery, worker) = (ctx.clone(), delivery.clone(), worker.clone());
+        Closure::wrap(Box::new(move |event : MessageEvent| {
+            let message = event.data().as_string().unwrap_or_default();
+            if message == READY {
+                if let Err(error) = worker.post_message(&JsValue::from_str(&text)) {
+                    fail(&ctx, &delivery, key, &js_error(error));
+                }
+                return;
+            }
+            match ron::from_str::<Response>(&message) {
+                Ok(Ok(computed)) => deliver(&delivery, key, computed),
+                Ok(Err(reason)) => fail(&ctx, &delivery, key, &reason),
+                Err(error) => fail(&ctx, &delivery, key, &error.to_string())
+            }
+            worker.terminate();
+            ctx.request_repaint();
+        }) as Box<dyn FnMut(MessageEvent)>)
+    };
+    let on_error = {
+        let (ctx, delivery) = (ctx.clone(), delivery.clone());
+        Closure::wrap(Box::new(move |event : ErrorEvent| {
+            fail(&ctx, &delivery, key, &event.message());
+        }) as Box<dyn FnMut(ErrorEvent)>)
+    };
+    worker.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    worker.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+    let computation = WorkerComputation {
+        worker,
+        key,
+        delivery,
+        _on_message : on_message,
+        _on_error : on_error
+    };
+    Ok((computation, promise))
+}
+
+/// Starts computing the data in a new worker. Should no worker start, the data
+/// is computed right away on the page's thread.
+pub fn spawn(
+    ctx : &egui::Context,
+    key : u64,
+    character : Character<StatIndexType>,
+    progression : Vec<ConcreteStatChange>,
+    other_branch : Option<Vec<ConcreteStatChange>>,
+    num_samples : Option<u64>
+) -> (Option<WorkerComputation>, DerivedData) {
+    let request = Request {
+        character,
+        progression,
+        other_branch,
+        num_samples
+    };
+    match start(ctx, key, &request) {
+        Ok((computation, promise)) => (Some(computation), promise),
+        Err(_error) => {
+            let computed = compute_or_report(
+                ctx,
+                request.character,
+                request.progression,
+                request.other_branch,
+                request.num_samples
+            );
+            (None, Promise::from_ready((key, Arc::new(computed))))
+        }
+    }
+}
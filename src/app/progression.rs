@@ -1,29 +1,136 @@
 use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
     fmt,
-    ops::{Deref, DerefMut}
+    ops::{Deref, DerefMut, Range},
+    sync::Arc
 };
 
 use eframe::epaint;
 use egui::{
-    vec2, Button, Context, CursorIcon, Id, InnerResponse, Label, NumExt, Rect, Sense, Shape, Ui,
-    Vec2
+    reset_button_with, vec2, Button, Color32, Context, CursorIcon, Grid, Id, InnerResponse, Key,
+    Label, Modifiers, NumExt, Rect, Response, RichText, ScrollArea, Sense, Shape, Slider, TextEdit,
+    Ui, Vec2
 };
-use fe_levels::StatChange;
+use fe_levels::{Character, GrowthType, StatChange, StatType};
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
-use self::gba::GbaFeStatChange;
+pub use self::{
+    booster::find_booster,
+    gba::{afas_drops, GbaFeStatChange},
+    text::parse_progression,
+    transfer::{transfer_bonus_window, TransferDialog}
+};
+use self::{
+    custom::CustomStatChange, por::PorStatChange, rd::RdStatChange,
+    shadow_dragon::ShadowDragonStatChange, sov::SoVStatChange, three_houses::ThreeHousesStatChange
+};
 
-use super::{sit::StatIndexType, GameData, GameKind, UsefulId};
+use super::{
+    database::{builtin_promotions, ClassTier},
+    effective_character,
+    hints::{hint, HintKey},
+    layout::AppWindow,
+    locale::{tr, tr_with},
+    manager::DataManaged,
+    numerical_text_box, on_screen, onboarding,
+    plotter::PlotterManager,
+    session_log::log_action,
+    sit::StatIndexType,
+    slots, GameData, GameKind, GrowthModifiers, SavedCharacter, UsefulId
+};
 
+mod booster;
+mod custom;
 mod gba;
+mod por;
+mod rd;
+mod scroll;
+mod shadow_dragon;
+mod sov;
+mod text;
+mod three_houses;
+mod transfer;
 
 #[derive(Deserialize, Serialize, Default)]
 pub struct ProgressionManager {
+    /// the shown templates in the order the user arranged them
     templates : Vec<ConcreteStatChange>,
+    #[serde(default)]
+    hidden_templates : Vec<ConcreteStatChange>,
     progression : Vec<ConcreteStatChange>,
     id : UsefulId,
     queued_insertion : Option<(usize, ConcreteStatChange)>,
-    promotion_selection_strategy : PromotionSelectionKind
+    /// the entry being reconfigured by the queued insertion, restored on cancel
+    #[serde(default)]
+    reconfigured_original : Option<ConcreteStatChange>,
+    promotion_selection_strategy : PromotionSelectionKind,
+    #[serde(skip)]
+    history : EditHistory,
+    #[serde(skip)]
+    clear_requested : bool,
+    #[serde(skip)]
+    bulk_level_ups : usize,
+    #[serde(default)]
+    collapse_level_ups : bool,
+    /// the first entries of the level-up runs the user expanded again
+    #[serde(skip)]
+    expanded_runs : BTreeSet<usize>,
+    /// the progression as text while it is being edited as such
+    #[serde(skip)]
+    text_edit : Option<String>,
+    /// copied entries, kept by value so that they can be pasted into the
+    /// progression of another character
+    #[serde(skip)]
+    clipboard : Vec<ConcreteStatChange>,
+    /// the percentile exported characters take their stats from, the rounded
+    /// expected values if `None`
+    #[serde(default)]
+    export_percentile : Option<u8>,
+    #[serde(default)]
+    branch : Option<Branch>,
+    /// the progression as of the last frame, to tell on which side of the
+    /// fork point it has been edited since
+    #[serde(skip)]
+    last_seen : Option<Vec<ConcreteStatChange>>,
+    /// the progression before the queued insertion, described to the session
+    /// log once the insertion is done or cancelled
+    #[serde(skip)]
+    unlogged : Option<Vec<ConcreteStatChange>>
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum BranchName {
+    A,
+    B
+}
+
+impl BranchName {
+    pub fn other(self) -> Self {
+        match self {
+            BranchName::A => BranchName::B,
+            BranchName::B => BranchName::A
+        }
+    }
+}
+
+impl fmt::Display for BranchName {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BranchName::A => write!(f, "{}", tr("progression.branch_a")),
+            BranchName::B => write!(f, "{}", tr("progression.branch_b"))
+        }
+    }
+}
+
+/// A second continuation of the progression. The entries before the fork are
+/// shared, the edited branch lives in the progression itself and the other
+/// one's entries after the fork are kept here.
+#[derive(Deserialize, Serialize)]
+struct Branch {
+    fork : usize,
+    other_tail : Vec<ConcreteStatChange>,
+    editing : BranchName
 }
 
 impl Deref for ProgressionManager {
@@ -37,18 +144,289 @@ impl DerefMut for ProgressionManager {
 
 impl ProgressionManager {
     fn id(&self) -> Id { Id::new(self.id) }
+
+    /// Remembers the current progression so that the next change to it can be
+    /// undone, has to be called before every such change.
+    pub fn checkpoint(&mut self) {
+        self.history.undo.push_front(self.progression.clone());
+        self.history.undo.truncate(HISTORY_LIMIT);
+        self.history.redo.clear();
+    }
+
+    /// Restores the progression to its state before the queued insertion, the
+    /// insertion itself having been taken out already.
+    fn cancel_queued_insertion(&mut self, index : usize) {
+        if let Some(original) = self.reconfigured_original.take() {
+            self.progression
+                .insert(index.at_most(self.progression.len()), original);
+        }
+        // the checkpoint taken when queueing would only undo to this very state
+        self.history.undo.pop_front();
+    }
+
+    /// Reverts the last change without the option to redo it, used for changes
+    /// that would drop a growth item before it was equipped.
+    fn refuse_edit(&mut self) {
+        if let Some(previous) = self.history.undo.pop_front() {
+            self.progression = previous;
+        }
+    }
+
+    fn undo(&mut self) {
+        if let Some(previous) = self.history.undo.pop_front() {
+            let current = std::mem::replace(&mut self.progression, previous);
+            self.history.redo.push_front(current);
+        }
+    }
+
+    fn redo(&mut self) {
+        if let Some(next) = self.history.redo.pop_front() {
+            let current = std::mem::replace(&mut self.progression, next);
+            self.history.undo.push_front(current);
+            self.history.undo.truncate(HISTORY_LIMIT);
+        }
+    }
+
+    /// the name of the branch being edited, if the progression is forked
+    pub fn editing_branch(&self) -> Option<BranchName> {
+        self.branch.as_ref().map(|branch| branch.editing)
+    }
+
+    /// The complete progression of the branch not being edited, if any.
+    pub fn other_branch(&self) -> Option<Vec<ConcreteStatChange>> {
+        self.branch.as_ref().map(|branch| {
+            self.progression[..branch.fork.at_most(self.progression.len())]
+                .iter()
+                .chain(branch.other_tail.iter())
+                .cloned()
+                .collect()
+        })
+    }
+
+    /// Forks the progression so that both branches share the first `fork`
+    /// entries and start out identical afterwards.
+    fn fork(&mut self, fork : usize) {
+        self.branch = Some(Branch {
+            fork,
+            other_tail : self.progression[fork..].to_vec(),
+            editing : BranchName::A
+        });
+    }
+
+    fn switch_branch(&mut self) {
+        if let Some(branch) = &mut self.branch {
+            let fork = branch.fork.at_most(self.progression.len());
+            let tail = self.progression.split_off(fork);
+            self.progression
+                .extend(std::mem::replace(&mut branch.other_tail, tail));
+            branch.editing = branch.editing.other();
+            // this is no edit, so the fork point must stay put
+            self.last_seen = Some(self.progression.clone());
+            // and the undo history belongs to the other branch now
+            self.history = Default::default();
+        }
+    }
+
+    /// Moves the fork point along with edits in front of it, edits behind it
+    /// only affect the edited branch. Edits spanning the fork point keep it in
+    /// place.
+    fn track_fork(&mut self) {
+        let last_seen = self.last_seen.replace(self.progression.clone());
+        if let (Some(branch), Some(old)) = (&mut self.branch, last_seen) {
+            let new = &self.progression;
+            let prefix = old
+                .iter()
+                .zip(new.iter())
+                .take_while(|(a, b)| a == b)
+                .count();
+            let suffix = old[prefix..]
+                .iter()
+                .rev()
+                .zip(new[prefix..].iter().rev())
+                .take_while(|(a, b)| a == b)
+                .count();
+            let changed_end = old.len() - suffix;
+            if changed_end < branch.fork || (changed_end == branch.fork && prefix < branch.fork) {
+                branch.fork = (branch.fork + new.len()).saturating_sub(old.len());
+            }
+            branch.fork = branch.fork.at_most(new.len());
+        }
+    }
+
+    fn paste(&mut self, index : usize) {
+        self.checkpoint();
+        let index = index.at_most(self.progression.len());
+        self.progression
+            .splice(index..index, self.clipboard.iter().cloned());
+    }
+
+    fn move_entry(&mut self, from : usize, to : usize) {
+        self.checkpoint();
+        let entry = self.progression.remove(from);
+        self.progression.insert(to, entry);
+    }
+
+    /// Keeps the user's order and hidden templates for all available ones,
+    /// dropping those no longer available and appending new ones at the end.
+    fn sync_templates(&mut self, available : Vec<ConcreteStatChange>) {
+        let mut remaining = available;
+        let mut keep_available = |templates : &mut Vec<ConcreteStatChange>| {
+            templates.retain(|template| {
+                match remaining.iter().position(|available| available == template) {
+                    Some(index) => {
+                        remaining.remove(index);
+                        true
+                    },
+                    None => false
+                }
+            })
+        };
+        keep_available(&mut self.templates);
+        keep_available(&mut self.hidden_templates);
+        self.templates.extend(remaining);
+    }
+
+    fn move_template(&mut self, from : usize, to : usize) {
+        let template = self.templates.remove(from);
+        self.templates
+            .insert(to.at_most(self.templates.len()), template);
+    }
+
+    fn hide_template(&mut self, index : usize) {
+        let template = self.templates.remove(index);
+        self.hidden_templates.push(template);
+    }
+
+    /// The game's template for a plain level-up, if it has one that can be
+    /// inserted without further clarification.
+    fn level_up_template(&self) -> Option<&ConcreteStatChange> {
+        self.templates
+            .iter()
+            .chain(&self.hidden_templates)
+            .find(|template| {
+                template.increases_level_counter() && !template.requires_clarification()
+            })
+    }
+
+    /// Groups the progression into the rows shown in the builder, runs of
+    /// plain level-ups sharing one row while collapsed. Insertions can then
+    /// only target the boundaries of a run, never its inside.
+    fn display_rows(&self) -> Vec<Range<usize>> {
+        let level_up = self.level_up_template().filter(|_| self.collapse_level_ups);
+        let mut rows = vec![];
+        let mut start = 0;
+        while start < self.progression.len() {
+            let run_length = self.progression[start..]
+                .iter()
+                .take_while(|entry| level_up == Some(*entry))
+                .count();
+            let end = start + run_length.at_least(1);
+            if run_length > 1 && !self.expanded_runs.contains(&start) {
+                rows.push(start..end);
+            }
+            else {
+                rows.extend((start..end).map(|row| row..row + 1));
+            }
+            start = end;
+        }
+        rows
+    }
+
+    fn insert_level_ups(&mut self, index : usize) {
+        if let Some(level_up) = self.level_up_template().cloned() {
+            self.checkpoint();
+            let index = index.at_most(self.progression.len());
+            self.progression.splice(
+                index..index,
+                std::iter::repeat(level_up).take(self.bulk_level_ups)
+            );
+        }
+    }
+}
+
+/// the most level-ups that can be inserted at once
+const MAX_BULK_LEVEL_UPS : usize = 99;
+
+/// the number of progression edits that can be undone
+const HISTORY_LIMIT : usize = 50;
+
+/// Earlier and later states of the progression, the most recent one in front.
+#[derive(Default)]
+struct EditHistory {
+    undo : VecDeque<Vec<ConcreteStatChange>>,
+    redo : VecDeque<Vec<ConcreteStatChange>>
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ConcreteStatChange {
-    GbaFeStatChange(GbaFeStatChange)
+    GbaFeStatChange(GbaFeStatChange),
+    PorStatChange(PorStatChange),
+    RdStatChange(RdStatChange),
+    ShadowDragonStatChange(ShadowDragonStatChange),
+    SoVStatChange(SoVStatChange),
+    ThreeHousesStatChange(ThreeHousesStatChange),
+    CustomStatChange(CustomStatChange)
+}
+
+/// A signed change of a growth in percentage points.
+pub type GrowthModifier = i32;
+
+/// Entries bracketing the level-ups an item changes the growths of while it's
+/// held, e.g. PoR's bands or FE5's scrolls.
+pub enum GrowthScope {
+    /// the item's name and its growth modifiers
+    Start(String, BTreeMap<StatIndexType, GrowthModifier>),
+    /// drops the most recently equipped item of the given name, or of any name
+    End(Option<String>)
+}
+
+/// The items held at some point in the order they were equipped.
+type HeldItems = Vec<(String, BTreeMap<StatIndexType, GrowthModifier>)>;
+
+/// Equips or drops the item of the scope, false if it drops an item that isn't
+/// held.
+fn update_held_items(held : &mut HeldItems, scope : Option<GrowthScope>) -> bool {
+    match scope {
+        Some(GrowthScope::Start(name, modifiers)) => held.push((name, modifiers)),
+        Some(GrowthScope::End(name)) => {
+            let position = match name {
+                Some(name) => held.iter().rposition(|(held_name, _)| *held_name == name),
+                None => held.len().checked_sub(1)
+            };
+            match position {
+                Some(position) => {
+                    held.remove(position);
+                },
+                None => return false
+            }
+        },
+        None => {}
+    }
+    true
 }
 
 pub trait UsefulStatChange: fmt::Display {
     fn compile(self) -> StatChange<StatIndexType>;
     fn cheap_to_execute(&self) -> bool;
+    /// relative cost of simulating this change for one sample, a plain
+    /// level-up costing 1, only needed to estimate the runtime in a browser
+    #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+    fn simulation_cost(&self) -> f64;
     fn increases_level_counter(&self) -> bool;
     fn resets_level_counter(&self) -> bool;
+    /// the level cap of the class a promotion promotes out of if it deviates
+    /// from the game's usual one, e.g. for FE8's trainees
+    fn level_cap_before(&self) -> Option<usize>;
+    /// the level a promotion requires the class promoted out of to have
+    /// reached, e.g. 10 for GBA's Master Seal
+    fn promotion_level(&self) -> Option<usize>;
+    /// whether this starts or ends the scope of a held growth item
+    fn growth_scope(&self) -> Option<GrowthScope>;
+    /// the growth bonuses of the class this changes into, replacing those of
+    /// the previous class, e.g. for Three Houses
+    fn class_growths(&self) -> Option<BTreeMap<StatIndexType, GrowthModifier>>;
+    /// what the entry does to the unit, for the help window
+    fn explanation(&self) -> String;
     fn generate_templates(game_option : GameKind) -> Vec<Self>
     where
         Self : Sized;
@@ -64,25 +442,121 @@ pub trait UsefulStatChange: fmt::Display {
 impl UsefulStatChange for ConcreteStatChange {
     fn compile(self) -> StatChange<StatIndexType> {
         match self {
-            ConcreteStatChange::GbaFeStatChange(data) => data.compile()
+            ConcreteStatChange::GbaFeStatChange(data) => data.compile(),
+            ConcreteStatChange::PorStatChange(data) => data.compile(),
+            ConcreteStatChange::RdStatChange(data) => data.compile(),
+            ConcreteStatChange::ShadowDragonStatChange(data) => data.compile(),
+            ConcreteStatChange::SoVStatChange(data) => data.compile(),
+            ConcreteStatChange::ThreeHousesStatChange(data) => data.compile(),
+            ConcreteStatChange::CustomStatChange(data) => data.compile()
         }
     }
 
     fn cheap_to_execute(&self) -> bool {
         match self {
-            ConcreteStatChange::GbaFeStatChange(data) => data.cheap_to_execute()
+            ConcreteStatChange::GbaFeStatChange(data) => data.cheap_to_execute(),
+            ConcreteStatChange::PorStatChange(data) => data.cheap_to_execute(),
+            ConcreteStatChange::RdStatChange(data) => data.cheap_to_execute(),
+            ConcreteStatChange::ShadowDragonStatChange(data) => data.cheap_to_execute(),
+            ConcreteStatChange::SoVStatChange(data) => data.cheap_to_execute(),
+            ConcreteStatChange::ThreeHousesStatChange(data) => data.cheap_to_execute(),
+            ConcreteStatChange::CustomStatChange(data) => data.cheap_to_execute()
+        }
+    }
+
+    fn simulation_cost(&self) -> f64 {
+        match self {
+            ConcreteStatChange::GbaFeStatChange(data) => data.simulation_cost(),
+            ConcreteStatChange::PorStatChange(data) => data.simulation_cost(),
+            ConcreteStatChange::RdStatChange(data) => data.simulation_cost(),
+            ConcreteStatChange::ShadowDragonStatChange(data) => data.simulation_cost(),
+            ConcreteStatChange::SoVStatChange(data) => data.simulation_cost(),
+            ConcreteStatChange::ThreeHousesStatChange(data) => data.simulation_cost(),
+            ConcreteStatChange::CustomStatChange(data) => data.simulation_cost()
         }
     }
 
     fn increases_level_counter(&self) -> bool {
         match self {
-            ConcreteStatChange::GbaFeStatChange(data) => data.increases_level_counter()
+            ConcreteStatChange::GbaFeStatChange(data) => data.increases_level_counter(),
+            ConcreteStatChange::PorStatChange(data) => data.increases_level_counter(),
+            ConcreteStatChange::RdStatChange(data) => data.increases_level_counter(),
+            ConcreteStatChange::ShadowDragonStatChange(data) => data.increases_level_counter(),
+            ConcreteStatChange::SoVStatChange(data) => data.increases_level_counter(),
+            ConcreteStatChange::ThreeHousesStatChange(data) => data.increases_level_counter(),
+            ConcreteStatChange::CustomStatChange(data) => data.increases_level_counter()
         }
     }
 
     fn resets_level_counter(&self) -> bool {
         match self {
-            ConcreteStatChange::GbaFeStatChange(data) => data.resets_level_counter()
+            ConcreteStatChange::GbaFeStatChange(data) => data.resets_level_counter(),
+            ConcreteStatChange::PorStatChange(data) => data.resets_level_counter(),
+            ConcreteStatChange::RdStatChange(data) => data.resets_level_counter(),
+            ConcreteStatChange::ShadowDragonStatChange(data) => data.resets_level_counter(),
+            ConcreteStatChange::SoVStatChange(data) => data.resets_level_counter(),
+            ConcreteStatChange::ThreeHousesStatChange(data) => data.resets_level_counter(),
+            ConcreteStatChange::CustomStatChange(data) => data.resets_level_counter()
+        }
+    }
+
+    fn level_cap_before(&self) -> Option<usize> {
+        match self {
+            ConcreteStatChange::GbaFeStatChange(data) => data.level_cap_before(),
+            ConcreteStatChange::PorStatChange(data) => data.level_cap_before(),
+            ConcreteStatChange::RdStatChange(data) => data.level_cap_before(),
+            ConcreteStatChange::ShadowDragonStatChange(data) => data.level_cap_before(),
+            ConcreteStatChange::SoVStatChange(data) => data.level_cap_before(),
+            ConcreteStatChange::ThreeHousesStatChange(data) => data.level_cap_before(),
+            ConcreteStatChange::CustomStatChange(data) => data.level_cap_before()
+        }
+    }
+
+    fn promotion_level(&self) -> Option<usize> {
+        match self {
+            ConcreteStatChange::GbaFeStatChange(data) => data.promotion_level(),
+            ConcreteStatChange::PorStatChange(data) => data.promotion_level(),
+            ConcreteStatChange::RdStatChange(data) => data.promotion_level(),
+            ConcreteStatChange::ShadowDragonStatChange(data) => data.promotion_level(),
+            ConcreteStatChange::SoVStatChange(data) => data.promotion_level(),
+            ConcreteStatChange::ThreeHousesStatChange(data) => data.promotion_level(),
+            ConcreteStatChange::CustomStatChange(data) => data.promotion_level()
+        }
+    }
+
+    fn growth_scope(&self) -> Option<GrowthScope> {
+        match self {
+            ConcreteStatChange::GbaFeStatChange(data) => data.growth_scope(),
+            ConcreteStatChange::PorStatChange(data) => data.growth_scope(),
+            ConcreteStatChange::RdStatChange(data) => data.growth_scope(),
+            ConcreteStatChange::ShadowDragonStatChange(data) => data.growth_scope(),
+            ConcreteStatChange::SoVStatChange(data) => data.growth_scope(),
+            ConcreteStatChange::ThreeHousesStatChange(data) => data.growth_scope(),
+            ConcreteStatChange::CustomStatChange(data) => data.growth_scope()
+        }
+    }
+
+    fn class_growths(&self) -> Option<BTreeMap<StatIndexType, GrowthModifier>> {
+        match self {
+            ConcreteStatChange::GbaFeStatChange(data) => data.class_growths(),
+            ConcreteStatChange::PorStatChange(data) => data.class_growths(),
+            ConcreteStatChange::RdStatChange(data) => data.class_growths(),
+            ConcreteStatChange::ShadowDragonStatChange(data) => data.class_growths(),
+            ConcreteStatChange::SoVStatChange(data) => data.class_growths(),
+            ConcreteStatChange::ThreeHousesStatChange(data) => data.class_growths(),
+            ConcreteStatChange::CustomStatChange(data) => data.class_growths()
+        }
+    }
+
+    fn explanation(&self) -> String {
+        match self {
+            ConcreteStatChange::GbaFeStatChange(data) => data.explanation(),
+            ConcreteStatChange::PorStatChange(data) => data.explanation(),
+            ConcreteStatChange::RdStatChange(data) => data.explanation(),
+            ConcreteStatChange::ShadowDragonStatChange(data) => data.explanation(),
+            ConcreteStatChange::SoVStatChange(data) => data.explanation(),
+            ConcreteStatChange::ThreeHousesStatChange(data) => data.explanation(),
+            ConcreteStatChange::CustomStatChange(data) => data.explanation()
         }
     }
 
@@ -92,13 +566,46 @@ impl UsefulStatChange for ConcreteStatChange {
                 .into_iter()
                 .map(ConcreteStatChange::GbaFeStatChange)
                 .collect(),
-            GameKind::PoR => vec![]
+            GameKind::PoR => PorStatChange::generate_templates(GameKind::PoR)
+                .into_iter()
+                .map(ConcreteStatChange::PorStatChange)
+                .collect(),
+            GameKind::RD => RdStatChange::generate_templates(GameKind::RD)
+                .into_iter()
+                .map(ConcreteStatChange::RdStatChange)
+                .collect(),
+            GameKind::ShadowDragon => {
+                ShadowDragonStatChange::generate_templates(GameKind::ShadowDragon)
+                    .into_iter()
+                    .map(ConcreteStatChange::ShadowDragonStatChange)
+                    .collect()
+            },
+            GameKind::SoV => SoVStatChange::generate_templates(GameKind::SoV)
+                .into_iter()
+                .map(ConcreteStatChange::SoVStatChange)
+                .collect(),
+            GameKind::ThreeHouses => {
+                ThreeHousesStatChange::generate_templates(GameKind::ThreeHouses)
+                    .into_iter()
+                    .map(ConcreteStatChange::ThreeHousesStatChange)
+                    .collect()
+            },
+            GameKind::Custom => CustomStatChange::generate_templates(GameKind::Custom)
+                .into_iter()
+                .map(ConcreteStatChange::CustomStatChange)
+                .collect()
         }
     }
 
     fn marking_worthy(&self) -> bool {
         match self {
-            ConcreteStatChange::GbaFeStatChange(data) => data.marking_worthy()
+            ConcreteStatChange::GbaFeStatChange(data) => data.marking_worthy(),
+            ConcreteStatChange::PorStatChange(data) => data.marking_worthy(),
+            ConcreteStatChange::RdStatChange(data) => data.marking_worthy(),
+            ConcreteStatChange::ShadowDragonStatChange(data) => data.marking_worthy(),
+            ConcreteStatChange::SoVStatChange(data) => data.marking_worthy(),
+            ConcreteStatChange::ThreeHousesStatChange(data) => data.marking_worthy(),
+            ConcreteStatChange::CustomStatChange(data) => data.marking_worthy()
         }
     }
 
@@ -107,13 +614,79 @@ impl UsefulStatChange for ConcreteStatChange {
             ConcreteStatChange::GbaFeStatChange(data) => {
                 let (data, ready) = data.clarification_dialogue(context, ui);
                 (ConcreteStatChange::GbaFeStatChange(data), ready)
+            },
+            ConcreteStatChange::PorStatChange(data) => {
+                let (data, ready) = data.clarification_dialogue(context, ui);
+                (ConcreteStatChange::PorStatChange(data), ready)
+            },
+            ConcreteStatChange::RdStatChange(data) => {
+                let (data, ready) = data.clarification_dialogue(context, ui);
+                (ConcreteStatChange::RdStatChange(data), ready)
+            },
+            ConcreteStatChange::ShadowDragonStatChange(data) => {
+                let (data, ready) = data.clarification_dialogue(context, ui);
+                (ConcreteStatChange::ShadowDragonStatChange(data), ready)
+            },
+            ConcreteStatChange::SoVStatChange(data) => {
+                let (data, ready) = data.clarification_dialogue(context, ui);
+                (ConcreteStatChange::SoVStatChange(data), ready)
+            },
+            ConcreteStatChange::ThreeHousesStatChange(data) => {
+                let (data, ready) = data.clarification_dialogue(context, ui);
+                (ConcreteStatChange::ThreeHousesStatChange(data), ready)
+            },
+            ConcreteStatChange::CustomStatChange(data) => {
+                let (data, ready) = data.clarification_dialogue(context, ui);
+                (ConcreteStatChange::CustomStatChange(data), ready)
             }
         }
     }
 
     fn requires_clarification(&self) -> bool {
         match self {
-            ConcreteStatChange::GbaFeStatChange(data) => data.requires_clarification()
+            ConcreteStatChange::GbaFeStatChange(data) => data.requires_clarification(),
+            ConcreteStatChange::PorStatChange(data) => data.requires_clarification(),
+            ConcreteStatChange::RdStatChange(data) => data.requires_clarification(),
+            ConcreteStatChange::ShadowDragonStatChange(data) => data.requires_clarification(),
+            ConcreteStatChange::SoVStatChange(data) => data.requires_clarification(),
+            ConcreteStatChange::ThreeHousesStatChange(data) => data.requires_clarification(),
+            ConcreteStatChange::CustomStatChange(data) => data.requires_clarification()
+        }
+    }
+}
+
+impl ConcreteStatChange {
+    /// The hint explaining the entry, if it has one.
+    pub fn hint_key(&self) -> Option<HintKey> {
+        match self {
+            ConcreteStatChange::GbaFeStatChange(GbaFeStatChange::LevelUp) => {
+                Some(HintKey::LevelUp(GameKind::GbaFe))
+            },
+            ConcreteStatChange::PorStatChange(PorStatChange::LevelUp) => {
+                Some(HintKey::LevelUp(GameKind::PoR))
+            },
+            ConcreteStatChange::RdStatChange(RdStatChange::LevelUp) => {
+                Some(HintKey::LevelUp(GameKind::RD))
+            },
+            ConcreteStatChange::ShadowDragonStatChange(ShadowDragonStatChange::LevelUp) => {
+                Some(HintKey::LevelUp(GameKind::ShadowDragon))
+            },
+            ConcreteStatChange::SoVStatChange(SoVStatChange::LevelUp) => {
+                Some(HintKey::LevelUp(GameKind::SoV))
+            },
+            ConcreteStatChange::ThreeHousesStatChange(ThreeHousesStatChange::LevelUp) => {
+                Some(HintKey::LevelUp(GameKind::ThreeHouses))
+            },
+            ConcreteStatChange::CustomStatChange(CustomStatChange::LevelUp) => {
+                Some(HintKey::LevelUp(GameKind::Custom))
+            },
+            ConcreteStatChange::GbaFeStatChange(GbaFeStatChange::StatBooster(stat, amount))
+            | ConcreteStatChange::PorStatChange(PorStatChange::StatBooster(stat, amount))
+            | ConcreteStatChange::RdStatChange(RdStatChange::StatBooster(stat, amount))
+            | ConcreteStatChange::CustomStatChange(CustomStatChange::StatBooster(stat, amount)) => {
+                Some(HintKey::StatBooster(*stat, *amount))
+            },
+            _ => None
         }
     }
 }
@@ -121,11 +694,31 @@ impl UsefulStatChange for ConcreteStatChange {
 impl fmt::Display for ConcreteStatChange {
     fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            ConcreteStatChange::GbaFeStatChange(sc) => fmt::Display::fmt(sc, f)
+            ConcreteStatChange::GbaFeStatChange(sc) => fmt::Display::fmt(sc, f),
+            ConcreteStatChange::PorStatChange(sc) => fmt::Display::fmt(sc, f),
+            ConcreteStatChange::RdStatChange(sc) => fmt::Display::fmt(sc, f),
+            ConcreteStatChange::ShadowDragonStatChange(sc) => fmt::Display::fmt(sc, f),
+            ConcreteStatChange::SoVStatChange(sc) => fmt::Display::fmt(sc, f),
+            ConcreteStatChange::ThreeHousesStatChange(sc) => fmt::Display::fmt(sc, f),
+            ConcreteStatChange::CustomStatChange(sc) => fmt::Display::fmt(sc, f)
         }
     }
 }
 
+/// How the level-ups and promotions of the game work, as the analysis models
+/// them.
+pub fn game_help(game_option : GameKind) -> String {
+    tr(match game_option {
+        GameKind::GbaFe => gba::HELP,
+        GameKind::PoR => por::HELP,
+        GameKind::RD => rd::HELP,
+        GameKind::ShadowDragon => shadow_dragon::HELP,
+        GameKind::SoV => sov::HELP,
+        GameKind::ThreeHouses => three_houses::HELP,
+        GameKind::Custom => custom::HELP
+    })
+}
+
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum PromotionSelectionKind {
     LoadSavedPromotion,
@@ -133,13 +726,118 @@ pub enum PromotionSelectionKind {
     ManualPromotionEntry
 }
 
+/// Lets the user enter the promotion gains and new caps or load a saved
+/// promotion, true on return indicates the user confirmed the promotion.
+/// `value_label` names what the entered values mean to the game, e.g. a gain.
+fn promotion_dialogue(
+    promotion_gains : &mut Character<StatIndexType>,
+    value_label : &str,
+    from_tier : Option<ClassTier>,
+    context : &mut GameData,
+    ui : &mut Ui
+) -> bool {
+    ui.horizontal(|ui| {
+        ui.radio_value(
+            &mut context.progression.promotion_selection_strategy,
+            PromotionSelectionKind::ManualPromotionEntry,
+            tr("promotion.manual")
+        );
+        ui.radio_value(
+            &mut context.progression.promotion_selection_strategy,
+            PromotionSelectionKind::LoadSavedPromotion,
+            tr("promotion.saved")
+        );
+    });
+
+    match context.progression.promotion_selection_strategy {
+        PromotionSelectionKind::LoadSavedPromotion => {
+            // the user's own promotions come first, shadowing equally named
+            // built-in ones
+            let builtins = builtin_promotions(context.game_option, &context.promotions, from_tier);
+            ScrollArea::vertical().show_rows(
+                ui,
+                ui.text_style_height(&egui::TextStyle::Body),
+                context.promotions.len() + builtins.len(),
+                |ui, range| {
+                    let saved = context
+                        .promotions
+                        .iter()
+                        .map(|(name, promo)| (RichText::new(name), promo));
+                    let shipped = builtins
+                        .iter()
+                        .map(|(name, promo)| (RichText::new(name).italics(), promo));
+                    for (name, promo) in saved.chain(shipped).take(range.end).skip(range.start) {
+                        ui.selectable_value(promotion_gains, promo.clone(), name);
+                        ui.end_row();
+                    }
+                }
+            );
+            ui.add_enabled(
+                context.promotions.contains_key(&promotion_gains.name)
+                    || builtins.contains_key(&promotion_gains.name),
+                Button::new(tr("promotion.load"))
+            )
+            .on_disabled_hover_text(tr("promotion.load_hint"))
+            .clicked()
+        },
+        PromotionSelectionKind::ManualPromotionEntry => {
+            ui.label(tr("promotion.target_class"));
+            ui.text_edit_singleline(&mut promotion_gains.name);
+            // the level of a promotion is the one it requires
+            ui.horizontal(|ui| {
+                ui.label(tr("promotion.required_level"));
+                numerical_text_box(ui, &mut promotion_gains.level);
+            });
+            Grid::new("Promotion Grid").num_columns(3).show(ui, |ui| {
+                ui.label("");
+                ui.label(value_label);
+                ui.label(tr("promotion.new_cap"));
+                ui.end_row();
+
+                for (sit, stat) in promotion_gains
+                    .stats
+                    .iter_mut()
+                    .sorted_by_key(|(sit, _stat)| sit.display_order())
+                {
+                    ui.label(format!("{sit}"));
+                    numerical_text_box(ui, &mut stat.value);
+                    numerical_text_box(ui, &mut stat.cap);
+                    ui.end_row();
+                }
+            });
+            let mut confirmed = false;
+            ui.horizontal(|ui| {
+                let name = &promotion_gains.name;
+                confirmed = ui
+                    .add_enabled(!name.is_empty(), Button::new(tr("common.confirm")))
+                    .on_disabled_hover_text(tr("promotion.confirm_hint"))
+                    .clicked();
+
+                if ui
+                    .add_enabled(
+                        context.promotions.check_legal_name(&promotion_gains.name),
+                        Button::new(tr("promotion.save"))
+                    )
+                    .on_disabled_hover_text(tr("promotion.save_hint"))
+                    .clicked()
+                {
+                    context
+                        .promotions
+                        .insert(promotion_gains.name.clone(), promotion_gains.clone());
+                }
+            });
+            confirmed
+        }
+    }
+}
+
 pub fn drag_source(
     ui : &mut Ui,
     id : Id,
     keep_showing_original : bool,
     mut drag_handle : impl FnMut(&mut Ui),
     context_menu : Option<impl FnOnce(&mut Ui)>
-) -> Option<Rect> {
+) -> Option<InnerResponse<Response>> {
     let is_being_dragged = ui.memory().is_being_dragged(id);
 
     if !is_being_dragged {
@@ -154,11 +852,14 @@ pub fn drag_source(
             }
 
             if let Some(context_menu) = context_menu {
-                response.context_menu(context_menu);
+                response.context_menu(context_menu)
+            }
+            else {
+                response
             }
         });
 
-        return Some(row_resp.response.rect);
+        return Some(row_resp);
     }
     else {
         ui.output().cursor_icon = CursorIcon::Grabbing;
@@ -185,50 +886,82 @@ pub fn drag_source(
     None
 }
 
+/// the height of the zones at the top and bottom of a column that scroll it
+/// while dragging over them
+const AUTO_SCROLL_MARGIN : f32 = 24.0;
+
 fn drop_target<R>(
     ui : &mut Ui,
     is_being_dragged : bool,
-    _scroll_id : BuilderColumn,
+    scroll_id : BuilderColumn,
     body : impl FnOnce(&mut Ui) -> R
 ) -> InnerResponse<R> {
     let margin = Vec2::splat(4.0);
-    /*ScrollArea::vertical()
-    .id_source(scroll_id)
-    .auto_shrink([true, true])
-    .show(ui, |ui| {*/
-    // perhaps show_rows works better here?
-    let outer_rect_bounds = ui.available_rect_before_wrap();
-    let inner_rect = outer_rect_bounds.shrink2(margin);
-    let where_to_put_background = ui.painter().add(Shape::Noop);
-
-    let mut content_ui = ui.child_ui(inner_rect, *ui.layout());
-
-    let ret = body(&mut content_ui);
-    let outer_rect = Rect::from_min_max(outer_rect_bounds.min, content_ui.min_rect().max + margin);
-    let (rect, response) = ui.allocate_at_least(outer_rect.size(), Sense::hover());
+    ScrollArea::vertical()
+        .id_source(scroll_id)
+        .auto_shrink([true, true])
+        .show(ui, |ui| {
+            let outer_rect_bounds = ui.available_rect_before_wrap();
+            let inner_rect = outer_rect_bounds.shrink2(margin);
+            let where_to_put_background = ui.painter().add(Shape::Noop);
+
+            let mut content_ui = ui.child_ui(inner_rect, *ui.layout());
+
+            let ret = body(&mut content_ui);
+            let outer_rect =
+                Rect::from_min_max(outer_rect_bounds.min, content_ui.min_rect().max + margin);
+            // the response rect moves with the scrolled content, so positions relative to
+            // it already account for the scroll offset
+            let (rect, response) = ui.allocate_at_least(outer_rect.size(), Sense::hover());
+
+            let style = if is_being_dragged && response.hovered() {
+                ui.visuals().widgets.active
+            }
+            else {
+                ui.visuals().widgets.inactive
+            };
+
+            let fill = style.bg_fill;
+            let stroke = style.bg_stroke;
+
+            ui.painter().set(
+                where_to_put_background,
+                epaint::RectShape {
+                    rounding : style.rounding,
+                    fill,
+                    stroke,
+                    rect
+                }
+            );
 
-    let style = if is_being_dragged && response.hovered() {
-        ui.visuals().widgets.active
-    }
-    else {
-        ui.visuals().widgets.inactive
-    };
+            if ui.memory().is_anything_being_dragged() {
+                auto_scroll(ui);
+            }
 
-    let fill = style.bg_fill;
-    let stroke = style.bg_stroke;
+            InnerResponse::new(ret, response)
+        })
+        .inner
+}
 
-    ui.painter().set(
-        where_to_put_background,
-        epaint::RectShape {
-            rounding : style.rounding,
-            fill,
-            stroke,
-            rect
+fn auto_scroll(ui : &mut Ui) {
+    let visible = ui.clip_rect();
+    if let Some(pointer) = ui
+        .ctx()
+        .pointer_hover_pos()
+        .filter(|pos| visible.contains(*pos))
+    {
+        let delta = if pointer.y < visible.top() + AUTO_SCROLL_MARGIN {
+            AUTO_SCROLL_MARGIN - (pointer.y - visible.top())
         }
-    );
-
-    InnerResponse::new(ret, response)
-    /* }) */
+        else if pointer.y > visible.bottom() - AUTO_SCROLL_MARGIN {
+            -(AUTO_SCROLL_MARGIN - (visible.bottom() - pointer.y))
+        }
+        else {
+            return;
+        };
+        ui.scroll_with_delta(vec2(0.0, delta / 2.0));
+        ui.ctx().request_repaint();
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -240,7 +973,13 @@ struct DndIntState<T : Clone + Send + Sync + 'static> {
 
     source_col_row : Option<(BuilderColumn, usize)>,
 
-    dragged_object : Option<T>
+    dragged_object : Option<T>,
+
+    /// the progression entry moved by the arrow keys
+    selected_row : Option<usize>,
+
+    /// the other end of the selected entries, set by shift-clicking
+    selection_end : Option<usize>
 }
 
 impl<T : Clone + Send + Sync + 'static> Default for DndIntState<T> {
@@ -249,7 +988,9 @@ impl<T : Clone + Send + Sync + 'static> Default for DndIntState<T> {
             target_row_id : Default::default(),
             drop_col : Default::default(),
             source_col_row : Default::default(),
-            dragged_object : Default::default()
+            dragged_object : Default::default(),
+            selected_row : Default::default(),
+            selection_end : Default::default()
         }
     }
 }
@@ -260,25 +1001,54 @@ impl<T : Clone + Send + Sync + 'static> DndIntState<T> {
     pub fn store(self, ctx : &Context, id : Id) { ctx.data().insert_temp(id, self); }
 }
 
+type RowHandler<'a> = dyn FnMut(&mut Ui, &ConcreteStatChange, usize) + 'a;
+type RunHandler<'a> = dyn FnMut(&mut Ui, &ConcreteStatChange, Range<usize>) + 'a;
+
 #[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Hash)]
 enum BuilderColumn {
     Levels,
     Templates
 }
 
-// TODO: make the left side scrollable
 pub fn character_progression_builder(data : &mut GameData, ctx : &egui::Context) {
-    if data.progression.templates != ConcreteStatChange::generate_templates(data.game_option) {
-        data.progression.templates = ConcreteStatChange::generate_templates(data.game_option);
-    }
-
-    let builder_rect = egui::Window::new("Character Progression Builder")
+    data.progression.track_fork();
+    let before = data
+        .progression
+        .unlogged
+        .take()
+        .unwrap_or_else(|| data.progression.to_vec());
+    let growth_scopes_matched = growth_scopes_match(&data.progression);
+
+    let builtin_templates = ConcreteStatChange::generate_templates(data.game_option);
+    let default_templates = builtin_templates
+        .iter()
+        .cloned()
+        .chain(data.custom_templates.values().cloned())
+        .collect::<Vec<_>>();
+    data.progression.sync_templates(default_templates.clone());
+    let template_names = data
+        .progression
+        .templates
+        .iter()
+        .map(|template| {
+            data.custom_templates
+                .iter()
+                .find(|(_name, custom)| {
+                    *custom == template && !builtin_templates.contains(template)
+                })
+                .map_or_else(|| template.to_string(), |(name, _custom)| name.clone())
+        })
+        .collect::<Vec<_>>();
+
+    let builder_rect = AppWindow::ProgressionBuilder
+        .window()
         .collapsible(data.progression.queued_insertion.is_none())
         .show(ctx, |ui| {
             ui.set_enabled(data.progression.queued_insertion.is_none());
+            slots::slot_tabs(data, ui);
 
-            let mut container_rect = None;
-            let mut row_rect = None;
+            let mut hovered_col = None;
+            let mut row_rects : Vec<(BuilderColumn, Rect)> = vec![];
 
             let mut state : DndIntState<ConcreteStatChange> =
                 DndIntState::load(ui.ctx(), data.progression.id()).unwrap_or_default();
@@ -287,45 +1057,265 @@ pub fn character_progression_builder(data : &mut GameData, ctx : &egui::Context)
             let source_col_row = &mut state.source_col_row;
             let drop_col = &mut state.drop_col;
             let dragged_object = &mut state.dragged_object;
-
-            ui.label(
-                "The index (#2) indicates the numerical x-axis coordinate for the result of this \
-                 stat change."
+            let selected_row = &mut state.selected_row;
+            let selection_end = &mut state.selection_end;
+            if selected_row.map_or(false, |row| row >= data.progression.len()) {
+                *selected_row = None;
+            }
+            if selected_row.is_none()
+                || selection_end.map_or(false, |row| row >= data.progression.len())
+            {
+                *selection_end = None;
+            }
+            let selection = selected_row.map(|start| {
+                let end = selection_end.unwrap_or(start);
+                start.min(end)..start.max(end) + 1
+            });
+            // the display row the dragged entry would be inserted before, only
+            // previewed while hovering the progression itself
+            let preview_target = match (*drop_col, &*source_col_row, &*dragged_object) {
+                (Some(BuilderColumn::Levels), Some(_), Some(_)) => *drag_target_row_position,
+                _ => None
+            };
+
+            // while dragging, summarize the progression as it would be after the drop
+            let preview = match (*source_col_row, preview_target, dragged_object.as_ref()) {
+                (Some((source_col, source_row)), Some(target), Some(item)) => {
+                    let mut preview = data.progression.progression.clone();
+                    let insert_index = data
+                        .progression
+                        .display_rows()
+                        .get(target)
+                        .map_or(preview.len(), |row| row.start);
+                    if source_col == BuilderColumn::Levels && source_row < preview.len() {
+                        preview.remove(source_row);
+                    }
+                    preview.insert(insert_index.at_most(preview.len()), item.clone());
+                    Some(preview)
+                },
+                _ => None
+            };
+            progression_summary(
+                ui,
+                preview.as_deref().unwrap_or(&data.progression),
+                data.character.level,
+                data.level_cap
             );
 
-            if ui.button("clear all").clicked() {
-                data.progression.progression.clear();
+            ui.label(tr("progression.index_hint"));
+
+            ui.horizontal(|ui| {
+                let progression = &mut data.progression;
+                // leave the shortcuts to text fields while they are being edited
+                let shortcuts_enabled =
+                    progression.queued_insertion.is_none() && ui.memory().focus().is_none();
+                let undo_shortcut =
+                    shortcuts_enabled && ui.input_mut().consume_key(Modifiers::COMMAND, Key::Z);
+                let redo_shortcut =
+                    shortcuts_enabled && ui.input_mut().consume_key(Modifiers::COMMAND, Key::Y);
+
+                if ui
+                    .add_enabled(
+                        !progression.history.undo.is_empty(),
+                        Button::new(tr("progression.undo"))
+                    )
+                    .on_hover_text(tr("progression.undo_hint"))
+                    .clicked()
+                    || undo_shortcut
+                {
+                    progression.undo();
+                }
+                if ui
+                    .add_enabled(
+                        !progression.history.redo.is_empty(),
+                        Button::new(tr("progression.redo"))
+                    )
+                    .on_hover_text(tr("progression.redo_hint"))
+                    .clicked()
+                    || redo_shortcut
+                {
+                    progression.redo();
+                }
+
+                ui.separator();
+
+                if !progression.clear_requested {
+                    progression.clear_requested = ui.button(tr("progression.clear")).clicked();
+                }
+                else {
+                    if ui.button(tr("progression.really_clear")).clicked() {
+                        progression.checkpoint();
+                        progression.progression.clear();
+                        progression.clear_requested = false;
+                    }
+                    if ui.button(tr("common.cancel")).clicked() {
+                        progression.clear_requested = false;
+                    }
+                }
+            });
+
+            ui.horizontal(|ui| {
+                let progression = &mut data.progression;
+                numerical_text_box(ui, &mut progression.bulk_level_ups);
+                progression.bulk_level_ups =
+                    progression.bulk_level_ups.clamp(1, MAX_BULK_LEVEL_UPS);
+                if ui
+                    .add_enabled(
+                        progression.level_up_template().is_some(),
+                        Button::new(tr("progression.append_level_ups"))
+                    )
+                    .clicked()
+                {
+                    progression.insert_level_ups(progression.len());
+                }
+                if ui
+                    .add_enabled(
+                        !progression.clipboard.is_empty(),
+                        Button::new(tr("progression.paste_at_end"))
+                    )
+                    .clicked()
+                {
+                    progression.paste(progression.len());
+                }
+                ui.separator();
+                if ui
+                    .checkbox(
+                        &mut progression.collapse_level_ups,
+                        tr("progression.collapse")
+                    )
+                    .changed()
+                {
+                    progression.expanded_runs.clear();
+                }
+            });
+
+            if let Some(editing) = data.progression.editing_branch() {
+                ui.horizontal(|ui| {
+                    for name in [BranchName::A, BranchName::B] {
+                        if ui
+                            .selectable_label(editing == name, name.to_string())
+                            .clicked()
+                            && editing != name
+                        {
+                            data.progression.switch_branch();
+                        }
+                    }
+                    if ui
+                        .button(tr_with(
+                            "progression.discard_branch",
+                            &[("branch", &editing.other())]
+                        ))
+                        .on_hover_text(tr("progression.discard_branch_hint"))
+                        .clicked()
+                    {
+                        data.progression.branch = None;
+                    }
+                });
             }
 
+            ui.horizontal(|ui| {
+                ui.label(tr("progression.level_cap"));
+                numerical_text_box(ui, &mut data.level_cap);
+                reset_button_with(ui, &mut data.level_cap, data.game_option.level_cap());
+                ui.checkbox(
+                    &mut data.ignore_promotion_levels,
+                    tr("progression.ignore_promotion_levels")
+                )
+                .on_hover_text(tr("progression.ignore_promotion_levels_hint"));
+                ui.separator();
+                let progression = &mut data.progression;
+                if ui
+                    .add_enabled(
+                        progression.templates != default_templates,
+                        Button::new(tr("progression.reset_templates"))
+                    )
+                    .on_hover_text(tr("progression.reset_templates_hint"))
+                    .clicked()
+                {
+                    progression.templates = default_templates.clone();
+                    progression.hidden_templates.clear();
+                }
+                if !progression.hidden_templates.is_empty() {
+                    ui.small(tr_with(
+                        "progression.hidden_templates",
+                        &[("count", &progression.hidden_templates.len())]
+                    ));
+                }
+            });
+
+            let mut editing_text = data.progression.text_edit.is_some();
+            if ui
+                .toggle_value(&mut editing_text, tr("progression.edit_as_text"))
+                .changed()
+            {
+                data.progression.text_edit = editing_text.then(|| {
+                    text::print_progression(&data.progression, data.game_option, &data.promotions)
+                });
+            }
+            if let Some(text) = &mut data.progression.text_edit {
+                ui.add(TextEdit::multiline(text).hint_text("10 levels, promote Paladin"));
+                let parsed = text::parse_progression(text, data.game_option, &data.promotions);
+                ui.horizontal(|ui| {
+                    let apply = ui
+                        .add_enabled(parsed.is_ok(), Button::new(tr("progression.apply")))
+                        .clicked();
+                    match parsed {
+                        Ok(parsed) if apply => {
+                            data.progression.checkpoint();
+                            data.progression.progression = parsed;
+                            data.progression.text_edit = None;
+                        },
+                        Err(error) => {
+                            ui.colored_label(Color32::RED, error.to_string());
+                        },
+                        _ => {}
+                    }
+                });
+            }
+
+            let level_rows = data.progression.display_rows();
+            let fork = data.progression.branch.as_ref().map(|branch| branch.fork);
+            let template_rows = (0..data.progression.templates.len())
+                .map(|row| row..row + 1)
+                .collect::<Vec<_>>();
+            let mut expanded_run = None;
+
             ui.columns(2, |uis| {
                 let id = data.progression.id();
                 let mut render_column =
                     |col_idx,
                      ui,
                      column : Vec<ConcreteStatChange>,
-                     drag_handler : &mut dyn FnMut(&mut Ui, &ConcreteStatChange, usize),
-                     mut context_handler : Option<
-                        &mut dyn FnMut(&mut Ui, &ConcreteStatChange, usize)
-                    >| {
+                     rows : &[Range<usize>],
+                     drag_handler : &mut RowHandler<'_>,
+                     mut context_handler : Option<&mut RowHandler<'_>>,
+                     mut run_handler : Option<&mut RunHandler<'_>>| {
                         let this_col_is_dest = drop_col.map(|x| x == col_idx).unwrap_or(false);
 
                         let response = drop_target(ui, this_col_is_dest, col_idx, |ui| {
                             //
                             ui.set_min_size(vec2(64.0, 100.0));
-                            for (row_idx, item) in column.iter().enumerate() {
+                            for (display_idx, row) in rows.iter().enumerate() {
+                                let row_idx = row.start;
+                                let item = &column[row_idx];
                                 let item_id = id.with(col_idx).with(row_idx);
 
                                 // this handles the preview label for non tail end insertions
-                                if source_col_row.is_some()
-                                    && *drag_target_row_position == Some(row_idx)
-                                    && drop_col
-                                        .map(|col| col == col_idx && col == BuilderColumn::Levels)
-                                        .unwrap_or(false)
-                                    && dragged_object.is_some()
+                                if col_idx == BuilderColumn::Levels
+                                    && preview_target == Some(display_idx)
                                 {
-                                    ui.add(Label::new(
-                                        dragged_object.as_ref().unwrap().to_string()
-                                    ));
+                                    if let Some(dragged) = dragged_object.as_ref() {
+                                        ui.add(Label::new(dragged.to_string()));
+                                    }
+                                }
+
+                                if row.len() > 1 {
+                                    if let Some(run_handler) = run_handler.as_mut() {
+                                        let run_row =
+                                            ui.horizontal(|ui| run_handler(ui, item, row.clone()));
+                                        row_rects.push((col_idx, run_row.response.rect));
+                                    }
+                                    continue;
                                 }
 
                                 let c_row_size_rect = drag_source(
@@ -342,8 +1332,19 @@ pub fn character_progression_builder(data : &mut GameData, ctx : &egui::Context)
                                     })
                                 );
 
-                                if c_row_size_rect.is_some() {
-                                    row_rect = c_row_size_rect;
+                                if let Some(row_response) = c_row_size_rect {
+                                    row_rects.push((col_idx, row_response.response.rect));
+                                    if col_idx == BuilderColumn::Levels
+                                        && row_response.inner.clicked()
+                                    {
+                                        if ui.input().modifiers.shift && selected_row.is_some() {
+                                            *selection_end = Some(row_idx);
+                                        }
+                                        else {
+                                            *selected_row = Some(row_idx);
+                                            *selection_end = None;
+                                        }
+                                    }
                                 }
 
                                 if ui.memory().is_being_dragged(item_id) {
@@ -353,16 +1354,12 @@ pub fn character_progression_builder(data : &mut GameData, ctx : &egui::Context)
                             }
 
                             // this handles the preview label for tail-end insertions
-                            if source_col_row.is_some()
-                                && drag_target_row_position
-                                    .map(|x| x >= column.len())
-                                    .unwrap_or(false)
-                                && drop_col
-                                    .map(|col| col == col_idx && col == BuilderColumn::Levels)
-                                    .unwrap_or(false)
-                                && dragged_object.is_some()
+                            if col_idx == BuilderColumn::Levels
+                                && preview_target.map_or(false, |target| target >= rows.len())
                             {
-                                ui.add(Label::new(dragged_object.as_ref().unwrap().to_string()));
+                                if let Some(dragged) = dragged_object.as_ref() {
+                                    ui.add(Label::new(dragged.to_string()));
+                                }
                             }
                         })
                         .response;
@@ -370,78 +1367,271 @@ pub fn character_progression_builder(data : &mut GameData, ctx : &egui::Context)
                         let is_being_dragged = source_col_row.is_some();
 
                         if is_being_dragged && response.hovered() {
-                            *drop_col = Some(col_idx);
-                            container_rect = Some(response.rect);
+                            hovered_col = Some(col_idx);
                         }
                     };
                 if let [ui1, ui2] = uis {
                     let copy = (data.progression.progression).clone();
+                    let averages = data
+                        .plotter
+                        .snapshot_averages(&data.effective_character(), &data.progression);
+                    let held = held_growth_items(&copy);
+                    let depths = growth_scope_depths(&copy);
+                    let early = if data.ignore_promotion_levels {
+                        vec![None; copy.len()]
+                    }
+                    else {
+                        early_promotions(data.character.level, &copy)
+                    };
                     render_column(
                         BuilderColumn::Levels,
                         ui1,
                         data.progression.progression.clone(),
+                        &level_rows,
                         &mut |ui, item, row_idx| {
-                            if item.increases_level_counter() {
-                                ui.label(format!(
-                                    "(#{}) {item} to {}",
-                                    row_idx + 2,
-                                    find_row_level(data.character.level, &copy, row_idx).unwrap()
-                                ));
+                            growth_scope_indent(ui, depths[row_idx]);
+                            let (level, level_cap) = find_row_level(
+                                data.character.level,
+                                data.level_cap,
+                                &copy,
+                                row_idx
+                            )
+                            .unwrap();
+                            let index = row_idx + 2;
+                            let text = if item.increases_level_counter() {
+                                tr_with(
+                                    "progression.entry_to_level",
+                                    &[("index", &index), ("entry", item), ("level", &level)]
+                                )
                             }
                             else {
-                                ui.label(format!("(#{}) {item}", row_idx + 2));
+                                tr_with("progression.entry", &[("index", &index), ("entry", item)])
+                            };
+                            if selection
+                                .as_ref()
+                                .map_or(false, |rows| rows.contains(&row_idx))
+                            {
+                                ui.label(
+                                    RichText::new(text)
+                                        .background_color(ui.visuals().selection.bg_fill)
+                                );
+                            }
+                            else {
+                                ui.label(text);
+                            }
+                            if item.increases_level_counter() {
+                                held_items_label(ui, &held[row_idx]);
+                                level_cap_warning(ui, level, level_cap);
                             }
+                            promotion_level_warning(ui, early[row_idx]);
+                            if fork == Some(row_idx) {
+                                ui.weak("⑂")
+                                    .on_hover_text(tr("progression.branches_differ"));
+                            }
+                            average_label(ui, &data.plotter, averages.as_deref(), row_idx + 1);
                         },
                         Some(&mut |ui, item, row_idx| {
+                            ui.set_enabled(
+                                data.progression.queued_insertion.is_none()
+                                    && !ui.memory().is_anything_being_dragged()
+                            );
                             if ui
                                 .add_enabled(
                                     item.requires_clarification(),
-                                    Button::new("reconfigure")
+                                    Button::new(tr("progression.reconfigure"))
                                 )
                                 .clicked()
                             {
+                                data.progression.checkpoint();
                                 let item = data.progression.progression.remove(row_idx);
+                                data.progression.reconfigured_original = Some(item.clone());
                                 data.progression.queued_insertion = Some((row_idx, item));
                                 ui.close_menu();
                             }
+                            if ui
+                                .add_enabled(
+                                    data.progression.level_up_template().is_some(),
+                                    Button::new(tr_with(
+                                        "progression.insert_level_ups",
+                                        &[("count", &data.progression.bulk_level_ups)]
+                                    ))
+                                )
+                                .clicked()
+                            {
+                                data.progression.insert_level_ups(row_idx);
+                                ui.close_menu();
+                            }
+                            let last_row = data.progression.len() - 1;
+                            for (label, target) in [
+                                ("progression.move_to_top", 0),
+                                ("progression.move_up", row_idx.saturating_sub(1)),
+                                ("progression.move_down", (row_idx + 1).at_most(last_row)),
+                                ("progression.move_to_bottom", last_row)
+                            ] {
+                                if ui
+                                    .add_enabled(target != row_idx, Button::new(tr(label)))
+                                    .clicked()
+                                {
+                                    data.progression.move_entry(row_idx, target);
+                                    ui.close_menu();
+                                }
+                            }
+                            if ui
+                                .add_enabled(
+                                    data.custom_templates.check_legal_name(&item.to_string()),
+                                    Button::new(tr("progression.save_as_template"))
+                                )
+                                .on_disabled_hover_text(tr("progression.template_exists"))
+                                .clicked()
+                            {
+                                data.custom_templates.insert(item.to_string(), item.clone());
+                                ui.close_menu();
+                            }
+                            if ui
+                                .add_enabled(
+                                    data.progression.branch.is_none(),
+                                    Button::new(tr("progression.fork"))
+                                )
+                                .on_hover_text(tr("progression.fork_hint"))
+                                .clicked()
+                            {
+                                data.progression.fork(row_idx + 1);
+                                ui.close_menu();
+                            }
+                            let copied = selection
+                                .clone()
+                                .filter(|rows| rows.contains(&row_idx))
+                                .unwrap_or(row_idx..row_idx + 1);
+                            if ui
+                                .button(tr_with(
+                                    "progression.copy_entries",
+                                    &[("count", &copied.len())]
+                                ))
+                                .on_hover_text(tr("progression.copy_entries_hint"))
+                                .clicked()
+                            {
+                                let segment = data.progression[copied].to_vec();
+                                ui.output().copied_text = text::print_progression(
+                                    &segment,
+                                    data.game_option,
+                                    &data.promotions
+                                );
+                                data.progression.clipboard = segment;
+                                ui.close_menu();
+                            }
+                            if ui
+                                .add_enabled(
+                                    !data.progression.clipboard.is_empty(),
+                                    Button::new(tr_with(
+                                        "progression.paste_entries",
+                                        &[("count", &data.progression.clipboard.len())]
+                                    ))
+                                )
+                                .clicked()
+                            {
+                                data.progression.paste(row_idx);
+                                ui.close_menu();
+                            }
+                            ui.menu_button(tr("progression.export_character"), |ui| {
+                                export_menu(
+                                    ui,
+                                    row_idx,
+                                    &data.plotter,
+                                    &data.character,
+                                    &data.growth_modifiers,
+                                    &mut data.progression,
+                                    &mut data.characters
+                                )
+                            });
+                            if ui.button(tr("progression.duplicate")).clicked() {
+                                data.progression.checkpoint();
+                                data.progression.insert(row_idx + 1, item.clone());
+                                ui.close_menu();
+                            }
+                            if ui.button(tr("progression.delete")).clicked() {
+                                data.progression.checkpoint();
+                                data.progression.remove(row_idx);
+                                ui.close_menu();
+                            }
+                        }),
+                        Some(&mut |ui, item, run| {
+                            growth_scope_indent(ui, depths[run.start]);
+                            let (level, level_cap) = find_row_level(
+                                data.character.level,
+                                data.level_cap,
+                                &copy,
+                                run.end - 1
+                            )
+                            .unwrap();
+                            if ui
+                                .add(
+                                    Label::new(tr_with(
+                                        "progression.run_to_level",
+                                        &[
+                                            ("first", &(run.start + 2)),
+                                            ("last", &(run.end + 1)),
+                                            ("count", &run.len()),
+                                            ("entry", item),
+                                            ("level", &level)
+                                        ]
+                                    ))
+                                    .sense(Sense::click())
+                                )
+                                .on_hover_text(tr("progression.expand_hint"))
+                                .clicked()
+                            {
+                                expanded_run = Some(run.start);
+                            }
+                            held_items_label(ui, &held[run.start]);
+                            level_cap_warning(ui, level, level_cap);
+                            promotion_level_warning(ui, early[run.start]);
+                            average_label(ui, &data.plotter, averages.as_deref(), run.end);
                         })
                     );
                     render_column(
                         BuilderColumn::Templates,
                         ui2,
                         (data.progression.templates).clone(),
-                        &mut |ui, item, _row_idx| {
-                            ui.label(item.to_string());
+                        &template_rows,
+                        &mut |ui, item, row_idx| {
+                            ui.label(&template_names[row_idx]);
+                            if let Some(key) = item.hint_key() {
+                                hint(ui, key);
+                            }
                         },
+                        Some(&mut |ui, _item, row_idx| {
+                            if ui.button(tr("progression.hide")).clicked() {
+                                data.progression.hide_template(row_idx);
+                                ui.close_menu();
+                            }
+                        }),
                         None
                     );
                 }
             });
 
-            if let (Some(_drop_col), Some(row_rect), Some(container_rect)) =
-                (*drop_col, row_rect, container_rect)
-            {
-                if ui.memory().is_anything_being_dragged() {
-                    let pos = ui.input().pointer.hover_pos();
-
-                    let row_rectr = row_rect.size();
-
-                    let offset = pos.unwrap() - container_rect.min;
-
-                    let drag_position =
-                        ((offset.y - row_rectr.y / 2.) / row_rectr.y).round() as usize;
-                    // .at_most(self.columns[drop_col].len().saturating_sub(1));
-
-                    *drag_target_row_position = Some(drag_position);
-                }
-                else {
-                    *drag_target_row_position = None;
-                }
+            if let Some(run_start) = expanded_run {
+                data.progression.expanded_runs.insert(run_start);
             }
-            else {
-                *drag_target_row_position = None;
+
+            // leaving both columns cancels the drop instead of keeping the last one
+            if source_col_row.is_some() {
+                *drop_col = hovered_col;
             }
 
+            let hover_pos = ui.input().pointer.hover_pos();
+            *drag_target_row_position = match (*drop_col, hover_pos) {
+                (Some(drop_col), Some(pos)) if ui.memory().is_anything_being_dragged() => {
+                    let drop_col_rects = row_rects
+                        .iter()
+                        .filter(|(col, _rect)| *col == drop_col)
+                        .map(|(_col, rect)| *rect)
+                        .collect::<Vec<_>>();
+                    Some(drop_position(&drop_col_rects, pos.y))
+                },
+                _ => None
+            };
+
             if let Some((source_col, source_row)) = *source_col_row {
                 if let Some(drop_col) = *drop_col {
                     //
@@ -449,6 +1639,24 @@ pub fn character_progression_builder(data : &mut GameData, ctx : &egui::Context)
                         // do the drop:
 
                         if let Some(drag_target_row_position) = drag_target_row_position {
+                            // template drops only change the progression once
+                            // clarified, the undo step still belongs to the drop
+                            if source_col == BuilderColumn::Levels
+                                || drop_col == BuilderColumn::Levels
+                            {
+                                data.progression.checkpoint();
+                            }
+                            if source_col == BuilderColumn::Templates
+                                && drop_col == BuilderColumn::Templates
+                            {
+                                let target = if *drag_target_row_position > source_row {
+                                    *drag_target_row_position - 1
+                                }
+                                else {
+                                    *drag_target_row_position
+                                };
+                                data.progression.move_template(source_row, target);
+                            }
                             let item = match source_col {
                                 BuilderColumn::Levels => {
                                     data.progression.progression.remove(source_row)
@@ -459,12 +1667,20 @@ pub fn character_progression_builder(data : &mut GameData, ctx : &egui::Context)
                             };
 
                             if drop_col == BuilderColumn::Levels {
-                                let insert_index = drag_target_row_position
-                                    .at_most(data.progression.progression.len());
+                                let insert_index = level_rows
+                                    .get(*drag_target_row_position)
+                                    .map_or(data.progression.len(), |row| row.start)
+                                    .at_most(data.progression.len());
                                 match source_col {
                                     BuilderColumn::Levels => {
                                         data.progression.progression.insert(insert_index, item)
                                     },
+                                    // custom templates were configured before being saved
+                                    BuilderColumn::Templates
+                                        if !builtin_templates.contains(&item) =>
+                                    {
+                                        data.progression.progression.insert(insert_index, item)
+                                    },
                                     BuilderColumn::Templates => {
                                         data.progression.queued_insertion =
                                             Some((insert_index, item))
@@ -476,6 +1692,32 @@ pub fn character_progression_builder(data : &mut GameData, ctx : &egui::Context)
                 }
             }
 
+            if let Some(row) = *selected_row {
+                let single_row = selection_end.is_none();
+                if ui.memory().focus().is_none()
+                    && data.progression.queued_insertion.is_none()
+                    && single_row
+                {
+                    let target = if ui.input_mut().consume_key(Modifiers::NONE, Key::ArrowUp) {
+                        row.checked_sub(1)
+                    }
+                    else if ui.input_mut().consume_key(Modifiers::NONE, Key::ArrowDown) {
+                        Some(row + 1).filter(|target| *target < data.progression.len())
+                    }
+                    else {
+                        None
+                    };
+                    if let Some(target) = target {
+                        data.progression.move_entry(row, target);
+                        *selected_row = Some(target);
+                    }
+                }
+                if ui.input_mut().consume_key(Modifiers::NONE, Key::Escape) {
+                    *selected_row = None;
+                    *selection_end = None;
+                }
+            }
+
             if ui.input().pointer.any_released() {
                 *source_col_row = None;
                 *drop_col = None;
@@ -486,41 +1728,792 @@ pub fn character_progression_builder(data : &mut GameData, ctx : &egui::Context)
             state.store(ui.ctx(), data.progression.id());
             ui.min_rect()
         });
+    if let Some(builder) = &builder_rect {
+        onboarding::mark_window(
+            ctx,
+            AppWindow::ProgressionBuilder.id(),
+            builder.response.rect
+        );
+    }
 
     if let Some((index, queued_insertion)) = std::mem::take(&mut data.progression.queued_insertion)
     {
-        egui::Window::new("Specify Details")
+        egui::Window::new(tr("progression.specify_details"))
             .collapsible(false)
-            .fixed_rect(builder_rect.unwrap().inner.unwrap())
+            .fixed_rect(on_screen(ctx, builder_rect.unwrap().inner.unwrap()))
             .show(ctx, |ui| {
                 ctx.move_to_top(ui.layer_id());
                 let (stat_change, ready) = queued_insertion.clarification_dialogue(data, ui);
                 if ready {
                     data.progression.insert(index, stat_change);
+                    data.progression.reconfigured_original = None;
+                }
+                else if ui.button(tr("common.cancel")).clicked() {
+                    data.progression.cancel_queued_insertion(index);
                 }
                 else {
                     data.progression.queued_insertion = Some((index, stat_change))
                 }
             });
     }
+
+    // refuse edits dropping a growth item before it was equipped, a
+    // reconfigured item only being taken out until it's reinserted
+    if growth_scopes_matched
+        && data.progression.queued_insertion.is_none()
+        && !growth_scopes_match(&data.progression)
+    {
+        data.progression.refuse_edit();
+    }
+
+    if data.progression.queued_insertion.is_some() {
+        data.progression.unlogged = Some(before);
+    }
+    else if let Some(edit) = describe_edit(&before, &data.progression) {
+        log_action(data, edit);
+    }
 }
 
-fn find_row_level(
-    base_level : usize,
+/// whether both hold the same entries, in whatever order
+fn same_entries(a : &[ConcreteStatChange], b : &[ConcreteStatChange]) -> bool {
+    let count = |entries : &[ConcreteStatChange], entry : &ConcreteStatChange| {
+        entries.iter().filter(|other| *other == entry).count()
+    };
+    a.len() == b.len() && a.iter().all(|entry| count(a, entry) == count(b, entry))
+}
+
+/// Describes the edit turning the old progression into the new one for the
+/// session log, by the entries between the start and the end both share.
+/// `None` if nothing changed.
+fn describe_edit(old : &[ConcreteStatChange], new : &[ConcreteStatChange]) -> Option<String> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let removed = &old[prefix..old.len() - suffix];
+    let added = &new[prefix..new.len() - suffix];
+    let first = prefix + 1;
+    let last = |entries : &[ConcreteStatChange]| prefix + entries.len();
+    let description = match (removed, added) {
+        ([], []) => return None,
+        ([], [entry]) => tr_with(
+            "progression.log_added_one",
+            &[("entry", entry), ("index", &first)]
+        ),
+        ([], _) => tr_with(
+            "progression.log_added",
+            &[
+                ("count", &added.len()),
+                ("first", &first),
+                ("last", &last(added))
+            ]
+        ),
+        ([entry], []) => tr_with(
+            "progression.log_removed_one",
+            &[("entry", entry), ("index", &first)]
+        ),
+        (_, []) => tr_with(
+            "progression.log_removed",
+            &[
+                ("count", &removed.len()),
+                ("first", &first),
+                ("last", &last(removed))
+            ]
+        ),
+        ([old_entry], [new_entry]) => tr_with(
+            "progression.log_changed_one",
+            &[("index", &first), ("old", old_entry), ("new", new_entry)]
+        ),
+        _ if same_entries(removed, added) => tr_with(
+            "progression.log_reordered",
+            &[("first", &first), ("last", &last(removed))]
+        ),
+        _ => {
+            let entries = added
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            tr_with(
+                "progression.log_replaced",
+                &[
+                    ("first", &first),
+                    ("last", &last(removed)),
+                    ("entries", &entries)
+                ]
+            )
+        }
+    };
+    Some(description)
+}
+
+/// The row a drop at the given height is inserted before, given the rects of
+/// the rendered rows from top to bottom. Rows count as passed once the pointer
+/// is below their center, so that row heights and gaps do not matter.
+fn drop_position(row_rects : &[Rect], y : f32) -> usize {
+    row_rects.partition_point(|rect| rect.center().y < y)
+}
+
+/// Lets the user save the character as of after the given entry as a new base
+/// character.
+fn export_menu(
+    ui : &mut Ui,
+    row_idx : usize,
+    plotter : &PlotterManager,
+    character : &Character<StatIndexType>,
+    growth_modifiers : &GrowthModifiers,
+    progression : &mut ProgressionManager,
+    characters : &mut DataManaged<SavedCharacter>
+) {
+    let percentile = &mut progression.export_percentile;
+    ui.radio_value(percentile, None, tr("progression.export_expected"));
+    ui.horizontal(|ui| {
+        if ui
+            .radio(percentile.is_some(), tr("progression.export_percentile"))
+            .clicked()
+            && percentile.is_none()
+        {
+            *percentile = Some(50);
+        }
+        if let Some(percentile) = percentile {
+            ui.add(Slider::new(percentile, 1..=99));
+        }
+    });
+
+    let values = plotter.snapshot_values(
+        &effective_character(character, growth_modifiers),
+        progression,
+        row_idx + 1,
+        progression.export_percentile
+    );
+    if ui
+        .add_enabled(values.is_some(), Button::new(tr("progression.export")))
+        .on_disabled_hover_text(tr("progression.export_pending"))
+        .clicked()
+    {
+        if let Some(values) = values {
+            let entries = &progression[..=row_idx];
+            let level = row_levels(character.level, entries)
+                .last()
+                .unwrap_or(character.level);
+            let base_name = tr_with(
+                "progression.exported_name",
+                &[
+                    ("name", &character.name),
+                    ("level", &level),
+                    ("index", &(row_idx + 1))
+                ]
+            );
+            let name = std::iter::once(base_name.clone())
+                .chain((2..).map(|copy| format!("{base_name} {copy}")))
+                .find(|name| characters.check_legal_name(name))
+                .unwrap_or(base_name);
+            let exported = exported_character(character, entries, &values, level, &name);
+            characters.insert(name, (exported, vec![], growth_modifiers.clone()));
+        }
+        ui.close_menu();
+    }
+}
+
+/// The character after the given entries with the given stats as its bases,
+/// keeping the caps and growths the entries changed deterministically.
+fn exported_character(
+    base : &Character<StatIndexType>,
     progression : &[ConcreteStatChange],
-    row_idx : usize
-) -> Option<usize> {
-    let mut current_level = base_level;
-    for (row, csc) in progression.iter().enumerate() {
+    values : &BTreeMap<StatIndexType, StatType>,
+    level : usize,
+    name : &str
+) -> Character<StatIndexType> {
+    let mut character = base.clone();
+    for stat_change in progression.iter().cloned().map(UsefulStatChange::compile) {
+        if let StatChange::Promotion { promo_changes } = stat_change {
+            for (sit, stat) in character.stats.iter_mut() {
+                *stat = promo_changes(sit, *stat);
+            }
+        }
+    }
+    for (sit, stat) in character.stats.iter_mut() {
+        let value = values.get(sit).copied().unwrap_or(stat.value).min(stat.cap);
+        stat.base = value;
+        stat.value = value;
+    }
+    character.level = level;
+    character.name = name.to_owned();
+    character
+}
+
+/// Compiles the progression, changing the growths of every level-up by the
+/// summed modifiers of the growth items held and the class at that point,
+/// clamped at 0. As the library rolls blank avoidance retries with the same
+/// growths, these apply to the retries as well.
+pub fn compile_progression(progression : &[ConcreteStatChange]) -> Vec<StatChange<StatIndexType>> {
+    let mut held = HeldItems::new();
+    let mut class_growths = BTreeMap::new();
+    progression
+        .iter()
+        .map(|csc| {
+            update_held_items(&mut held, csc.growth_scope());
+            if let Some(growths) = csc.class_growths() {
+                class_growths = growths;
+            }
+            let mut compiled = csc.clone().compile();
+            if let StatChange::LevelUp {
+                temporary_growth_override,
+                ..
+            } = &mut compiled
+            {
+                let bonuses = held
+                    .iter()
+                    .map(|(_name, modifiers)| modifiers)
+                    .chain(std::iter::once(&class_growths))
+                    .filter(|modifiers| !modifiers.is_empty())
+                    .cloned()
+                    .collect::<Vec<_>>();
+                if !bonuses.is_empty() {
+                    let previous = temporary_growth_override.take();
+                    *temporary_growth_override = Some(Arc::new(move |sit, growth| {
+                        let growth = previous.as_ref().map_or(growth, |f| f(sit, growth));
+                        let modifier : GrowthModifier = bonuses
+                            .iter()
+                            .filter_map(|modifiers| modifiers.get(sit))
+                            .sum();
+                        let modified = (GrowthModifier::from(growth) + modifier).max(0);
+                        GrowthType::try_from(modified).unwrap_or(GrowthType::MAX)
+                    }));
+                }
+            }
+            compiled
+        })
+        .collect()
+}
+
+/// The names of the growth items held at every entry, `None` from an entry
+/// dropping an item that isn't held onwards.
+fn held_growth_items(progression : &[ConcreteStatChange]) -> Vec<Option<Vec<String>>> {
+    progression
+        .iter()
+        .scan(Some(HeldItems::new()), |held, csc| {
+            let names = |held : &HeldItems| -> Vec<String> {
+                held.iter().map(|(name, _)| name.clone()).collect()
+            };
+            let before = held.as_ref().map(names);
+            let starts = matches!(csc.growth_scope(), Some(GrowthScope::Start(..)));
+            if let Some(items) = held {
+                if !update_held_items(items, csc.growth_scope()) {
+                    *held = None;
+                }
+            }
+            // both ends of a scope are outside of it
+            Some(
+                if starts {
+                    before
+                }
+                else {
+                    held.as_ref().map(names)
+                }
+            )
+        })
+        .collect()
+}
+
+/// How many growth items are held at every entry, `None` from an entry
+/// dropping an item that isn't held onwards.
+fn growth_scope_depths(progression : &[ConcreteStatChange]) -> Vec<Option<usize>> {
+    held_growth_items(progression)
+        .into_iter()
+        .map(|held| held.map(|names| names.len()))
+        .collect()
+}
+
+/// Whether every entry ending a growth item's scope has a matching start.
+pub fn growth_scopes_match(progression : &[ConcreteStatChange]) -> bool {
+    growth_scope_depths(progression).iter().all(Option::is_some)
+}
+
+/// Yields the in-game level reached after each entry of the progression.
+pub fn row_levels(
+    base_level : usize,
+    progression : &[ConcreteStatChange]
+) -> impl Iterator<Item = usize> + '_ {
+    progression.iter().scan(base_level, |current_level, csc| {
         if csc.increases_level_counter() {
-            current_level += 1;
+            *current_level += 1;
         }
         if csc.resets_level_counter() {
-            current_level = 1;
+            *current_level = 1;
+        }
+        Some(*current_level)
+    })
+}
+
+/// The level reached and the level required for every entry that is a
+/// promotion taken below its required level.
+pub fn early_promotions(
+    base_level : usize,
+    progression : &[ConcreteStatChange]
+) -> Vec<Option<(usize, usize)>> {
+    std::iter::once(base_level)
+        .chain(row_levels(base_level, progression))
+        .zip(progression)
+        .map(|(level, csc)| {
+            csc.promotion_level()
+                .filter(|required| level < *required)
+                .map(|required| (level, required))
+        })
+        .collect()
+}
+
+/// The caps of the stats before any entry and after every entry, as only
+/// promotion-like entries change them.
+pub fn snapshot_caps(
+    base : &Character<StatIndexType>,
+    progression : &[ConcreteStatChange]
+) -> Vec<BTreeMap<StatIndexType, StatType>> {
+    let mut character = base.clone();
+    let caps = |character : &Character<StatIndexType>| {
+        character
+            .stats
+            .iter()
+            .map(|(sit, stat)| (*sit, stat.cap))
+            .collect::<BTreeMap<_, _>>()
+    };
+    std::iter::once(caps(&character))
+        .chain(progression.iter().cloned().map(|csc| {
+            if let StatChange::Promotion { promo_changes } = csc.compile() {
+                for (sit, stat) in character.stats.iter_mut() {
+                    *stat = promo_changes(sit, *stat);
+                }
+            }
+            caps(&character)
+        }))
+        .collect()
+}
+
+/// The level cap in effect at every entry, i.e. the one of the class the next
+/// promotion promotes out of.
+fn row_level_caps(level_cap : usize, progression : &[ConcreteStatChange]) -> Vec<usize> {
+    let mut current_cap = level_cap;
+    let mut caps = progression
+        .iter()
+        .rev()
+        .map(|csc| {
+            let cap = current_cap;
+            if csc.resets_level_counter() {
+                current_cap = csc.level_cap_before().unwrap_or(level_cap);
+            }
+            cap
+        })
+        .collect::<Vec<_>>();
+    caps.reverse();
+    caps
+}
+
+/// One line overview of the progression, warning about level-ups past the cap.
+fn progression_summary(
+    ui : &mut Ui,
+    progression : &[ConcreteStatChange],
+    base_level : usize,
+    level_cap : usize
+) {
+    ui.horizontal(|ui| {
+        if progression.is_empty() {
+            ui.label(tr("progression.empty"));
+            return;
         }
-        if row == row_idx {
-            return Some(current_level);
+        let level_ups = progression
+            .iter()
+            .filter(|csc| csc.increases_level_counter())
+            .count();
+        let promotions = progression
+            .iter()
+            .filter(|csc| csc.resets_level_counter())
+            .count();
+        let final_level = row_levels(base_level, progression)
+            .last()
+            .unwrap_or(base_level);
+        let compiled = compile_progression(progression);
+        let cost = if fe_levels::supports_exact_analysis(&compiled) {
+            tr("progression.exact")
+        }
+        else {
+            tr("progression.simulated")
+        };
+        ui.label(tr_with(
+            "progression.summary",
+            &[
+                ("count", &progression.len()),
+                ("level_ups", &level_ups),
+                ("promotions", &promotions),
+                ("level", &final_level),
+                ("cost", &cost)
+            ]
+        ));
+
+        let capped_levels = row_levels(base_level, progression)
+            .zip(row_level_caps(level_cap, progression))
+            .zip(progression.iter())
+            .filter(|((level, cap), csc)| csc.increases_level_counter() && level > cap)
+            .count();
+        if capped_levels > 0 {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                tr_with("progression.capped_levels", &[("count", &capped_levels)])
+            );
+        }
+    });
+}
+
+/// Indents the entries held growth items apply to, marking those dropping an
+/// item that isn't held.
+fn growth_scope_indent(ui : &mut Ui, depth : Option<usize>) {
+    match depth {
+        Some(depth) => ui.add_space(depth as f32 * ui.spacing().indent),
+        None => {
+            ui.colored_label(Color32::RED, "⚠")
+                .on_hover_text(tr("progression.unheld_drop"));
         }
     }
-    None
+}
+
+/// Marks level-ups changed by held growth items with their number, listing
+/// them on hover.
+fn held_items_label(ui : &mut Ui, held : &Option<Vec<String>>) {
+    if let Some(held) = held.as_ref().filter(|held| !held.is_empty()) {
+        let count : String = held
+            .len()
+            .to_string()
+            .chars()
+            .filter_map(|digit| digit.to_digit(10))
+            .filter_map(|digit| "⁰¹²³⁴⁵⁶⁷⁸⁹".chars().nth(digit as usize))
+            .collect();
+        ui.weak(count).on_hover_text(tr_with(
+            "progression.holding",
+            &[("items", &held.join(", "))]
+        ));
+    }
+}
+
+fn level_cap_warning(ui : &mut Ui, level : usize, level_cap : usize) {
+    if level > level_cap {
+        ui.colored_label(ui.visuals().warn_fg_color, "⚠")
+            .on_hover_text(tr_with(
+                "progression.above_level_cap",
+                &[("level", &level), ("cap", &level_cap)]
+            ));
+    }
+}
+
+fn promotion_level_warning(ui : &mut Ui, early : Option<(usize, usize)>) {
+    if let Some((level, required)) = early {
+        ui.colored_label(ui.visuals().warn_fg_color, "⚠")
+            .on_hover_text(tr_with(
+                "progression.early_promotion",
+                &[("required", &required), ("level", &level)]
+            ));
+    }
+}
+
+/// Shows the average of the stat focused by the plotter after the given
+/// snapshot, or a spinner while that snapshot is still being computed.
+fn average_label(
+    ui : &mut Ui,
+    plotter : &PlotterManager,
+    averages : Option<&[BTreeMap<StatIndexType, f64>]>,
+    snapshot : usize
+) {
+    let averages = match averages.and_then(|averages| averages.get(snapshot)) {
+        Some(averages) => averages,
+        None => {
+            ui.spinner();
+            return;
+        }
+    };
+    let focused = plotter
+        .focused_stat()
+        .filter(|stat| averages.contains_key(stat))
+        .or_else(|| {
+            averages
+                .keys()
+                .copied()
+                .min_by_key(StatIndexType::display_order)
+        });
+    if let Some(stat) = focused {
+        let value = format!("{:.1}", averages[&stat]);
+        ui.weak(tr_with(
+            "progression.average",
+            &[("stat", &stat), ("value", &value)]
+        ))
+        .on_hover_text(
+            averages
+                .iter()
+                .sorted_by_key(|(stat, _average)| stat.display_order())
+                .map(|(stat, average)| format!("{stat}: {average:.2}"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+    }
+}
+
+/// The level after the given entry and the level cap at that point.
+fn find_row_level(
+    base_level : usize,
+    level_cap : usize,
+    progression : &[ConcreteStatChange],
+    row_idx : usize
+) -> Option<(usize, usize)> {
+    row_levels(base_level, progression)
+        .zip(row_level_caps(level_cap, progression))
+        .nth(row_idx)
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::pos2;
+
+    use super::*;
+
+    /// rows of differing heights with gaps in between, as a run row or a
+    /// preview label would produce
+    fn synthetic_rects() -> Vec<Rect> {
+        vec![
+            Rect::from_min_max(pos2(0.0, 0.0), pos2(100.0, 20.0)),
+            Rect::from_min_max(pos2(0.0, 24.0), pos2(100.0, 60.0)),
+            Rect::from_min_max(pos2(0.0, 64.0), pos2(100.0, 84.0)),
+        ]
+    }
+
+    #[test]
+    fn drop_position_before_first_and_after_last_row() {
+        let rects = synthetic_rects();
+        assert_eq!(drop_position(&rects, -50.0), 0);
+        assert_eq!(drop_position(&rects, 5.0), 0);
+        assert_eq!(drop_position(&rects, 80.0), 3);
+        assert_eq!(drop_position(&rects, 500.0), 3);
+    }
+
+    #[test]
+    fn drop_position_splits_rows_at_their_center() {
+        let rects = synthetic_rects();
+        assert_eq!(drop_position(&rects, 15.0), 1);
+        assert_eq!(drop_position(&rects, 41.0), 1);
+        assert_eq!(drop_position(&rects, 43.0), 2);
+    }
+
+    #[test]
+    fn drop_position_on_row_boundaries() {
+        let rects = synthetic_rects();
+        // the gap between two rows and both of its edges insert between them
+        assert_eq!(drop_position(&rects, 20.0), 1);
+        assert_eq!(drop_position(&rects, 22.0), 1);
+        assert_eq!(drop_position(&rects, 24.0), 1);
+        assert_eq!(drop_position(&rects, 62.0), 2);
+    }
+
+    #[test]
+    fn drop_position_into_empty_column() {
+        assert_eq!(drop_position(&[], 10.0), 0);
+    }
+
+    #[test]
+    fn trainee_tier_is_capped_at_level_ten() {
+        let level_up = ConcreteStatChange::GbaFeStatChange(GbaFeStatChange::LevelUp);
+        let promotion = |tier| {
+            ConcreteStatChange::GbaFeStatChange(GbaFeStatChange::Promotion(
+                tier,
+                Character {
+                    stats : BTreeMap::new(),
+                    name : "".to_owned(),
+                    level : 1
+                }
+            ))
+        };
+        let progression = [
+            level_up.clone(),
+            promotion(ClassTier::Trainee),
+            level_up.clone(),
+            promotion(ClassTier::Unpromoted),
+            level_up
+        ];
+        assert_eq!(row_level_caps(20, &progression), vec![10, 20, 20, 20, 20]);
+        assert_eq!(row_level_caps(20, &progression[2..]), vec![20, 20, 20]);
+    }
+
+    #[test]
+    fn promotions_below_their_level_are_early() {
+        let level_up = ConcreteStatChange::GbaFeStatChange(GbaFeStatChange::LevelUp);
+        let promotion = ConcreteStatChange::GbaFeStatChange(GbaFeStatChange::Promotion(
+            ClassTier::Unpromoted,
+            Character {
+                stats : BTreeMap::new(),
+                name : "".to_owned(),
+                level : 10
+            }
+        ));
+        let progression = [level_up.clone(), promotion.clone(), level_up, promotion];
+        assert_eq!(
+            early_promotions(9, &progression),
+            vec![None, None, None, Some((2, 10))]
+        );
+        assert_eq!(early_promotions(8, &progression)[1], Some((9, 10)));
+    }
+
+    #[test]
+    fn edits_are_described_by_the_entries_they_touch() {
+        let level_up = ConcreteStatChange::PorStatChange(PorStatChange::LevelUp);
+        let unequip = ConcreteStatChange::PorStatChange(PorStatChange::UnequipGrowthBand);
+        let progression = vec![level_up.clone(); 4];
+        assert_eq!(describe_edit(&progression, &progression), None);
+
+        let mut inserted = progression.clone();
+        inserted.insert(1, unequip.clone());
+        let added = describe_edit(&progression, &inserted);
+        assert_eq!(added, Some(format!("Added {unequip} as #2")));
+        let removed = describe_edit(&inserted, &progression);
+        assert_eq!(removed, Some(format!("Removed {unequip} from #2")));
+        let cleared = describe_edit(&progression, &[]);
+        assert_eq!(cleared.as_deref(), Some("Removed 4 entries from #1 to #4"));
+
+        let mut moved = inserted.clone();
+        moved.swap(1, 3);
+        assert_eq!(
+            describe_edit(&inserted, &moved).as_deref(),
+            Some("Reordered #2 to #4")
+        );
+        let mut changed = progression.clone();
+        changed[2] = unequip.clone();
+        let description = describe_edit(&progression, &changed);
+        assert_eq!(
+            description,
+            Some(format!("Changed #3 from {level_up} to {unequip}"))
+        );
+        let replaced = describe_edit(&progression, &[unequip.clone(), unequip.clone()]);
+        assert_eq!(
+            replaced,
+            Some(format!("Replaced #1 to #4 with: {unequip}, {unequip}"))
+        );
+    }
+
+    #[test]
+    fn growth_band_scopes_nest_and_reject_unmatched_ends() {
+        let level_up = ConcreteStatChange::PorStatChange(PorStatChange::LevelUp);
+        let equip = ConcreteStatChange::PorStatChange(PorStatChange::GrowthBand(BTreeMap::new()));
+        let unequip = ConcreteStatChange::PorStatChange(PorStatChange::UnequipGrowthBand);
+        let progression = [
+            equip.clone(),
+            level_up.clone(),
+            equip,
+            level_up.clone(),
+            unequip.clone(),
+            unequip.clone(),
+            level_up,
+            unequip
+        ];
+        assert_eq!(
+            growth_scope_depths(&progression),
+            vec![
+                Some(0),
+                Some(1),
+                Some(1),
+                Some(2),
+                Some(1),
+                Some(0),
+                Some(0),
+                None
+            ]
+        );
+        assert!(growth_scopes_match(&progression[..7]));
+        assert!(!growth_scopes_match(&progression));
+    }
+
+    #[test]
+    fn class_changes_replace_the_class_growths() {
+        let class_change = |name| {
+            ConcreteStatChange::ThreeHousesStatChange(ThreeHousesStatChange::ClassChange(
+                three_houses::find_class(name).unwrap()
+            ))
+        };
+        let level_up = ConcreteStatChange::ThreeHousesStatChange(ThreeHousesStatChange::LevelUp);
+        let progression = [
+            class_change("Warrior"),
+            level_up.clone(),
+            class_change("Mage"),
+            level_up
+        ];
+        let hp = StatIndexType::arbitrary_valid(GameKind::ThreeHouses);
+        let hp_growths = compile_progression(&progression)
+            .into_iter()
+            .filter_map(|stat_change| match stat_change {
+                StatChange::LevelUp {
+                    temporary_growth_override: Some(growth_override),
+                    ..
+                } => Some(growth_override(&hp, 50)),
+                _ => None
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(hp_growths, vec![80, 50]);
+    }
+
+    #[test]
+    fn scrolls_stack_and_drop_by_name() {
+        let scroll = |name : &str| {
+            ConcreteStatChange::CustomStatChange(CustomStatChange::Scroll(name.to_owned()))
+        };
+        let drop = |name : &str| {
+            ConcreteStatChange::CustomStatChange(CustomStatChange::DropScroll(name.to_owned()))
+        };
+        let level_up = ConcreteStatChange::CustomStatChange(CustomStatChange::LevelUp);
+        let progression = [
+            scroll("Odo"),
+            scroll("Baldo"),
+            level_up.clone(),
+            drop("Odo"),
+            level_up,
+            drop("Odo")
+        ];
+        let held = |names : &[&str]| -> Option<Vec<String>> {
+            Some(names.iter().map(|name| (*name).to_owned()).collect())
+        };
+        assert_eq!(
+            held_growth_items(&progression),
+            vec![
+                held(&[]),
+                held(&["Odo"]),
+                held(&["Odo", "Baldo"]),
+                held(&["Baldo"]),
+                held(&["Baldo"]),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn transfer_bonuses_stop_at_the_cap() {
+        let mut character = StatIndexType::new_default_character(GameKind::RD);
+        let mut stats = StatIndexType::new(GameKind::RD).into_iter();
+        let (near_cap, far_from_cap) = (stats.next().unwrap(), stats.next().unwrap());
+        let cap = character.stats[&near_cap].cap;
+        for (sit, value) in [(near_cap, cap - 1), (far_from_cap, 0)] {
+            let stat = character.stats.get_mut(&sit).unwrap();
+            stat.base = 0;
+            stat.value = value;
+        }
+        let bonuses = [(near_cap, 2), (far_from_cap, 2)].into_iter().collect();
+        let progression = [ConcreteStatChange::RdStatChange(
+            RdStatChange::TransferBonus(bonuses)
+        )];
+        let transferred = exported_character(&character, &progression, &BTreeMap::new(), 1, "");
+        assert_eq!(transferred.stats[&near_cap].value, cap);
+        assert_eq!(transferred.stats[&far_from_cap].value, 2);
+    }
+
+    #[test]
+    fn growth_boosters_saved_without_amounts_load_as_afas_drops() {
+        let loaded : GbaFeStatChange = ron::from_str("GrowthBooster").unwrap();
+        assert_eq!(loaded, gba::afas_drops());
+        let saved = ron::to_string(&loaded).unwrap();
+        assert_eq!(ron::from_str::<GbaFeStatChange>(&saved).unwrap(), loaded);
+    }
 }
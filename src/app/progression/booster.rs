@@ -0,0 +1,214 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use egui::{Grid, TextEdit, Ui};
+use fe_levels::{GrowthType, StatChange, StatType};
+
+use crate::app::{
+    locale::tr,
+    numerical_text_box,
+    sit::{template_stat, StatIndexType},
+    GameKind
+};
+
+/// A stat boosting item as it's named in-game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoosterItem {
+    pub name : &'static str,
+    pub stat : StatIndexType,
+    pub amount : StatType
+}
+
+/// The boosters of every stat in the iteration order of the game, GBA
+/// following FE7 and FE8 which grant +2 Con with the Body Ring and +2 Mov with
+/// the Boots.
+const GBA_FE_BOOSTERS : [(&str, StatType); 9] = [
+    ("Angelic Robe", 7),
+    ("Energy Ring", 2),
+    ("Secret Book", 2),
+    ("Speedwings", 2),
+    ("Goddess Icon", 2),
+    ("Dragonshield", 2),
+    ("Talisman", 2),
+    ("Body Ring", 2),
+    ("Boots", 2)
+];
+const TELLIUS_BOOSTERS : [(&str, StatType); 8] = [
+    ("Seraph Robe", 7),
+    ("Energy Drop", 2),
+    ("Spirit Dust", 2),
+    ("Secret Book", 2),
+    ("Speedwing", 2),
+    ("Ashera Icon", 2),
+    ("Dracoshield", 2),
+    ("Talisman", 2)
+];
+
+/// The stat boosters of the given game, empty for games without a fixed set
+/// of them.
+pub fn booster_catalog(game : GameKind) -> Vec<BoosterItem> {
+    let items : &[(&str, StatType)] = match game {
+        GameKind::GbaFe => &GBA_FE_BOOSTERS,
+        GameKind::PoR | GameKind::RD => &TELLIUS_BOOSTERS,
+        // SoV has no stat boosters, Shadow Dragon's and Three Houses' aren't
+        // listed yet and custom games name their own
+        GameKind::ShadowDragon | GameKind::SoV | GameKind::ThreeHouses | GameKind::Custom => &[]
+    };
+    StatIndexType::new(game)
+        .into_iter()
+        .zip(items)
+        .map(|(stat, &(name, amount))| BoosterItem { name, stat, amount })
+        .collect()
+}
+
+/// The item granting exactly the given amount of the given stat, if any.
+pub fn find_booster(stat : StatIndexType, amount : StatType) -> Option<BoosterItem> {
+    booster_catalog(stat.game())
+        .into_iter()
+        .find(|item| item.stat == stat && item.amount == amount)
+}
+
+/// The amount the game's booster of the given stat grants, +7 HP and +2 for
+/// the rest where the game has no catalog.
+pub fn default_booster_amount(stat : StatIndexType) -> StatType {
+    booster_catalog(stat.game())
+        .into_iter()
+        .find(|item| item.stat == stat)
+        .map_or(if stat.is_hp() { 7 } else { 2 }, |item| item.amount)
+}
+
+/// Names the booster by its item where it matches one.
+pub fn booster_name(stat : StatIndexType, amount : StatType) -> String {
+    if stat == template_stat(stat.game()) {
+        "Stat Booster".to_owned()
+    }
+    else if let Some(item) = find_booster(stat, amount) {
+        format!("{} (+{amount} {stat})", item.name)
+    }
+    else {
+        format!("+{amount} {stat} Booster")
+    }
+}
+
+/// Lets the user pick one of the game's boosters or adjust the stat and the
+/// amount freely, true on return indicating they confirmed the booster.
+pub fn booster_dialogue(
+    game_option : GameKind,
+    stat : &mut StatIndexType,
+    amount : &mut StatType,
+    ui : &mut Ui
+) -> bool {
+    if *stat == template_stat(game_option) {
+        *stat = StatIndexType::arbitrary_valid(game_option);
+        *amount = default_booster_amount(*stat);
+    }
+
+    let catalog = booster_catalog(game_option);
+    if !catalog.is_empty() {
+        let custom = tr("progression.custom_booster");
+        let selected = find_booster(*stat, *amount).map_or(custom.as_str(), |item| item.name);
+        egui::containers::ComboBox::from_label(tr("progression.booster_item"))
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                for item in catalog {
+                    if ui
+                        .selectable_label(item.name == selected, item.name)
+                        .clicked()
+                    {
+                        *stat = item.stat;
+                        *amount = item.amount;
+                    }
+                }
+            });
+    }
+    egui::containers::ComboBox::from_label(tr("progression.boosted_stat"))
+        .selected_text(stat.to_string())
+        .show_ui(ui, |ui| {
+            for key in StatIndexType::new(game_option) {
+                ui.selectable_value(stat, key, key.to_string());
+            }
+        });
+    ui.horizontal(|ui| {
+        ui.label(tr("progression.amount"));
+        numerical_text_box(ui, amount);
+    });
+    ui.button(tr("common.confirm")).clicked()
+}
+
+/// One template per booster of the game, led by a configurable one for
+/// boosters not in the catalog.
+pub fn booster_templates<T>(
+    game_option : GameKind,
+    make : impl Fn(StatIndexType, StatType) -> T
+) -> Vec<T> {
+    std::iter::once(make(template_stat(game_option), 0))
+        .chain(
+            booster_catalog(game_option)
+                .into_iter()
+                .map(|item| make(item.stat, item.amount))
+        )
+        .collect()
+}
+
+/// The item of the game permanently raising every growth by the same amount,
+/// custom games starting from Afa's Drops as most romhacks keep them.
+fn growth_booster_preset(game : GameKind) -> Option<(&'static str, GrowthType)> {
+    match game {
+        GameKind::GbaFe | GameKind::Custom => Some(("Afa's Drops", 5)),
+        GameKind::RD => Some(("Metis Tome", 2)),
+        GameKind::PoR | GameKind::ShadowDragon | GameKind::SoV | GameKind::ThreeHouses => None
+    }
+}
+
+/// The growth increases and the name of the game's growth booster, if it has
+/// one.
+pub fn growth_booster_template(
+    game : GameKind
+) -> Option<(BTreeMap<StatIndexType, GrowthType>, String)> {
+    let (name, amount) = growth_booster_preset(game)?;
+    let per_stat = StatIndexType::new(game)
+        .into_iter()
+        .filter(StatIndexType::is_growable)
+        .map(|stat| (stat, amount))
+        .collect();
+    Some((per_stat, name.to_owned()))
+}
+
+/// Raises the growths by the given amounts for the rest of the progression.
+pub fn boost_growths(per_stat : BTreeMap<StatIndexType, GrowthType>) -> StatChange<StatIndexType> {
+    StatChange::Promotion {
+        promo_changes : Arc::new(move |sit, mut stat| {
+            if let Some(increase) = per_stat.get(sit) {
+                stat.growth = stat.growth.saturating_add(*increase);
+            }
+            stat
+        })
+    }
+}
+
+/// Lets the user adjust the name and the growth increases of a growth
+/// booster, true on return indicating they confirmed it.
+pub fn growth_booster_dialogue(
+    game_option : GameKind,
+    per_stat : &mut BTreeMap<StatIndexType, GrowthType>,
+    name : &mut String,
+    ui : &mut Ui
+) -> bool {
+    ui.horizontal(|ui| {
+        ui.label(tr("progression.item_name"));
+        ui.add(TextEdit::singleline(name));
+    });
+    Grid::new("Growth Booster Grid")
+        .num_columns(2)
+        .show(ui, |ui| {
+            ui.label(tr("common.stat"));
+            ui.label(tr("progression.growth_increase"));
+            ui.end_row();
+            for stat in StatIndexType::new(game_option) {
+                ui.label(stat.to_string());
+                numerical_text_box(ui, per_stat.entry(stat).or_default());
+                ui.end_row();
+            }
+        });
+    ui.add_enabled(!name.is_empty(), egui::Button::new(tr("common.confirm")))
+        .clicked()
+}
@@ -0,0 +1,309 @@
+use std::{collections::BTreeMap, fmt, sync::Arc};
+
+use egui::Ui;
+use fe_levels::{BlankAvoidance, Character, GrowthType, StatChange, StatType};
+use serde::{Deserialize, Serialize};
+
+use crate::app::{
+    locale::tr,
+    sit::{template_stat, StatIndexType},
+    GameData, GameKind
+};
+
+use super::{
+    booster::{
+        boost_growths, booster_dialogue, booster_name, booster_templates, growth_booster_dialogue,
+        growth_booster_template
+    },
+    promotion_dialogue,
+    scroll::{scroll_dialogue, scroll_modifiers},
+    GrowthModifier, GrowthScope, UsefulStatChange
+};
+
+/// The stat changes of a game with user-named stats, following GBA FE rules as
+/// most romhacks are built on those engines.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CustomStatChange {
+    Promotion(Character<StatIndexType>),
+    LevelUp,
+    /// raising the stat by the given amount
+    StatBooster(StatIndexType, StatType),
+    /// raising the growths by the given amounts for good, e.g. with Afa's Drops
+    GrowthBooster {
+        per_stat : BTreeMap<StatIndexType, GrowthType>,
+        name : String
+    },
+    /// equipping the named FE5 scroll, changing the growths of the level-ups
+    /// until it's dropped again, summed with those of the other scrolls held
+    Scroll(String),
+    /// dropping the named scroll again
+    DropScroll(String)
+}
+
+/// The key of the help on the game of user-named stats, following GBA's rules.
+pub const HELP : &str = "help.custom";
+
+impl UsefulStatChange for CustomStatChange {
+    fn compile(self) -> StatChange<StatIndexType> {
+        match self {
+            CustomStatChange::Promotion(promotion_gains) => StatChange::Promotion {
+                promo_changes : Arc::new(move |sit, mut stat| {
+                    promotion_gains
+                        .stats
+                        .get(sit)
+                        .map(|bonus| {
+                            stat.cap = bonus.cap;
+                            stat.increase_value(bonus.value);
+                            stat
+                        })
+                        .unwrap_or(stat)
+                })
+            },
+            CustomStatChange::LevelUp => StatChange::LevelUp {
+                temporary_growth_override : None,
+                blank_avoidance : BlankAvoidance::RetriesForNoBlank(2)
+            },
+            CustomStatChange::StatBooster(boosted_sit, amount) => StatChange::Promotion {
+                promo_changes : Arc::new(move |sit, mut stat| {
+                    if *sit == boosted_sit {
+                        stat.increase_value(amount)
+                    }
+                    stat
+                })
+            },
+            CustomStatChange::GrowthBooster { per_stat, .. } => boost_growths(per_stat),
+            // the held scrolls' growths are folded into the level-ups by
+            // `compile_progression`
+            CustomStatChange::Scroll(_) | CustomStatChange::DropScroll(_) => StatChange::Promotion {
+                promo_changes : Arc::new(|_sit, stat| stat)
+            }
+        }
+    }
+
+    fn marking_worthy(&self) -> bool {
+        match self {
+            CustomStatChange::Promotion(_) => true,
+            CustomStatChange::LevelUp => false,
+            CustomStatChange::StatBooster(..) => false,
+            CustomStatChange::GrowthBooster { .. } => false,
+            CustomStatChange::Scroll(_) => false,
+            CustomStatChange::DropScroll(_) => false
+        }
+    }
+
+    fn increases_level_counter(&self) -> bool {
+        match self {
+            CustomStatChange::Promotion(_) => false,
+            CustomStatChange::LevelUp => true,
+            CustomStatChange::StatBooster(..) => false,
+            CustomStatChange::GrowthBooster { .. } => false,
+            CustomStatChange::Scroll(_) => false,
+            CustomStatChange::DropScroll(_) => false
+        }
+    }
+
+    fn resets_level_counter(&self) -> bool {
+        match self {
+            CustomStatChange::Promotion(_) => true,
+            CustomStatChange::LevelUp => false,
+            CustomStatChange::StatBooster(..) => false,
+            CustomStatChange::GrowthBooster { .. } => false,
+            CustomStatChange::Scroll(_) => false,
+            CustomStatChange::DropScroll(_) => false
+        }
+    }
+
+    fn clarification_dialogue(
+        self,
+        context : &mut GameData,
+        ui : &mut Ui
+    ) -> (CustomStatChange, bool) {
+        match self {
+            CustomStatChange::Promotion(mut promotion_gains) => {
+                // saved promotions may predate stats the user added or removed since
+                let stats = StatIndexType::new(GameKind::Custom);
+                promotion_gains
+                    .stats
+                    .retain(|sit, _stat| stats.contains(sit));
+                for sit in stats {
+                    promotion_gains
+                        .stats
+                        .entry(sit)
+                        .or_insert_with(|| sit.default_stat());
+                }
+                let confirmed = promotion_dialogue(
+                    &mut promotion_gains,
+                    &tr("promotion.gain"),
+                    None,
+                    context,
+                    ui
+                );
+                (CustomStatChange::Promotion(promotion_gains), confirmed)
+            },
+            CustomStatChange::LevelUp => (self, true),
+            // boosters dragged from their item's template are complete already
+            CustomStatChange::StatBooster(stat, _) if stat != template_stat(GameKind::Custom) => {
+                (self, true)
+            },
+            CustomStatChange::StatBooster(mut stat, mut amount) => {
+                let confirmed = booster_dialogue(GameKind::Custom, &mut stat, &mut amount, ui);
+                (CustomStatChange::StatBooster(stat, amount), confirmed)
+            },
+            CustomStatChange::GrowthBooster {
+                mut per_stat,
+                mut name
+            } => {
+                let confirmed =
+                    growth_booster_dialogue(GameKind::Custom, &mut per_stat, &mut name, ui);
+                (
+                    CustomStatChange::GrowthBooster { per_stat, name },
+                    confirmed
+                )
+            },
+            CustomStatChange::Scroll(mut name) => {
+                let confirmed = scroll_dialogue(&mut name, ui);
+                (CustomStatChange::Scroll(name), confirmed)
+            },
+            CustomStatChange::DropScroll(mut name) => {
+                let confirmed = scroll_dialogue(&mut name, ui);
+                (CustomStatChange::DropScroll(name), confirmed)
+            }
+        }
+    }
+
+    fn requires_clarification(&self) -> bool {
+        match self {
+            CustomStatChange::Promotion(_) => true,
+            CustomStatChange::LevelUp => false,
+            CustomStatChange::StatBooster(stat, _) => *stat == template_stat(GameKind::Custom),
+            CustomStatChange::GrowthBooster { .. } => true,
+            CustomStatChange::Scroll(name) => name.is_empty(),
+            CustomStatChange::DropScroll(name) => name.is_empty()
+        }
+    }
+
+    fn level_cap_before(&self) -> Option<usize> { None }
+
+    fn promotion_level(&self) -> Option<usize> {
+        match self {
+            CustomStatChange::Promotion(promotion) => Some(promotion.level),
+            _ => None
+        }
+    }
+
+    fn growth_scope(&self) -> Option<GrowthScope> {
+        match self {
+            CustomStatChange::Scroll(name) => {
+                Some(GrowthScope::Start(name.clone(), scroll_modifiers(name)))
+            },
+            CustomStatChange::DropScroll(name) => Some(GrowthScope::End(Some(name.clone()))),
+            _ => None
+        }
+    }
+
+    fn class_growths(&self) -> Option<BTreeMap<StatIndexType, GrowthModifier>> { None }
+
+    fn cheap_to_execute(&self) -> bool { true }
+
+    fn simulation_cost(&self) -> f64 {
+        match self {
+            CustomStatChange::LevelUp => 1.0,
+            CustomStatChange::Promotion(_) => 0.3,
+            CustomStatChange::StatBooster(..) => 0.3,
+            CustomStatChange::GrowthBooster { .. } => 0.3,
+            CustomStatChange::Scroll(_) => 0.3,
+            CustomStatChange::DropScroll(_) => 0.3
+        }
+    }
+
+    fn explanation(&self) -> String {
+        tr(match self {
+            CustomStatChange::Promotion(_) => "explain.promotion",
+            CustomStatChange::LevelUp => "explain.level_up_two_rerolls",
+            CustomStatChange::StatBooster(..) => "explain.stat_booster",
+            CustomStatChange::GrowthBooster { .. } => "explain.growth_booster",
+            CustomStatChange::Scroll(_) => "explain.scroll",
+            CustomStatChange::DropScroll(_) => "explain.drop_scroll"
+        })
+    }
+
+    fn generate_templates(game_option : GameKind) -> Vec<Self>
+    where
+        Self : Sized
+    {
+        debug_assert!(game_option == GameKind::Custom);
+        let mut templates = vec![
+            CustomStatChange::LevelUp,
+            CustomStatChange::Scroll(String::new()),
+            CustomStatChange::DropScroll(String::new()),
+            CustomStatChange::Promotion(Character {
+                stats : StatIndexType::new_default_character(GameKind::Custom)
+                    .stats
+                    .into_iter()
+                    .map(|(sit, mut stat)| {
+                        stat.growth = 0;
+                        stat.value = 2;
+                        if !sit.is_hp() && !sit.is_luck() {
+                            stat.cap += 5;
+                        };
+                        (sit, stat)
+                    })
+                    .collect(),
+                name : "".to_owned(),
+                // the Master Seal requires level 10
+                level : 10
+            }),
+        ];
+        if let Some((per_stat, name)) = growth_booster_template(GameKind::Custom) {
+            templates.push(CustomStatChange::GrowthBooster { per_stat, name });
+        }
+        templates.extend(booster_templates(
+            GameKind::Custom,
+            CustomStatChange::StatBooster
+        ));
+        templates
+    }
+}
+
+impl fmt::Display for CustomStatChange {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CustomStatChange::Promotion(promotion) => {
+                if promotion.name.is_empty() {
+                    write!(f, "Promotion")
+                }
+                else {
+                    write!(f, "{} Promotion", promotion.name)
+                }
+            },
+            CustomStatChange::LevelUp => write!(f, "Level-Up"),
+            CustomStatChange::StatBooster(stat, amount) => {
+                write!(f, "{}", booster_name(*stat, *amount))
+            },
+            CustomStatChange::GrowthBooster { name, .. } => {
+                if name.is_empty() {
+                    write!(f, "Growth Booster")
+                }
+                else {
+                    write!(f, "{name}")
+                }
+            },
+            CustomStatChange::Scroll(name) => {
+                if name.is_empty() {
+                    write!(f, "Crusader Scroll")
+                }
+                else {
+                    write!(f, "{name} Scroll")
+                }
+            },
+            CustomStatChange::DropScroll(name) => {
+                if name.is_empty() {
+                    write!(f, "Drop Scroll")
+                }
+                else {
+                    write!(f, "Drop {name} Scroll")
+                }
+            },
+        }
+    }
+}
@@ -1,29 +1,85 @@
-use std::{fmt, sync::Arc};
+use std::{collections::BTreeMap, fmt, sync::Arc};
 
-use egui::{Button, Grid, ScrollArea, Ui};
-use fe_levels::{BlankAvoidance, Character, StatChange};
+use egui::Ui;
+use fe_levels::{BlankAvoidance, Character, GrowthType, StatChange, StatType};
 use serde::{Deserialize, Serialize};
 
 use crate::app::{
-    numerical_text_box,
+    database::ClassTier,
+    locale::tr,
     sit::{template_stat, StatIndexType},
     GameData, GameKind
 };
 
-use super::{PromotionSelectionKind, UsefulStatChange};
+use super::{
+    booster::{
+        boost_growths, booster_dialogue, booster_name, booster_templates, growth_booster_dialogue,
+        growth_booster_template
+    },
+    promotion_dialogue, GrowthModifier, GrowthScope, UsefulStatChange
+};
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "SavedGbaFeStatChange")]
 pub enum GbaFeStatChange {
-    Promotion(Character<StatIndexType>),
+    /// promoting a class of the given tier, resetting to level 1 in the next
+    Promotion(ClassTier, Character<StatIndexType>),
+    LevelUp,
+    /// raising the growths by the given amounts for good, e.g. with Afa's Drops
+    #[serde(rename = "NamedGrowthBooster")]
+    GrowthBooster {
+        per_stat : BTreeMap<StatIndexType, GrowthType>,
+        name : String
+    },
+    /// raising the stat by the given amount, e.g. with an Energy Ring
+    StatBooster(StatIndexType, StatType)
+}
+
+/// [`GbaFeStatChange`] as it's saved, growth boosters saved before they were
+/// configurable being Afa's Drops.
+#[derive(Deserialize)]
+enum SavedGbaFeStatChange {
+    Promotion(ClassTier, Character<StatIndexType>),
     LevelUp,
     GrowthBooster,
-    StatBooster(StatIndexType)
+    NamedGrowthBooster {
+        per_stat : BTreeMap<StatIndexType, GrowthType>,
+        name : String
+    },
+    StatBooster(StatIndexType, StatType)
+}
+
+impl From<SavedGbaFeStatChange> for GbaFeStatChange {
+    fn from(saved : SavedGbaFeStatChange) -> Self {
+        match saved {
+            SavedGbaFeStatChange::Promotion(tier, promotion) => {
+                GbaFeStatChange::Promotion(tier, promotion)
+            },
+            SavedGbaFeStatChange::LevelUp => GbaFeStatChange::LevelUp,
+            SavedGbaFeStatChange::GrowthBooster => afas_drops(),
+            SavedGbaFeStatChange::NamedGrowthBooster { per_stat, name } => {
+                GbaFeStatChange::GrowthBooster { per_stat, name }
+            },
+            SavedGbaFeStatChange::StatBooster(stat, amount) => {
+                GbaFeStatChange::StatBooster(stat, amount)
+            },
+        }
+    }
+}
+
+/// The growth booster of the GBA games, raising every growth by 5.
+pub fn afas_drops() -> GbaFeStatChange {
+    let (per_stat, name) = growth_booster_template(GameKind::GbaFe).unwrap_or_default();
+    GbaFeStatChange::GrowthBooster { per_stat, name }
 }
 
+/// The key of the help on FE6 to FE8 as the analysis models them.
+pub const HELP : &str = "help.gba";
+
 impl UsefulStatChange for GbaFeStatChange {
     fn compile(self) -> StatChange<StatIndexType> {
         match self {
-            GbaFeStatChange::Promotion(promotion_gains) => StatChange::Promotion {
+            GbaFeStatChange::Promotion(_tier, promotion_gains) => StatChange::Promotion {
                 promo_changes : Arc::new(move |sit, mut stat| {
                     promotion_gains
                         .stats
@@ -40,16 +96,11 @@ impl UsefulStatChange for GbaFeStatChange {
                 temporary_growth_override : None,
                 blank_avoidance : BlankAvoidance::RetriesForNoBlank(2)
             },
-            GbaFeStatChange::GrowthBooster => StatChange::Promotion {
-                promo_changes : Arc::new(|_sit, mut stat| {
-                    stat.growth = stat.growth.saturating_add(5);
-                    stat
-                })
-            },
-            GbaFeStatChange::StatBooster(boosted_sit) => StatChange::Promotion {
+            GbaFeStatChange::GrowthBooster { per_stat, .. } => boost_growths(per_stat),
+            GbaFeStatChange::StatBooster(boosted_sit, amount) => StatChange::Promotion {
                 promo_changes : Arc::new(move |sit, mut stat| {
                     if *sit == boosted_sit {
-                        stat.increase_value(if boosted_sit.is_hp() { 7 } else { 2 })
+                        stat.increase_value(amount)
                     }
                     stat
                 })
@@ -59,28 +110,28 @@ impl UsefulStatChange for GbaFeStatChange {
 
     fn marking_worthy(&self) -> bool {
         match self {
-            GbaFeStatChange::Promotion(_) => true,
+            GbaFeStatChange::Promotion(..) => true,
             GbaFeStatChange::LevelUp => false,
-            GbaFeStatChange::GrowthBooster => false,
-            GbaFeStatChange::StatBooster(_) => false
+            GbaFeStatChange::GrowthBooster { .. } => false,
+            GbaFeStatChange::StatBooster(..) => false
         }
     }
 
     fn increases_level_counter(&self) -> bool {
         match self {
-            GbaFeStatChange::Promotion(_) => false,
+            GbaFeStatChange::Promotion(..) => false,
             GbaFeStatChange::LevelUp => true,
-            GbaFeStatChange::GrowthBooster => false,
-            GbaFeStatChange::StatBooster(_) => false
+            GbaFeStatChange::GrowthBooster { .. } => false,
+            GbaFeStatChange::StatBooster(..) => false
         }
     }
 
     fn resets_level_counter(&self) -> bool {
         match self {
-            GbaFeStatChange::Promotion(_) => true,
+            GbaFeStatChange::Promotion(..) => true,
             GbaFeStatChange::LevelUp => false,
-            GbaFeStatChange::GrowthBooster => false,
-            GbaFeStatChange::StatBooster(_) => false
+            GbaFeStatChange::GrowthBooster { .. } => false,
+            GbaFeStatChange::StatBooster(..) => false
         }
     }
 
@@ -90,157 +141,132 @@ impl UsefulStatChange for GbaFeStatChange {
         ui : &mut Ui
     ) -> (GbaFeStatChange, bool) {
         match self {
-            GbaFeStatChange::Promotion(mut promotion_gains) => {
-                ui.horizontal(|ui| {
-                    ui.radio_value(
-                        &mut context.progression.promotion_selection_strategy,
-                        PromotionSelectionKind::ManualPromotionEntry,
-                        "Manual Promotion Entry"
-                    );
-                    ui.radio_value(
-                        &mut context.progression.promotion_selection_strategy,
-                        PromotionSelectionKind::LoadSavedPromotion,
-                        "Select Saved Promotion"
-                    );
-                });
-
-                match context.progression.promotion_selection_strategy {
-                    PromotionSelectionKind::LoadSavedPromotion => {
-                        ScrollArea::vertical().show_rows(
-                            ui,
-                            ui.text_style_height(&egui::TextStyle::Body),
-                            context.progression.progression.len(),
-                            |ui, range| {
-                                for (name, promo) in
-                                    context.promotions.iter().take(range.end).skip(range.start)
-                                {
-                                    ui.selectable_value(&mut promotion_gains, promo.clone(), name);
-                                    ui.end_row();
-                                }
-                            }
-                        );
-                        let clicked = ui
-                            .add_enabled(
-                                context.promotions.contains_key(&promotion_gains.name),
-                                Button::new("load")
-                            )
-                            .on_disabled_hover_text("Please select a promotion.")
-                            .clicked();
-                        (GbaFeStatChange::Promotion(promotion_gains), clicked)
-                    },
-                    PromotionSelectionKind::ManualPromotionEntry => {
-                        ui.label("Promotion Target Class: ");
-                        ui.text_edit_singleline(&mut promotion_gains.name);
-                        Grid::new("Promotion Grid").num_columns(3).show(ui, |ui| {
-                            ui.label("");
-                            ui.label("promotion gain");
-                            ui.label("new cap");
-                            ui.end_row();
-
-                            for (sit, stat) in promotion_gains.stats.iter_mut() {
-                                ui.label(format!("{sit}"));
-                                numerical_text_box(ui, &mut stat.value);
-                                numerical_text_box(ui, &mut stat.cap);
-                                ui.end_row();
-                            }
-                        });
-                        let mut confirmed = false;
-                        ui.horizontal(|ui| {
-                            let name = &promotion_gains.name;
-                            confirmed = ui
-                                .add_enabled(!name.is_empty(), Button::new("confirm"))
-                                .on_disabled_hover_text(
-                                    "Please name the class you're promoting into."
-                                )
-                                .clicked();
-
-                            if ui
-                                .add_enabled(
-                                    context.promotions.check_legal_name(&promotion_gains.name),
-                                    Button::new("save")
-                                )
-                                .on_disabled_hover_text(
-                                    "Please name the class you're promoting into and make sure \
-                                     that you didn't previously save an equally named promotion."
-                                )
-                                .clicked()
-                            {
-                                context
-                                    .promotions
-                                    .insert(promotion_gains.name.clone(), promotion_gains.clone());
-                            }
-                        });
-
-                        (GbaFeStatChange::Promotion(promotion_gains), confirmed)
-                    }
-                }
+            GbaFeStatChange::Promotion(tier, mut promotion_gains) => {
+                let confirmed = promotion_dialogue(
+                    &mut promotion_gains,
+                    &tr("promotion.gain"),
+                    Some(tier),
+                    context,
+                    ui
+                );
+                (GbaFeStatChange::Promotion(tier, promotion_gains), confirmed)
             },
             GbaFeStatChange::LevelUp => (self, true),
-            GbaFeStatChange::GrowthBooster => (self, true),
-            GbaFeStatChange::StatBooster(mut stat) => {
-                if stat == template_stat(GameKind::GbaFe) {
-                    stat = StatIndexType::new(GameKind::GbaFe)[0];
-                }
-                egui::containers::ComboBox::from_label("Stat to Boost")
-                    .selected_text(format!("{}", stat))
-                    .show_ui(ui, |ui| {
-                        StatIndexType::new(GameKind::GbaFe).iter().for_each(|key| {
-                            ui.selectable_value(&mut stat, *key, key.to_string());
-                        });
-                    });
-                (
-                    GbaFeStatChange::StatBooster(stat),
-                    ui.button("Confirm").clicked()
-                )
+            GbaFeStatChange::GrowthBooster {
+                mut per_stat,
+                mut name
+            } => {
+                let confirmed =
+                    growth_booster_dialogue(GameKind::GbaFe, &mut per_stat, &mut name, ui);
+                (GbaFeStatChange::GrowthBooster { per_stat, name }, confirmed)
+            },
+            // boosters dragged from their item's template are complete already
+            GbaFeStatChange::StatBooster(stat, _) if stat != template_stat(GameKind::GbaFe) => {
+                (self, true)
+            },
+            GbaFeStatChange::StatBooster(mut stat, mut amount) => {
+                let confirmed = booster_dialogue(GameKind::GbaFe, &mut stat, &mut amount, ui);
+                (GbaFeStatChange::StatBooster(stat, amount), confirmed)
             }
         }
     }
 
     fn requires_clarification(&self) -> bool {
         match self {
-            GbaFeStatChange::Promotion(_) => true,
+            GbaFeStatChange::Promotion(..) => true,
             GbaFeStatChange::LevelUp => false,
-            GbaFeStatChange::GrowthBooster => false,
-            GbaFeStatChange::StatBooster(_) => true
+            GbaFeStatChange::GrowthBooster { .. } => true,
+            GbaFeStatChange::StatBooster(stat, _) => *stat == template_stat(GameKind::GbaFe)
+        }
+    }
+
+    fn level_cap_before(&self) -> Option<usize> {
+        match self {
+            GbaFeStatChange::Promotion(ClassTier::Trainee, _) => Some(10),
+            _ => None
         }
     }
 
+    fn promotion_level(&self) -> Option<usize> {
+        match self {
+            GbaFeStatChange::Promotion(_tier, promotion) => Some(promotion.level),
+            _ => None
+        }
+    }
+
+    fn growth_scope(&self) -> Option<GrowthScope> { None }
+
+    fn class_growths(&self) -> Option<BTreeMap<StatIndexType, GrowthModifier>> { None }
+
     fn cheap_to_execute(&self) -> bool { true }
 
+    fn simulation_cost(&self) -> f64 {
+        match self {
+            // blank avoidance may roll the level-up up to three times
+            GbaFeStatChange::LevelUp => 1.0,
+            GbaFeStatChange::Promotion(..) => 0.3,
+            GbaFeStatChange::GrowthBooster { .. } => 0.3,
+            GbaFeStatChange::StatBooster(..) => 0.3
+        }
+    }
+
+    fn explanation(&self) -> String {
+        tr(match self {
+            GbaFeStatChange::Promotion(..) => "explain.promotion",
+            GbaFeStatChange::LevelUp => "explain.level_up_two_rerolls",
+            GbaFeStatChange::GrowthBooster { .. } => "explain.growth_booster_gba",
+            GbaFeStatChange::StatBooster(..) => "explain.stat_booster_gba"
+        })
+    }
+
     fn generate_templates(game_option : GameKind) -> Vec<Self>
     where
         Self : Sized
     {
         debug_assert!(game_option == GameKind::GbaFe);
-        vec![
-            GbaFeStatChange::GrowthBooster,
+        let mut templates = vec![
+            afas_drops(),
             GbaFeStatChange::LevelUp,
-            GbaFeStatChange::StatBooster(template_stat(GameKind::GbaFe)),
-            GbaFeStatChange::Promotion(Character {
-                stats : StatIndexType::new_default_character(GameKind::GbaFe)
-                    .stats
-                    .into_iter()
-                    .map(|(sit, mut stat)| {
-                        stat.growth = 0;
-                        stat.value = 2;
-                        if !sit.is_hp() && !sit.is_luck() {
-                            stat.cap += 5;
-                        };
-                        (sit, stat)
-                    })
-                    .collect(),
-                name : "".to_owned(),
-                level : 1
-            }),
-        ]
+            GbaFeStatChange::Promotion(ClassTier::Unpromoted, promotion_template(5)),
+            GbaFeStatChange::Promotion(ClassTier::Trainee, promotion_template(0)),
+        ];
+        templates.extend(booster_templates(
+            GameKind::GbaFe,
+            GbaFeStatChange::StatBooster
+        ));
+        templates
+    }
+}
+
+/// Promotion gains of 2 in every stat, raising the caps other than HP and Luck
+/// by the given amount. Every GBA promotion requires level 10.
+fn promotion_template(cap_increase : StatType) -> Character<StatIndexType> {
+    Character {
+        stats : StatIndexType::new_default_character(GameKind::GbaFe)
+            .stats
+            .into_iter()
+            .map(|(sit, mut stat)| {
+                stat.growth = 0;
+                stat.value = 2;
+                if !sit.is_hp() && !sit.is_luck() {
+                    stat.cap += cap_increase;
+                };
+                (sit, stat)
+            })
+            .collect(),
+        name : "".to_owned(),
+        level : 10
     }
 }
 
 impl fmt::Display for GbaFeStatChange {
     fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            GbaFeStatChange::Promotion(promotion) => {
-                if promotion.name.is_empty() {
+            GbaFeStatChange::Promotion(tier, promotion) => {
+                if promotion.name.is_empty() && *tier == ClassTier::Trainee {
+                    write!(f, "Trainee Promotion")
+                }
+                else if promotion.name.is_empty() {
                     write!(f, "Promotion")
                 }
                 else {
@@ -248,17 +274,16 @@ impl fmt::Display for GbaFeStatChange {
                 }
             },
             GbaFeStatChange::LevelUp => write!(f, "Level-Up"),
-            GbaFeStatChange::GrowthBooster => write!(f, "5% Growth-Booster"),
-            GbaFeStatChange::StatBooster(stat) => {
-                if stat == &template_stat(GameKind::GbaFe) {
-                    write!(f, "Stat Booster")
-                }
-                else if stat.is_hp() {
-                    write!(f, "+7 HP Booster") // this is the angelic robe
+            GbaFeStatChange::GrowthBooster { name, .. } => {
+                if name.is_empty() {
+                    write!(f, "Growth Booster")
                 }
                 else {
-                    write!(f, "+2 {stat} Booster")
+                    write!(f, "{name}")
                 }
+            },
+            GbaFeStatChange::StatBooster(stat, amount) => {
+                write!(f, "{}", booster_name(*stat, *amount))
             }
         }
     }
@@ -0,0 +1,271 @@
+use std::{collections::BTreeMap, fmt, sync::Arc};
+
+use egui::{Grid, Ui};
+use fe_levels::{BlankAvoidance, Character, GrowthType, StatChange, StatType};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::app::{
+    locale::tr,
+    numerical_text_box,
+    sit::{template_stat, StatIndexType},
+    GameData, GameKind
+};
+
+use super::{
+    booster::{booster_dialogue, booster_name, booster_templates},
+    promotion_dialogue, GrowthModifier, GrowthScope, UsefulStatChange
+};
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PorStatChange {
+    Promotion(Character<StatIndexType>),
+    LevelUp,
+    /// raising the stat by the given amount, e.g. with an Energy Drop
+    StatBooster(StatIndexType, StatType),
+    /// equipping a band or the Knight Ward, raising the growths of the
+    /// level-ups by the given amounts until it's unequipped again
+    GrowthBand(BTreeMap<StatIndexType, GrowthType>),
+    /// unequipping the most recently equipped growth item
+    UnequipGrowthBand
+}
+
+/// The key of the help on Path of Radiance, whose level-ups have no protection
+/// at all.
+pub const HELP : &str = "help.por";
+
+impl UsefulStatChange for PorStatChange {
+    fn compile(self) -> StatChange<StatIndexType> {
+        match self {
+            PorStatChange::Promotion(promotion_gains) => StatChange::Promotion {
+                promo_changes : Arc::new(move |sit, mut stat| {
+                    promotion_gains
+                        .stats
+                        .get(sit)
+                        .map(|bonus| {
+                            stat.cap = bonus.cap;
+                            stat.increase_value(bonus.value);
+                            stat
+                        })
+                        .unwrap_or(stat)
+                })
+            },
+            // PoR has no protection against empty level-ups
+            PorStatChange::LevelUp => StatChange::LevelUp {
+                temporary_growth_override : None,
+                blank_avoidance : BlankAvoidance::NoAvoidance
+            },
+            PorStatChange::StatBooster(boosted_sit, amount) => StatChange::Promotion {
+                promo_changes : Arc::new(move |sit, mut stat| {
+                    if *sit == boosted_sit {
+                        stat.increase_value(amount)
+                    }
+                    stat
+                })
+            },
+            // the held items' growths are folded into the level-ups by
+            // `compile_progression`
+            PorStatChange::GrowthBand(_) | PorStatChange::UnequipGrowthBand => {
+                StatChange::Promotion {
+                    promo_changes : Arc::new(|_sit, stat| stat)
+                }
+            },
+        }
+    }
+
+    fn marking_worthy(&self) -> bool {
+        match self {
+            PorStatChange::Promotion(_) => true,
+            PorStatChange::LevelUp => false,
+            PorStatChange::StatBooster(..) => false,
+            PorStatChange::GrowthBand(_) => false,
+            PorStatChange::UnequipGrowthBand => false
+        }
+    }
+
+    fn increases_level_counter(&self) -> bool {
+        match self {
+            PorStatChange::Promotion(_) => false,
+            PorStatChange::LevelUp => true,
+            PorStatChange::StatBooster(..) => false,
+            PorStatChange::GrowthBand(_) => false,
+            PorStatChange::UnequipGrowthBand => false
+        }
+    }
+
+    fn resets_level_counter(&self) -> bool {
+        match self {
+            PorStatChange::Promotion(_) => true,
+            PorStatChange::LevelUp => false,
+            PorStatChange::StatBooster(..) => false,
+            PorStatChange::GrowthBand(_) => false,
+            PorStatChange::UnequipGrowthBand => false
+        }
+    }
+
+    fn clarification_dialogue(
+        self,
+        context : &mut GameData,
+        ui : &mut Ui
+    ) -> (PorStatChange, bool) {
+        match self {
+            PorStatChange::Promotion(mut promotion_gains) => {
+                let confirmed = promotion_dialogue(
+                    &mut promotion_gains,
+                    &tr("promotion.gain"),
+                    None,
+                    context,
+                    ui
+                );
+                (PorStatChange::Promotion(promotion_gains), confirmed)
+            },
+            PorStatChange::LevelUp => (self, true),
+            // boosters dragged from their item's template are complete already
+            PorStatChange::StatBooster(stat, _) if stat != template_stat(GameKind::PoR) => {
+                (self, true)
+            },
+            PorStatChange::StatBooster(mut stat, mut amount) => {
+                let confirmed = booster_dialogue(GameKind::PoR, &mut stat, &mut amount, ui);
+                (PorStatChange::StatBooster(stat, amount), confirmed)
+            },
+            PorStatChange::GrowthBand(mut bonuses) => {
+                ui.label(tr("progression.band_increase"));
+                Grid::new("Growth Band Grid").num_columns(2).show(ui, |ui| {
+                    for sit in StatIndexType::new(GameKind::PoR) {
+                        ui.label(format!("{sit}"));
+                        let bonus = bonuses.entry(sit).or_default();
+                        numerical_text_box(ui, bonus);
+                        ui.end_row();
+                    }
+                });
+                bonuses.retain(|_sit, bonus| *bonus > 0);
+                let confirmed = ui.button(tr("common.confirm")).clicked();
+                (PorStatChange::GrowthBand(bonuses), confirmed)
+            },
+            PorStatChange::UnequipGrowthBand => (self, true)
+        }
+    }
+
+    fn requires_clarification(&self) -> bool {
+        match self {
+            PorStatChange::Promotion(_) => true,
+            PorStatChange::LevelUp => false,
+            PorStatChange::StatBooster(stat, _) => *stat == template_stat(GameKind::PoR),
+            PorStatChange::GrowthBand(_) => true,
+            PorStatChange::UnequipGrowthBand => false
+        }
+    }
+
+    fn level_cap_before(&self) -> Option<usize> { None }
+
+    fn promotion_level(&self) -> Option<usize> {
+        match self {
+            PorStatChange::Promotion(promotion) => Some(promotion.level),
+            _ => None
+        }
+    }
+
+    fn growth_scope(&self) -> Option<GrowthScope> {
+        match self {
+            PorStatChange::GrowthBand(bonuses) => Some(GrowthScope::Start(
+                self.to_string(),
+                bonuses
+                    .iter()
+                    .map(|(sit, bonus)| (*sit, GrowthModifier::from(*bonus)))
+                    .collect()
+            )),
+            PorStatChange::UnequipGrowthBand => Some(GrowthScope::End(None)),
+            _ => None
+        }
+    }
+
+    fn class_growths(&self) -> Option<BTreeMap<StatIndexType, GrowthModifier>> { None }
+
+    fn cheap_to_execute(&self) -> bool { true }
+
+    fn simulation_cost(&self) -> f64 {
+        match self {
+            PorStatChange::LevelUp => 1.0,
+            PorStatChange::Promotion(_) => 0.3,
+            PorStatChange::StatBooster(..) => 0.3,
+            PorStatChange::GrowthBand(_) => 0.3,
+            PorStatChange::UnequipGrowthBand => 0.3
+        }
+    }
+
+    fn explanation(&self) -> String {
+        tr(match self {
+            PorStatChange::Promotion(_) => "explain.promotion",
+            PorStatChange::LevelUp => "explain.level_up_no_rerolls",
+            PorStatChange::StatBooster(..) => "explain.stat_booster_tellius",
+            PorStatChange::GrowthBand(_) => "explain.growth_band",
+            PorStatChange::UnequipGrowthBand => "explain.unequip_band"
+        })
+    }
+
+    fn generate_templates(game_option : GameKind) -> Vec<Self>
+    where
+        Self : Sized
+    {
+        debug_assert!(game_option == GameKind::PoR);
+        let mut templates = vec![
+            PorStatChange::LevelUp,
+            PorStatChange::GrowthBand(BTreeMap::new()),
+            PorStatChange::UnequipGrowthBand,
+            PorStatChange::Promotion(Character {
+                stats : StatIndexType::new_default_character(GameKind::PoR)
+                    .stats
+                    .into_iter()
+                    .map(|(sit, mut stat)| {
+                        stat.growth = 0;
+                        stat.value = 2;
+                        if !sit.is_hp() && !sit.is_luck() {
+                            stat.cap += 10;
+                        };
+                        (sit, stat)
+                    })
+                    .collect(),
+                name : "".to_owned(),
+                // the Master Seal requires level 10
+                level : 10
+            }),
+        ];
+        templates.extend(booster_templates(GameKind::PoR, PorStatChange::StatBooster));
+        templates
+    }
+}
+
+impl fmt::Display for PorStatChange {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PorStatChange::Promotion(promotion) => {
+                if promotion.name.is_empty() {
+                    write!(f, "Promotion")
+                }
+                else {
+                    write!(f, "{} Promotion", promotion.name)
+                }
+            },
+            PorStatChange::LevelUp => write!(f, "Level-Up"),
+            PorStatChange::StatBooster(stat, amount) => {
+                write!(f, "{}", booster_name(*stat, *amount))
+            },
+            PorStatChange::GrowthBand(bonuses) => {
+                if bonuses.is_empty() {
+                    write!(f, "Growth Band")
+                }
+                else {
+                    write!(
+                        f,
+                        "Growth Band ({})",
+                        bonuses
+                            .iter()
+                            .map(|(stat, bonus)| format!("+{bonus}% {stat}"))
+                            .join(", ")
+                    )
+                }
+            },
+            PorStatChange::UnequipGrowthBand => write!(f, "Unequip Growth Band")
+        }
+    }
+}
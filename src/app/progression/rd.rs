@@ -0,0 +1,318 @@
+use std::{collections::BTreeMap, fmt, ops::Bound, sync::Arc};
+
+use egui::Ui;
+use fe_levels::{BlankAvoidance, Character, GrowthType, StatChange, StatType};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::app::{
+    locale::tr,
+    sit::{template_stat, StatIndexType},
+    GameData, GameKind
+};
+
+use super::{
+    booster::{
+        boost_growths, booster_dialogue, booster_name, booster_templates, growth_booster_dialogue,
+        growth_booster_template
+    },
+    promotion_dialogue, GrowthModifier, GrowthScope, UsefulStatChange
+};
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PromotionTier {
+    /// promoting a first tier class, e.g. using a Master Seal
+    Second,
+    /// promoting a second tier class, e.g. using a Master Crown
+    Third
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RdStatChange {
+    Promotion(PromotionTier, Character<StatIndexType>),
+    LevelUp,
+    /// a level-up bought with bonus experience, always granting exactly three
+    /// stats
+    BexpLevelUp,
+    /// raising the stat by the given amount, e.g. with an Energy Drop
+    StatBooster(StatIndexType, StatType),
+    /// raising the growths by the given amounts for good, e.g. with the Metis
+    /// Tome
+    GrowthBooster {
+        per_stat : BTreeMap<StatIndexType, GrowthType>,
+        name : String
+    },
+    /// the bonuses to the bases granted for stats capped in the PoR save data
+    TransferBonus(BTreeMap<StatIndexType, StatType>)
+}
+
+/// The key of the help on Radiant Dawn, the only game with two kinds of
+/// level-ups.
+pub const HELP : &str = "help.rd";
+
+impl UsefulStatChange for RdStatChange {
+    fn compile(self) -> StatChange<StatIndexType> {
+        match self {
+            RdStatChange::Promotion(_tier, promotion_gains) => StatChange::Promotion {
+                promo_changes : Arc::new(move |sit, mut stat| {
+                    promotion_gains
+                        .stats
+                        .get(sit)
+                        .map(|bonus| {
+                            stat.cap = bonus.cap;
+                            stat.increase_value(bonus.value);
+                            stat
+                        })
+                        .unwrap_or(stat)
+                })
+            },
+            // regular level-ups are re-rolled until at least one stat grows
+            RdStatChange::LevelUp => StatChange::LevelUp {
+                temporary_growth_override : None,
+                blank_avoidance : BlankAvoidance::new_guaranteed_stats(1..)
+            },
+            // BEXP level-ups skip capped stats and break ties in display order
+            RdStatChange::BexpLevelUp => StatChange::LevelUp {
+                temporary_growth_override : None,
+                blank_avoidance : BlankAvoidance::GuaranteedStats(
+                    (Bound::Included(3), Bound::Included(3)),
+                    StatIndexType::new(GameKind::RD)
+                )
+            },
+            RdStatChange::StatBooster(boosted_sit, amount) => StatChange::Promotion {
+                promo_changes : Arc::new(move |sit, mut stat| {
+                    if *sit == boosted_sit {
+                        stat.increase_value(amount)
+                    }
+                    stat
+                })
+            },
+            RdStatChange::GrowthBooster { per_stat, .. } => boost_growths(per_stat),
+            RdStatChange::TransferBonus(bonuses) => StatChange::Promotion {
+                promo_changes : Arc::new(move |sit, mut stat| {
+                    if let Some(bonus) = bonuses.get(sit) {
+                        stat.increase_value(*bonus)
+                    }
+                    stat
+                })
+            }
+        }
+    }
+
+    fn marking_worthy(&self) -> bool {
+        match self {
+            RdStatChange::Promotion(..) => true,
+            RdStatChange::LevelUp => false,
+            RdStatChange::BexpLevelUp => false,
+            RdStatChange::StatBooster(..) => false,
+            RdStatChange::GrowthBooster { .. } => false,
+            RdStatChange::TransferBonus(_) => false
+        }
+    }
+
+    fn increases_level_counter(&self) -> bool {
+        match self {
+            RdStatChange::Promotion(..) => false,
+            RdStatChange::LevelUp => true,
+            RdStatChange::BexpLevelUp => true,
+            RdStatChange::StatBooster(..) => false,
+            RdStatChange::GrowthBooster { .. } => false,
+            RdStatChange::TransferBonus(_) => false
+        }
+    }
+
+    fn resets_level_counter(&self) -> bool {
+        match self {
+            RdStatChange::Promotion(..) => true,
+            RdStatChange::LevelUp => false,
+            RdStatChange::BexpLevelUp => false,
+            RdStatChange::StatBooster(..) => false,
+            RdStatChange::GrowthBooster { .. } => false,
+            RdStatChange::TransferBonus(_) => false
+        }
+    }
+
+    fn clarification_dialogue(self, context : &mut GameData, ui : &mut Ui) -> (RdStatChange, bool) {
+        match self {
+            RdStatChange::Promotion(tier, mut promotion_gains) => {
+                let confirmed = promotion_dialogue(
+                    &mut promotion_gains,
+                    &tr("promotion.gain"),
+                    None,
+                    context,
+                    ui
+                );
+                (RdStatChange::Promotion(tier, promotion_gains), confirmed)
+            },
+            RdStatChange::LevelUp => (self, true),
+            RdStatChange::BexpLevelUp => (self, true),
+            // boosters dragged from their item's template are complete already
+            RdStatChange::StatBooster(stat, _) if stat != template_stat(GameKind::RD) => {
+                (self, true)
+            },
+            RdStatChange::StatBooster(mut stat, mut amount) => {
+                let confirmed = booster_dialogue(GameKind::RD, &mut stat, &mut amount, ui);
+                (RdStatChange::StatBooster(stat, amount), confirmed)
+            },
+            RdStatChange::GrowthBooster {
+                mut per_stat,
+                mut name
+            } => {
+                let confirmed = growth_booster_dialogue(GameKind::RD, &mut per_stat, &mut name, ui);
+                (RdStatChange::GrowthBooster { per_stat, name }, confirmed)
+            },
+            RdStatChange::TransferBonus(_) => (self, true)
+        }
+    }
+
+    fn requires_clarification(&self) -> bool {
+        match self {
+            RdStatChange::Promotion(..) => true,
+            RdStatChange::LevelUp => false,
+            RdStatChange::BexpLevelUp => false,
+            RdStatChange::StatBooster(stat, _) => *stat == template_stat(GameKind::RD),
+            RdStatChange::GrowthBooster { .. } => true,
+            RdStatChange::TransferBonus(_) => false
+        }
+    }
+
+    fn level_cap_before(&self) -> Option<usize> { None }
+
+    fn promotion_level(&self) -> Option<usize> {
+        match self {
+            RdStatChange::Promotion(_tier, promotion) => Some(promotion.level),
+            _ => None
+        }
+    }
+
+    fn growth_scope(&self) -> Option<GrowthScope> { None }
+
+    fn class_growths(&self) -> Option<BTreeMap<StatIndexType, GrowthModifier>> { None }
+
+    fn cheap_to_execute(&self) -> bool {
+        match self {
+            RdStatChange::Promotion(..) => true,
+            // neither kind of level-up can be analyzed exactly yet
+            RdStatChange::LevelUp => false,
+            RdStatChange::BexpLevelUp => false,
+            RdStatChange::StatBooster(..) => true,
+            RdStatChange::GrowthBooster { .. } => true,
+            RdStatChange::TransferBonus(_) => true
+        }
+    }
+
+    fn simulation_cost(&self) -> f64 {
+        match self {
+            // blank level-ups are re-rolled, which is rare for decent growths
+            RdStatChange::LevelUp => 1.2,
+            RdStatChange::BexpLevelUp => 1.5,
+            RdStatChange::Promotion(..) => 0.3,
+            RdStatChange::StatBooster(..) => 0.3,
+            RdStatChange::GrowthBooster { .. } => 0.3,
+            RdStatChange::TransferBonus(_) => 0.3
+        }
+    }
+
+    fn explanation(&self) -> String {
+        tr(match self {
+            RdStatChange::Promotion(..) => "explain.promotion",
+            RdStatChange::LevelUp => "explain.level_up_one_grows",
+            RdStatChange::BexpLevelUp => "explain.bexp_level_up",
+            RdStatChange::StatBooster(..) => "explain.stat_booster_tellius",
+            RdStatChange::GrowthBooster { .. } => "explain.growth_booster_rd",
+            RdStatChange::TransferBonus(_) => "explain.transfer_bonus"
+        })
+    }
+
+    fn generate_templates(game_option : GameKind) -> Vec<Self>
+    where
+        Self : Sized
+    {
+        debug_assert!(game_option == GameKind::RD);
+        let mut templates = vec![
+            RdStatChange::LevelUp,
+            RdStatChange::BexpLevelUp,
+            RdStatChange::Promotion(PromotionTier::Second, promotion_template(3, 10)),
+            RdStatChange::Promotion(PromotionTier::Third, promotion_template(2, 10)),
+        ];
+        if let Some((per_stat, name)) = growth_booster_template(GameKind::RD) {
+            templates.push(RdStatChange::GrowthBooster { per_stat, name });
+        }
+        templates.extend(booster_templates(GameKind::RD, RdStatChange::StatBooster));
+        templates
+    }
+}
+
+/// A promotion granting `gain` to every stat and raising all caps but HP and
+/// Luck by `cap_increase`, requiring level 10 like both of RD's promotion
+/// items.
+fn promotion_template(gain : StatType, cap_increase : StatType) -> Character<StatIndexType> {
+    Character {
+        stats : StatIndexType::new_default_character(GameKind::RD)
+            .stats
+            .into_iter()
+            .map(|(sit, mut stat)| {
+                stat.growth = 0;
+                stat.value = gain;
+                if !sit.is_hp() && !sit.is_luck() {
+                    stat.cap += cap_increase;
+                };
+                (sit, stat)
+            })
+            .collect(),
+        name : "".to_owned(),
+        level : 10
+    }
+}
+
+impl fmt::Display for PromotionTier {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PromotionTier::Second => write!(f, "Second Tier"),
+            PromotionTier::Third => write!(f, "Third Tier")
+        }
+    }
+}
+
+impl fmt::Display for RdStatChange {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RdStatChange::Promotion(tier, promotion) => {
+                if promotion.name.is_empty() {
+                    write!(f, "{tier} Promotion")
+                }
+                else {
+                    write!(f, "{} Promotion", promotion.name)
+                }
+            },
+            RdStatChange::LevelUp => write!(f, "Level-Up"),
+            RdStatChange::BexpLevelUp => write!(f, "BEXP Level-Up"),
+            RdStatChange::StatBooster(stat, amount) => {
+                write!(f, "{}", booster_name(*stat, *amount))
+            },
+            RdStatChange::GrowthBooster { name, .. } => {
+                if name.is_empty() {
+                    write!(f, "Growth Booster")
+                }
+                else {
+                    write!(f, "{name}")
+                }
+            },
+            RdStatChange::TransferBonus(bonuses) => {
+                if bonuses.is_empty() {
+                    write!(f, "No Transfer Bonus")
+                }
+                else {
+                    write!(
+                        f,
+                        "Transfer Bonus ({})",
+                        bonuses
+                            .iter()
+                            .map(|(stat, bonus)| format!("+{bonus} {stat}"))
+                            .join(", ")
+                    )
+                }
+            },
+        }
+    }
+}
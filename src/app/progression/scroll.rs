@@ -0,0 +1,92 @@
+use std::collections::BTreeMap;
+
+use egui::{Grid, Ui};
+
+use crate::app::{locale::tr, sit::StatIndexType, GameKind};
+
+use super::GrowthModifier;
+
+/// The stats FE5's scrolls modify, matched against the names of the stats of
+/// a custom game, Bld also matching Con.
+const SCROLL_STATS : [&str; 8] = ["HP", "Str", "Mag", "Skl", "Spd", "Lck", "Def", "Bld"];
+/// The crusader scrolls of FE5 with their growth modifiers in the order of
+/// [`SCROLL_STATS`].
+const CRUSADER_SCROLLS : [(&str, [GrowthModifier; 8]); 12] = [
+    ("Baldo", [30, 5, 0, 5, 5, 5, 0, 0]),
+    ("Hezul", [30, 10, 0, 0, -10, 0, 10, 0]),
+    ("Odo", [0, 0, -10, 30, 10, 0, 0, 0]),
+    ("Dain", [0, 10, -10, 0, 0, 0, 30, 0]),
+    ("Noba", [10, 0, 0, 10, 0, 0, 30, -10]),
+    ("Neir", [0, 30, -10, 0, 0, 0, 10, 0]),
+    ("Ulir", [0, 10, 0, 10, 30, -10, 0, 0]),
+    ("Tordo", [0, 0, 30, 10, 0, 0, -10, 0]),
+    ("Fjalar", [0, 0, 0, 10, 30, 0, -10, 0]),
+    ("Blagi", [0, 0, 10, 0, 0, 30, -10, 0]),
+    ("Sety", [-10, 0, 30, 0, 10, 0, 0, 0]),
+    ("Heim", [0, 0, 30, 0, 0, 10, -10, 0])
+];
+
+/// The modifiers of the scroll of the given name in the order of
+/// [`SCROLL_STATS`].
+fn modifiers_of(name : &str) -> Option<[GrowthModifier; 8]> {
+    CRUSADER_SCROLLS
+        .iter()
+        .find(|(scroll, _modifiers)| *scroll == name)
+        .map(|(_scroll, modifiers)| *modifiers)
+}
+
+/// The name of the scroll matching the given one up to capitalization.
+pub fn find_scroll(name : &str) -> Option<&'static str> {
+    CRUSADER_SCROLLS
+        .iter()
+        .map(|(scroll, _modifiers)| *scroll)
+        .find(|scroll| scroll.eq_ignore_ascii_case(name))
+}
+
+/// The growth modifiers of the named scroll for those stats of the custom game
+/// that are named like FE5's.
+pub fn scroll_modifiers(name : &str) -> BTreeMap<StatIndexType, GrowthModifier> {
+    let modifiers = match modifiers_of(name) {
+        Some(modifiers) => modifiers,
+        None => return BTreeMap::new()
+    };
+    StatIndexType::new(GameKind::Custom)
+        .into_iter()
+        .filter_map(|sit| {
+            let index = SCROLL_STATS
+                .iter()
+                .position(|stat| names_stat(stat, &sit.to_string()))?;
+            Some((sit, modifiers[index]))
+        })
+        .filter(|(_sit, modifier)| *modifier != 0)
+        .collect()
+}
+
+fn names_stat(scroll_stat : &str, name : &str) -> bool {
+    scroll_stat.eq_ignore_ascii_case(name)
+        || (scroll_stat == "Bld" && name.eq_ignore_ascii_case("Con"))
+}
+
+/// Lets the user pick one of the scrolls, showing its modifiers, true on return
+/// indicating they confirmed their choice.
+pub fn scroll_dialogue(name : &mut String, ui : &mut Ui) -> bool {
+    egui::containers::ComboBox::from_label(tr("progression.scroll"))
+        .selected_text(name.as_str())
+        .show_ui(ui, |ui| {
+            for (scroll, _modifiers) in CRUSADER_SCROLLS {
+                ui.selectable_value(name, scroll.to_owned(), scroll);
+            }
+        });
+    if let Some(modifiers) = modifiers_of(name) {
+        Grid::new("Scroll Grid").num_columns(2).show(ui, |ui| {
+            for (stat, modifier) in SCROLL_STATS.iter().zip(modifiers) {
+                ui.label(*stat);
+                ui.label(format!("{modifier:+}%"));
+                ui.end_row();
+            }
+        });
+    }
+    ui.weak(tr("progression.scroll_stats_hint"));
+    ui.add_enabled(!name.is_empty(), egui::Button::new(tr("common.confirm")))
+        .clicked()
+}
@@ -0,0 +1,359 @@
+use std::{collections::BTreeMap, fmt, sync::Arc};
+
+use egui::Ui;
+use fe_levels::{BlankAvoidance, Character, StatChange, StatType};
+use serde::{Deserialize, Serialize};
+
+use crate::app::{locale::tr, sit::StatIndexType, GameData, GameKind};
+
+use super::{promotion_dialogue, GrowthModifier, GrowthScope, UsefulStatChange};
+
+/// A class of Shadow Dragon by its bases, growths and caps, all following the
+/// iteration order of [`GameKind::ShadowDragon`].
+type ClassData = (&'static str, [StatType; 8], [StatType; 8], [StatType; 8]);
+
+const UNPROMOTED_CAPS : [StatType; 8] = [60, 20, 20, 20, 20, 20, 20, 20];
+
+/// The unpromoted classes a unit can be reclassed between.
+const CLASSES : [ClassData; 12] = [
+    (
+        "Cavalier",
+        [18, 6, 0, 5, 6, 0, 5, 0],
+        [10, 10, 0, 10, 10, 0, 5, 0],
+        UNPROMOTED_CAPS
+    ),
+    (
+        "Knight",
+        [18, 7, 0, 3, 2, 0, 9, 0],
+        [10, 10, 0, 5, 0, 0, 15, 0],
+        UNPROMOTED_CAPS
+    ),
+    (
+        "Archer",
+        [16, 5, 0, 6, 5, 0, 5, 0],
+        [5, 5, 0, 15, 5, 0, 5, 0],
+        UNPROMOTED_CAPS
+    ),
+    (
+        "Pegasus Knight",
+        [16, 4, 0, 6, 8, 0, 4, 6],
+        [0, 5, 0, 10, 15, 0, 0, 15],
+        UNPROMOTED_CAPS
+    ),
+    (
+        "Fighter",
+        [20, 8, 0, 3, 5, 0, 3, 0],
+        [20, 15, 0, 5, 5, 0, 0, 0],
+        UNPROMOTED_CAPS
+    ),
+    (
+        "Hunter",
+        [17, 6, 0, 5, 6, 0, 3, 0],
+        [10, 5, 0, 10, 10, 0, 0, 0],
+        UNPROMOTED_CAPS
+    ),
+    (
+        "Mercenary",
+        [18, 5, 0, 8, 7, 0, 4, 0],
+        [10, 5, 0, 10, 10, 0, 5, 0],
+        UNPROMOTED_CAPS
+    ),
+    (
+        "Myrmidon",
+        [17, 5, 0, 9, 10, 0, 3, 0],
+        [5, 5, 0, 15, 15, 0, 0, 0],
+        UNPROMOTED_CAPS
+    ),
+    (
+        "Pirate",
+        [19, 7, 0, 3, 6, 0, 4, 0],
+        [15, 15, 0, 5, 10, 0, 0, 0],
+        UNPROMOTED_CAPS
+    ),
+    (
+        "Thief",
+        [16, 3, 0, 3, 9, 0, 2, 0],
+        [0, 0, 0, 10, 15, 0, 0, 0],
+        UNPROMOTED_CAPS
+    ),
+    (
+        "Mage",
+        [16, 0, 5, 3, 5, 0, 2, 4],
+        [0, 0, 15, 5, 10, 0, 0, 10],
+        UNPROMOTED_CAPS
+    ),
+    (
+        "Curate",
+        [16, 0, 1, 4, 5, 0, 2, 6],
+        [0, 0, 10, 10, 5, 0, 0, 15],
+        UNPROMOTED_CAPS
+    )
+];
+
+/// A class as a reclass sees it, its bases applying as offsets to the stats.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShadowDragonClass {
+    pub name : String,
+    bases : BTreeMap<StatIndexType, StatType>,
+    growths : BTreeMap<StatIndexType, StatType>,
+    caps : BTreeMap<StatIndexType, StatType>
+}
+
+impl ShadowDragonClass {
+    fn from_data((name, bases, growths, caps) : ClassData) -> Self {
+        let stats = StatIndexType::new(GameKind::ShadowDragon);
+        ShadowDragonClass {
+            name : name.to_owned(),
+            bases : stats.iter().copied().zip(bases).collect(),
+            growths : stats.iter().copied().zip(growths).collect(),
+            caps : stats.into_iter().zip(caps).collect()
+        }
+    }
+
+    fn empty() -> Self {
+        ShadowDragonClass {
+            name : "".to_owned(),
+            bases : BTreeMap::new(),
+            growths : BTreeMap::new(),
+            caps : BTreeMap::new()
+        }
+    }
+}
+
+/// The class of the given name up to capitalization.
+pub fn find_class(name : &str) -> Option<ShadowDragonClass> {
+    CLASSES
+        .into_iter()
+        .find(|(class, ..)| class.eq_ignore_ascii_case(name))
+        .map(ShadowDragonClass::from_data)
+}
+
+/// The difference between the classes' values of the stat.
+fn class_offset(
+    from : &BTreeMap<StatIndexType, StatType>,
+    to : &BTreeMap<StatIndexType, StatType>,
+    sit : &StatIndexType
+) -> i32 {
+    let value =
+        |class : &BTreeMap<StatIndexType, StatType>| class.get(sit).copied().map_or(0, i32::from);
+    value(to) - value(from)
+}
+
+/// Shifts the value by the offset, keeping it within 0 and the cap.
+fn offset_value(value : StatType, offset : i32, cap : StatType) -> StatType {
+    let shifted = (i32::from(value) + offset).clamp(0, i32::from(cap));
+    StatType::try_from(shifted).unwrap_or(cap)
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ShadowDragonStatChange {
+    Promotion(Character<StatIndexType>),
+    LevelUp,
+    /// Swapping from the first class into the second. The stats and growths
+    /// are shifted by the differences of the class bases and class growths
+    /// and the stats are capped at the new class' caps, the level is kept.
+    Reclass(ShadowDragonClass, ShadowDragonClass)
+}
+
+/// The key of the help on Shadow Dragon, with reclassing but no protection
+/// against empty level-ups.
+pub const HELP : &str = "help.shadow_dragon";
+
+impl UsefulStatChange for ShadowDragonStatChange {
+    fn compile(self) -> StatChange<StatIndexType> {
+        match self {
+            ShadowDragonStatChange::Promotion(promotion_gains) => StatChange::Promotion {
+                promo_changes : Arc::new(move |sit, mut stat| {
+                    promotion_gains
+                        .stats
+                        .get(sit)
+                        .map(|bonus| {
+                            stat.cap = bonus.cap;
+                            stat.increase_value(bonus.value);
+                            stat
+                        })
+                        .unwrap_or(stat)
+                })
+            },
+            ShadowDragonStatChange::LevelUp => StatChange::LevelUp {
+                temporary_growth_override : None,
+                blank_avoidance : BlankAvoidance::NoAvoidance
+            },
+            // the analysis applies this to every possible value on its own, so
+            // the re-clamping is exact rather than based on the average
+            ShadowDragonStatChange::Reclass(from, to) => StatChange::Promotion {
+                promo_changes : Arc::new(move |sit, mut stat| {
+                    stat.cap = to.caps.get(sit).copied().unwrap_or(stat.cap);
+                    let value_offset = class_offset(&from.bases, &to.bases, sit);
+                    stat.value = offset_value(stat.value, value_offset, stat.cap);
+                    let growth_offset = class_offset(&from.growths, &to.growths, sit);
+                    stat.growth = offset_value(stat.growth, growth_offset, StatType::MAX);
+                    stat
+                })
+            }
+        }
+    }
+
+    fn marking_worthy(&self) -> bool {
+        match self {
+            ShadowDragonStatChange::Promotion(_) => true,
+            ShadowDragonStatChange::LevelUp => false,
+            ShadowDragonStatChange::Reclass(..) => true
+        }
+    }
+
+    fn increases_level_counter(&self) -> bool {
+        match self {
+            ShadowDragonStatChange::Promotion(_) => false,
+            ShadowDragonStatChange::LevelUp => true,
+            ShadowDragonStatChange::Reclass(..) => false
+        }
+    }
+
+    fn resets_level_counter(&self) -> bool {
+        match self {
+            ShadowDragonStatChange::Promotion(_) => true,
+            ShadowDragonStatChange::LevelUp => false,
+            ShadowDragonStatChange::Reclass(..) => false
+        }
+    }
+
+    fn clarification_dialogue(
+        self,
+        context : &mut GameData,
+        ui : &mut Ui
+    ) -> (ShadowDragonStatChange, bool) {
+        match self {
+            ShadowDragonStatChange::Promotion(mut promotion_gains) => {
+                let confirmed = promotion_dialogue(
+                    &mut promotion_gains,
+                    &tr("promotion.gain"),
+                    None,
+                    context,
+                    ui
+                );
+                (
+                    ShadowDragonStatChange::Promotion(promotion_gains),
+                    confirmed
+                )
+            },
+            ShadowDragonStatChange::LevelUp => (self, true),
+            ShadowDragonStatChange::Reclass(mut from, mut to) => {
+                class_picker(&tr("progression.reclass_from"), &mut from, ui);
+                class_picker(&tr("progression.reclass_to"), &mut to, ui);
+                let confirmed = ui
+                    .add_enabled(
+                        !from.name.is_empty() && !to.name.is_empty() && from != to,
+                        egui::Button::new(tr("common.confirm"))
+                    )
+                    .clicked();
+                (ShadowDragonStatChange::Reclass(from, to), confirmed)
+            }
+        }
+    }
+
+    fn requires_clarification(&self) -> bool {
+        match self {
+            ShadowDragonStatChange::Promotion(_) => true,
+            ShadowDragonStatChange::LevelUp => false,
+            ShadowDragonStatChange::Reclass(..) => true
+        }
+    }
+
+    fn level_cap_before(&self) -> Option<usize> { None }
+
+    fn promotion_level(&self) -> Option<usize> {
+        match self {
+            ShadowDragonStatChange::Promotion(promotion) => Some(promotion.level),
+            _ => None
+        }
+    }
+
+    fn growth_scope(&self) -> Option<GrowthScope> { None }
+
+    fn class_growths(&self) -> Option<BTreeMap<StatIndexType, GrowthModifier>> { None }
+
+    fn cheap_to_execute(&self) -> bool { true }
+
+    fn simulation_cost(&self) -> f64 {
+        match self {
+            ShadowDragonStatChange::LevelUp => 1.0,
+            ShadowDragonStatChange::Promotion(_) => 0.3,
+            ShadowDragonStatChange::Reclass(..) => 0.3
+        }
+    }
+
+    fn explanation(&self) -> String {
+        tr(match self {
+            ShadowDragonStatChange::Promotion(_) => "explain.promotion",
+            ShadowDragonStatChange::LevelUp => "explain.level_up_no_rerolls",
+            ShadowDragonStatChange::Reclass(..) => "explain.reclass"
+        })
+    }
+
+    fn generate_templates(game_option : GameKind) -> Vec<Self>
+    where
+        Self : Sized
+    {
+        debug_assert!(game_option == GameKind::ShadowDragon);
+        vec![
+            ShadowDragonStatChange::LevelUp,
+            ShadowDragonStatChange::Reclass(ShadowDragonClass::empty(), ShadowDragonClass::empty()),
+            ShadowDragonStatChange::Promotion(Character {
+                stats : StatIndexType::new_default_character(GameKind::ShadowDragon)
+                    .stats
+                    .into_iter()
+                    .map(|(sit, mut stat)| {
+                        stat.growth = 0;
+                        stat.value = 2;
+                        if !sit.is_hp() {
+                            stat.cap += 10;
+                        };
+                        (sit, stat)
+                    })
+                    .collect(),
+                name : "".to_owned(),
+                // the Master Seal requires level 10
+                level : 10
+            }),
+        ]
+    }
+}
+
+fn class_picker(label : &str, class : &mut ShadowDragonClass, ui : &mut Ui) {
+    egui::containers::ComboBox::from_label(label)
+        .selected_text(class.name.as_str())
+        .show_ui(ui, |ui| {
+            for data in CLASSES {
+                let candidate = ShadowDragonClass::from_data(data);
+                let selected = *class == candidate;
+                if ui.selectable_label(selected, &candidate.name).clicked() {
+                    *class = candidate;
+                }
+            }
+        });
+}
+
+impl fmt::Display for ShadowDragonStatChange {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShadowDragonStatChange::Promotion(promotion) => {
+                if promotion.name.is_empty() {
+                    write!(f, "Promotion")
+                }
+                else {
+                    write!(f, "{} Promotion", promotion.name)
+                }
+            },
+            ShadowDragonStatChange::LevelUp => write!(f, "Level-Up"),
+            ShadowDragonStatChange::Reclass(from, to) => {
+                if from.name.is_empty() || to.name.is_empty() {
+                    write!(f, "Reclass")
+                }
+                else {
+                    write!(f, "Reclass {} to {}", from.name, to.name)
+                }
+            }
+        }
+    }
+}
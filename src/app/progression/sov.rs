@@ -0,0 +1,171 @@
+use std::{collections::BTreeMap, fmt, sync::Arc};
+
+use egui::Ui;
+use fe_levels::{BlankAvoidance, Character, StatChange};
+use serde::{Deserialize, Serialize};
+
+use crate::app::{locale::tr, sit::StatIndexType, GameData, GameKind};
+
+use super::{promotion_dialogue, GrowthModifier, GrowthScope, UsefulStatChange};
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SoVStatChange {
+    /// Changing into a new class, which raises every stat below the class' base
+    /// to that base and replaces the caps. This covers promotions, a Villager
+    /// picking their first class at level 3 or later and the class loops from
+    /// Dread Fighter and Cantor back to Villager, as all of them also reset the
+    /// level to 1.
+    ClassChange(Character<StatIndexType>),
+    LevelUp
+}
+
+/// The key of the help on Shadows of Valentia, which makes up for empty
+/// level-ups with HP.
+pub const HELP : &str = "help.sov";
+
+impl UsefulStatChange for SoVStatChange {
+    fn compile(self) -> StatChange<StatIndexType> {
+        match self {
+            // the analysis applies this to every possible value on its own, so
+            // raising to the base is exact rather than based on the average
+            SoVStatChange::ClassChange(class_bases) => StatChange::Promotion {
+                promo_changes : Arc::new(move |sit, mut stat| {
+                    class_bases
+                        .stats
+                        .get(sit)
+                        .map(|class_base| {
+                            stat.cap = class_base.cap;
+                            stat.value = stat.value.max(class_base.value).min(stat.cap);
+                            stat
+                        })
+                        .unwrap_or(stat)
+                })
+            },
+            // an empty level-up grants a point of HP instead
+            SoVStatChange::LevelUp => StatChange::LevelUp {
+                temporary_growth_override : None,
+                blank_avoidance : BlankAvoidance::AwardFixedStatOnBlank(
+                    StatIndexType::arbitrary_valid(GameKind::SoV)
+                )
+            }
+        }
+    }
+
+    fn marking_worthy(&self) -> bool {
+        match self {
+            SoVStatChange::ClassChange(_) => true,
+            SoVStatChange::LevelUp => false
+        }
+    }
+
+    fn increases_level_counter(&self) -> bool {
+        match self {
+            SoVStatChange::ClassChange(_) => false,
+            SoVStatChange::LevelUp => true
+        }
+    }
+
+    fn resets_level_counter(&self) -> bool {
+        match self {
+            SoVStatChange::ClassChange(_) => true,
+            SoVStatChange::LevelUp => false
+        }
+    }
+
+    fn clarification_dialogue(
+        self,
+        context : &mut GameData,
+        ui : &mut Ui
+    ) -> (SoVStatChange, bool) {
+        match self {
+            SoVStatChange::ClassChange(mut class_bases) => {
+                let confirmed = promotion_dialogue(
+                    &mut class_bases,
+                    &tr("promotion.class_base"),
+                    None,
+                    context,
+                    ui
+                );
+                (SoVStatChange::ClassChange(class_bases), confirmed)
+            },
+            SoVStatChange::LevelUp => (self, true)
+        }
+    }
+
+    fn requires_clarification(&self) -> bool {
+        match self {
+            SoVStatChange::ClassChange(_) => true,
+            SoVStatChange::LevelUp => false
+        }
+    }
+
+    fn level_cap_before(&self) -> Option<usize> { None }
+
+    fn promotion_level(&self) -> Option<usize> {
+        match self {
+            SoVStatChange::ClassChange(class) => Some(class.level),
+            _ => None
+        }
+    }
+
+    fn growth_scope(&self) -> Option<GrowthScope> { None }
+
+    fn class_growths(&self) -> Option<BTreeMap<StatIndexType, GrowthModifier>> { None }
+
+    fn cheap_to_execute(&self) -> bool { true }
+
+    fn simulation_cost(&self) -> f64 {
+        match self {
+            SoVStatChange::LevelUp => 1.0,
+            SoVStatChange::ClassChange(_) => 0.3
+        }
+    }
+
+    fn explanation(&self) -> String {
+        tr(match self {
+            SoVStatChange::ClassChange(_) => "explain.sov_class_change",
+            SoVStatChange::LevelUp => "explain.level_up_hp"
+        })
+    }
+
+    fn generate_templates(game_option : GameKind) -> Vec<Self>
+    where
+        Self : Sized
+    {
+        debug_assert!(game_option == GameKind::SoV);
+        vec![
+            SoVStatChange::LevelUp,
+            SoVStatChange::ClassChange(Character {
+                stats : StatIndexType::new_default_character(GameKind::SoV)
+                    .stats
+                    .into_iter()
+                    .map(|(sit, mut stat)| {
+                        stat.growth = 0;
+                        if !sit.is_hp() && !sit.is_luck() {
+                            stat.cap += 5;
+                        };
+                        (sit, stat)
+                    })
+                    .collect(),
+                name : "".to_owned(),
+                level : 1
+            }),
+        ]
+    }
+}
+
+impl fmt::Display for SoVStatChange {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SoVStatChange::ClassChange(class) => {
+                if class.name.is_empty() {
+                    write!(f, "Class Change")
+                }
+                else {
+                    write!(f, "{} Class Change", class.name)
+                }
+            },
+            SoVStatChange::LevelUp => write!(f, "Level-Up")
+        }
+    }
+}
@@ -0,0 +1,1087 @@
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use fe_levels::{Character, GrowthType, Stat, StatType};
+use itertools::Itertools;
+
+use crate::app::{
+    database::{builtin_promotions, ClassTier},
+    locale::{tr, tr_with},
+    manager::DataManaged,
+    sit::StatIndexType,
+    GameKind
+};
+
+use super::{
+    booster::{booster_catalog, default_booster_amount, find_booster, growth_booster_template},
+    custom::CustomStatChange,
+    gba::GbaFeStatChange,
+    growth_scopes_match,
+    por::PorStatChange,
+    rd::{PromotionTier, RdStatChange},
+    scroll::find_scroll,
+    shadow_dragon::{self, ShadowDragonStatChange},
+    sov::SoVStatChange,
+    three_houses::{self, ThreeHousesStatChange},
+    ConcreteStatChange, MAX_BULK_LEVEL_UPS
+};
+
+/// Names the line (1-based) and the token of a progression text that could not
+/// be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    line : usize,
+    token : String,
+    reason : String
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            tr_with(
+                "text.parse_error",
+                &[
+                    ("line", &self.line),
+                    ("token", &self.token),
+                    ("reason", &self.reason)
+                ]
+            )
+        )
+    }
+}
+
+type ItemError = (String, String);
+
+/// The promotions a progression text may name, the saved ones and the game's
+/// built-in ones.
+fn known_promotions(
+    game_option : GameKind,
+    promotions : &DataManaged<Character<StatIndexType>>
+) -> BTreeMap<String, Character<StatIndexType>> {
+    let mut known_promotions = builtin_promotions(game_option, promotions, None);
+    known_promotions.extend(
+        promotions
+            .iter()
+            .map(|(name, promo)| (name.clone(), promo.clone()))
+    );
+    known_promotions
+}
+
+/// Parses one stat change per comma separated item, e.g.
+/// `10 levels, promote Paladin, 19 levels, +2 Spd booster`. Every item may be
+/// prefixed with a number of repetitions and promotions are looked up by the
+/// name they were saved under, falling back to the game's built-in ones,
+/// unless they spell out their gains, e.g.
+/// `promote Paladin {level 10, HP +2 cap 60, Def +1 cap 25}`.
+pub fn parse_progression(
+    text : &str,
+    game_option : GameKind,
+    promotions : &DataManaged<Character<StatIndexType>>
+) -> Result<Vec<ConcreteStatChange>, ParseError> {
+    let promotions = &known_promotions(game_option, promotions);
+
+    let mut progression = vec![];
+    for (line_idx, line) in text.lines().enumerate() {
+        for item in split_items(line)
+            .into_iter()
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+        {
+            let (count, stat_change) = match game_option {
+                GameKind::GbaFe => parse_gba_item(item, promotions)
+                    .map(|(count, sc)| (count, ConcreteStatChange::GbaFeStatChange(sc))),
+                GameKind::PoR => parse_por_item(item, promotions)
+                    .map(|(count, sc)| (count, ConcreteStatChange::PorStatChange(sc))),
+                GameKind::RD => parse_rd_item(item, promotions)
+                    .map(|(count, sc)| (count, ConcreteStatChange::RdStatChange(sc))),
+                GameKind::ShadowDragon => parse_shadow_dragon_item(item, promotions)
+                    .map(|(count, sc)| (count, ConcreteStatChange::ShadowDragonStatChange(sc))),
+                GameKind::SoV => parse_sov_item(item, promotions)
+                    .map(|(count, sc)| (count, ConcreteStatChange::SoVStatChange(sc))),
+                GameKind::ThreeHouses => parse_three_houses_item(item)
+                    .map(|(count, sc)| (count, ConcreteStatChange::ThreeHousesStatChange(sc))),
+                GameKind::Custom => parse_custom_item(item, promotions)
+                    .map(|(count, sc)| (count, ConcreteStatChange::CustomStatChange(sc)))
+            }
+            .map_err(|(token, reason)| ParseError {
+                line : line_idx + 1,
+                token,
+                reason
+            })?;
+            progression.extend(std::iter::repeat(stat_change).take(count));
+            if !growth_scopes_match(&progression) {
+                return Err(ParseError {
+                    line : line_idx + 1,
+                    token : item.to_owned(),
+                    reason : tr("text.unheld_drop")
+                });
+            }
+        }
+    }
+    Ok(progression)
+}
+
+/// Splits a line at the commas separating its items, skipping those within
+/// quoted names and spelled out promotion gains.
+fn split_items(line : &str) -> Vec<&str> {
+    let mut items = vec![];
+    let mut start = 0;
+    let mut quoted = false;
+    let mut depth = 0usize;
+    for (idx, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '{' if !quoted => depth += 1,
+            '}' if !quoted => depth = depth.saturating_sub(1),
+            ',' if !quoted && depth == 0 => {
+                items.push(&line[start..idx]);
+                start = idx + 1;
+            },
+            _ => {}
+        }
+    }
+    items.push(&line[start..]);
+    items
+}
+
+/// Splits off the first word of the text, returning it and the trimmed rest.
+fn next_word(text : &str) -> Option<(&str, &str)> {
+    let text = text.trim_start();
+    if text.is_empty() {
+        return None;
+    }
+    let end = text.find(char::is_whitespace).unwrap_or(text.len());
+    Some((&text[..end], text[end..].trim_start()))
+}
+
+/// The text after its first word.
+fn after_word(text : &str) -> &str { next_word(text).map_or("", |(_word, rest)| rest) }
+
+/// Splits an item into its number of repetitions, its keyword, the remaining
+/// words and the text they were taken from. Repetitions are capped like the
+/// builder's bulk level-ups.
+fn split_item(item : &str) -> Result<(usize, &str, Vec<&str>, &str), ItemError> {
+    let missing = || (item.to_owned(), tr("text.missing_stat_change"));
+    let (first, after_first) = next_word(item).ok_or_else(missing)?;
+    // `+2 Spd booster` starts with the amount of a booster, not a repetition
+    let repetitions = if first.starts_with('+') {
+        None
+    }
+    else {
+        first.parse::<usize>().ok()
+    };
+    let (count, text) = match repetitions {
+        Some(count) if count > MAX_BULK_LEVEL_UPS => {
+            return Err((
+                item.to_owned(),
+                tr_with("text.too_many_repetitions", &[("max", &MAX_BULK_LEVEL_UPS)])
+            ))
+        },
+        Some(count) => (count, after_first),
+        None => (1, item)
+    };
+    let (keyword, rest) = next_word(text).ok_or_else(missing)?;
+    Ok((count, keyword, rest.split_whitespace().collect_vec(), rest))
+}
+
+/// Finds a promotion by the name it's known under, ignoring case and runs of
+/// whitespace.
+fn lookup_promotion<'a>(
+    name : &str,
+    promotions : &'a BTreeMap<String, Character<StatIndexType>>
+) -> Option<&'a Character<StatIndexType>> {
+    let name = name.split_whitespace().join(" ");
+    promotions
+        .iter()
+        .find(|(known_name, _promotion)| {
+            known_name
+                .split_whitespace()
+                .join(" ")
+                .eq_ignore_ascii_case(&name)
+        })
+        .map(|(_name, promotion)| promotion)
+}
+
+/// Parses a promotion, either by the name it's known under or by its name
+/// followed by its gains, e.g. `Paladin {level 10, HP +2 cap 60}`.
+fn find_promotion(
+    text : &str,
+    game_option : GameKind,
+    promotions : &BTreeMap<String, Character<StatIndexType>>
+) -> Result<Character<StatIndexType>, ItemError> {
+    let (name, gains) = split_promotion_name(text)?;
+    match gains.trim() {
+        "" if name.is_empty() => Err((text.to_owned(), tr("text.needs_promotion_name"))),
+        "" => lookup_promotion(&name, promotions)
+            .cloned()
+            .ok_or_else(|| (name, tr("text.unknown_promotion"))),
+        gains => parse_promotion_gains(gains, name, game_option)
+    }
+}
+
+/// Splits the name off a promotion. Names containing commas, braces or quotes
+/// are quoted, doubling the quotes within, e.g. `"Paladin, early"`.
+fn split_promotion_name(text : &str) -> Result<(String, &str), ItemError> {
+    match text.strip_prefix('"') {
+        Some(quoted) => {
+            let mut name = String::new();
+            let mut chars = quoted.char_indices().peekable();
+            while let Some((idx, c)) = chars.next() {
+                if c != '"' {
+                    name.push(c);
+                }
+                else if chars.next_if(|(_idx, c)| *c == '"').is_some() {
+                    name.push('"');
+                }
+                else {
+                    return Ok((name, &quoted[idx + 1..]));
+                }
+            }
+            Err((text.to_owned(), tr("text.missing_quote")))
+        },
+        None => {
+            let end = text.find('{').unwrap_or(text.len());
+            Ok((text[..end].trim().to_owned(), &text[end..]))
+        }
+    }
+}
+
+/// Parses promotion gains like `{level 10, HP +2 cap 60, Mag +1 cap 20
+/// growth 5% base 1}`. Caps default to the stat's usual one, growths and bases
+/// to 0.
+fn parse_promotion_gains(
+    text : &str,
+    name : String,
+    game_option : GameKind
+) -> Result<Character<StatIndexType>, ItemError> {
+    let gains = text
+        .strip_prefix('{')
+        .and_then(|gains| gains.strip_suffix('}'))
+        .ok_or_else(|| (text.to_owned(), tr("text.needs_braces")))?;
+    let mut level = None;
+    let mut stats = BTreeMap::new();
+    for gain in gains
+        .split(',')
+        .map(str::trim)
+        .filter(|gain| !gain.is_empty())
+    {
+        match gain.split_whitespace().collect_vec().as_slice() {
+            [keyword, value] if keyword.eq_ignore_ascii_case("level") => {
+                level = Some(parse_number(value)?);
+            },
+            [stat, value, attributes @ ..] if attributes.len() % 2 == 0 => {
+                let sit = parse_stat(stat, game_option)?;
+                let mut parsed = Stat {
+                    base : 0,
+                    cap : sit.default_stat().cap,
+                    growth : 0,
+                    value : parse_number(value.strip_prefix('+').unwrap_or(value))?
+                };
+                for (attribute, value) in attributes.iter().tuples() {
+                    match attribute.to_lowercase().as_str() {
+                        "cap" => parsed.cap = parse_number(value)?,
+                        "growth" => parsed.growth = parse_number(value.trim_end_matches('%'))?,
+                        "base" => parsed.base = parse_number(value)?,
+                        _ => return Err(((*attribute).to_owned(), tr("text.unknown_attribute")))
+                    }
+                }
+                stats.insert(sit, parsed);
+            },
+            _ => return Err((gain.to_owned(), tr("text.not_a_gain")))
+        }
+    }
+    let level = level.ok_or_else(|| (text.to_owned(), tr("text.needs_level")))?;
+    Ok(Character { stats, name, level })
+}
+
+fn parse_number<T : FromStr>(word : &str) -> Result<T, ItemError> {
+    word.parse()
+        .map_err(|_| (word.to_owned(), tr("text.not_a_number")))
+}
+
+/// Parses `+N Stat booster`, `Stat booster` for the amount of the game's
+/// booster of that stat or the name of a booster item, e.g. `Energy Ring`.
+fn parse_booster(
+    keyword : &str,
+    rest : &[&str],
+    game_option : GameKind
+) -> Result<(StatIndexType, StatType), ItemError> {
+    match rest {
+        [stat, booster] if keyword.starts_with('+') && is_booster(booster) => {
+            let amount = keyword[1..]
+                .parse()
+                .map_err(|_| (keyword.to_owned(), tr("text.not_a_booster_amount")))?;
+            Ok((parse_stat(stat, game_option)?, amount))
+        },
+        [booster] if is_booster(booster) => {
+            let stat = parse_stat(keyword, game_option)?;
+            Ok((stat, default_booster_amount(stat)))
+        },
+        _ => {
+            let name = std::iter::once(keyword)
+                .chain(rest.iter().copied())
+                .join(" ");
+            booster_catalog(game_option)
+                .into_iter()
+                .find(|booster| booster.name.eq_ignore_ascii_case(&name))
+                .map(|booster| (booster.stat, booster.amount))
+                .ok_or_else(|| (keyword.to_owned(), tr("text.unknown_stat_change")))
+        }
+    }
+}
+
+fn parse_gba_item(
+    item : &str,
+    promotions : &BTreeMap<String, Character<StatIndexType>>
+) -> Result<(usize, GbaFeStatChange), ItemError> {
+    let (count, keyword, rest, rest_text) = split_item(item)?;
+
+    let stat_change = match (keyword.to_lowercase().as_str(), rest.as_slice()) {
+        ("level" | "levels" | "level-up" | "level-ups", []) => GbaFeStatChange::LevelUp,
+        ("promote" | "trainee", []) => {
+            return Err((keyword.to_owned(), tr("text.needs_promotion_name")))
+        },
+        ("promote", _name) => GbaFeStatChange::Promotion(
+            ClassTier::Unpromoted,
+            find_promotion(rest_text, GameKind::GbaFe, promotions)?
+        ),
+        // FE8's trainees promoting into a regular class are written
+        // `trainee promote <name>`
+        ("trainee", [promote, ..]) if promote.eq_ignore_ascii_case("promote") => {
+            GbaFeStatChange::Promotion(
+                ClassTier::Trainee,
+                find_promotion(after_word(rest_text), GameKind::GbaFe, promotions)?
+            )
+        },
+        ("growth", [booster, increases @ ..]) if is_booster(booster) => {
+            let (per_stat, name) = parse_growth_booster(increases, GameKind::GbaFe)?;
+            GbaFeStatChange::GrowthBooster { per_stat, name }
+        },
+        _ => {
+            let (stat, amount) = parse_booster(keyword, &rest, GameKind::GbaFe)?;
+            GbaFeStatChange::StatBooster(stat, amount)
+        }
+    };
+    Ok((count, stat_change))
+}
+
+fn parse_custom_item(
+    item : &str,
+    promotions : &BTreeMap<String, Character<StatIndexType>>
+) -> Result<(usize, CustomStatChange), ItemError> {
+    let (count, keyword, rest, rest_text) = split_item(item)?;
+
+    let stat_change = match (keyword.to_lowercase().as_str(), rest.as_slice()) {
+        ("level" | "levels" | "level-up" | "level-ups", []) => CustomStatChange::LevelUp,
+        ("promote", []) => return Err((keyword.to_owned(), tr("text.needs_promotion_name"))),
+        ("promote", _name) => {
+            CustomStatChange::Promotion(find_promotion(rest_text, GameKind::Custom, promotions)?)
+        },
+        ("scroll", [name]) => CustomStatChange::Scroll(parse_scroll(name)?),
+        ("drop", [scroll, name]) if scroll.eq_ignore_ascii_case("scroll") => {
+            CustomStatChange::DropScroll(parse_scroll(name)?)
+        },
+        ("growth", [booster, increases @ ..]) if is_booster(booster) => {
+            let (per_stat, name) = parse_growth_booster(increases, GameKind::Custom)?;
+            CustomStatChange::GrowthBooster { per_stat, name }
+        },
+        _ => {
+            let (stat, amount) = parse_booster(keyword, &rest, GameKind::Custom)?;
+            CustomStatChange::StatBooster(stat, amount)
+        }
+    };
+    Ok((count, stat_change))
+}
+
+fn parse_por_item(
+    item : &str,
+    promotions : &BTreeMap<String, Character<StatIndexType>>
+) -> Result<(usize, PorStatChange), ItemError> {
+    let (count, keyword, rest, rest_text) = split_item(item)?;
+
+    let stat_change = match (keyword.to_lowercase().as_str(), rest.as_slice()) {
+        ("level" | "levels" | "level-up" | "level-ups", []) => PorStatChange::LevelUp,
+        ("promote", []) => return Err((keyword.to_owned(), tr("text.needs_promotion_name"))),
+        ("promote", _name) => {
+            PorStatChange::Promotion(find_promotion(rest_text, GameKind::PoR, promotions)?)
+        },
+        ("growth", [band, bonuses @ ..]) if band.eq_ignore_ascii_case("band") => {
+            PorStatChange::GrowthBand(parse_increases(
+                bonuses,
+                GameKind::PoR,
+                "text.not_a_growth_increase"
+            )?)
+        },
+        ("unequip", [growth, band])
+            if growth.eq_ignore_ascii_case("growth") && band.eq_ignore_ascii_case("band") =>
+        {
+            PorStatChange::UnequipGrowthBand
+        },
+        _ => {
+            let (stat, amount) = parse_booster(keyword, &rest, GameKind::PoR)?;
+            PorStatChange::StatBooster(stat, amount)
+        }
+    };
+    Ok((count, stat_change))
+}
+
+fn parse_rd_item(
+    item : &str,
+    promotions : &BTreeMap<String, Character<StatIndexType>>
+) -> Result<(usize, RdStatChange), ItemError> {
+    let (count, keyword, rest, rest_text) = split_item(item)?;
+
+    let stat_change = match (keyword.to_lowercase().as_str(), rest.as_slice()) {
+        ("level" | "levels" | "level-up" | "level-ups", []) => RdStatChange::LevelUp,
+        ("bexp", []) => RdStatChange::BexpLevelUp,
+        ("bexp", [level]) if is_level(level) => RdStatChange::BexpLevelUp,
+        ("promote" | "master", []) => {
+            return Err((keyword.to_owned(), tr("text.needs_promotion_name")))
+        },
+        ("promote", _name) => RdStatChange::Promotion(
+            PromotionTier::Second,
+            find_promotion(rest_text, GameKind::RD, promotions)?
+        ),
+        // third tier promotions are written `master promote <name>`
+        ("master", [promote, ..]) if promote.eq_ignore_ascii_case("promote") => {
+            RdStatChange::Promotion(
+                PromotionTier::Third,
+                find_promotion(after_word(rest_text), GameKind::RD, promotions)?
+            )
+        },
+        ("growth", [booster, increases @ ..]) if is_booster(booster) => {
+            let (per_stat, name) = parse_growth_booster(increases, GameKind::RD)?;
+            RdStatChange::GrowthBooster { per_stat, name }
+        },
+        ("transfer", bonuses) => RdStatChange::TransferBonus(parse_increases(
+            bonuses,
+            GameKind::RD,
+            "text.not_a_stat_increase"
+        )?),
+        _ => {
+            let (stat, amount) = parse_booster(keyword, &rest, GameKind::RD)?;
+            RdStatChange::StatBooster(stat, amount)
+        }
+    };
+    Ok((count, stat_change))
+}
+
+/// Reclasses are written `reclass <class> to <class>`.
+fn parse_shadow_dragon_item(
+    item : &str,
+    promotions : &BTreeMap<String, Character<StatIndexType>>
+) -> Result<(usize, ShadowDragonStatChange), ItemError> {
+    let (count, keyword, rest, rest_text) = split_item(item)?;
+
+    let stat_change = match (keyword.to_lowercase().as_str(), rest.as_slice()) {
+        ("level" | "levels" | "level-up" | "level-ups", []) => ShadowDragonStatChange::LevelUp,
+        ("promote", []) => return Err((keyword.to_owned(), tr("text.needs_promotion_name"))),
+        ("promote", _name) => ShadowDragonStatChange::Promotion(find_promotion(
+            rest_text,
+            GameKind::ShadowDragon,
+            promotions
+        )?),
+        ("reclass", classes) => {
+            let to_position = classes
+                .iter()
+                .position(|word| word.eq_ignore_ascii_case("to"))
+                .ok_or_else(|| (item.to_owned(), tr("text.needs_reclass_classes")))?;
+            let find_class = |words : &[&str]| {
+                let name = words.join(" ");
+                shadow_dragon::find_class(&name).ok_or_else(|| (name, tr("text.unknown_class")))
+            };
+            ShadowDragonStatChange::Reclass(
+                find_class(&classes[..to_position])?,
+                find_class(&classes[to_position + 1..])?
+            )
+        },
+        _ => return Err((keyword.to_owned(), tr("text.unknown_stat_change")))
+    };
+    Ok((count, stat_change))
+}
+
+fn parse_sov_item(
+    item : &str,
+    promotions : &BTreeMap<String, Character<StatIndexType>>
+) -> Result<(usize, SoVStatChange), ItemError> {
+    let (count, keyword, rest, rest_text) = split_item(item)?;
+
+    let stat_change = match (keyword.to_lowercase().as_str(), rest.as_slice()) {
+        ("level" | "levels" | "level-up" | "level-ups", []) => SoVStatChange::LevelUp,
+        ("promote" | "change", []) => {
+            return Err((keyword.to_owned(), tr("text.needs_class_name")))
+        },
+        ("promote" | "change", _name) => {
+            SoVStatChange::ClassChange(find_promotion(rest_text, GameKind::SoV, promotions)?)
+        },
+        _ => return Err((keyword.to_owned(), tr("text.unknown_stat_change")))
+    };
+    Ok((count, stat_change))
+}
+
+/// Class changes are looked up among the built-in classes.
+fn parse_three_houses_item(item : &str) -> Result<(usize, ThreeHousesStatChange), ItemError> {
+    let (count, keyword, rest, _rest_text) = split_item(item)?;
+
+    let stat_change = match (keyword.to_lowercase().as_str(), rest.as_slice()) {
+        ("level" | "levels" | "level-up" | "level-ups", []) => ThreeHousesStatChange::LevelUp,
+        ("change", []) => return Err((keyword.to_owned(), tr("text.needs_class_name"))),
+        ("change", name) => {
+            let name = name.join(" ");
+            ThreeHousesStatChange::ClassChange(
+                three_houses::find_class(&name).ok_or_else(|| (name, tr("text.unknown_class")))?
+            )
+        },
+        _ => return Err((keyword.to_owned(), tr("text.unknown_stat_change")))
+    };
+    Ok((count, stat_change))
+}
+
+/// Parses pairs like `+10 Str +5 Def` into increases of the game's stats or
+/// growths, `invalid` keying the error of a bonus that isn't one.
+fn parse_increases<T : FromStr>(
+    words : &[&str],
+    game_option : GameKind,
+    invalid : &str
+) -> Result<BTreeMap<StatIndexType, T>, ItemError> {
+    if words.len() % 2 != 0 {
+        return Err((words.join(" "), tr("text.needs_pairs")));
+    }
+    words
+        .iter()
+        .tuples()
+        .map(|(bonus, stat)| -> Result<_, ItemError> {
+            let parsed = bonus
+                .strip_prefix('+')
+                .map(|bonus| bonus.trim_end_matches('%'))
+                .and_then(|bonus| bonus.parse().ok())
+                .ok_or_else(|| ((*bonus).to_owned(), tr(invalid)))?;
+            Ok((parse_stat(stat, game_option)?, parsed))
+        })
+        .collect()
+}
+
+/// Growth boosters are written `growth booster`, followed by increases like
+/// `+5 HP` where they differ from the game's item.
+fn parse_growth_booster(
+    increases : &[&str],
+    game_option : GameKind
+) -> Result<(BTreeMap<StatIndexType, GrowthType>, String), ItemError> {
+    if increases.is_empty() {
+        return growth_booster_template(game_option)
+            .ok_or_else(|| ("growth booster".to_owned(), tr("text.needs_increases")));
+    }
+    Ok((
+        parse_increases(increases, game_option, "text.not_a_growth_increase")?,
+        "Growth Booster".to_owned()
+    ))
+}
+
+fn parse_scroll(name : &str) -> Result<String, ItemError> {
+    find_scroll(name)
+        .map(str::to_owned)
+        .ok_or_else(|| (name.to_owned(), tr("text.unknown_scroll")))
+}
+
+fn is_level(word : &str) -> bool {
+    ["level", "levels", "level-up", "level-ups"]
+        .iter()
+        .any(|level| word.eq_ignore_ascii_case(level))
+}
+
+fn is_booster(word : &str) -> bool {
+    word.eq_ignore_ascii_case("booster") || word.eq_ignore_ascii_case("boosters")
+}
+
+fn parse_stat(word : &str, game_option : GameKind) -> Result<StatIndexType, ItemError> {
+    StatIndexType::new(game_option)
+        .into_iter()
+        .find(|stat| stat.to_string().eq_ignore_ascii_case(word))
+        .ok_or_else(|| (word.to_owned(), tr("text.not_a_stat")))
+}
+
+/// The inverse of [`parse_progression`], writing one line per run of equal
+/// stat changes.
+pub fn print_progression(
+    progression : &[ConcreteStatChange],
+    game_option : GameKind,
+    promotions : &DataManaged<Character<StatIndexType>>
+) -> String {
+    let promotions = &known_promotions(game_option, promotions);
+    progression
+        .iter()
+        .dedup_with_count()
+        .map(|(count, stat_change)| {
+            let item = match stat_change {
+                ConcreteStatChange::GbaFeStatChange(stat_change) => {
+                    print_gba_item(stat_change, promotions)
+                },
+                ConcreteStatChange::PorStatChange(stat_change) => {
+                    print_por_item(stat_change, promotions)
+                },
+                ConcreteStatChange::RdStatChange(stat_change) => {
+                    print_rd_item(stat_change, promotions)
+                },
+                ConcreteStatChange::ShadowDragonStatChange(stat_change) => {
+                    print_shadow_dragon_item(stat_change, promotions)
+                },
+                ConcreteStatChange::SoVStatChange(stat_change) => {
+                    print_sov_item(stat_change, promotions)
+                },
+                ConcreteStatChange::ThreeHousesStatChange(stat_change) => {
+                    print_three_houses_item(stat_change)
+                },
+                ConcreteStatChange::CustomStatChange(stat_change) => {
+                    print_custom_item(stat_change, promotions)
+                },
+            };
+            match (count, item.as_str()) {
+                (1, _) => item,
+                (_, "level") => format!("{count} levels"),
+                _ => format!("{count} {item}")
+            }
+        })
+        .join("\n")
+}
+
+/// The inverse of [`parse_growth_booster`].
+fn print_growth_booster(
+    per_stat : &BTreeMap<StatIndexType, GrowthType>,
+    game_option : GameKind
+) -> String {
+    match growth_booster_template(game_option) {
+        Some((preset, _name)) if preset == *per_stat => "growth booster".to_owned(),
+        _ => std::iter::once("growth booster".to_owned())
+            .chain(
+                per_stat
+                    .iter()
+                    .map(|(stat, increase)| format!("+{increase} {stat}"))
+            )
+            .join(" ")
+    }
+}
+
+/// The inverse of [`find_promotion`], naming promotions by the name they're
+/// known under and spelling out the gains of all others.
+fn print_promotion(
+    promotion : &Character<StatIndexType>,
+    promotions : &BTreeMap<String, Character<StatIndexType>>
+) -> String {
+    if let Some(name) = promotions
+        .keys()
+        .find(|name| lookup_promotion(name, promotions) == Some(promotion))
+    {
+        return quote_name(name);
+    }
+    let gains = std::iter::once(format!("level {}", promotion.level))
+        .chain(promotion.stats.iter().map(|(sit, stat)| {
+            let mut gain = format!("{sit} +{} cap {}", stat.value, stat.cap);
+            if stat.growth != 0 {
+                gain += &format!(" growth {}%", stat.growth);
+            }
+            if stat.base != 0 {
+                gain += &format!(" base {}", stat.base);
+            }
+            gain
+        }))
+        .join(", ");
+    match promotion.name.as_str() {
+        "" => format!("{{{gains}}}"),
+        name => format!("{} {{{gains}}}", quote_name(name))
+    }
+}
+
+/// Quotes names that would otherwise be read differently, doubling the quotes
+/// within.
+fn quote_name(name : &str) -> String {
+    if name.contains(&[',', '{', '}', '"'][..]) || name.split_whitespace().join(" ") != name {
+        format!("\"{}\"", name.replace('"', "\"\""))
+    }
+    else {
+        name.to_owned()
+    }
+}
+
+/// Names boosters by their item where they match one.
+fn print_booster(stat : StatIndexType, amount : StatType) -> String {
+    find_booster(stat, amount).map_or_else(
+        || format!("+{amount} {stat} booster"),
+        |booster| booster.name.to_owned()
+    )
+}
+
+fn print_gba_item(
+    stat_change : &GbaFeStatChange,
+    promotions : &BTreeMap<String, Character<StatIndexType>>
+) -> String {
+    match stat_change {
+        GbaFeStatChange::LevelUp => "level".to_owned(),
+        GbaFeStatChange::Promotion(ClassTier::Trainee, promotion) => {
+            format!("trainee promote {}", print_promotion(promotion, promotions))
+        },
+        GbaFeStatChange::Promotion(_tier, promotion) => {
+            format!("promote {}", print_promotion(promotion, promotions))
+        },
+        GbaFeStatChange::GrowthBooster { per_stat, .. } => {
+            print_growth_booster(per_stat, GameKind::GbaFe)
+        },
+        GbaFeStatChange::StatBooster(stat, amount) => print_booster(*stat, *amount)
+    }
+}
+
+fn print_por_item(
+    stat_change : &PorStatChange,
+    promotions : &BTreeMap<String, Character<StatIndexType>>
+) -> String {
+    match stat_change {
+        PorStatChange::LevelUp => "level".to_owned(),
+        PorStatChange::Promotion(promotion) => {
+            format!("promote {}", print_promotion(promotion, promotions))
+        },
+        PorStatChange::StatBooster(stat, amount) => print_booster(*stat, *amount),
+        PorStatChange::GrowthBand(bonuses) => std::iter::once("growth band".to_owned())
+            .chain(
+                bonuses
+                    .iter()
+                    .map(|(stat, bonus)| format!("+{bonus} {stat}"))
+            )
+            .join(" "),
+        PorStatChange::UnequipGrowthBand => "unequip growth band".to_owned()
+    }
+}
+
+fn print_rd_item(
+    stat_change : &RdStatChange,
+    promotions : &BTreeMap<String, Character<StatIndexType>>
+) -> String {
+    match stat_change {
+        RdStatChange::LevelUp => "level".to_owned(),
+        RdStatChange::BexpLevelUp => "bexp level".to_owned(),
+        RdStatChange::StatBooster(stat, amount) => print_booster(*stat, *amount),
+        RdStatChange::GrowthBooster { per_stat, .. } => {
+            print_growth_booster(per_stat, GameKind::RD)
+        },
+        RdStatChange::Promotion(PromotionTier::Second, promotion) => {
+            format!("promote {}", print_promotion(promotion, promotions))
+        },
+        RdStatChange::Promotion(PromotionTier::Third, promotion) => {
+            format!("master promote {}", print_promotion(promotion, promotions))
+        },
+        RdStatChange::TransferBonus(bonuses) => std::iter::once("transfer".to_owned())
+            .chain(
+                bonuses
+                    .iter()
+                    .map(|(stat, bonus)| format!("+{bonus} {stat}"))
+            )
+            .join(" ")
+    }
+}
+
+fn print_shadow_dragon_item(
+    stat_change : &ShadowDragonStatChange,
+    promotions : &BTreeMap<String, Character<StatIndexType>>
+) -> String {
+    match stat_change {
+        ShadowDragonStatChange::LevelUp => "level".to_owned(),
+        ShadowDragonStatChange::Promotion(promotion) => {
+            format!("promote {}", print_promotion(promotion, promotions))
+        },
+        ShadowDragonStatChange::Reclass(from, to) => format!("reclass {} to {}", from.name, to.name)
+    }
+}
+
+fn print_sov_item(
+    stat_change : &SoVStatChange,
+    promotions : &BTreeMap<String, Character<StatIndexType>>
+) -> String {
+    match stat_change {
+        SoVStatChange::LevelUp => "level".to_owned(),
+        SoVStatChange::ClassChange(class) => {
+            format!("change {}", print_promotion(class, promotions))
+        }
+    }
+}
+
+fn print_three_houses_item(stat_change : &ThreeHousesStatChange) -> String {
+    match stat_change {
+        ThreeHousesStatChange::LevelUp => "level".to_owned(),
+        ThreeHousesStatChange::ClassChange(class) => format!("change {}", class.name)
+    }
+}
+
+fn print_custom_item(
+    stat_change : &CustomStatChange,
+    promotions : &BTreeMap<String, Character<StatIndexType>>
+) -> String {
+    match stat_change {
+        CustomStatChange::LevelUp => "level".to_owned(),
+        CustomStatChange::Promotion(promotion) => {
+            format!("promote {}", print_promotion(promotion, promotions))
+        },
+        CustomStatChange::StatBooster(stat, amount) => print_booster(*stat, *amount),
+        CustomStatChange::GrowthBooster { per_stat, .. } => {
+            print_growth_booster(per_stat, GameKind::Custom)
+        },
+        CustomStatChange::Scroll(name) => format!("scroll {name}"),
+        CustomStatChange::DropScroll(name) => format!("drop scroll {name}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{progression::gba::afas_drops, sit::CustomStats};
+
+    fn assert_round_trip(
+        progression : Vec<ConcreteStatChange>,
+        game_option : GameKind,
+        promotions : &DataManaged<Character<StatIndexType>>
+    ) {
+        let text = print_progression(&progression, game_option, promotions);
+        assert_eq!(
+            parse_progression(&text, game_option, promotions),
+            Ok(progression),
+            "{text}"
+        );
+    }
+
+    /// A promotion as the clarification dialogue leaves it, matching no saved
+    /// one and named in a way that needs quoting.
+    fn edited_promotion(game_option : GameKind) -> Character<StatIndexType> {
+        let mut promotion = StatIndexType::new_default_character(game_option);
+        promotion.name = "Paladin, \"early\"".to_owned();
+        promotion.level = 10;
+        for (gain, stat) in promotion.stats.values_mut().enumerate() {
+            stat.value = gain as StatType;
+            stat.cap += 5;
+        }
+        promotion
+    }
+
+    /// Saves a promotion under the given name, returning it.
+    fn save_promotion(
+        promotions : &mut DataManaged<Character<StatIndexType>>,
+        game_option : GameKind,
+        name : &str
+    ) -> Character<StatIndexType> {
+        let mut promotion = edited_promotion(game_option);
+        promotion.name = name.to_owned();
+        promotion.level = 20;
+        promotions.insert(name.to_owned(), promotion.clone());
+        promotion
+    }
+
+    #[test]
+    fn gba_progressions_round_trip() {
+        let mut promotions = DataManaged::default();
+        let saved = save_promotion(&mut promotions, GameKind::GbaFe, "Hero");
+        let mut changed = saved.clone();
+        changed.level = 15;
+        let builtin = builtin_promotions(GameKind::GbaFe, &promotions, None)
+            .into_values()
+            .next()
+            .unwrap();
+        let stat = StatIndexType::new(GameKind::GbaFe)[1];
+        let progression = [
+            GbaFeStatChange::LevelUp,
+            GbaFeStatChange::LevelUp,
+            GbaFeStatChange::Promotion(ClassTier::Trainee, edited_promotion(GameKind::GbaFe)),
+            GbaFeStatChange::Promotion(ClassTier::Unpromoted, saved),
+            GbaFeStatChange::Promotion(ClassTier::Unpromoted, changed),
+            GbaFeStatChange::Promotion(ClassTier::Unpromoted, builtin),
+            afas_drops(),
+            GbaFeStatChange::StatBooster(stat, 2),
+            GbaFeStatChange::StatBooster(stat, 3),
+            GbaFeStatChange::LevelUp
+        ]
+        .into_iter()
+        .map(ConcreteStatChange::GbaFeStatChange)
+        .collect();
+        assert_round_trip(progression, GameKind::GbaFe, &promotions);
+    }
+
+    #[test]
+    fn por_progressions_round_trip() {
+        let mut promotions = DataManaged::default();
+        let saved = save_promotion(&mut promotions, GameKind::PoR, "Paladin");
+        let stats = StatIndexType::new(GameKind::PoR);
+        let progression = [
+            PorStatChange::LevelUp,
+            PorStatChange::GrowthBand([(stats[0], 10), (stats[3], 5)].into_iter().collect()),
+            PorStatChange::LevelUp,
+            PorStatChange::UnequipGrowthBand,
+            PorStatChange::Promotion(saved),
+            PorStatChange::Promotion(edited_promotion(GameKind::PoR)),
+            PorStatChange::StatBooster(stats[2], 2)
+        ]
+        .into_iter()
+        .map(ConcreteStatChange::PorStatChange)
+        .collect();
+        assert_round_trip(progression, GameKind::PoR, &promotions);
+    }
+
+    #[test]
+    fn rd_progressions_round_trip() {
+        let mut promotions = DataManaged::default();
+        let saved = save_promotion(&mut promotions, GameKind::RD, "Silver Knight");
+        let stats = StatIndexType::new(GameKind::RD);
+        let (per_stat, name) = growth_booster_template(GameKind::RD).unwrap();
+        let progression = [
+            RdStatChange::TransferBonus([(stats[1], 1), (stats[4], 2)].into_iter().collect()),
+            RdStatChange::LevelUp,
+            RdStatChange::BexpLevelUp,
+            RdStatChange::Promotion(PromotionTier::Second, saved),
+            RdStatChange::Promotion(PromotionTier::Third, edited_promotion(GameKind::RD)),
+            RdStatChange::GrowthBooster { per_stat, name },
+            RdStatChange::StatBooster(stats[0], 7)
+        ]
+        .into_iter()
+        .map(ConcreteStatChange::RdStatChange)
+        .collect();
+        assert_round_trip(progression, GameKind::RD, &promotions);
+    }
+
+    #[test]
+    fn shadow_dragon_progressions_round_trip() {
+        let mut promotions = DataManaged::default();
+        let saved = save_promotion(&mut promotions, GameKind::ShadowDragon, "Paladin");
+        let progression = [
+            ShadowDragonStatChange::LevelUp,
+            ShadowDragonStatChange::Reclass(
+                shadow_dragon::find_class("Cavalier").unwrap(),
+                shadow_dragon::find_class("Knight").unwrap()
+            ),
+            ShadowDragonStatChange::Promotion(saved),
+            ShadowDragonStatChange::Promotion(edited_promotion(GameKind::ShadowDragon))
+        ]
+        .into_iter()
+        .map(ConcreteStatChange::ShadowDragonStatChange)
+        .collect();
+        assert_round_trip(progression, GameKind::ShadowDragon, &promotions);
+    }
+
+    #[test]
+    fn sov_progressions_round_trip() {
+        let mut promotions = DataManaged::default();
+        let saved = save_promotion(&mut promotions, GameKind::SoV, "Mercenary");
+        let progression = [
+            SoVStatChange::LevelUp,
+            SoVStatChange::ClassChange(saved),
+            SoVStatChange::LevelUp,
+            SoVStatChange::ClassChange(edited_promotion(GameKind::SoV))
+        ]
+        .into_iter()
+        .map(ConcreteStatChange::SoVStatChange)
+        .collect();
+        assert_round_trip(progression, GameKind::SoV, &promotions);
+    }
+
+    #[test]
+    fn three_houses_progressions_round_trip() {
+        let progression = [
+            ThreeHousesStatChange::LevelUp,
+            ThreeHousesStatChange::ClassChange(three_houses::find_class("Mercenary").unwrap()),
+            ThreeHousesStatChange::LevelUp
+        ]
+        .into_iter()
+        .map(ConcreteStatChange::ThreeHousesStatChange)
+        .collect();
+        assert_round_trip(progression, GameKind::ThreeHouses, &DataManaged::default());
+    }
+
+    #[test]
+    fn custom_progressions_round_trip() {
+        CustomStats::default().publish();
+        let mut promotions = DataManaged::default();
+        let saved = save_promotion(&mut promotions, GameKind::Custom, "Lord Knight");
+        let stat = StatIndexType::new(GameKind::Custom)[1];
+        let (per_stat, name) = growth_booster_template(GameKind::Custom).unwrap();
+        let progression = [
+            CustomStatChange::Scroll("Neir".to_owned()),
+            CustomStatChange::LevelUp,
+            CustomStatChange::DropScroll("Neir".to_owned()),
+            CustomStatChange::Promotion(saved),
+            CustomStatChange::Promotion(edited_promotion(GameKind::Custom)),
+            CustomStatChange::GrowthBooster { per_stat, name },
+            CustomStatChange::StatBooster(stat, 2)
+        ]
+        .into_iter()
+        .map(ConcreteStatChange::CustomStatChange)
+        .collect();
+        assert_round_trip(progression, GameKind::Custom, &promotions);
+    }
+
+    #[test]
+    fn promotions_are_found_by_name_or_spelled_out() {
+        let mut promotions = DataManaged::default();
+        let saved = save_promotion(&mut promotions, GameKind::GbaFe, "Hero");
+        let parsed = parse_progression(
+            "promote hero, promote \"Hero, early\" {level 10, HP +3 cap 60, Atk +1}",
+            GameKind::GbaFe,
+            &promotions
+        )
+        .unwrap();
+        let stats = StatIndexType::new(GameKind::GbaFe);
+        let spelled_out = Character {
+            stats : [
+                (
+                    stats[0],
+                    Stat {
+                        base : 0,
+                        cap : 60,
+                        growth : 0,
+                        value : 3
+                    }
+                ),
+                (
+                    stats[1],
+                    Stat {
+                        base : 0,
+                        cap : stats[1].default_stat().cap,
+                        growth : 0,
+                        value : 1
+                    }
+                )
+            ]
+            .into_iter()
+            .collect(),
+            name : "Hero, early".to_owned(),
+            level : 10
+        };
+        assert_eq!(
+            parsed,
+            vec![
+                ConcreteStatChange::GbaFeStatChange(GbaFeStatChange::Promotion(
+                    ClassTier::Unpromoted,
+                    saved
+                )),
+                ConcreteStatChange::GbaFeStatChange(GbaFeStatChange::Promotion(
+                    ClassTier::Unpromoted,
+                    spelled_out
+                )),
+            ]
+        );
+    }
+
+    #[test]
+    fn repetitions_are_capped() {
+        let promotions = DataManaged::default();
+        let text = format!("{MAX_BULK_LEVEL_UPS} levels");
+        let progression = parse_progression(&text, GameKind::GbaFe, &promotions);
+        assert_eq!(
+            progression.map(|progression| progression.len()),
+            Ok(MAX_BULK_LEVEL_UPS)
+        );
+
+        let error = parse_progression("2 levels,\n999999999 levels", GameKind::GbaFe, &promotions);
+        assert_eq!(
+            error,
+            Err(ParseError {
+                line : 2,
+                token : "999999999 levels".to_owned(),
+                reason : tr_with("text.too_many_repetitions", &[("max", &MAX_BULK_LEVEL_UPS)])
+            })
+        );
+    }
+}
@@ -0,0 +1,334 @@
+use std::{collections::BTreeMap, fmt, ops::Bound, sync::Arc};
+
+use egui::{Grid, TextEdit, Ui};
+use fe_levels::{BlankAvoidance, StatChange, StatType};
+use serde::{Deserialize, Serialize};
+
+use crate::app::{locale::tr, numerical_text_box, sit::StatIndexType, GameData, GameKind};
+
+use super::{GrowthModifier, GrowthScope, UsefulStatChange};
+
+/// A class of Three Houses by its stat minimums and growth bonuses, both
+/// following the iteration order of [`GameKind::ThreeHouses`].
+type ClassData = (&'static str, [StatType; 9], [GrowthModifier; 9]);
+
+/// The common intermediate and advanced classes.
+const CLASSES : [ClassData; 23] = [
+    (
+        "Mercenary",
+        [26, 10, 3, 9, 8, 5, 7, 2, 6],
+        [10, 10, 0, 10, 10, 0, 0, 0, 0]
+    ),
+    (
+        "Thief",
+        [20, 6, 2, 10, 11, 5, 4, 3, 5],
+        [0, 0, 0, 20, 20, 10, -5, 0, 0]
+    ),
+    (
+        "Armored Knight",
+        [30, 12, 2, 7, 2, 4, 14, 1, 4],
+        [10, 10, 0, 0, -10, 0, 15, -5, 0]
+    ),
+    (
+        "Cavalier",
+        [28, 11, 3, 8, 7, 4, 9, 3, 5],
+        [10, 10, 0, 0, 0, 0, 10, 0, 0]
+    ),
+    (
+        "Brigand",
+        [32, 14, 1, 6, 6, 2, 4, 0, 3],
+        [20, 20, -10, 0, 0, 0, 0, -5, 0]
+    ),
+    (
+        "Archer",
+        [24, 9, 3, 11, 7, 5, 5, 2, 4],
+        [0, 10, 0, 20, 0, 0, 0, 0, 0]
+    ),
+    (
+        "Brawler",
+        [28, 10, 2, 8, 8, 4, 5, 2, 4],
+        [15, 10, 0, 10, 10, 0, 0, 0, 0]
+    ),
+    (
+        "Mage",
+        [22, 4, 10, 6, 6, 4, 3, 7, 5],
+        [0, 0, 20, 0, 0, 0, 0, 10, 0]
+    ),
+    (
+        "Dark Mage",
+        [22, 5, 11, 5, 5, 3, 2, 6, 4],
+        [0, 0, 20, 0, 0, -5, 0, 10, 0]
+    ),
+    (
+        "Priest",
+        [22, 4, 9, 6, 6, 5, 3, 8, 6],
+        [0, -5, 10, 0, 0, 0, 0, 15, 0]
+    ),
+    (
+        "Pegasus Knight",
+        [22, 8, 4, 8, 10, 7, 5, 8, 6],
+        [0, 0, 0, 10, 20, 0, 0, 10, 0]
+    ),
+    (
+        "Hero",
+        [32, 15, 3, 12, 11, 7, 10, 4, 8],
+        [10, 20, 0, 10, 10, 0, 0, 0, 0]
+    ),
+    (
+        "Swordmaster",
+        [30, 13, 4, 14, 15, 8, 7, 5, 6],
+        [0, 10, 0, 10, 20, 0, 0, 0, 0]
+    ),
+    (
+        "Assassin",
+        [28, 11, 4, 15, 15, 7, 6, 5, 6],
+        [0, 10, 0, 20, 20, 0, 0, 0, 0]
+    ),
+    (
+        "Fortress Knight",
+        [36, 15, 2, 9, 4, 5, 18, 3, 6],
+        [20, 15, 0, 0, -10, 0, 20, -5, 0]
+    ),
+    (
+        "Paladin",
+        [32, 14, 4, 10, 10, 6, 12, 6, 7],
+        [10, 10, 0, 0, 10, 0, 10, 5, 0]
+    ),
+    (
+        "Wyvern Rider",
+        [32, 15, 2, 8, 10, 5, 11, 2, 6],
+        [10, 15, 0, 0, 10, 0, 10, -5, 0]
+    ),
+    (
+        "Warrior",
+        [36, 17, 2, 9, 10, 5, 7, 2, 5],
+        [30, 20, 0, 0, 0, 0, 0, -5, 0]
+    ),
+    (
+        "Sniper",
+        [28, 12, 3, 16, 10, 6, 7, 4, 5],
+        [0, 10, 0, 30, 10, 0, 0, 0, 0]
+    ),
+    (
+        "Grappler",
+        [32, 14, 3, 12, 12, 6, 7, 4, 6],
+        [20, 10, 0, 10, 15, 0, 0, 0, 0]
+    ),
+    (
+        "Warlock",
+        [26, 6, 15, 9, 9, 5, 5, 10, 6],
+        [0, 0, 20, 0, 10, 0, 0, 10, 0]
+    ),
+    (
+        "Dark Bishop",
+        [26, 6, 15, 8, 8, 4, 5, 11, 5],
+        [0, 0, 20, 0, 0, -5, 0, 15, 0]
+    ),
+    (
+        "Bishop",
+        [26, 5, 14, 8, 8, 6, 5, 12, 7],
+        [0, 0, 10, 0, 0, 0, 0, 20, 5]
+    )
+];
+
+/// A class to change into, raising every stat below its minimum to that and
+/// adding its growth bonuses to the personal growths while the unit is in it.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThreeHousesClass {
+    pub name : String,
+    minimums : BTreeMap<StatIndexType, StatType>,
+    growths : BTreeMap<StatIndexType, GrowthModifier>
+}
+
+impl ThreeHousesClass {
+    fn from_data((name, minimums, growths) : ClassData) -> Self {
+        let stats = StatIndexType::new(GameKind::ThreeHouses);
+        ThreeHousesClass {
+            name : name.to_owned(),
+            minimums : stats.iter().copied().zip(minimums).collect(),
+            growths : stats.into_iter().zip(growths).collect()
+        }
+    }
+}
+
+/// The built-in class of the given name up to capitalization.
+pub fn find_class(name : &str) -> Option<ThreeHousesClass> {
+    CLASSES
+        .into_iter()
+        .find(|(class, _minimums, _growths)| class.eq_ignore_ascii_case(name))
+        .map(ThreeHousesClass::from_data)
+}
+
+fn is_builtin(class : &ThreeHousesClass) -> bool { find_class(&class.name).as_ref() == Some(class) }
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThreeHousesStatChange {
+    /// Changing into a new class. Unlike the promotions of the other games it
+    /// grants no flat gains and leaves the level as is.
+    ClassChange(ThreeHousesClass),
+    LevelUp
+}
+
+/// The key of the help on Three Houses, whose students always grow at least two
+/// stats.
+pub const HELP : &str = "help.three_houses";
+
+impl UsefulStatChange for ThreeHousesStatChange {
+    fn compile(self) -> StatChange<StatIndexType> {
+        match self {
+            // the analysis applies this to every possible value on its own, so
+            // raising to the minimum is exact rather than based on the average
+            ThreeHousesStatChange::ClassChange(class) => StatChange::Promotion {
+                promo_changes : Arc::new(move |sit, mut stat| {
+                    if let Some(minimum) = class.minimums.get(sit) {
+                        stat.value = stat.value.max(*minimum).min(stat.cap);
+                    }
+                    stat
+                })
+            },
+            // students gain at least two stats on every level-up
+            ThreeHousesStatChange::LevelUp => StatChange::LevelUp {
+                temporary_growth_override : None,
+                blank_avoidance : BlankAvoidance::GuaranteedStats(
+                    (Bound::Included(2), Bound::Unbounded),
+                    StatIndexType::new(GameKind::ThreeHouses)
+                )
+            }
+        }
+    }
+
+    fn marking_worthy(&self) -> bool {
+        match self {
+            ThreeHousesStatChange::ClassChange(_) => true,
+            ThreeHousesStatChange::LevelUp => false
+        }
+    }
+
+    fn increases_level_counter(&self) -> bool {
+        match self {
+            ThreeHousesStatChange::ClassChange(_) => false,
+            ThreeHousesStatChange::LevelUp => true
+        }
+    }
+
+    fn resets_level_counter(&self) -> bool { false }
+
+    fn clarification_dialogue(
+        self,
+        _context : &mut GameData,
+        ui : &mut Ui
+    ) -> (ThreeHousesStatChange, bool) {
+        match self {
+            // classes dragged from their built-in template are complete already
+            ThreeHousesStatChange::ClassChange(class) if is_builtin(&class) => {
+                (ThreeHousesStatChange::ClassChange(class), true)
+            },
+            ThreeHousesStatChange::ClassChange(mut class) => {
+                ui.horizontal(|ui| {
+                    ui.label(tr("progression.class"));
+                    ui.add(TextEdit::singleline(&mut class.name));
+                });
+                Grid::new("Class Change Grid")
+                    .num_columns(3)
+                    .show(ui, |ui| {
+                        ui.label(tr("common.stat"));
+                        ui.label(tr("progression.minimum"));
+                        ui.label(tr("progression.growth_bonus"));
+                        ui.end_row();
+                        for sit in StatIndexType::new(GameKind::ThreeHouses) {
+                            ui.label(sit.to_string());
+                            numerical_text_box(ui, class.minimums.entry(sit).or_default());
+                            numerical_text_box(ui, class.growths.entry(sit).or_default());
+                            ui.end_row();
+                        }
+                    });
+                let confirmed = ui
+                    .add_enabled(
+                        !class.name.is_empty(),
+                        egui::Button::new(tr("common.confirm"))
+                    )
+                    .clicked();
+                (ThreeHousesStatChange::ClassChange(class), confirmed)
+            },
+            ThreeHousesStatChange::LevelUp => (self, true)
+        }
+    }
+
+    fn requires_clarification(&self) -> bool {
+        match self {
+            ThreeHousesStatChange::ClassChange(class) => !is_builtin(class),
+            ThreeHousesStatChange::LevelUp => false
+        }
+    }
+
+    fn level_cap_before(&self) -> Option<usize> { None }
+
+    fn promotion_level(&self) -> Option<usize> { None }
+
+    fn growth_scope(&self) -> Option<GrowthScope> { None }
+
+    fn class_growths(&self) -> Option<BTreeMap<StatIndexType, GrowthModifier>> {
+        match self {
+            ThreeHousesStatChange::ClassChange(class) => Some(class.growths.clone()),
+            ThreeHousesStatChange::LevelUp => None
+        }
+    }
+
+    // the analysis can't handle guaranteed stats yet
+    fn cheap_to_execute(&self) -> bool {
+        match self {
+            ThreeHousesStatChange::ClassChange(_) => true,
+            ThreeHousesStatChange::LevelUp => false
+        }
+    }
+
+    fn simulation_cost(&self) -> f64 {
+        match self {
+            ThreeHousesStatChange::LevelUp => 1.0,
+            ThreeHousesStatChange::ClassChange(_) => 0.3
+        }
+    }
+
+    fn explanation(&self) -> String {
+        tr(match self {
+            ThreeHousesStatChange::ClassChange(_) => "explain.three_houses_class_change",
+            ThreeHousesStatChange::LevelUp => "explain.level_up_two_grow"
+        })
+    }
+
+    fn generate_templates(game_option : GameKind) -> Vec<Self>
+    where
+        Self : Sized
+    {
+        debug_assert!(game_option == GameKind::ThreeHouses);
+        let custom_class = ThreeHousesClass {
+            name : "".to_owned(),
+            minimums : BTreeMap::new(),
+            growths : BTreeMap::new()
+        };
+        let classes = CLASSES.into_iter().map(ThreeHousesClass::from_data);
+        [
+            ThreeHousesStatChange::LevelUp,
+            ThreeHousesStatChange::ClassChange(custom_class)
+        ]
+        .into_iter()
+        .chain(classes.map(ThreeHousesStatChange::ClassChange))
+        .collect()
+    }
+}
+
+impl fmt::Display for ThreeHousesStatChange {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThreeHousesStatChange::ClassChange(class) => {
+                if class.name.is_empty() {
+                    write!(f, "Class Change")
+                }
+                else {
+                    write!(f, "{} Class Change", class.name)
+                }
+            },
+            ThreeHousesStatChange::LevelUp => write!(f, "Level-Up")
+        }
+    }
+}
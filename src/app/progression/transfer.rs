@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+
+use egui::{Grid, Slider};
+use fe_levels::StatType;
+use serde::{Deserialize, Serialize};
+
+use crate::app::{
+    effective_character, locale::tr, plotter::find_percentile, sit::StatIndexType, GameData,
+    GameKind, SavedCharacter
+};
+
+use super::{compile_progression, exported_character, rd::RdStatChange, ConcreteStatChange};
+
+/// The bonus RD grants to the bases of every stat capped in the PoR save data.
+const TRANSFER_BONUS : StatType = 2;
+
+/// The final cap and value distribution of every stat of a PoR character,
+/// keyed by the RD stat at the same position.
+type EndgameStats = BTreeMap<StatIndexType, (StatType, BTreeMap<StatType, f64>)>;
+
+#[derive(Deserialize, Serialize, Default)]
+pub struct TransferDialog {
+    /// the name of the saved PoR character the bonuses are computed for
+    character : String,
+    /// the percentile of the endgame stats that has to reach the cap for the
+    /// bonus, the expected bonuses being rounded instead if unset
+    percentile : Option<u8>,
+    /// the endgame of the saved character it was computed for, `None` if its
+    /// progression can't be computed
+    #[serde(skip)]
+    endgame : Option<(SavedCharacter, Option<EndgameStats>)>
+}
+
+/// Computes the endgame of the saved PoR character. Returns `None` unless the
+/// library can analyze the progression.
+fn endgame_stats(
+    (character, progression, growth_modifiers) : &SavedCharacter
+) -> Option<EndgameStats> {
+    let character = effective_character(character, growth_modifiers);
+    let histograms =
+        fe_levels::generate_histograms(&compile_progression(progression), &character, None);
+    let endgame = histograms.last()?;
+    // the caps only change deterministically, so they can be read off the
+    // promotions directly
+    let capped = exported_character(
+        &character,
+        progression,
+        &BTreeMap::new(),
+        character.level,
+        &character.name
+    );
+    Some(
+        StatIndexType::new(GameKind::PoR)
+            .into_iter()
+            .zip(StatIndexType::new(GameKind::RD))
+            .filter_map(|(por, rd)| {
+                Some((
+                    rd,
+                    (capped.stats.get(&por)?.cap, endgame.get(&por)?.clone())
+                ))
+            })
+            .collect()
+    )
+}
+
+fn cap_chance(cap : StatType, distribution : &BTreeMap<StatType, f64>) -> f64 {
+    distribution
+        .range(cap..)
+        .map(|(_value, chance)| chance)
+        .sum()
+}
+
+/// The bonus for the stat, either granted in full if the percentile reaches
+/// the cap or the rounded expected bonus.
+fn transfer_bonus(
+    cap : StatType,
+    distribution : &BTreeMap<StatType, f64>,
+    percentile : Option<u8>
+) -> StatType {
+    match percentile {
+        Some(percentile) => {
+            let value = find_percentile(distribution, percentile as f64 / 100.0);
+            if value.map_or(false, |value| value >= cap as f64) {
+                TRANSFER_BONUS
+            }
+            else {
+                0
+            }
+        },
+        None => (cap_chance(cap, distribution) * TRANSFER_BONUS as f64).round() as StatType
+    }
+}
+
+/// Lets the user compute the bonuses a saved PoR character transfers to RD,
+/// returning the stat change granting them once the user adds it.
+pub fn transfer_bonus_window(
+    dialog : &mut TransferDialog,
+    por_data : Option<&GameData>,
+    ctx : &egui::Context
+) -> Option<ConcreteStatChange> {
+    let mut transfer = None;
+    egui::Window::new(tr("progression.transfer_title"))
+        .id(egui::Id::new("Transfer Bonuses"))
+        .show(ctx, |ui| {
+            let saved = match por_data {
+                Some(data) if !data.characters.is_empty() => &data.characters,
+                _ => {
+                    ui.label(tr("progression.transfer_nothing_saved"));
+                    return;
+                }
+            };
+            egui::containers::ComboBox::from_label(tr("progression.transfer_character"))
+                .selected_text(dialog.character.as_str())
+                .show_ui(ui, |ui| {
+                    for name in saved.keys() {
+                        ui.selectable_value(&mut dialog.character, name.clone(), name);
+                    }
+                });
+            let selected = match saved.get(&dialog.character) {
+                Some(selected) => selected,
+                None => return
+            };
+
+            let percentile = &mut dialog.percentile;
+            ui.radio_value(percentile, None, tr("progression.transfer_rounded"));
+            ui.horizontal(|ui| {
+                if ui
+                    .radio(percentile.is_some(), tr("progression.export_percentile"))
+                    .clicked()
+                    && percentile.is_none()
+                {
+                    *percentile = Some(50);
+                }
+                if let Some(percentile) = percentile {
+                    ui.add(Slider::new(percentile, 1..=99));
+                }
+            });
+
+            if !matches!(&dialog.endgame, Some((computed, _)) if computed == selected) {
+                dialog.endgame = Some((selected.clone(), endgame_stats(selected)));
+            }
+            let endgame = match &dialog.endgame {
+                Some((_, Some(endgame))) => endgame,
+                _ => {
+                    ui.label(tr("progression.transfer_pending"));
+                    return;
+                }
+            };
+
+            let bonuses : BTreeMap<StatIndexType, StatType> = endgame
+                .iter()
+                .map(|(sit, (cap, distribution))| {
+                    (*sit, transfer_bonus(*cap, distribution, dialog.percentile))
+                })
+                .filter(|(_sit, bonus)| *bonus > 0)
+                .collect();
+            Grid::new("Transfer Bonus Grid")
+                .num_columns(3)
+                .show(ui, |ui| {
+                    ui.label(tr("common.stat"));
+                    ui.label(tr("progression.transfer_capped"));
+                    ui.label(tr("progression.transfer_bonus"));
+                    ui.end_row();
+                    for (sit, (cap, distribution)) in endgame {
+                        ui.label(sit.to_string());
+                        ui.label(format!("{:.1}%", cap_chance(*cap, distribution) * 100.0));
+                        ui.label(format!(
+                            "+{}",
+                            bonuses.get(sit).copied().unwrap_or_default()
+                        ));
+                        ui.end_row();
+                    }
+                });
+
+            if ui.button(tr("progression.transfer_add")).clicked() {
+                transfer = Some(ConcreteStatChange::RdStatChange(
+                    RdStatChange::TransferBonus(bonuses)
+                ));
+            }
+        });
+    transfer
+}
@@ -0,0 +1,156 @@
+//! What was done to the data this session, for tracing how it ended up as it
+//! is. Every game keeps its own log in memory only, the oldest entries giving
+//! way once it's full. Code with the game's data logs with [`log_action`],
+//! other code reports to the context like it does toasts, the app collecting
+//! the reports into the current game's log every frame.
+
+use std::collections::VecDeque;
+
+use egui::{Button, Id, ScrollArea};
+
+use super::{
+    locale::{tr, tr_with},
+    manager::now,
+    GameData
+};
+
+/// the most entries a log keeps
+const MAX_ENTRIES : usize = 500;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct LogEntry {
+    /// seconds since the log started
+    elapsed : u64,
+    text : String
+}
+
+pub struct SessionLog {
+    /// when the game's data was loaded this session, in seconds since the
+    /// Unix epoch
+    started : u64,
+    entries : VecDeque<LogEntry>
+}
+
+impl Default for SessionLog {
+    fn default() -> Self {
+        Self {
+            started : now(),
+            entries : VecDeque::new()
+        }
+    }
+}
+
+/// the seconds as hours, minutes and seconds, e.g. "01:02:03"
+fn clock(seconds : u64) -> String {
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3_600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+impl SessionLog {
+    fn record(&mut self, time : u64, text : String) {
+        self.entries.push_back(LogEntry {
+            elapsed : time.saturating_sub(self.started),
+            text
+        });
+        while self.entries.len() > MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+    }
+
+    /// The entries as lines of text, oldest first.
+    fn text(&self) -> String {
+        self.entries
+            .iter()
+            .map(|entry| format!("{} {}\n", clock(entry.elapsed), entry.text))
+            .collect()
+    }
+}
+
+/// Adds the action to the game's log.
+pub fn log_action(data : &mut GameData, text : impl Into<String>) {
+    data.session_log.record(now(), text.into());
+}
+
+/// where the actions reported this frame wait in the context's memory
+fn pending_id() -> Id { Id::new("Pending Log Entries") }
+
+/// Adds the action to the current game's log once the frame is done, for code
+/// without access to the game's data.
+pub fn report(ctx : &egui::Context, text : impl Into<String>) {
+    ctx.data()
+        .get_temp_mut_or_default::<Vec<String>>(pending_id())
+        .push(text.into());
+}
+
+/// Moves the actions reported this frame into the game's log.
+pub fn collect(data : &mut GameData, ctx : &egui::Context) {
+    let reported = ctx
+        .data()
+        .get_temp::<Vec<String>>(pending_id())
+        .unwrap_or_default();
+    ctx.data().remove::<Vec<String>>(pending_id());
+    for text in reported {
+        log_action(data, text);
+    }
+}
+
+pub fn session_log_window(open : &mut bool, data : &GameData, ctx : &egui::Context) {
+    let log = &data.session_log;
+    egui::Window::new(tr("log.title"))
+        .id(Id::new("Session Log"))
+        .open(open)
+        .default_width(400.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                let copy = Button::new(tr("log.copy"));
+                if ui.add_enabled(!log.entries.is_empty(), copy).clicked() {
+                    ui.output().copied_text = log.text();
+                }
+                let count = log.entries.len();
+                ui.weak(tr_with(
+                    "log.count",
+                    &[("count", &count), ("max", &MAX_ENTRIES)]
+                ));
+            });
+            if log.entries.is_empty() {
+                ui.label(tr("log.empty"));
+            }
+            ScrollArea::vertical().stick_to_bottom(true).show(ui, |ui| {
+                for entry in &log.entries {
+                    ui.horizontal_wrapped(|ui| {
+                        ui.monospace(clock(entry.elapsed));
+                        ui.label(&entry.text);
+                    });
+                }
+            });
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_logs_drop_their_oldest_entries() {
+        let mut log = SessionLog {
+            started : 1_000,
+            entries : VecDeque::new()
+        };
+        log.record(1_005, "first".to_owned());
+        log.record(1_000 + 3_723, "second".to_owned());
+        assert_eq!(log.text(), "00:00:05 first\n01:02:03 second\n");
+
+        for index in 0..MAX_ENTRIES {
+            log.record(2_000, index.to_string());
+        }
+        assert_eq!(log.entries.len(), MAX_ENTRIES);
+        assert_eq!(log.entries.front().unwrap().text, "0");
+        assert!(log
+            .text()
+            .ends_with(&format!("{} {}\n", clock(1_000), MAX_ENTRIES - 1)));
+    }
+}
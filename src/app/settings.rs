@@ -0,0 +1,137 @@
+//! How the interface is laid out, for small screens and for readability, and
+//! the language it speaks. The settings are saved with the app and applied
+//! from its first frame on.
+
+use std::ops::RangeInclusive;
+
+use egui::{style::Margin, vec2, ComboBox, Slider, Spacing, Style};
+use serde::{Deserialize, Serialize};
+
+use super::locale::{self, tr, Language, ALL_LANGUAGES};
+
+/// the text sizes offered, relative to egui's
+const TEXT_SCALES : RangeInclusive<f32> = 0.75..=2.0;
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(default)]
+pub struct Settings {
+    /// the size of all text relative to egui's
+    pub text_scale : f32,
+    /// tighter spacing for small screens
+    pub compact : bool,
+    /// sliders showing their values for the growths and caps in the character
+    /// builder, rather than the narrow text boxes
+    pub stat_sliders : bool,
+    /// the language of the windows' texts
+    pub language : Language
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            text_scale : 1.0,
+            compact : false,
+            stat_sliders : false,
+            language : Language::English
+        }
+    }
+}
+
+fn spacing(compact : bool) -> Spacing {
+    let mut spacing = Spacing::default();
+    if compact {
+        spacing.item_spacing = vec2(4.0, 2.0);
+        spacing.button_padding = vec2(2.0, 0.0);
+        spacing.window_margin = Margin::same(3.0);
+        spacing.menu_margin = Margin::same(3.0);
+        spacing.indent = 12.0;
+        spacing.interact_size.y = 16.0;
+    }
+    spacing
+}
+
+impl Settings {
+    /// The style with the settings' spacing and text sizes, keeping e.g. the
+    /// colors of the dark or light mode.
+    fn styled(&self, style : &Style) -> Style {
+        let default = Style::default();
+        let mut style = style.clone();
+        style.spacing = spacing(self.compact);
+        for (text_style, font) in style.text_styles.iter_mut() {
+            if let Some(default_font) = default.text_styles.get(text_style) {
+                font.size = default_font.size * self.text_scale;
+            }
+        }
+        style
+    }
+
+    /// Restyles the interface once the settings changed, waiting for the
+    /// pointer's release so that the scale slider doesn't move while dragged.
+    pub fn apply(&self, applied : &mut Option<Settings>, ctx : &egui::Context) {
+        if *applied == Some(*self) {
+            return;
+        }
+        let dragging = ctx.input().pointer.any_down();
+        if dragging && applied.is_some() {
+            return;
+        }
+        let style = self.styled(&ctx.style());
+        ctx.set_style(style);
+        locale::set_language(self.language);
+        *applied = Some(*self);
+    }
+}
+
+pub fn settings_window(open : &mut bool, settings : &mut Settings, ctx : &egui::Context) {
+    egui::Window::new(tr("settings.title"))
+        .id(egui::Id::new("Settings"))
+        .open(open)
+        .show(ctx, |ui| {
+            ComboBox::from_label(tr("settings.language"))
+                .selected_text(settings.language.name())
+                .show_ui(ui, |ui| {
+                    for language in ALL_LANGUAGES {
+                        ui.selectable_value(&mut settings.language, language, language.name());
+                    }
+                });
+            let text_size = Slider::new(&mut settings.text_scale, TEXT_SCALES);
+            ui.add(text_size.text(tr("settings.text_size")));
+            ui.checkbox(&mut settings.compact, tr("settings.compact"))
+                .on_hover_text(tr("settings.compact_hint"));
+            ui.checkbox(&mut settings.stat_sliders, tr("settings.stat_sliders"))
+                .on_hover_text(tr("settings.stat_sliders_hint"));
+            // the language stays, as the user might not read the default one
+            if ui.button(tr("settings.reset")).clicked() {
+                *settings = Settings {
+                    language : settings.language,
+                    ..Default::default()
+                };
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn styles_scale_the_text_and_keep_the_colors() {
+        let light = Style {
+            visuals : egui::Visuals::light(),
+            ..Default::default()
+        };
+        let settings = Settings {
+            text_scale : 1.5,
+            compact : true,
+            stat_sliders : false,
+            language : Language::German
+        };
+        let styled = settings.styled(&light);
+        assert_eq!(styled.visuals, egui::Visuals::light());
+        let body = |style : &Style| style.text_styles[&egui::TextStyle::Body].size;
+        assert_eq!(body(&styled), body(&Style::default()) * 1.5);
+        assert!(styled.spacing.item_spacing.y < Spacing::default().item_spacing.y);
+        // styling twice doesn't compound the scale
+        assert_eq!(body(&settings.styled(&styled)), body(&styled));
+    }
+}
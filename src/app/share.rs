@@ -0,0 +1,136 @@
+//! Links to the web app that open it with a character and their progression.
+
+use std::{
+    collections::HashMap,
+    io::{Read, Write}
+};
+
+use fe_levels::Character;
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+
+use super::{
+    generate_default_gamedata,
+    locale::{tr, tr_with},
+    progression::ConcreteStatChange,
+    session_log::log_action,
+    sit::StatIndexType,
+    GameData, GameKind
+};
+
+/// The fragment of the link the unit is encoded in follows this.
+const FRAGMENT_PREFIX : &str = "unit=";
+/// The longest link that survives being pasted into forums and chats.
+const MAX_LINK_LENGTH : usize = 2_000;
+
+/// The game, character and progression a link opens the app with.
+pub type SharedUnit = (GameKind, Character<StatIndexType>, Vec<ConcreteStatChange>);
+
+fn encode(unit : &SharedUnit) -> Result<String, String> {
+    let json = serde_json::to_vec(unit).map_err(|error| error.to_string())?;
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+    encoder
+        .write_all(&json)
+        .map_err(|error| error.to_string())?;
+    let compressed = encoder.finish().map_err(|error| error.to_string())?;
+    Ok(base64::encode_config(compressed, base64::URL_SAFE_NO_PAD))
+}
+
+fn decode(fragment : &str) -> Option<SharedUnit> {
+    let compressed = base64::decode_config(fragment, base64::URL_SAFE_NO_PAD).ok()?;
+    let mut json = Vec::new();
+    DeflateDecoder::new(compressed.as_slice())
+        .read_to_end(&mut json)
+        .ok()?;
+    serde_json::from_slice(&json).ok()
+}
+
+/// A link to the app as it is open now that loads the unit. Fails with the
+/// length the link would have if it's too long to be shared reliably.
+pub fn share_link(unit : &SharedUnit) -> Result<String, String> {
+    let location = web_sys::window()
+        .ok_or_else(|| tr("share.no_window"))?
+        .location();
+    let href = location.href().map_err(|error| format!("{error:?}"))?;
+    let page = href.split('#').next().unwrap_or_default();
+    let link = format!("{page}#{FRAGMENT_PREFIX}{}", encode(unit)?);
+    if link.len() > MAX_LINK_LENGTH {
+        Err(tr_with(
+            "share.too_long",
+            &[("length", &link.len()), ("max", &MAX_LINK_LENGTH)]
+        ))
+    }
+    else {
+        Ok(link)
+    }
+}
+
+/// The unit of the link the app was opened with, if any.
+pub fn linked_unit() -> Option<SharedUnit> {
+    let hash = web_sys::window()?.location().hash().ok()?;
+    decode(hash.strip_prefix('#')?.strip_prefix(FRAGMENT_PREFIX)?)
+}
+
+/// Drops the unit from the link so that reloading the page doesn't offer it
+/// again.
+fn clear_link() {
+    if let Some(window) = web_sys::window() {
+        let _best_effort = window.location().set_hash("");
+    }
+}
+
+/// Asks whether to load the unit of the link, replacing the character and
+/// progression being built in its game.
+pub fn linked_unit_window(
+    linked : &mut Option<SharedUnit>,
+    game_option : &mut GameKind,
+    game_data : &mut HashMap<GameKind, GameData>,
+    ctx : &egui::Context
+) {
+    let (game, character, progression) = match std::mem::take(linked) {
+        Some(unit) => unit,
+        None => return
+    };
+    let mut confirmed = false;
+    let mut cancelled = false;
+    egui::Window::new(tr("share.title"))
+        .id(egui::Id::new("Shared Character"))
+        .collapsible(false)
+        .show(ctx, |ui| {
+            ui.label(tr_with(
+                "share.offer",
+                &[
+                    ("name", &character.name),
+                    ("level", &character.level),
+                    ("game", &game.name()),
+                    ("count", &progression.len())
+                ]
+            ));
+            ui.horizontal(|ui| {
+                confirmed = ui.button(tr("share.load")).clicked();
+                cancelled = ui.button(tr("share.ignore")).clicked();
+            });
+        });
+    if confirmed {
+        let data = game_data
+            .entry(game)
+            .or_insert_with(|| generate_default_gamedata(game));
+        log_action(
+            data,
+            tr_with("log.shared_unit", &[("name", &character.name)])
+        );
+        data.character = character;
+        data.growth_modifiers = Default::default();
+        data.weapon_ranks = Default::default();
+        data.progression.checkpoint();
+        *data.progression = progression;
+        data.insert_missing_stats();
+        *game_option = game;
+        clear_link();
+    }
+    else if cancelled {
+        clear_link();
+    }
+    else {
+        *linked = Some((game, character, progression));
+    }
+}
@@ -1,6 +1,7 @@
-use std::fmt;
+use std::{fmt, sync::RwLock};
 
 use fe_levels::{Character, Stat, StatType};
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
 use super::GameKind;
@@ -8,12 +9,37 @@ use super::GameKind;
 #[derive(PartialEq, Eq, Hash, Serialize, Deserialize, Clone, Debug, Copy)]
 pub struct StatIndexType(usize, GameKind);
 
+/// What a stat does in the damage formulas, as the games split attack and
+/// defense differently.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum StatRole {
+    PhysicalAttack,
+    MagicalAttack,
+    PhysicalDefense,
+    MagicalDefense
+}
+
+impl StatRole {
+    /// the names custom stats fill the role with
+    fn custom_names(&self) -> &'static [&'static str] {
+        match self {
+            StatRole::PhysicalAttack => &["Str", "Atk", "Pow"],
+            StatRole::MagicalAttack => &["Mag", "Atk", "Pow"],
+            StatRole::PhysicalDefense => &["Def"],
+            StatRole::MagicalDefense => &["Res"]
+        }
+    }
+}
+
 impl PartialOrd for StatIndexType {
     fn partial_cmp(&self, other : &Self) -> Option<std::cmp::Ordering> {
         Some(Self::cmp(self, other))
     }
 }
 
+/// Custom stats are ordered by their ids, which never change, so that maps
+/// keyed by them stay valid as the user rearranges them. Use
+/// [`StatIndexType::display_order`] to list them.
 impl Ord for StatIndexType {
     fn cmp(&self, other : &Self) -> std::cmp::Ordering {
         //assert!(self.1 == other.1);
@@ -24,6 +50,13 @@ impl Ord for StatIndexType {
 impl fmt::Display for StatIndexType {
     fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
         let Self(id, kind) = self;
+        if *kind == GameKind::Custom {
+            let stats = CUSTOM_STATS.read().map_err(|_| fmt::Error)?;
+            return match stats.iter().find(|(custom_id, _name)| custom_id == id) {
+                Some((_id, name)) => write!(f, "{name}"),
+                None => write!(f, "(removed stat)")
+            };
+        }
         write!(
             f,
             "{}",
@@ -36,6 +69,17 @@ impl fmt::Display for StatIndexType {
 
 impl StatIndexType {
     pub fn new(game_option : GameKind) -> Vec<Self> {
+        if game_option == GameKind::Custom {
+            return CUSTOM_STATS
+                .read()
+                .map(|stats| {
+                    stats
+                        .iter()
+                        .map(|(id, _name)| StatIndexType(*id, game_option))
+                        .collect()
+                })
+                .unwrap_or_default();
+        }
         look_up_iteration_order(game_option)
             .into_iter()
             .enumerate()
@@ -48,25 +92,127 @@ impl StatIndexType {
         *Self::new(game_option).first().unwrap()
     }
 
-    pub fn is_hp(&self) -> bool { self.0 == 0 }
+    pub fn game(&self) -> GameKind { self.1 }
+
+    /// The position the stat is listed at, custom stats following the order
+    /// the user arranged them in.
+    pub fn display_order(&self) -> (usize, usize) {
+        match self.1 {
+            GameKind::Custom => custom_position(self.0),
+            _ => (self.0, self.0)
+        }
+    }
+
+    pub fn is_hp(&self) -> bool {
+        match self.1 {
+            GameKind::Custom => self.custom_name_is(&["HP"]),
+            _ => self.0 == 0
+        }
+    }
 
     pub fn is_luck(&self) -> bool {
+        if self.1 == GameKind::Custom {
+            return self.custom_name_is(&["Lck", "Luck"]);
+        }
         self.0
             == match self.1 {
-                GameKind::GbaFe => 4,
-                GameKind::PoR => 5
+                GameKind::GbaFe | GameKind::SoV => 4,
+                GameKind::PoR | GameKind::RD | GameKind::ShadowDragon | GameKind::ThreeHouses => 5,
+                GameKind::Custom => unreachable!()
+            }
+    }
+
+    /// returns true iff the stat fills the role in the damage formulas, e.g.
+    /// GBA Atk both attacking roles as those games have no separate Mag
+    pub fn has_role(&self, role : StatRole) -> bool {
+        // the positions of the physical and magical attack and defense stats
+        let [physical_attack, magical_attack, physical_defense, magical_defense] = match self.1 {
+            GameKind::GbaFe | GameKind::SoV => [1, 1, 5, 6],
+            GameKind::PoR | GameKind::RD | GameKind::ShadowDragon | GameKind::ThreeHouses => {
+                [1, 2, 6, 7]
+            },
+            GameKind::Custom => return self.custom_name_is(role.custom_names())
+        };
+        self.0
+            == match role {
+                StatRole::PhysicalAttack => physical_attack,
+                StatRole::MagicalAttack => magical_attack,
+                StatRole::PhysicalDefense => physical_defense,
+                StatRole::MagicalDefense => magical_defense
             }
     }
 
+    /// The game's stat filling the role in the damage formulas, if any.
+    pub fn with_role(game_option : GameKind, role : StatRole) -> Option<Self> {
+        Self::new(game_option)
+            .into_iter()
+            .find(|sit| sit.has_role(role))
+    }
+
     /// returns true iff the stat is relevant for weight calculations
     pub fn is_con(&self) -> bool {
         self.0
             == match self.1 {
                 GameKind::GbaFe => 7,
-                GameKind::PoR => 1
+                GameKind::PoR | GameKind::RD => 1,
+                // none of them has a stat offsetting weapon weight
+                GameKind::ShadowDragon | GameKind::SoV | GameKind::ThreeHouses => return false,
+                GameKind::Custom => return self.custom_name_is(&["Con"])
             }
     }
 
+    fn is_mov(&self) -> bool { self.1 == GameKind::GbaFe && self.0 == 8 }
+
+    /// returns false for stats that never grow on level-ups, e.g. GBA Con and
+    /// Mov, which only change with promotions and items
+    pub fn is_growable(&self) -> bool {
+        match self.1 {
+            GameKind::GbaFe => !self.is_con() && !self.is_mov(),
+            _ => true
+        }
+    }
+
+    /// custom stats are told apart by the names the user gave them
+    fn custom_name_is(&self, names : &[&str]) -> bool {
+        let own_name = self.to_string();
+        names.iter().any(|name| own_name.eq_ignore_ascii_case(name))
+    }
+
+    /// the name the stat goes by in community json and the REPL's saved
+    /// units, e.g. "skl"
+    pub fn key(&self) -> String { self.to_string().to_lowercase() }
+
+    /// The game's stat going by the name in community json. The names other
+    /// games or spreadsheets use for it are accepted as well, e.g. "str" for
+    /// GBA Atk.
+    pub fn from_key(game_option : GameKind, key : &str) -> Option<Self> {
+        let key = key.trim().to_lowercase();
+        let stats = Self::new(game_option);
+        let aliases = STAT_ALIASES
+            .iter()
+            .find(|aliases| aliases.contains(&key.as_str()))
+            .map_or(&[][..], |aliases| *aliases);
+        stats
+            .iter()
+            .find(|sit| sit.key() == key)
+            .or_else(|| {
+                aliases
+                    .iter()
+                    .find_map(|alias| stats.iter().find(|sit| sit.key() == *alias))
+            })
+            .copied()
+    }
+
+    /// The first of the stat's aliases, naming it alike in every game, e.g.
+    /// "str" for GBA Atk. None for custom stats named unlike any of them.
+    pub fn canonical_key(&self) -> Option<&'static str> {
+        let key = self.key();
+        STAT_ALIASES
+            .iter()
+            .find(|aliases| aliases.contains(&key.as_str()))
+            .map(|aliases| aliases[0])
+    }
+
     pub fn default_stat(&self) -> Stat {
         let Self(_index, game) = self;
         match game {
@@ -80,17 +226,58 @@ impl StatIndexType {
                 else if self.is_con() {
                     25
                 }
+                else if self.is_mov() {
+                    15
+                }
                 else {
                     20
                 };
+                // most classes move five spaces
+                let base = if self.is_mov() { 5 } else { cap / 4 };
+                Stat {
+                    base,
+                    cap,
+                    growth : if self.is_growable() { 40 } else { 0 },
+                    value : base
+                }
+            },
+            GameKind::Custom => {
+                let cap = if self.is_hp() { 60 } else { 30 };
+                Stat {
+                    base : cap / 4,
+                    cap,
+                    growth : 40,
+                    value : cap / 4
+                }
+            },
+            GameKind::SoV => {
+                let cap = if self.is_hp() { 52 } else { 40 };
                 Stat {
                     base : cap / 4,
                     cap,
-                    growth : if self.is_con() { 0 } else { 40 },
+                    growth : if self.is_hp() { 60 } else { 40 },
                     value : cap / 4
                 }
             },
-            GameKind::PoR => {
+            GameKind::ShadowDragon => {
+                let cap = if self.is_hp() { 60 } else { 20 };
+                Stat {
+                    base : cap / 4,
+                    cap,
+                    growth : 40,
+                    value : cap / 4
+                }
+            },
+            GameKind::ThreeHouses => {
+                let cap = if self.is_hp() { 80 } else { 50 };
+                Stat {
+                    base : cap / 4,
+                    cap,
+                    growth : 40,
+                    value : cap / 4
+                }
+            },
+            GameKind::PoR | GameKind::RD => {
                 let cap = if self.is_hp() || self.is_luck() {
                     40
                 }
@@ -140,12 +327,108 @@ impl StatIndexType {
 const TEMPLATE_INDEX : usize = 100;
 pub const fn template_stat(game : GameKind) -> StatIndexType { StatIndexType(TEMPLATE_INDEX, game) }
 
-const GBA_FE_ORDER : [&str; 8] = ["HP", "Atk", "Skl", "Spd", "Lck", "Def", "Res", "Con"];
+const GBA_FE_ORDER : [&str; 9] = ["HP", "Atk", "Skl", "Spd", "Lck", "Def", "Res", "Con", "Mov"];
 const POR_ORDER : [&str; 8] = ["HP", "Str", "Mag", "SKl", "Spd", "Lck", "Def", "Res"];
+const RD_ORDER : [&str; 8] = ["HP", "Str", "Mag", "Skl", "Spd", "Lck", "Def", "Res"];
+const SHADOW_DRAGON_ORDER : [&str; 8] = ["HP", "Str", "Mag", "Skl", "Spd", "Lck", "Def", "Res"];
+const SOV_ORDER : [&str; 7] = ["HP", "Atk", "Skl", "Spd", "Lck", "Def", "Res"];
+const THREE_HOUSES_ORDER : [&str; 9] =
+    ["HP", "Str", "Mag", "Dex", "Spd", "Lck", "Def", "Res", "Cha"];
+
+/// Names of the same stat, lowercase, as games and spreadsheets name them
+/// differently.
+const STAT_ALIASES : [&[&str]; 11] = [
+    &["hp", "health"],
+    &["str", "atk", "pow", "strength", "attack"],
+    &["mag", "magic"],
+    &["skl", "dex", "skill", "dexterity"],
+    &["spd", "speed"],
+    &["lck", "luck", "lk"],
+    &["def", "defense", "defence"],
+    &["res", "resistance"],
+    &["con", "constitution", "bld", "build"],
+    &["mov", "move", "movement"],
+    &["cha", "charm"]
+];
 
 fn look_up_iteration_order(game : GameKind) -> Vec<&'static str> {
     match game {
         GameKind::GbaFe => Vec::from(GBA_FE_ORDER),
-        GameKind::PoR => Vec::from(POR_ORDER)
+        GameKind::PoR => Vec::from(POR_ORDER),
+        GameKind::RD => Vec::from(RD_ORDER),
+        GameKind::ShadowDragon => Vec::from(SHADOW_DRAGON_ORDER),
+        GameKind::SoV => Vec::from(SOV_ORDER),
+        GameKind::ThreeHouses => Vec::from(THREE_HOUSES_ORDER),
+        // named by the user, see CustomStats
+        GameKind::Custom => vec![]
+    }
+}
+
+/// The published stats of [`GameKind::Custom`] in display order, as
+/// [`StatIndexType`] has no access to the [`GameData`](super::GameData) owning
+/// them.
+static CUSTOM_STATS : RwLock<Vec<(usize, String)>> = RwLock::new(Vec::new());
+
+/// The position of the custom stat as the user arranged them, removed stats
+/// going last.
+fn custom_position(id : usize) -> (usize, usize) {
+    let position = CUSTOM_STATS
+        .read()
+        .ok()
+        .and_then(|stats| stats.iter().position(|(custom_id, _name)| *custom_id == id));
+    (position.unwrap_or(usize::MAX), id)
+}
+
+/// The user-defined stats of [`GameKind::Custom`] in display order. Each stat
+/// keeps the id its [`StatIndexType`] carries for its whole life, so renaming
+/// or moving it keeps all data saved for it.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct CustomStats {
+    stats : Vec<(usize, String)>,
+    /// ids are never reused so data of removed stats can't resurface
+    next_id : usize
+}
+
+impl Default for CustomStats {
+    fn default() -> Self {
+        let stats = ["HP", "Str", "Mag", "Skl", "Spd", "Lck", "Def", "Res", "Con"]
+            .into_iter()
+            .enumerate()
+            .map(|(id, name)| (id, name.to_owned()))
+            .collect_vec();
+        Self {
+            next_id : stats.len(),
+            stats
+        }
+    }
+}
+
+impl CustomStats {
+    /// makes these the stats every custom [`StatIndexType`] refers to
+    pub fn publish(&self) {
+        if let Ok(mut stats) = CUSTOM_STATS.write() {
+            if *stats != self.stats {
+                stats.clone_from(&self.stats);
+            }
+        }
+    }
+
+    pub fn stats_mut(&mut self) -> &mut Vec<(usize, String)> { &mut self.stats }
+
+    /// removes the stat at the given position and returns it
+    pub fn remove(&mut self, index : usize) -> StatIndexType {
+        let (id, _name) = self.stats.remove(index);
+        StatIndexType(id, GameKind::Custom)
+    }
+
+    /// adds a stat with a fresh id at the end and returns it
+    pub fn add(&mut self, name : String) -> StatIndexType {
+        if self.next_id == TEMPLATE_INDEX {
+            self.next_id += 1;
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        self.stats.push((id, name));
+        StatIndexType(id, GameKind::Custom)
     }
 }
@@ -0,0 +1,191 @@
+//! The units being worked on side by side, shown as tabs across the builders.
+//! The active unit lives in the fields of [`GameData`] the windows edit, while
+//! the units of the other tabs wait in its slots. States saved before the tabs
+//! existed thus restore as a single tab.
+
+use egui::{Button, Ui};
+use fe_levels::Character;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    locale::tr, plotter::PlotterManager, progression::ProgressionManager, sit::StatIndexType,
+    weapon::WeaponRanks, GameData, GameKind, GrowthModifiers
+};
+
+/// A unit of a tab other than the active one, with everything the builders
+/// and the plotter keep of them.
+#[derive(Deserialize, Serialize, Default)]
+#[serde(default)]
+pub struct WorkingSlot {
+    pub plotter : PlotterManager,
+    pub character : Character<StatIndexType>,
+    pub growth_modifiers : GrowthModifiers,
+    pub current_values : bool,
+    pub weapon_ranks : WeaponRanks,
+    pub progression : ProgressionManager
+}
+
+impl WorkingSlot {
+    fn new(game_option : GameKind) -> Self {
+        Self {
+            character : StatIndexType::new_default_character(game_option),
+            ..Default::default()
+        }
+    }
+}
+
+/// A change of the tabs, applied before the windows are drawn so that no
+/// window mixes the units of two tabs within a frame.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TabEdit {
+    Switch(usize),
+    Open,
+    Close
+}
+
+fn tab_name(character : &Character<StatIndexType>) -> String {
+    match character.name.as_str() {
+        "" => tr("slots.unnamed"),
+        name => name.to_owned()
+    }
+}
+
+impl GameData {
+    /// the position of the active unit among the tabs
+    fn active_tab(&self) -> usize { self.active_slot.min(self.slots.len()) }
+
+    fn take_active(&mut self) -> WorkingSlot {
+        WorkingSlot {
+            plotter : std::mem::take(&mut self.plotter),
+            character : std::mem::take(&mut self.character),
+            growth_modifiers : std::mem::take(&mut self.growth_modifiers),
+            current_values : std::mem::take(&mut self.current_values),
+            weapon_ranks : std::mem::take(&mut self.weapon_ranks),
+            progression : std::mem::take(&mut self.progression)
+        }
+    }
+
+    fn put_active(&mut self, slot : WorkingSlot) {
+        self.plotter = slot.plotter;
+        self.character = slot.character;
+        self.growth_modifiers = slot.growth_modifiers;
+        self.current_values = slot.current_values;
+        self.weapon_ranks = slot.weapon_ranks;
+        self.progression = slot.progression;
+    }
+
+    /// Parks the active unit in its slot and activates the unit of the tab.
+    fn switch_tab(&mut self, tab : usize) {
+        let active_tab = self.active_tab();
+        if tab == active_tab || tab > self.slots.len() {
+            return;
+        }
+        let active = self.take_active();
+        self.slots.insert(active_tab, active);
+        let slot = self.slots.remove(tab);
+        self.put_active(slot);
+        self.active_slot = tab;
+    }
+
+    /// Parks the active unit and starts a fresh one in a new last tab.
+    fn open_tab(&mut self) {
+        let active = self.take_active();
+        self.slots.insert(self.active_tab(), active);
+        self.put_active(WorkingSlot::new(self.game_option));
+        self.active_slot = self.slots.len();
+    }
+
+    /// Drops the active unit, activating the tab after it or else the one
+    /// before it. The last tab can't be closed.
+    fn close_tab(&mut self) {
+        if self.slots.is_empty() {
+            return;
+        }
+        let tab = self.active_tab().min(self.slots.len() - 1);
+        let slot = self.slots.remove(tab);
+        self.put_active(slot);
+        self.active_slot = tab;
+    }
+
+    pub fn apply_tab_edit(&mut self) {
+        match self.tab_edit.take() {
+            Some(TabEdit::Switch(tab)) => self.switch_tab(tab),
+            Some(TabEdit::Open) => self.open_tab(),
+            Some(TabEdit::Close) => self.close_tab(),
+            None => {}
+        }
+    }
+
+    /// The names of the units of all tabs, in their order.
+    fn tab_names(&self) -> Vec<String> {
+        let mut names = self
+            .slots
+            .iter()
+            .map(|slot| tab_name(&slot.character))
+            .collect::<Vec<_>>();
+        names.insert(self.active_tab(), tab_name(&self.character));
+        names
+    }
+}
+
+/// The tabs switching between the units being worked on, queueing the edit
+/// of the clicked one for the next frame.
+pub fn slot_tabs(data : &mut GameData, ui : &mut Ui) {
+    let active_tab = data.active_tab();
+    ui.horizontal_wrapped(|ui| {
+        for (tab, name) in data.tab_names().into_iter().enumerate() {
+            if ui.selectable_label(tab == active_tab, name).clicked() {
+                data.tab_edit = Some(TabEdit::Switch(tab));
+            }
+        }
+        if ui
+            .small_button("➕")
+            .on_hover_text(tr("slots.open_hint"))
+            .clicked()
+        {
+            data.tab_edit = Some(TabEdit::Open);
+        }
+        if ui
+            .add_enabled(!data.slots.is_empty(), Button::new("🗙").small())
+            .on_hover_text(tr("slots.close_hint"))
+            .clicked()
+        {
+            data.tab_edit = Some(TabEdit::Close);
+        }
+    });
+    if data.tab_edit.is_some() {
+        ui.ctx().request_repaint();
+    }
+    ui.separator();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::generate_default_gamedata;
+
+    fn named(data : &mut GameData, name : &str) { data.character.name = name.to_owned(); }
+
+    #[test]
+    fn tabs_keep_their_units() {
+        let mut data = generate_default_gamedata(GameKind::GbaFe);
+        named(&mut data, "Eliwood");
+        data.open_tab();
+        named(&mut data, "Hector");
+        data.open_tab();
+        named(&mut data, "Lyn");
+        assert_eq!(data.tab_names(), vec!["Eliwood", "Hector", "Lyn"]);
+
+        data.switch_tab(0);
+        assert_eq!(data.character.name, "Eliwood");
+        assert_eq!(data.tab_names(), vec!["Eliwood", "Hector", "Lyn"]);
+
+        data.switch_tab(1);
+        data.close_tab();
+        assert_eq!(data.character.name, "Lyn");
+        assert_eq!(data.tab_names(), vec!["Eliwood", "Lyn"]);
+        data.close_tab();
+        data.close_tab();
+        assert_eq!(data.tab_names(), vec!["Eliwood"]);
+    }
+}
@@ -0,0 +1,282 @@
+//! Stat blocks pasted from wikis like Serenes Forest, e.g.
+//!
+//! ```text
+//! HP 16 Str 4 Skl 8 Spd 9 Lck 4 Def 3 Res 1
+//! HP 80% Str 45% Skl 50% Spd 40% Lck 45% Def 30% Res 35%
+//! ```
+//!
+//! Every line pairs stat names with the numbers following them, whatever lies
+//! in between. A line of only names is a table header naming the numbers of
+//! the lines below it. Lines labelled "bases", "growths" or "caps" fill that
+//! part of the stats, lines with percentages the growths and all others the
+//! part picked in the dialog.
+
+use std::collections::VecDeque;
+
+use egui::{TextEdit, Ui};
+use fe_levels::{Character, StatType};
+
+use super::{
+    locale::{tr, tr_with},
+    sit::StatIndexType,
+    GameKind
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum StatPart {
+    #[default]
+    Base,
+    Growth,
+    Cap
+}
+
+/// the words labelling the lines of a part
+const PART_LABELS : [(&str, StatPart); 9] = [
+    ("base", StatPart::Base),
+    ("bases", StatPart::Base),
+    ("growth", StatPart::Growth),
+    ("growths", StatPart::Growth),
+    ("rates", StatPart::Growth),
+    ("cap", StatPart::Cap),
+    ("caps", StatPart::Cap),
+    ("max", StatPart::Cap),
+    ("maximum", StatPart::Cap)
+];
+
+fn part_label(word : &str) -> Option<StatPart> {
+    let word = word.to_lowercase();
+    PART_LABELS
+        .iter()
+        .find(|(label, _part)| *label == word)
+        .map(|(_label, part)| *part)
+}
+
+/// The runs of letters and of digits in the line, dropping everything else.
+fn tokens(line : &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    let mut previous_digit = false;
+    for (index, char) in line.char_indices() {
+        let is_digit = char.is_ascii_digit();
+        let is_token = is_digit || char.is_alphabetic();
+        match start {
+            Some(begin) if !is_token || is_digit != previous_digit => {
+                tokens.push(&line[begin..index]);
+                start = is_token.then_some(index);
+            },
+            None if is_token => start = Some(index),
+            _ => {}
+        }
+        previous_digit = is_digit;
+    }
+    if let Some(begin) = start {
+        tokens.push(&line[begin..]);
+    }
+    tokens
+}
+
+/// The stats read from a pasted block, together with the tokens that named no
+/// stat of the game or were left without a partner.
+#[derive(Default, Debug)]
+pub struct StatBlock {
+    pub values : Vec<(StatPart, StatIndexType, StatType)>,
+    pub unknown : Vec<String>
+}
+
+impl StatBlock {
+    pub fn parse(game_option : GameKind, text : &str, unlabelled : StatPart) -> Self {
+        let mut block = Self::default();
+        let mut header : Vec<StatIndexType> = Vec::new();
+        for line in text.lines() {
+            let mut part = if line.contains('%') {
+                StatPart::Growth
+            }
+            else {
+                unlabelled
+            };
+            let mut names = Vec::new();
+            let mut pending = VecDeque::new();
+            let mut pairs = Vec::new();
+            let mut unpaired = Vec::new();
+            for token in tokens(line) {
+                if let Some(sit) = StatIndexType::from_key(game_option, token) {
+                    names.push(sit);
+                    pending.push_back(sit);
+                }
+                else if let Ok(number) = token.parse::<StatType>() {
+                    match pending.pop_front() {
+                        Some(sit) => pairs.push((sit, number)),
+                        None => unpaired.push(number)
+                    }
+                }
+                else if let Some(label) = part_label(token) {
+                    part = label;
+                }
+                else {
+                    block.unknown.push(token.to_owned());
+                }
+            }
+
+            if names.is_empty() {
+                // a row of the table below the last header
+                pairs = header
+                    .iter()
+                    .copied()
+                    .zip(unpaired.iter().copied())
+                    .collect();
+                unpaired.drain(..pairs.len());
+            }
+            else if pairs.is_empty() && unpaired.is_empty() {
+                header = names;
+                pending.clear();
+            }
+            block
+                .unknown
+                .extend(unpaired.iter().map(|number| number.to_string()));
+            block
+                .unknown
+                .extend(pending.iter().map(|sit| sit.to_string()));
+            block
+                .values
+                .extend(pairs.into_iter().map(|(sit, number)| (part, sit, number)));
+        }
+        block
+    }
+
+    /// Overwrites the parts of the character's stats the block names. Pasted
+    /// bases become the current values, too.
+    pub fn apply(&self, character : &mut Character<StatIndexType>) {
+        for (part, sit, number) in &self.values {
+            if let Some(stat) = character.stats.get_mut(sit) {
+                match part {
+                    StatPart::Base => {
+                        stat.base = *number;
+                        stat.value = *number;
+                    },
+                    StatPart::Growth => stat.growth = *number,
+                    StatPart::Cap => stat.cap = *number
+                }
+            }
+        }
+    }
+
+    fn count(&self, part : StatPart) -> usize {
+        self.values
+            .iter()
+            .filter(|(value_part, _sit, _number)| *value_part == part)
+            .count()
+    }
+}
+
+#[derive(Default)]
+pub struct PasteDialog {
+    pub open : bool,
+    text : String,
+    unlabelled : StatPart
+}
+
+/// The text area taking the pasted block, listing what it couldn't read.
+/// Returns whether the block was applied to the character.
+pub fn paste_dialog(
+    dialog : &mut PasteDialog,
+    character : &mut Character<StatIndexType>,
+    game_option : GameKind,
+    ui : &mut Ui
+) -> bool {
+    ui.horizontal(|ui| {
+        ui.label(tr("builder.unlabelled"));
+        ui.radio_value(&mut dialog.unlabelled, StatPart::Base, tr("builder.bases"));
+        ui.radio_value(
+            &mut dialog.unlabelled,
+            StatPart::Growth,
+            tr("builder.growths")
+        );
+        ui.radio_value(&mut dialog.unlabelled, StatPart::Cap, tr("builder.caps"));
+    });
+    ui.add(
+        TextEdit::multiline(&mut dialog.text)
+            .desired_rows(3)
+            .hint_text("HP 16 Str 4 Skl 8 Spd 9 Lck 4 Def 3 Res 1")
+    );
+    let block = StatBlock::parse(game_option, &dialog.text, dialog.unlabelled);
+    ui.label(tr_with(
+        "builder.read_stats",
+        &[
+            ("bases", &block.count(StatPart::Base)),
+            ("growths", &block.count(StatPart::Growth)),
+            ("caps", &block.count(StatPart::Cap))
+        ]
+    ));
+    if !block.unknown.is_empty() {
+        ui.colored_label(
+            ui.visuals().warn_fg_color,
+            tr_with(
+                "builder.not_recognized",
+                &[("tokens", &block.unknown.join(", "))]
+            )
+        );
+    }
+    let mut applied = false;
+    ui.horizontal(|ui| {
+        if ui
+            .add_enabled(
+                !block.values.is_empty(),
+                egui::Button::new(tr("builder.apply"))
+            )
+            .clicked()
+        {
+            block.apply(character);
+            dialog.text.clear();
+            dialog.open = false;
+            applied = true;
+        }
+        if ui.button(tr("common.cancel")).clicked() {
+            dialog.open = false;
+        }
+    });
+    applied
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stat(game_option : GameKind, key : &str) -> StatIndexType {
+        StatIndexType::from_key(game_option, key).unwrap()
+    }
+
+    #[test]
+    fn lines_are_read_by_their_labels_and_aliases() {
+        let text = "Bases: HP 16 / Atk 4 / Skl 8\nHP 80% Str 45%\nCaps - HP: 60, Str: 20";
+        let block = StatBlock::parse(GameKind::GbaFe, text, StatPart::Base);
+        assert!(block.unknown.is_empty());
+        let (hp, atk) = (stat(GameKind::GbaFe, "hp"), stat(GameKind::GbaFe, "str"));
+        assert!(block.values.contains(&(StatPart::Base, atk, 4)));
+        assert!(block.values.contains(&(StatPart::Growth, atk, 45)));
+        assert!(block.values.contains(&(StatPart::Cap, hp, 60)));
+        assert_eq!(block.values.len(), 7);
+
+        let mut character = StatIndexType::new_default_character(GameKind::GbaFe);
+        block.apply(&mut character);
+        assert_eq!(character.stats[&hp].base, 16);
+        assert_eq!(character.stats[&hp].value, 16);
+        assert_eq!(character.stats[&hp].growth, 80);
+    }
+
+    #[test]
+    fn tables_are_read_by_their_headers() {
+        let text = "HP\tStr\tSpd\n19\t5\t7\n80%\t45%\t40%";
+        let block = StatBlock::parse(GameKind::PoR, text, StatPart::Base);
+        assert!(block.unknown.is_empty());
+        let spd = stat(GameKind::PoR, "spd");
+        assert!(block.values.contains(&(StatPart::Base, spd, 7)));
+        assert!(block.values.contains(&(StatPart::Growth, spd, 40)));
+    }
+
+    #[test]
+    fn unknown_and_unpaired_tokens_are_reported() {
+        let block = StatBlock::parse(GameKind::GbaFe, "Lv 1 HP 16 Bravery 3 Spd", StatPart::Base);
+        assert_eq!(block.unknown, vec!["Lv", "Bravery", "1", "3", "Spd"]);
+        assert_eq!(block.values.len(), 1);
+    }
+}
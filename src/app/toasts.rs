@@ -0,0 +1,162 @@
+//! Notifications shown for a few seconds in the corner of the window, telling
+//! what went wrong without interrupting the user. Anything with access to the
+//! context reports them, the app collecting them into its queue every frame.
+
+use std::{collections::VecDeque, time::Duration};
+
+use egui::{Align2, Frame, Id, Order, Ui};
+
+/// how long a toast stays, in seconds
+const TOAST_SECONDS : f64 = 6.0;
+/// the most toasts shown at once, the oldest ones giving way
+const MAX_TOASTS : usize = 5;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ToastKind {
+    Info,
+    Error
+}
+
+#[derive(Clone, Debug)]
+struct Toast {
+    kind : ToastKind,
+    text : String,
+    /// when the toast disappears, `None` until it's first shown
+    expires : Option<f64>
+}
+
+/// where the toasts reported this frame wait in the context's memory
+fn pending_id() -> Id { Id::new("Pending Toasts") }
+
+pub fn report(ctx : &egui::Context, kind : ToastKind, text : impl Into<String>) {
+    ctx.data()
+        .get_temp_mut_or_default::<Vec<Toast>>(pending_id())
+        .push(Toast {
+            kind,
+            text : text.into(),
+            expires : None
+        });
+    ctx.request_repaint();
+}
+
+pub fn error(ctx : &egui::Context, text : impl Into<String>) { report(ctx, ToastKind::Error, text) }
+
+pub fn info(ctx : &egui::Context, text : impl Into<String>) { report(ctx, ToastKind::Info, text) }
+
+#[derive(Default)]
+pub struct Toasts {
+    queue : VecDeque<Toast>
+}
+
+impl Toasts {
+    /// Queues the reported toasts and drops the expired ones. A toast whose
+    /// text is shown already only restarts its time, so that errors repeated
+    /// every frame don't pile up.
+    fn update(&mut self, reported : Vec<Toast>, now : f64) {
+        for toast in reported {
+            let expires = Some(now + TOAST_SECONDS);
+            match self
+                .queue
+                .iter_mut()
+                .find(|queued| queued.text == toast.text)
+            {
+                Some(queued) => queued.expires = expires,
+                None => self.queue.push_back(Toast { expires, ..toast })
+            }
+        }
+        self.queue
+            .retain(|toast| toast.expires.map_or(true, |expires| expires > now));
+        while self.queue.len() > MAX_TOASTS {
+            self.queue.pop_front();
+        }
+    }
+
+    fn toast_frame(toast : &Toast, ui : &mut Ui) -> bool {
+        let color = match toast.kind {
+            ToastKind::Info => ui.visuals().text_color(),
+            ToastKind::Error => ui.visuals().error_fg_color
+        };
+        let mut dismissed = false;
+        Frame::popup(ui.style()).show(ui, |ui| {
+            ui.set_max_width(300.0);
+            ui.horizontal(|ui| {
+                dismissed = ui.small_button("🗙").clicked();
+                ui.colored_label(color, &toast.text);
+            });
+        });
+        dismissed
+    }
+
+    /// Shows the toasts in the bottom right corner until they expire or are
+    /// dismissed.
+    pub fn show(&mut self, ctx : &egui::Context) {
+        let reported = ctx
+            .data()
+            .get_temp::<Vec<Toast>>(pending_id())
+            .unwrap_or_default();
+        ctx.data().remove::<Vec<Toast>>(pending_id());
+        let now = ctx.input().time;
+        self.update(reported, now);
+        if self.queue.is_empty() {
+            return;
+        }
+
+        let mut dismissed = None;
+        egui::Area::new("Toasts")
+            .order(Order::Foreground)
+            .anchor(Align2::RIGHT_BOTTOM, [-8.0, -8.0])
+            .show(ctx, |ui| {
+                for (index, toast) in self.queue.iter().enumerate() {
+                    if Self::toast_frame(toast, ui) {
+                        dismissed = Some(index);
+                    }
+                }
+            });
+        if let Some(index) = dismissed {
+            self.queue.remove(index);
+        }
+
+        let next_expiry = self
+            .queue
+            .iter()
+            .filter_map(|toast| toast.expires)
+            .fold(f64::INFINITY, f64::min);
+        ctx.request_repaint_after(Duration::from_secs_f64((next_expiry - now).max(0.0)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn toast(text : &str) -> Toast {
+        Toast {
+            kind : ToastKind::Error,
+            text : text.to_owned(),
+            expires : None
+        }
+    }
+
+    #[test]
+    fn repeated_toasts_restart_their_time_and_expire() {
+        let mut toasts = Toasts::default();
+        toasts.update(vec![toast("first"), toast("second")], 0.0);
+        toasts.update(vec![toast("first")], 5.0);
+        assert_eq!(toasts.queue.len(), 2);
+
+        toasts.update(Vec::new(), TOAST_SECONDS + 1.0);
+        let texts = toasts
+            .queue
+            .iter()
+            .map(|toast| toast.text.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(texts, vec!["first"]);
+
+        toasts.update(
+            (0..10).map(|index| toast(&index.to_string())).collect(),
+            7.0
+        );
+        assert_eq!(toasts.queue.len(), MAX_TOASTS);
+        assert_eq!(toasts.queue.back().unwrap().text, "9");
+    }
+}
@@ -1,25 +1,330 @@
-use egui::Ui;
+use std::collections::{BTreeMap, BTreeSet};
+
+use egui::{ComboBox, Slider, Ui};
+use fe_levels::{Character, StatType};
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
 use self::gba::GbaFeWeapon;
+pub use self::gba::{weapon_rank_dialogue, WeaponRanks};
 
-use super::{GameData, GameKind};
+use super::{
+    locale::{tr, tr_with},
+    sit::StatIndexType,
+    GameData, GameKind
+};
 
 mod gba;
 
-#[derive(Serialize, Deserialize, Debug)]
+/// The chances of every value of every stat at one point of the progression.
+type StatDistributions = BTreeMap<StatIndexType, BTreeMap<StatType, f64>>;
+
+/// What a saved enemy fights with, kept next to the enemy by its name.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct EnemyEquipment {
+    /// the name of the saved weapon the enemy has equipped, none if empty
+    pub weapon : String,
+    /// the names of the saved weapons that are effective against the enemy
+    pub weak_to : BTreeSet<String>
+}
+
+/// The most a game's forge can raise a weapon's stats by.
+#[derive(Debug, Clone, Copy)]
+pub struct ForgeLimits {
+    pub might : StatType,
+    pub hit : StatType,
+    pub crit : StatType
+}
+
+/// A weapon being forged from a saved one by the boosts to its stats.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct Forge {
+    /// the name of the saved weapon being forged
+    pub base : String,
+    /// the name of the forged weapon
+    pub name : String,
+    pub might : StatType,
+    pub hit : StatType,
+    pub crit : StatType
+}
+
+impl Forge {
+    pub fn new(base : &str) -> Self {
+        Forge {
+            base : base.to_owned(),
+            name : format!("{base}+"),
+            ..Default::default()
+        }
+    }
+}
+
+/// Lets the user name the forged weapon and pick its boosts within the limits.
+pub fn forge_dialogue(forge : &mut Forge, limits : ForgeLimits, ui : &mut Ui) {
+    ui.label(tr_with("weapon.forging", &[("base", &forge.base)]));
+    ui.horizontal(|ui| {
+        ui.label(tr("common.name"));
+        ui.text_edit_singleline(&mut forge.name);
+    });
+    ui.add(Slider::new(&mut forge.might, 0..=limits.might).text(tr("weapon.might")));
+    ui.add(Slider::new(&mut forge.hit, 0..=limits.hit).text(tr("weapon.hit")));
+    ui.add(Slider::new(&mut forge.crit, 0..=limits.crit).text(tr("weapon.crit")));
+}
+
+/// An enemy as the combat computations see them.
+pub struct Opponent<'a> {
+    pub character : &'a Character<StatIndexType>,
+    pub weapon : Option<&'a Weapon>,
+    /// whether the weapon attacking the enemy is effective against them
+    pub weak : bool
+}
+
+impl<'a> Opponent<'a> {
+    fn stat(&self, is_stat : impl Fn(&StatIndexType) -> bool) -> StatType {
+        self.character
+            .stats
+            .iter()
+            .find(|(sit, _stat)| is_stat(sit))
+            .map_or(0, |(_sit, stat)| stat.value)
+    }
+
+    /// The attack speed of the enemy, the weight of their weapon exceeding
+    /// their Con slowing them down.
+    pub fn attack_speed(&self) -> StatType {
+        let speed = self.stat(|sit| sit.to_string() == "Spd");
+        let weight = self.weapon.map_or(0, Weapon::weight);
+        speed.saturating_sub(weight.saturating_sub(self.stat(StatIndexType::is_con)))
+    }
+
+    fn gba_weapon(&self) -> Option<&'a GbaFeWeapon> {
+        match self.weapon {
+            Some(Weapon::GbaFeWeapon(data)) => Some(data),
+            _ => None
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum Weapon {
     GbaFeWeapon(GbaFeWeapon),
-    PoRWeapon
+    PoRWeapon,
+    RdWeapon,
+    ShadowDragonWeapon,
+    SoVWeapon,
+    ThreeHousesWeapon,
+    CustomWeapon
 }
 
 impl Weapon {
     pub fn new(game_option : GameKind) -> Self {
         match game_option {
             GameKind::GbaFe => Self::GbaFeWeapon(GbaFeWeapon::default()),
-            GameKind::PoR => Self::PoRWeapon
+            GameKind::PoR => Self::PoRWeapon,
+            GameKind::RD => Self::RdWeapon,
+            GameKind::ShadowDragon => Self::ShadowDragonWeapon,
+            GameKind::SoV => Self::SoVWeapon,
+            GameKind::ThreeHouses => Self::ThreeHousesWeapon,
+            GameKind::Custom => Self::CustomWeapon
+        }
+    }
+
+    /// whether the weapon carries stats, which only GBA weapons do so far
+    pub fn has_stats(&self) -> bool { matches!(self, Weapon::GbaFeWeapon(_)) }
+
+    /// The weapon forged with the boosts, `None` if it has no stats to forge.
+    pub fn forged(&self, forge : &Forge) -> Option<Weapon> {
+        match self {
+            Weapon::GbaFeWeapon(data) => Some(Weapon::GbaFeWeapon(data.forged(forge))),
+            Weapon::PoRWeapon
+            | Weapon::RdWeapon
+            | Weapon::ShadowDragonWeapon
+            | Weapon::SoVWeapon
+            | Weapon::ThreeHousesWeapon
+            | Weapon::CustomWeapon => None
+        }
+    }
+
+    /// whether the weapon is effective against some enemies
+    pub fn is_effective(&self) -> bool {
+        match self {
+            Weapon::GbaFeWeapon(data) => data.is_effective(),
+            Weapon::PoRWeapon
+            | Weapon::RdWeapon
+            | Weapon::ShadowDragonWeapon
+            | Weapon::SoVWeapon
+            | Weapon::ThreeHousesWeapon
+            | Weapon::CustomWeapon => false
+        }
+    }
+
+    /// whether a character with the given ranks can wield the weapon
+    pub fn usable_with(&self, ranks : &WeaponRanks) -> bool {
+        match self {
+            Weapon::GbaFeWeapon(data) => data.usable_with(ranks),
+            Weapon::PoRWeapon
+            | Weapon::RdWeapon
+            | Weapon::ShadowDragonWeapon
+            | Weapon::SoVWeapon
+            | Weapon::ThreeHousesWeapon
+            | Weapon::CustomWeapon => true
         }
     }
+
+    /// the rank needed to wield the weapon, for games that have ranks
+    pub fn requirement(&self) -> Option<String> {
+        match self {
+            Weapon::GbaFeWeapon(data) => Some(data.requirement()),
+            Weapon::PoRWeapon
+            | Weapon::RdWeapon
+            | Weapon::ShadowDragonWeapon
+            | Weapon::SoVWeapon
+            | Weapon::ThreeHousesWeapon
+            | Weapon::CustomWeapon => None
+        }
+    }
+
+    /// whether the weapon heals its target rather than attacking it
+    pub fn heals(&self) -> bool {
+        match self {
+            Weapon::GbaFeWeapon(data) => data.heals(),
+            Weapon::PoRWeapon
+            | Weapon::RdWeapon
+            | Weapon::ShadowDragonWeapon
+            | Weapon::SoVWeapon
+            | Weapon::ThreeHousesWeapon
+            | Weapon::CustomWeapon => false
+        }
+    }
+
+    pub fn weight(&self) -> StatType {
+        match self {
+            Weapon::GbaFeWeapon(data) => data.weight(),
+            Weapon::PoRWeapon
+            | Weapon::RdWeapon
+            | Weapon::ShadowDragonWeapon
+            | Weapon::SoVWeapon
+            | Weapon::ThreeHousesWeapon
+            | Weapon::CustomWeapon => 0
+        }
+    }
+
+    /// The amounts the weapon raises the stats of its wielder by.
+    pub fn stat_buffs(&self) -> BTreeMap<StatIndexType, StatType> {
+        match self {
+            Weapon::GbaFeWeapon(data) => data.stat_buffs(),
+            Weapon::PoRWeapon
+            | Weapon::RdWeapon
+            | Weapon::ShadowDragonWeapon
+            | Weapon::SoVWeapon
+            | Weapon::ThreeHousesWeapon
+            | Weapon::CustomWeapon => BTreeMap::new()
+        }
+    }
+
+    /// The distribution of the attack speed of a character with the given stats
+    /// wielding this weapon. Returns `None` for games whose combat isn't
+    /// modeled yet.
+    pub fn attack_speed(&self, stats : &StatDistributions) -> Option<BTreeMap<StatType, f64>> {
+        match self {
+            Weapon::GbaFeWeapon(data) => Some(data.attack_speed(stats)),
+            Weapon::PoRWeapon
+            | Weapon::RdWeapon
+            | Weapon::ShadowDragonWeapon
+            | Weapon::SoVWeapon
+            | Weapon::ThreeHousesWeapon
+            | Weapon::CustomWeapon => None
+        }
+    }
+
+    /// The distribution of the hit rates a character with the given stats is
+    /// shown against the enemy with this weapon. Returns `None` for games
+    /// whose combat isn't modeled yet.
+    pub fn hit_rates(
+        &self,
+        stats : &StatDistributions,
+        enemy : &Opponent
+    ) -> Option<BTreeMap<u8, f64>> {
+        match self {
+            Weapon::GbaFeWeapon(data) => Some(data.hit_rates(stats, enemy)),
+            Weapon::PoRWeapon
+            | Weapon::RdWeapon
+            | Weapon::ShadowDragonWeapon
+            | Weapon::SoVWeapon
+            | Weapon::ThreeHousesWeapon
+            | Weapon::CustomWeapon => None
+        }
+    }
+
+    /// The chance that a character with the given stats kills the enemy in one
+    /// round of combat with this weapon. Returns `None` for games whose combat
+    /// isn't modeled yet.
+    pub fn orko_chance(&self, stats : &StatDistributions, enemy : &Opponent) -> Option<f64> {
+        match self {
+            Weapon::GbaFeWeapon(data) => Some(data.orko_chance(stats, enemy)),
+            Weapon::PoRWeapon
+            | Weapon::RdWeapon
+            | Weapon::ShadowDragonWeapon
+            | Weapon::SoVWeapon
+            | Weapon::ThreeHousesWeapon
+            | Weapon::CustomWeapon => None
+        }
+    }
+
+    /// The chance that a character with the given stats wielding this weapon
+    /// survives the enemies, each attacking as often as given. Returns `None`
+    /// for games whose combat isn't modeled yet.
+    pub fn survival_chance(
+        &self,
+        stats : &StatDistributions,
+        gauntlet : &[(Opponent, usize)]
+    ) -> Option<f64> {
+        match self {
+            Weapon::GbaFeWeapon(data) => Some(data.survival_chance(stats, gauntlet)),
+            Weapon::PoRWeapon
+            | Weapon::RdWeapon
+            | Weapon::ShadowDragonWeapon
+            | Weapon::SoVWeapon
+            | Weapon::ThreeHousesWeapon
+            | Weapon::CustomWeapon => None
+        }
+    }
+}
+
+/// Lets the user pick the enemy's weapon and the weapons effective against
+/// them among the saved ones.
+pub fn equipment_dialogue(
+    equipment : &mut EnemyEquipment,
+    weapons : &BTreeMap<String, Weapon>,
+    ui : &mut Ui
+) {
+    ComboBox::from_label(tr("weapon.equipped"))
+        .selected_text(equipment.weapon.as_str())
+        .show_ui(ui, |ui| {
+            ui.selectable_value(&mut equipment.weapon, String::new(), tr("weapon.none"));
+            for name in weapons.keys() {
+                ui.selectable_value(&mut equipment.weapon, name.clone(), name);
+            }
+        });
+    let effective = weapons
+        .iter()
+        .filter(|(_name, weapon)| weapon.is_effective())
+        .map(|(name, _weapon)| name)
+        .collect_vec();
+    if effective.is_empty() {
+        return;
+    }
+    ui.horizontal_wrapped(|ui| {
+        ui.label(tr("weapon.weak_to"));
+        for name in effective {
+            let mut weak = equipment.weak_to.contains(name);
+            ui.toggle_value(&mut weak, name);
+            if weak {
+                equipment.weak_to.insert(name.clone());
+            }
+            else {
+                equipment.weak_to.remove(name);
+            }
+        }
+    });
 }
 
 pub trait UsableWeapon {
@@ -34,7 +339,12 @@ impl UsableWeapon for Weapon {
     fn name(&self) -> &str {
         match self {
             Weapon::GbaFeWeapon(data) => data.name(),
-            Weapon::PoRWeapon => ""
+            Weapon::PoRWeapon
+            | Weapon::RdWeapon
+            | Weapon::ShadowDragonWeapon
+            | Weapon::SoVWeapon
+            | Weapon::ThreeHousesWeapon
+            | Weapon::CustomWeapon => ""
         }
     }
 
@@ -47,7 +357,12 @@ impl UsableWeapon for Weapon {
                 let (weapon, ready) = data.clarification_dialogue(context, ui);
                 (Self::GbaFeWeapon(weapon), ready)
             },
-            Weapon::PoRWeapon => (self, true)
+            Weapon::PoRWeapon
+            | Weapon::RdWeapon
+            | Weapon::ShadowDragonWeapon
+            | Weapon::SoVWeapon
+            | Weapon::ThreeHousesWeapon
+            | Weapon::CustomWeapon => (self, true)
         }
     }
 }
@@ -6,14 +6,20 @@ use std::{
 
 use egui::{Button, ComboBox, Grid, Slider, TextEdit, Ui};
 use fe_levels::StatType;
-use itertools::Itertools;
+use itertools::{iproduct, Itertools};
 use serde::{Deserialize, Serialize};
 
-use crate::app::{numerical_text_box, sit::StatIndexType, GameData, GameKind};
+use crate::app::{
+    combat::{sample, simulate_survival, simulation_rng, survival_chance, HitRng, Strikes},
+    locale::{tr, tr_with},
+    numerical_text_box,
+    sit::{StatIndexType, StatRole},
+    GameData, GameKind
+};
 
-use super::UsableWeapon;
+use super::{Forge, Opponent, StatDistributions, UsableWeapon};
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum GbaWeaponClass {
     Dark,
     Anima,
@@ -49,6 +55,58 @@ impl fmt::Display for GbaWeaponClass {
     }
 }
 
+/// The weapon ranks from lowest to highest.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum WeaponRank {
+    #[default]
+    E,
+    D,
+    C,
+    B,
+    A,
+    S
+}
+
+const ALL_WEAPON_RANKS : [WeaponRank; 6] = {
+    use WeaponRank::*;
+    [E, D, C, B, A, S]
+};
+
+impl fmt::Display for WeaponRank {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{self:?}") }
+}
+
+/// The ranks a character has in the weapon classes they can wield, no ranks
+/// at all meaning they weren't entered and every weapon is allowed.
+pub type WeaponRanks = BTreeMap<GbaWeaponClass, WeaponRank>;
+
+/// Lets the user enter the character's rank in every weapon class.
+pub fn weapon_rank_dialogue(ranks : &mut WeaponRanks, ui : &mut Ui) {
+    let label = |rank : Option<WeaponRank>| rank.map_or("-".to_owned(), |rank| rank.to_string());
+    Grid::new("Weapon Rank Grid").show(ui, |ui| {
+        for (index, class) in ALL_WEAPON_CLASSES.into_iter().enumerate() {
+            let mut rank = ranks.get(&class).copied();
+            ui.label(class.to_string());
+            ComboBox::from_id_source(format!("{class} Rank"))
+                .selected_text(label(rank))
+                .width(ui.spacing().interact_size.x)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut rank, None, label(None));
+                    for option in ALL_WEAPON_RANKS {
+                        ui.selectable_value(&mut rank, Some(option), label(Some(option)));
+                    }
+                });
+            match rank {
+                Some(rank) => ranks.insert(class, rank),
+                None => ranks.remove(&class)
+            };
+            if index % 4 == 3 {
+                ui.end_row();
+            }
+        }
+    });
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 pub enum GbaSpecialProperties {
     Brave,
@@ -81,7 +139,7 @@ impl fmt::Display for GbaSpecialProperties {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GbaFeWeapon {
     weapon_class : GbaWeaponClass,
     might : StatType,
@@ -91,7 +149,18 @@ pub struct GbaFeWeapon {
     name : String,
     range : RangeInclusive<u16>,
     stat_change : BTreeMap<StatIndexType, StatType>,
-    special_properties : BTreeSet<GbaSpecialProperties>
+    special_properties : BTreeSet<GbaSpecialProperties>,
+    /// the factor the might is multiplied with against enemies the weapon is
+    /// effective against, e.g. 3 for an Armorslayer, `None` if it isn't
+    /// effective against anyone
+    #[serde(default)]
+    effectiveness : Option<StatType>,
+    /// the rank required in the weapon's class to wield it
+    #[serde(default)]
+    rank : WeaponRank,
+    /// the name of the weapon this one was forged from, if any
+    #[serde(default)]
+    forged_from : Option<String>
 }
 impl UsableWeapon for GbaFeWeapon {
     fn name(&self) -> &str { &self.name }
@@ -101,13 +170,13 @@ impl UsableWeapon for GbaFeWeapon {
         Self : Sized
     {
         ui.horizontal(|ui| {
-            ui.label("Name:");
+            ui.label(tr("common.name"));
             ui.add(
                 TextEdit::singleline(&mut self.name)
                     .desired_width(ui.spacing().text_edit_width * 0.88)
             );
             ComboBox::from_id_source("Special Weapon Properties")
-                .selected_text("Special")
+                .selected_text(tr("weapon.special"))
                 .show_ui(ui, |ui| {
                     for property in ALL_SPECIAL_PROPERTIES {
                         let mut selected = self.special_properties.contains(&property);
@@ -122,10 +191,14 @@ impl UsableWeapon for GbaFeWeapon {
                 });
         });
 
+        if let Some(base) = &self.forged_from {
+            ui.label(tr_with("weapon.forged_from", &[("base", base)]));
+        }
+
         Grid::new("GBA Weapon Grid")
             .max_col_width(ui.spacing().interact_size.x * 1.15)
             .show(ui, |ui| {
-                ui.label("Class:");
+                ui.label(tr("weapon.class"));
                 ComboBox::from_id_source("Weapon Class")
                     .selected_text(self.weapon_class.to_string())
                     .show_ui(ui, |ui| {
@@ -134,7 +207,7 @@ impl UsableWeapon for GbaFeWeapon {
                         }
                     });
 
-                ui.label("Range:");
+                ui.label(tr("weapon.range"));
                 ui.horizontal(|ui| {
                     let (mut start, mut end) = self.range.clone().into_inner();
                     numerical_text_box(ui, &mut start);
@@ -143,23 +216,46 @@ impl UsableWeapon for GbaFeWeapon {
                     self.range = RangeInclusive::new(start, end);
                 });
 
-                ui.label("Weight:");
+                ui.label(tr("weapon.weight"));
                 numerical_text_box(ui, &mut self.weight);
                 ui.end_row();
 
-                ui.label("Might:");
+                ui.label(tr("weapon.might_field"));
                 numerical_text_box(ui, &mut self.might);
 
-                ui.label("Hit:");
+                ui.label(tr("weapon.hit_field"));
                 numerical_text_box(ui, &mut self.hitrate);
 
-                ui.label("Crit:");
+                ui.label(tr("weapon.crit_field"));
                 numerical_text_box(ui, &mut self.critrate);
                 ui.end_row();
+
+                ui.label(tr("weapon.effective"));
+                ComboBox::from_id_source("Weapon Effectiveness")
+                    .selected_text(effectiveness_label(self.effectiveness))
+                    .show_ui(ui, |ui| {
+                        for effectiveness in [None, Some(2), Some(3)] {
+                            ui.selectable_value(
+                                &mut self.effectiveness,
+                                effectiveness,
+                                effectiveness_label(effectiveness)
+                            );
+                        }
+                    });
+
+                ui.label(tr("weapon.rank"));
+                ComboBox::from_id_source("Weapon Rank")
+                    .selected_text(self.rank.to_string())
+                    .show_ui(ui, |ui| {
+                        for rank in ALL_WEAPON_RANKS {
+                            ui.selectable_value(&mut self.rank, rank, rank.to_string());
+                        }
+                    });
+                ui.end_row();
             });
 
         if self.stat_change.is_empty() {
-            if ui.button("Add Stat Buff").clicked() {
+            if ui.button(tr("weapon.add_buff")).clicked() {
                 self.stat_change
                     .insert(StatIndexType::arbitrary_valid(GameKind::GbaFe), 0);
             }
@@ -173,16 +269,22 @@ impl UsableWeapon for GbaFeWeapon {
                     .filter(|sit| !used_keys.contains(sit))
                     .collect();
                 for (mut index, mut buff) in buffs {
-                    ComboBox::from_id_source(format!("{index} Combo-Box")).selected_text(index.to_string()).show_ui(ui, |ui| {
-                        for index_option in valid_keys
-                            .iter()
-                            .map(|sit| *sit)
-                            .chain(std::iter::once(index.clone()))
-                            .sorted_by_key(|x| *x)
-                        {
-                            ui.selectable_value(&mut index, index_option, index_option.to_string());
-                        }
-                    });
+                    ComboBox::from_id_source(format!("{index} Combo-Box"))
+                        .selected_text(index.to_string())
+                        .show_ui(ui, |ui| {
+                            for index_option in valid_keys
+                                .iter()
+                                .copied()
+                                .chain(std::iter::once(index))
+                                .sorted_by_key(|x| *x)
+                            {
+                                ui.selectable_value(
+                                    &mut index,
+                                    index_option,
+                                    index_option.to_string()
+                                );
+                            }
+                        });
                     ui.add(Slider::new(&mut buff, 0..=20).clamp_to_range(false));
                     let mut removed = false;
                     ui.horizontal(|ui| {
@@ -209,10 +311,8 @@ impl UsableWeapon for GbaFeWeapon {
 
         (
             self,
-            ui.add_enabled(confirmation_ready, Button::new("confirm"))
-                .on_disabled_hover_text(
-                    "Please give this weapon a unique name and make sure the range is correct."
-                )
+            ui.add_enabled(confirmation_ready, Button::new(tr("common.confirm")))
+                .on_disabled_hover_text(tr("weapon.confirm_hint"))
                 .clicked()
         )
     }
@@ -229,7 +329,563 @@ impl Default for GbaFeWeapon {
             name : Default::default(),               // textbox
             range : 1..=1,                           // double slider?
             special_properties : Default::default(), // combo box into x-able list
-            stat_change : BTreeMap::new()            // x-able array of combo box + slider (0-20)
+            stat_change : BTreeMap::new(),           // x-able array of combo box + slider (0-20)
+            effectiveness : None,                    // combo box
+            rank : WeaponRank::E,                    // combo box
+            forged_from : None
+        }
+    }
+}
+
+/// The GBA stat of the given name.
+fn gba_stat(name : &str) -> StatIndexType {
+    StatIndexType::new(GameKind::GbaFe)
+        .into_iter()
+        .find(|sit| sit.to_string() == name)
+        .unwrap()
+}
+
+/// The GBA stat filling the role in the damage formulas.
+fn role_stat(role : StatRole) -> StatIndexType {
+    StatIndexType::with_role(GameKind::GbaFe, role).unwrap()
+}
+
+/// The value of the enemy's GBA stat of the given name.
+fn enemy_stat(enemy : &Opponent, name : &str) -> StatType { enemy_value(enemy, gba_stat(name)) }
+
+fn enemy_value(enemy : &Opponent, sit : StatIndexType) -> StatType {
+    enemy.character.stats.get(&sit).map_or(0, |stat| stat.value)
+}
+
+fn effectiveness_label(effectiveness : Option<StatType>) -> String {
+    match effectiveness {
+        Some(factor) => format!("×{factor} Mt"),
+        None => tr("weapon.not_effective")
+    }
+}
+
+/// Whether the first class has the advantage over the second one in the
+/// weapon triangle or the magic triangle.
+fn beats(attacker : GbaWeaponClass, defender : GbaWeaponClass) -> bool {
+    use GbaWeaponClass::*;
+    matches!(
+        (attacker, defender),
+        (Sword, Axe)
+            | (Axe, Lance)
+            | (Lance, Sword)
+            | (Anima, Light)
+            | (Light, Dark)
+            | (Dark, Anima)
+    )
+}
+
+/// the number of cases up to which the survival chance is computed exactly,
+/// beyond it runs are simulated
+const EXACT_SURVIVAL_CASES : usize = 1_000;
+/// the number of runs simulated per survival chance
+const SURVIVAL_RUNS : usize = 2_000;
+
+/// The stats of the character the attacks of the enemies depend on.
+#[derive(Debug, Clone, Copy)]
+struct Defender {
+    attack_speed : StatType,
+    luck : StatType,
+    defense : StatType,
+    resistance : StatType
+}
+
+/// The chance that at least `hits` of the `strikes` land.
+fn at_least(hits : u32, strikes : u32, chance : f64) -> f64 {
+    (hits..=strikes)
+        .map(|landed| {
+            let ways = (0..landed).fold(1.0, |acc, k| acc * (strikes - k) as f64 / (k + 1) as f64);
+            ways * chance.powi(landed as i32) * (1.0 - chance).powi((strikes - landed) as i32)
+        })
+        .sum()
+}
+
+impl GbaFeWeapon {
+    pub fn weight(&self) -> StatType { self.weight }
+
+    pub fn is_effective(&self) -> bool { self.effectiveness.is_some() }
+
+    fn has(&self, property : GbaSpecialProperties) -> bool {
+        self.special_properties.contains(&property)
+    }
+
+    pub fn heals(&self) -> bool { self.has(GbaSpecialProperties::Heals) }
+
+    /// Whether a character with the given ranks can wield the weapon.
+    pub fn usable_with(&self, ranks : &WeaponRanks) -> bool {
+        ranks.is_empty()
+            || ranks
+                .get(&self.weapon_class)
+                .map_or(false, |rank| *rank >= self.rank)
+    }
+
+    pub fn forged(&self, forge : &Forge) -> Self {
+        GbaFeWeapon {
+            might : self.might.saturating_add(forge.might),
+            hitrate : self.hitrate.saturating_add(forge.hit),
+            critrate : self.critrate.saturating_add(forge.crit),
+            name : forge.name.clone(),
+            forged_from : Some(forge.base.clone()),
+            ..self.clone()
         }
     }
+
+    pub fn requirement(&self) -> String {
+        tr_with(
+            "weapon.requirement",
+            &[("class", &self.weapon_class), ("rank", &self.rank)]
+        )
+    }
+
+    fn is_reaver(&self) -> bool { self.has(GbaSpecialProperties::Reaver) }
+
+    /// brave weapons strike twice per attack
+    fn strikes_per_attack(&self) -> u32 {
+        if self.has(GbaSpecialProperties::Brave) {
+            2
+        }
+        else {
+            1
+        }
+    }
+
+    /// The weapon triangle levels the weapon has on the enemy's, each granting
+    /// +1 Mt and +15 hit or taking them if negative. A single Reaver reverses
+    /// the triangle and doubles it, two of them cancel out.
+    fn triangle_bonus(&self, enemy_weapon : Option<&GbaFeWeapon>) -> i32 {
+        let enemy_weapon = match enemy_weapon {
+            Some(enemy_weapon) => enemy_weapon,
+            None => return 0
+        };
+        let advantage = if beats(self.weapon_class, enemy_weapon.weapon_class) {
+            1
+        }
+        else if beats(enemy_weapon.weapon_class, self.weapon_class) {
+            -1
+        }
+        else {
+            0
+        };
+        if self.is_reaver() != enemy_weapon.is_reaver() {
+            -2 * advantage
+        }
+        else {
+            advantage
+        }
+    }
+
+    /// The might against the enemy, effectiveness and the triangle applied.
+    fn might_against(&self, enemy : &Opponent) -> i32 {
+        let factor = self.effectiveness.filter(|_factor| enemy.weak).unwrap_or(1);
+        i32::from(self.might * factor) + self.triangle_bonus(enemy.gba_weapon())
+    }
+
+    /// Whether Res rather than Def reduces the damage. Magic swords deal magic
+    /// damage when striking from range, which they're assumed to do whenever
+    /// their range allows it.
+    fn targets_resistance(&self) -> bool {
+        let magic = matches!(
+            self.weapon_class,
+            GbaWeaponClass::Dark | GbaWeaponClass::Anima | GbaWeaponClass::Light
+        );
+        magic || (self.has(GbaSpecialProperties::MagicSword) && *self.range.end() > 1)
+    }
+
+    fn attacking_role(&self) -> StatRole {
+        if self.targets_resistance() {
+            StatRole::MagicalAttack
+        }
+        else {
+            StatRole::PhysicalAttack
+        }
+    }
+
+    /// The role of the stat reducing the damage, none for Luna weapons.
+    fn defending_role(&self) -> Option<StatRole> {
+        if self.has(GbaSpecialProperties::IgnoresDefense) {
+            None
+        }
+        else if self.targets_resistance() {
+            Some(StatRole::MagicalDefense)
+        }
+        else {
+            Some(StatRole::PhysicalDefense)
+        }
+    }
+
+    /// The chance a Devil weapon's attack backfires on its wielder instead,
+    /// (21 - Lck)%.
+    fn backfire_chance(&self, luck : StatType) -> f64 {
+        if self.has(GbaSpecialProperties::Devil) {
+            21u16.saturating_sub(luck) as f64 / 100.0
+        }
+        else {
+            0.0
+        }
+    }
+
+    pub fn stat_buffs(&self) -> BTreeMap<StatIndexType, StatType> {
+        self.stat_change
+            .iter()
+            .filter(|(_sit, buff)| **buff > 0)
+            .map(|(sit, buff)| (*sit, *buff))
+            .collect()
+    }
+
+    /// The distribution of the character's stat with the weapon's buff applied.
+    fn buffed(&self, stats : &StatDistributions, name : &str) -> BTreeMap<StatType, f64> {
+        self.buffed_stat(stats, gba_stat(name))
+    }
+
+    fn buffed_stat(
+        &self,
+        stats : &StatDistributions,
+        sit : StatIndexType
+    ) -> BTreeMap<StatType, f64> {
+        let buff = self.stat_change.get(&sit).copied().unwrap_or_default();
+        stats
+            .get(&sit)
+            .map(|distribution| {
+                distribution
+                    .iter()
+                    .map(|(value, chance)| (value + buff, *chance))
+                    .collect()
+            })
+            .unwrap_or_else(|| BTreeMap::from([(buff, 1.0)]))
+    }
+
+    /// The distribution of the attack speed, the weight exceeding the
+    /// character's Con slowing them down.
+    pub fn attack_speed(&self, stats : &StatDistributions) -> BTreeMap<StatType, f64> {
+        let mut speeds = BTreeMap::new();
+        let cons = self.buffed(stats, "Con");
+        for ((speed, speed_chance), (con, con_chance)) in self
+            .buffed(stats, "Spd")
+            .iter()
+            .cartesian_product(cons.iter())
+        {
+            let attack_speed = speed.saturating_sub(self.weight.saturating_sub(*con));
+            *speeds.entry(attack_speed).or_insert(0.0) += speed_chance * con_chance;
+        }
+        speeds
+    }
+
+    /// The hit rate displayed against the enemy for the given Skl and Lck.
+    fn displayed_hit(&self, enemy : &Opponent, skill : StatType, luck : StatType) -> u8 {
+        let avoid = i32::from(2 * enemy.attack_speed() + enemy_stat(enemy, "Lck"));
+        let hit = i32::from(self.hitrate) + 15 * self.triangle_bonus(enemy.gba_weapon());
+        (hit + i32::from(2 * skill + luck / 2) - avoid).clamp(0, 100) as u8
+    }
+
+    /// The distribution of the hit rates displayed against the enemy.
+    pub fn hit_rates(&self, stats : &StatDistributions, enemy : &Opponent) -> BTreeMap<u8, f64> {
+        let mut hit_rates = BTreeMap::new();
+        let lucks = self.buffed(stats, "Lck");
+        for ((skill, skill_chance), (luck, luck_chance)) in self
+            .buffed(stats, "Skl")
+            .iter()
+            .cartesian_product(lucks.iter())
+        {
+            *hit_rates
+                .entry(self.displayed_hit(enemy, *skill, *luck))
+                .or_insert(0.0) += skill_chance * luck_chance;
+        }
+        hit_rates
+    }
+
+    /// The chance to kill the enemy within one round of combat, the stats of
+    /// the character being independent of each other. Only the character's
+    /// strikes are considered, not their crits, and strikes backfiring on the
+    /// character spare the enemy.
+    pub fn orko_chance(&self, stats : &StatDistributions, enemy : &Opponent) -> f64 {
+        if self.heals() {
+            return 0.0;
+        }
+        let defense = self
+            .defending_role()
+            .map_or(0, |role| enemy_value(enemy, role_stat(role)));
+        let strikes_per_attack = self.strikes_per_attack();
+
+        let double_chance : f64 = self
+            .attack_speed(stats)
+            .range(enemy.attack_speed().saturating_add(4)..)
+            .map(|(_speed, chance)| chance)
+            .sum();
+
+        let hit_rng = HitRng::of(GameKind::GbaFe);
+        let might = self.might_against(enemy);
+        let health = enemy_stat(enemy, "HP") as u32;
+        let attacks = self.buffed_stat(stats, role_stat(self.attacking_role()));
+        let skills = self.buffed(stats, "Skl");
+        let lucks = self.buffed(stats, "Lck");
+        iproduct!(attacks.iter(), skills.iter(), lucks.iter())
+            .map(
+                |((attack, attack_chance), (skill, skill_chance), (luck, luck_chance))| {
+                    let damage = (i32::from(*attack) + might - i32::from(defense)).max(0) as u32;
+                    let displayed = self.displayed_hit(enemy, *skill, *luck);
+                    let lands = hit_rng.true_hit(displayed) * (1.0 - self.backfire_chance(*luck));
+                    let kill_chance = |strikes| match (health, damage) {
+                        (0, _) => 1.0,
+                        (_, 0) => 0.0,
+                        _ => {
+                            let needed = (health + damage - 1) / damage;
+                            at_least(needed, strikes, lands)
+                        }
+                    };
+                    let round = double_chance * kill_chance(2 * strikes_per_attack)
+                        + (1.0 - double_chance) * kill_chance(strikes_per_attack);
+                    attack_chance * skill_chance * luck_chance * round
+                }
+            )
+            .sum()
+    }
+
+    /// The round of the enemy attacking the character wielding this weapon,
+    /// `None` if the enemy has no weapon to attack with.
+    fn enemy_round(&self, enemy : &Opponent, defender : Defender) -> Option<Strikes> {
+        let weapon = enemy.gba_weapon().filter(|weapon| !weapon.heals())?;
+        let triangle = weapon.triangle_bonus(Some(self));
+        let defense = match weapon.defending_role() {
+            Some(StatRole::MagicalDefense) => defender.resistance,
+            Some(_) => defender.defense,
+            None => 0
+        };
+        let attack = enemy_value(enemy, role_stat(weapon.attacking_role()));
+        let damage = i32::from(attack) + i32::from(weapon.might) + triangle - i32::from(defense);
+        let avoid = i32::from(2 * defender.attack_speed + defender.luck);
+        let hit = i32::from(weapon.hitrate)
+            + 15 * triangle
+            + i32::from(2 * enemy_stat(enemy, "Skl") + enemy_stat(enemy, "Lck") / 2)
+            - avoid;
+        let doubles = enemy.attack_speed() >= defender.attack_speed.saturating_add(4);
+        // strikes backfiring on the enemy spare the character
+        let backfire = weapon.backfire_chance(enemy_stat(enemy, "Lck"));
+        Some(Strikes {
+            count : weapon.strikes_per_attack() * if doubles { 2 } else { 1 },
+            hit : HitRng::of(GameKind::GbaFe).true_hit(hit.clamp(0, 100) as u8) * (1.0 - backfire),
+            damage : damage.max(0) as u32
+        })
+    }
+
+    /// The chance to survive the enemies attacking one after another, each as
+    /// often as given, without healing in between. The stats of the character
+    /// are independent of each other and only the enemies' strikes are
+    /// considered. Runs are simulated if there are too many cases to go
+    /// through.
+    pub fn survival_chance(
+        &self,
+        stats : &StatDistributions,
+        gauntlet : &[(Opponent, usize)]
+    ) -> f64 {
+        let attack_speeds = self.attack_speed(stats);
+        let lucks = self.buffed(stats, "Lck");
+        let defenses = self.buffed_stat(stats, role_stat(StatRole::PhysicalDefense));
+        let resistances = self.buffed_stat(stats, role_stat(StatRole::MagicalDefense));
+        let health = self.buffed(stats, "HP");
+        let rounds_against = |defender| {
+            gauntlet
+                .iter()
+                .filter_map(|(enemy, count)| Some((self.enemy_round(enemy, defender)?, *count)))
+                .flat_map(|(strikes, count)| std::iter::repeat(strikes).take(count))
+                .collect_vec()
+        };
+
+        let rounds : usize = gauntlet.iter().map(|(_enemy, count)| count).sum();
+        let cases = attack_speeds.len() * lucks.len() * defenses.len() * resistances.len() * rounds;
+        if cases <= EXACT_SURVIVAL_CASES {
+            iproduct!(
+                attack_speeds.iter(),
+                lucks.iter(),
+                defenses.iter(),
+                resistances.iter()
+            )
+            .map(|(speed, luck, defense, resistance)| {
+                let defender = Defender {
+                    attack_speed : *speed.0,
+                    luck : *luck.0,
+                    defense : *defense.0,
+                    resistance : *resistance.0
+                };
+                let chance = speed.1 * luck.1 * defense.1 * resistance.1;
+                chance * survival_chance(&rounds_against(defender), &health)
+            })
+            .sum()
+        }
+        else {
+            // a fixed seed keeps the chart from flickering between frames
+            let mut rng = simulation_rng(Some(0));
+            let survived = (0..SURVIVAL_RUNS)
+                .filter(|_run| {
+                    let defender = Defender {
+                        attack_speed : sample(&attack_speeds, &mut rng),
+                        luck : sample(&lucks, &mut rng),
+                        defense : sample(&defenses, &mut rng),
+                        resistance : sample(&resistances, &mut rng)
+                    };
+                    let health = sample(&health, &mut rng);
+                    simulate_survival(&rounds_against(defender), health, &mut rng)
+                })
+                .count();
+            survived as f64 / SURVIVAL_RUNS as f64
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fe_levels::Character;
+
+    use super::*;
+    use crate::app::weapon::Weapon;
+
+    fn assert_close(actual : f64, expected : f64) {
+        assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+    }
+
+    /// A GBA character with the given stats, all others being 0.
+    fn character(values : &[(&str, StatType)]) -> Character<StatIndexType> {
+        let mut character = StatIndexType::new_default_enemy(GameKind::GbaFe);
+        for (sit, stat) in character.stats.iter_mut() {
+            stat.value = values
+                .iter()
+                .find(|(name, _value)| sit.to_string() == *name)
+                .map_or(0, |(_name, value)| *value);
+        }
+        character
+    }
+
+    /// The stats of a character known for sure.
+    fn known_stats(values : &[(&str, StatType)]) -> StatDistributions {
+        character(values)
+            .stats
+            .into_iter()
+            .map(|(sit, stat)| (sit, BTreeMap::from([(stat.value, 1.0)])))
+            .collect()
+    }
+
+    /// A weightless weapon with 5 Mt that never misses the enemies below.
+    fn weapon(properties : &[GbaSpecialProperties]) -> GbaFeWeapon {
+        GbaFeWeapon {
+            might : 5,
+            weight : 0,
+            hitrate : 100,
+            special_properties : properties.iter().copied().collect(),
+            ..Default::default()
+        }
+    }
+
+    fn orko_chance(
+        weapon : &GbaFeWeapon,
+        attacker : &[(&str, StatType)],
+        enemy : &[(&str, StatType)]
+    ) -> f64 {
+        let enemy = character(enemy);
+        let enemy = Opponent {
+            character : &enemy,
+            weapon : None,
+            weak : false
+        };
+        weapon.orko_chance(&known_stats(attacker), &enemy)
+    }
+
+    #[test]
+    fn only_gba_atk_fills_both_attacking_roles() {
+        assert_eq!(role_stat(StatRole::PhysicalAttack).to_string(), "Atk");
+        assert_eq!(role_stat(StatRole::MagicalAttack).to_string(), "Atk");
+        for (role, name) in [
+            (StatRole::PhysicalAttack, "Str"),
+            (StatRole::MagicalAttack, "Mag"),
+            (StatRole::PhysicalDefense, "Def"),
+            (StatRole::MagicalDefense, "Res")
+        ] {
+            let sit = StatIndexType::with_role(GameKind::PoR, role).unwrap();
+            assert_eq!(sit.to_string(), name);
+        }
+    }
+
+    #[test]
+    fn luna_ignores_defense() {
+        let enemy = [("HP", 15), ("Def", 20)];
+        assert_close(orko_chance(&weapon(&[]), &[("Atk", 10)], &enemy), 0.0);
+        let luna = weapon(&[GbaSpecialProperties::IgnoresDefense]);
+        assert_close(orko_chance(&luna, &[("Atk", 10)], &enemy), 1.0);
+    }
+
+    #[test]
+    fn runesword_targets_resistance_from_range() {
+        let enemy = [("HP", 15), ("Def", 20)];
+        let mut runesword = GbaFeWeapon {
+            weapon_class : GbaWeaponClass::Sword,
+            range : 1..=2,
+            ..weapon(&[GbaSpecialProperties::MagicSword])
+        };
+        assert_close(orko_chance(&runesword, &[("Atk", 10)], &enemy), 1.0);
+        runesword.range = 1..=1;
+        assert_close(orko_chance(&runesword, &[("Atk", 10)], &enemy), 0.0);
+    }
+
+    #[test]
+    fn brave_weapons_strike_twice() {
+        // 8 damage per strike against 15 HP without doubling
+        let enemy = [("HP", 15), ("Def", 7)];
+        assert_close(orko_chance(&weapon(&[]), &[("Atk", 10)], &enemy), 0.0);
+        let brave = weapon(&[GbaSpecialProperties::Brave]);
+        assert_close(orko_chance(&brave, &[("Atk", 10)], &enemy), 1.0);
+    }
+
+    #[test]
+    fn devil_weapons_backfire_below_21_luck() {
+        let devil = weapon(&[GbaSpecialProperties::Devil]);
+        let enemy = [("HP", 15)];
+        assert_close(
+            orko_chance(&devil, &[("Atk", 10), ("Lck", 11)], &enemy),
+            0.9
+        );
+        assert_close(
+            orko_chance(&devil, &[("Atk", 10), ("Lck", 21)], &enemy),
+            1.0
+        );
+
+        let enemy = character(&[("Atk", 10), ("Lck", 16)]);
+        let devil = Weapon::GbaFeWeapon(devil);
+        let enemy = Opponent {
+            character : &enemy,
+            weapon : Some(&devil),
+            weak : false
+        };
+        let defender = Defender {
+            attack_speed : 0,
+            luck : 0,
+            defense : 3,
+            resistance : 0
+        };
+        let strikes = weapon(&[]).enemy_round(&enemy, defender).unwrap();
+        assert_eq!((strikes.count, strikes.damage), (1, 12));
+        assert_close(strikes.hit, 0.95);
+    }
+
+    #[test]
+    fn healing_weapons_never_attack() {
+        let staff = weapon(&[GbaSpecialProperties::Heals]);
+        assert_close(orko_chance(&staff, &[("Atk", 30)], &[("HP", 1)]), 0.0);
+
+        let enemy = character(&[("Atk", 30)]);
+        let staff = Weapon::GbaFeWeapon(staff);
+        let enemy = Opponent {
+            character : &enemy,
+            weapon : Some(&staff),
+            weak : false
+        };
+        let defender = Defender {
+            attack_speed : 0,
+            luck : 0,
+            defense : 0,
+            resistance : 0
+        };
+        assert!(weapon(&[]).enemy_round(&enemy, defender).is_none());
+    }
 }
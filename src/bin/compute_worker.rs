@@ -0,0 +1,14 @@
+#![warn(clippy::all, rust_2018_idioms)]
+
+//! The web worker computing the plotter's data off the page's thread, built by
+//! trunk next to the app and loaded by `assets/compute_worker_loader.js`.
+
+#[cfg(target_arch = "wasm32")]
+fn main() {
+    console_error_panic_hook::set_once();
+    fe_levels_gui::run_compute_worker();
+}
+
+/// Natively the app computes on threads of its own, so there's nothing to do.
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {}
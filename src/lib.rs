@@ -1,4 +1,6 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
 mod app;
+#[cfg(target_arch = "wasm32")]
+pub use app::run_compute_worker;
 pub use app::FeLevelGui;